@@ -0,0 +1,48 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts heap allocations of each thread, to check that the playout path does
+// not allocate. With the count-allocs feature it is the global allocator,
+// otherwise nothing is counted.
+pub struct CountingAllocator;
+
+thread_local! {
+    // Const initialized without a destructor, so the allocator can use it
+    // at any point of the thread's life
+    static ALLOC_CNT: Cell<u64> = const { Cell::new(0) };
+}
+
+fn count() {
+    let _ = ALLOC_CNT.try_with(|cnt| cnt.set(cnt.get() + 1));
+}
+
+// Allocations and reallocations made by this thread so far, None when they are
+// not counted
+pub fn thread_alloc_cnt() -> Option<u64> {
+    cfg!(feature = "count-allocs").then(|| ALLOC_CNT.with(Cell::get))
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[cfg(feature = "count-allocs")]
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
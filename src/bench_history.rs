@@ -0,0 +1,154 @@
+use crate::benchmark::BenchmarkStats;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Benchmark results kept in a CSV file, one run per line, to catch throughput
+// regressions against the best earlier run of the same configuration
+
+const HEADER: &str = "timestamp,git_rev,config,playouts,kpps,cc_per_move";
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BenchRecord {
+    // Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub git_rev: String,
+    // Free form description, only runs with equal config are compared
+    pub config: String,
+    pub playout_cnt: usize,
+    pub kpps: f64,
+    pub cc_per_move: f64,
+}
+
+impl BenchRecord {
+    // Record of a run made now from the current git revision
+    pub fn new(config: &str, stats: &BenchmarkStats) -> Self {
+        BenchRecord {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            git_rev: git_rev(),
+            config: config.to_string(),
+            playout_cnt: stats.playout_cnt,
+            kpps: stats.kpps as f64,
+            cc_per_move: stats.perf_cc_per_move.unwrap_or(stats.cc_per_move),
+        }
+    }
+
+    // Commas in text fields are replaced by semicolons
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{:.3},{:.1}",
+            self.timestamp,
+            self.git_rev.replace(",", ";"),
+            self.config.replace(",", ";"),
+            self.playout_cnt,
+            self.kpps,
+            self.cc_per_move
+        )
+    }
+
+    fn of_csv(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() != 6 {
+            return None;
+        }
+        Some(BenchRecord {
+            timestamp: fields[0].parse().ok()?,
+            git_rev: fields[1].to_string(),
+            config: fields[2].to_string(),
+            playout_cnt: fields[3].parse().ok()?,
+            kpps: fields[4].parse().ok()?,
+            cc_per_move: fields[5].parse().ok()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BenchHistory {
+    pub records: Vec<BenchRecord>,
+}
+
+impl BenchHistory {
+    // A missing file is an empty history, malformed lines are errors
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        let mut records = Vec::new();
+        for (line_idx, line) in text.lines().enumerate() {
+            if line.is_empty() || line == HEADER {
+                continue;
+            }
+            let record = BenchRecord::of_csv(line)
+                .ok_or_else(|| format!("{}:{}: bad record", path.display(), line_idx + 1))?;
+            records.push(record);
+        }
+        Ok(BenchHistory { records })
+    }
+
+    // Appends to the file, writing the header if the file is new
+    pub fn append(path: &Path, record: &BenchRecord) -> Result<(), String> {
+        let error = |e: std::io::Error| format!("{}: {}", path.display(), e);
+        let is_new = !path.exists();
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(error)?;
+        if is_new {
+            writeln!(file, "{}", HEADER).map_err(error)?;
+        }
+        writeln!(file, "{}", record.to_csv()).map_err(error)
+    }
+
+    // Fastest earlier run of the configuration
+    pub fn best(&self, config: &str) -> Option<&BenchRecord> {
+        self.records
+            .iter()
+            .filter(|r| r.config == config)
+            .max_by(|a, b| a.kpps.total_cmp(&b.kpps))
+    }
+
+    // Err with a report if current is more than max_regression (e.g. 0.05 for 5%)
+    // slower than the best run of its configuration
+    pub fn check(&self, current: &BenchRecord, max_regression: f64) -> Result<String, String> {
+        let Some(best) = self.best(&current.config) else {
+            return Ok(format!(
+                "{:.3} kpps, first run of '{}'",
+                current.kpps, current.config
+            ));
+        };
+        let change = current.kpps / best.kpps - 1.0;
+        let report = format!(
+            "{:.3} kpps at {} vs best {:.3} kpps at {} ({:+.1}%), CC/move {:.1} vs {:.1}",
+            current.kpps,
+            current.git_rev,
+            best.kpps,
+            best.git_rev,
+            100.0 * change,
+            current.cc_per_move,
+            best.cc_per_move
+        );
+        if change < -max_regression {
+            Err(format!("Throughput regression: {}", report))
+        } else {
+            Ok(report)
+        }
+    }
+}
+
+// Short hash of HEAD, "unknown" outside a git checkout
+pub fn git_rev() -> String {
+    std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|rev| rev.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
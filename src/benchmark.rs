@@ -1,140 +1,1010 @@
-use crate::board::Board;
+use crate::alloc_counter::thread_alloc_cnt;
+use crate::board::{Board, BoardSnapshot};
 use crate::fast_random::FastRandom;
 use crate::gammas::Gammas;
 use crate::perf_counter::PerfCounter;
+use crate::policy::{MogoPolicy, PlayoutPolicy, SamplerPolicy, UniformPolicy};
 use crate::sampler::Sampler;
-use crate::types::{Player, PlayerMap};
+use crate::score::PlayoutOutcome;
+use crate::sgf::parse_game;
+use crate::tune::SamplerParams;
+use crate::types::{Player, Vertex};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+// Runs of run_repeated that are not measured
+pub const WARMUP_RUNS: usize = 1;
+
+// Random seed of Benchmark::new
+pub const DEFAULT_BENCH_SEED: u32 = 123;
+
+// One side of a policy comparison
+pub struct PolicyConfig<'a> {
+    pub gammas: &'a Gammas,
+    // Direct and diagonal neighbor bonus, see Sampler::set_proximity_bonus
+    pub proximity_bonus: [f64; 2],
+}
+
+impl<'a> PolicyConfig<'a> {
+    pub fn new(gammas: &'a Gammas) -> Self {
+        PolicyConfig {
+            gammas,
+            proximity_bonus: [10.0, 10.0],
+        }
+    }
+
+    fn policy(&self, board: &Board) -> SamplerPolicy<'a> {
+        let mut sampler = Sampler::new(board, self.gammas);
+        sampler.set_proximity_bonus(self.proximity_bonus[0], self.proximity_bonus[1]);
+        SamplerPolicy::new(sampler, self.gammas)
+    }
+}
+
+pub struct ComparisonResult {
+    pub game_cnt: usize,
+    pub a_win_cnt: usize,
+    pub jigo_cnt: usize,
+}
+
+impl ComparisonResult {
+    // Jigo counts as half a win, None without games
+    pub fn a_win_rate(&self) -> Option<f64> {
+        if self.game_cnt == 0 {
+            return None;
+        }
+        Some((self.a_win_cnt as f64 + 0.5 * self.jigo_cnt as f64) / self.game_cnt as f64)
+    }
+
+    // Wilson score interval of A's win rate at ~95% confidence, None without
+    // games
+    pub fn confidence_interval(&self) -> Option<(f64, f64)> {
+        const Z: f64 = 1.96;
+        let n = self.game_cnt as f64;
+        let p = self.a_win_rate()?;
+        let center = (p + Z * Z / (2.0 * n)) / (1.0 + Z * Z / n);
+        let half_width = Z / (1.0 + Z * Z / n) * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt();
+        Some((center - half_width, center + half_width))
+    }
+}
+
+impl std::fmt::Display for ComparisonResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "A won {}/{} games", self.a_win_cnt, self.game_cnt)?;
+        if self.jigo_cnt > 0 {
+            write!(f, " with {} jigo", self.jigo_cnt)?;
+        }
+        let (Some(win_rate), Some((low, high))) = (self.a_win_rate(), self.confidence_interval())
+        else {
+            return Ok(());
+        };
+        write!(
+            f,
+            " = {:.2}% (95% CI {:.2}% - {:.2}%)",
+            100.0 * win_rate,
+            100.0 * low,
+            100.0 * high
+        )
+    }
+}
+
+// Result of Benchmark::run_stats
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BenchmarkStats {
+    pub playout_cnt: usize,
+    pub move_cnt: usize,
+    pub seconds: f32,
+    pub kpps: f32,
+    // Estimated from time and CPU frequency
+    pub cc_per_move: f64,
+    // Core cycles actually spent, when perf counters are available. They are
+    // not on machines without perf_event access.
+    pub perf_cc_per_move: Option<f64>,
+    pub perf_cache_misses_per_move: Option<f64>,
+    pub cpu_freq_ghz: f64,
+    pub freq_source: FreqSource,
+    // With the count-allocs feature
+    pub allocs_per_playout: Option<f64>,
+    pub black_win_cnt: usize,
+    pub white_win_cnt: usize,
+    // Possible with integer komi only
+    pub jigo_cnt: usize,
+}
+
+// Black wins, white wins and jigos of a batch of playouts
+#[derive(Clone, Copy, Debug, Default)]
+struct OutcomeCounts {
+    black_win_cnt: usize,
+    white_win_cnt: usize,
+    jigo_cnt: usize,
+}
+
+impl OutcomeCounts {
+    fn add(&mut self, outcome: PlayoutOutcome) {
+        match outcome {
+            PlayoutOutcome::BlackWin => self.black_win_cnt += 1,
+            PlayoutOutcome::WhiteWin => self.white_win_cnt += 1,
+            PlayoutOutcome::Jigo => self.jigo_cnt += 1,
+        }
+    }
+}
+
+// Where BenchmarkStats::cpu_freq_ghz comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FreqSource {
+    // Time stamp counter calibrated against Instant
+    Tsc,
+    // Current frequency of cpu0 reported by the kernel
+    Sysfs,
+    // 1 GHz when neither is available
+    Assumed,
+}
+
+impl std::fmt::Display for FreqSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            FreqSource::Tsc => "TSC",
+            FreqSource::Sysfs => "sysfs",
+            FreqSource::Assumed => "assumed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Median, sample standard deviation and range of a measurement over runs
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spread {
+    pub median: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Spread {
+    pub fn of(values: &[f64]) -> Self {
+        assert!(!values.is_empty(), "Spread of no values");
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+        };
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let variance = if n > 1 {
+            sorted.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        Spread {
+            median,
+            stddev: variance.sqrt(),
+            min: sorted[0],
+            max: sorted[n - 1],
+        }
+    }
+}
+
+impl std::fmt::Display for Spread {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.3} (stddev {:.3}, min {:.3}, max {:.3})",
+            self.median, self.stddev, self.min, self.max
+        )
+    }
+}
+
+// Result of Benchmark::run_repeated, without the warm-up runs
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepeatedStats {
+    pub runs: Vec<BenchmarkStats>,
+    pub kpps: Spread,
+    // Perf counter cycles when available, time*freq otherwise
+    pub cc_per_move: Spread,
+}
+
+impl std::fmt::Display for RepeatedStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} runs of {} playouts\nkpps: {}\nCC/move: {}",
+            self.runs.len(),
+            self.runs[0].playout_cnt,
+            self.kpps,
+            self.cc_per_move
+        )
+    }
+}
+
+// Progress of Benchmark::run_checkpointed, saved as key=value lines
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchCheckpoint {
+    pub playout_cnt: usize,
+    pub done_cnt: usize,
+    pub random_seed: u32,
+    pub move_cnt: usize,
+    pub black_win_cnt: usize,
+    pub white_win_cnt: usize,
+    pub jigo_cnt: usize,
+    // Time spent in all sessions so far
+    pub seconds: f64,
+}
+
+impl BenchCheckpoint {
+    fn start(playout_cnt: usize, seed: u32) -> Self {
+        BenchCheckpoint {
+            playout_cnt,
+            done_cnt: 0,
+            random_seed: FastRandom::new(seed).seed(),
+            move_cnt: 0,
+            black_win_cnt: 0,
+            white_win_cnt: 0,
+            jigo_cnt: 0,
+            seconds: 0.0,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let field = |key: &str| {
+            text.lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                .ok_or_else(|| format!("{}: missing {}", path.display(), key))
+        };
+        let bad = |key: &str| format!("{}: bad {}", path.display(), key);
+        Ok(BenchCheckpoint {
+            playout_cnt: field("playouts")?.parse().map_err(|_| bad("playouts"))?,
+            done_cnt: field("done")?.parse().map_err(|_| bad("done"))?,
+            random_seed: field("random_seed")?
+                .parse()
+                .map_err(|_| bad("random_seed"))?,
+            move_cnt: field("moves")?.parse().map_err(|_| bad("moves"))?,
+            black_win_cnt: field("black_wins")?
+                .parse()
+                .map_err(|_| bad("black_wins"))?,
+            white_win_cnt: field("white_wins")?
+                .parse()
+                .map_err(|_| bad("white_wins"))?,
+            // Missing in checkpoints from before jigo was counted
+            jigo_cnt: match field("jigos") {
+                Ok(jigos) => jigos.parse().map_err(|_| bad("jigos"))?,
+                Err(_) => 0,
+            },
+            seconds: field("seconds")?.parse().map_err(|_| bad("seconds"))?,
+        })
+    }
+
+    // Written to a temporary file first, so an interrupted save keeps the
+    // previous checkpoint
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = format!(
+            "playouts={}\ndone={}\nrandom_seed={}\nmoves={}\nblack_wins={}\nwhite_wins={}\njigos={}\nseconds={}\n",
+            self.playout_cnt,
+            self.done_cnt,
+            self.random_seed,
+            self.move_cnt,
+            self.black_win_cnt,
+            self.white_win_cnt,
+            self.jigo_cnt,
+            self.seconds
+        );
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, text)
+            .and_then(|_| std::fs::rename(&tmp_path, path))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+// Policy of Benchmark::run and the other methods without _with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BenchPolicy {
+    // SamplerPolicy with the benchmark gammas
+    #[default]
+    Sampler,
+    Uniform,
+    Mogo,
+}
+
+// Where BenchmarkBuilder::build gets the gammas
+enum GammasSource {
+    Uniform,
+    Gammas(Gammas),
+    // Gammas::load of a text table
+    File(PathBuf),
+}
+
+// Start position of the playouts
+enum StartPosition {
+    Empty { width: usize, height: usize },
+    Board(Box<Board>),
+    // Final position of the main line
+    Sgf(String),
+    // BoardSnapshot rows, '#' black, 'O' white and '.' empty
+    Ascii { rows: Vec<String>, to_move: Player },
+}
+
+// Configures a Benchmark. The defaults are those of Benchmark::new: the empty
+// 9x9 board, uniform gammas, seed DEFAULT_BENCH_SEED and the Sampler policy.
+pub struct BenchmarkBuilder {
+    start: StartPosition,
+    gammas: GammasSource,
+    seed: u32,
+    policy: BenchPolicy,
+    lazy_atari: bool,
+    sampler_params: SamplerParams,
+}
+
+impl BenchmarkBuilder {
+    pub fn new() -> Self {
+        BenchmarkBuilder {
+            start: StartPosition::Empty {
+                width: 9,
+                height: 9,
+            },
+            gammas: GammasSource::Uniform,
+            seed: DEFAULT_BENCH_SEED,
+            policy: BenchPolicy::Sampler,
+            lazy_atari: false,
+            sampler_params: SamplerParams::default(),
+        }
+    }
+
+    // Empty board of the size, replacing any start position
+    pub fn board_size(mut self, width: usize, height: usize) -> Self {
+        self.start = StartPosition::Empty { width, height };
+        self
+    }
+
+    pub fn position(mut self, board: &Board) -> Self {
+        self.start = StartPosition::Board(Box::new(board.clone()));
+        self
+    }
+
+    // The position at the end of the SGF main line, with its size and komi
+    pub fn sgf(mut self, text: &str) -> Self {
+        self.start = StartPosition::Sgf(text.to_string());
+        self
+    }
+
+    // One row per line from the top, as in BoardSnapshot::rows, size taken
+    // from the rows
+    pub fn ascii(mut self, text: &str, to_move: Player) -> Self {
+        let rows = text
+            .lines()
+            .map(|line| line.split_whitespace().collect::<String>())
+            .filter(|row| !row.is_empty())
+            .collect();
+        self.start = StartPosition::Ascii { rows, to_move };
+        self
+    }
+
+    pub fn gammas(mut self, gammas: Gammas) -> Self {
+        self.gammas = GammasSource::Gammas(gammas);
+        self
+    }
+
+    // Gammas::load of the file when building
+    pub fn gammas_file(mut self, path: &Path) -> Self {
+        self.gammas = GammasSource::File(path.to_path_buf());
+        self
+    }
+
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn policy(mut self, policy: BenchPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    // See Benchmark::set_lazy_atari
+    pub fn lazy_atari(mut self, lazy: bool) -> Self {
+        self.lazy_atari = lazy;
+        self
+    }
+
+    // Constants of the Sampler policy
+    pub fn sampler_params(mut self, params: SamplerParams) -> Self {
+        self.sampler_params = params;
+        self
+    }
+
+    pub fn build(self) -> Result<Benchmark, String> {
+        let start_board = match self.start {
+            StartPosition::Empty { width, height } => {
+                Board::try_with_size(width, height).map_err(|e| e.to_string())?
+            }
+            StartPosition::Board(board) => *board,
+            StartPosition::Sgf(text) => parse_game(&text)
+                .map_err(|e| e.to_string())?
+                .final_position()
+                .map_err(|e| e.to_string())?,
+            StartPosition::Ascii { rows, to_move } => {
+                let snapshot = BoardSnapshot {
+                    width: rows.first().map_or(0, |row| row.chars().count()),
+                    height: rows.len(),
+                    komi: Board::new().komi(),
+                    rows,
+                    to_move,
+                    ko: Vertex::none(),
+                    move_cnt: 0,
+                };
+                Board::from_snapshot(&snapshot).map_err(|e| e.to_string())?
+            }
+        };
+        let gammas = match self.gammas {
+            GammasSource::Uniform => Gammas::new(),
+            GammasSource::Gammas(gammas) => gammas,
+            GammasSource::File(path) => Gammas::load(&path)?,
+        };
+
+        let mut bench = Benchmark {
+            runner: BenchRunner {
+                start_board: start_board.clone(),
+                board: start_board,
+                random: FastRandom::new(self.seed),
+                seed: self.seed,
+                move_count: 0,
+            },
+            gammas,
+            policy: self.policy,
+            sampler_params: self.sampler_params,
+        };
+        bench.set_lazy_atari(self.lazy_atari);
+        Ok(bench)
+    }
+}
+
+impl Default for BenchmarkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Measures playouts from the start position of BenchmarkBuilder with its
+// policy, or with any PlayoutPolicy through the _with methods
 pub struct Benchmark {
-    empty_board: Board,
+    runner: BenchRunner,
+    gammas: Gammas,
+    policy: BenchPolicy,
+    sampler_params: SamplerParams,
+}
+
+// The state of Benchmark apart from the gammas, so a policy can borrow them
+struct BenchRunner {
+    start_board: Board,
     board: Board,
     random: FastRandom,
-    gammas: Gammas,
+    seed: u32,
     move_count: usize,
 }
 
+// Evaluates body with the runner and the selected policy of a Benchmark. Every
+// policy gets its own copy of body, so playouts do not go through dyn calls.
+macro_rules! with_policy {
+    ($bench:expr, |$runner:ident, $policy:ident| $body:expr) => {
+        match $bench.policy {
+            BenchPolicy::Sampler => {
+                let mut $policy = $bench
+                    .sampler_params
+                    .policy(&$bench.runner.board, &$bench.gammas);
+                let $runner = &mut $bench.runner;
+                $body
+            }
+            BenchPolicy::Uniform => {
+                let mut $policy = UniformPolicy::new();
+                let $runner = &mut $bench.runner;
+                $body
+            }
+            BenchPolicy::Mogo => {
+                let mut $policy = MogoPolicy::new();
+                let $runner = &mut $bench.runner;
+                $body
+            }
+        }
+    };
+}
+
 impl Benchmark {
     pub fn new() -> Self {
-        let mut empty_board = Board::new();
-        empty_board.clear();
+        Self::builder().build().unwrap()
+    }
 
-        Benchmark {
-            empty_board: empty_board.clone(),
-            board: empty_board,
-            random: FastRandom::new(123),
-            gammas: Gammas::new(),
-            move_count: 0,
-        }
+    pub fn builder() -> BenchmarkBuilder {
+        BenchmarkBuilder::new()
     }
 
-    fn do_playouts(&mut self, playout_cnt: usize, win_cnt: &mut PlayerMap<usize>) {
-        let mut sampler = Sampler::new(&self.board, &self.gammas);
+    pub fn start_board(&self) -> &Board {
+        &self.runner.start_board
+    }
 
+    pub fn gammas(&self) -> &Gammas {
+        &self.gammas
+    }
+
+    // Playouts on boards with lazy atari bits, see Board::set_lazy_atari
+    pub fn set_lazy_atari(&mut self, lazy: bool) {
+        self.runner.start_board.set_lazy_atari(lazy);
+        self.runner.board.set_lazy_atari(lazy);
+    }
+
+    // Plays game_cnt playouts where policy A and B alternate colors (A is black in even games)
+    pub fn compare(
+        &mut self,
+        a: &PolicyConfig,
+        b: &PolicyConfig,
+        game_cnt: usize,
+    ) -> ComparisonResult {
+        let mut policy_a = a.policy(&self.runner.board);
+        let mut policy_b = b.policy(&self.runner.board);
+        self.runner.compare(&mut policy_a, &mut policy_b, game_cnt)
+    }
+
+    // compare of any two policies
+    pub fn compare_with<A: PlayoutPolicy, B: PlayoutPolicy>(
+        &mut self,
+        a: &mut A,
+        b: &mut B,
+        game_cnt: usize,
+    ) -> ComparisonResult {
+        self.runner.compare(a, b, game_cnt)
+    }
+
+    pub fn run(&mut self, playout_cnt: usize, expected_moves: Option<usize>) -> String {
+        with_policy!(self, |runner, policy| runner.run(
+            &mut policy,
+            playout_cnt,
+            expected_moves
+        ))
+    }
+
+    // run of another policy
+    pub fn run_with<P: PlayoutPolicy>(
+        &mut self,
+        policy: &mut P,
+        playout_cnt: usize,
+        expected_moves: Option<usize>,
+    ) -> String {
+        self.runner.run(policy, playout_cnt, expected_moves)
+    }
+
+    // Same measurement as run, as numbers
+    pub fn run_stats(&mut self, playout_cnt: usize) -> BenchmarkStats {
+        with_policy!(self, |runner, policy| runner
+            .run_stats(&mut policy, playout_cnt))
+    }
+
+    // run_stats from start instead of the start position of the builder,
+    // which is kept for later runs
+    pub fn run_from(&mut self, start: &Board, playout_cnt: usize) -> BenchmarkStats {
+        let builder_start = std::mem::replace(&mut self.runner.start_board, start.clone());
+        let stats = self.run_stats(playout_cnt);
+        self.runner.start_board = builder_start;
+        stats
+    }
+
+    pub fn run_stats_with<P: PlayoutPolicy>(
+        &mut self,
+        policy: &mut P,
+        playout_cnt: usize,
+    ) -> BenchmarkStats {
+        self.runner.run_stats(policy, playout_cnt)
+    }
+
+    // run_cnt measured runs of run_stats after WARMUP_RUNS discarded ones,
+    // which fill caches and let the CPU clock settle. Every run plays the same
+    // playouts.
+    pub fn run_repeated(&mut self, run_cnt: usize, playout_cnt: usize) -> RepeatedStats {
+        with_policy!(self, |runner, policy| runner.run_repeated(
+            &mut policy,
+            run_cnt,
+            playout_cnt
+        ))
+    }
+
+    pub fn run_repeated_with<P: PlayoutPolicy>(
+        &mut self,
+        policy: &mut P,
+        run_cnt: usize,
+        playout_cnt: usize,
+    ) -> RepeatedStats {
+        self.runner.run_repeated(policy, run_cnt, playout_cnt)
+    }
+
+    // run_stats saving a checkpoint to path every interval playouts. With resume
+    // an existing checkpoint of the same playout count is continued, giving the
+    // same move and win counts as an uninterrupted run. Perf counters are not
+    // used, seconds add up the time of all sessions.
+    pub fn run_checkpointed(
+        &mut self,
+        playout_cnt: usize,
+        path: &Path,
+        interval: usize,
+        resume: bool,
+    ) -> Result<BenchmarkStats, String> {
+        with_policy!(self, |runner, policy| runner.run_checkpointed(
+            &mut policy,
+            playout_cnt,
+            path,
+            interval,
+            resume
+        ))
+    }
+}
+
+impl BenchRunner {
+    fn do_playouts<P: PlayoutPolicy>(
+        &mut self,
+        policy: &mut P,
+        playout_cnt: usize,
+        outcomes: &mut OutcomeCounts,
+    ) {
         for _i in 0..playout_cnt {
-            self.board.load(&self.empty_board);
-            sampler.new_playout(&self.board, &self.gammas);
+            self.board.load(&self.start_board);
+            policy.new_playout(&self.board);
 
             while !self.board.both_player_pass() {
                 let pl = self.board.act_player();
-                let v = sampler.sample_move(&self.board, &mut self.random);
+                let v = policy.sample(&self.board, &mut self.random);
 
                 self.board.play_legal(pl, v);
-                sampler.move_played(&self.board, &self.gammas);
+                policy.move_played(&self.board);
             }
 
-            let winner = self.board.playout_winner();
-            win_cnt[winner] += 1;
+            outcomes.add(self.board.playout_outcome());
             self.move_count += self.board.move_count();
         }
     }
 
-    pub fn run(&mut self, playout_cnt: usize, expected_moves: Option<usize>) -> String {
+    fn compare<A: PlayoutPolicy, B: PlayoutPolicy>(
+        &mut self,
+        a: &mut A,
+        b: &mut B,
+        game_cnt: usize,
+    ) -> ComparisonResult {
+        self.random = FastRandom::new(self.seed);
+        let mut a_win_cnt = 0;
+        let mut jigo_cnt = 0;
+
+        for game_idx in 0..game_cnt {
+            let a_player = if game_idx % 2 == 0 {
+                Player::Black
+            } else {
+                Player::White
+            };
+
+            self.board.load(&self.start_board);
+            a.new_playout(&self.board);
+            b.new_playout(&self.board);
+
+            while !self.board.both_player_pass() {
+                let pl = self.board.act_player();
+                let v = if pl == a_player {
+                    a.sample(&self.board, &mut self.random)
+                } else {
+                    b.sample(&self.board, &mut self.random)
+                };
+
+                self.board.play_legal(pl, v);
+                a.move_played(&self.board);
+                b.move_played(&self.board);
+            }
+
+            match self.board.playout_outcome().winner() {
+                Some(winner) if winner == a_player => a_win_cnt += 1,
+                Some(_) => {}
+                None => jigo_cnt += 1,
+            }
+        }
+
+        ComparisonResult {
+            game_cnt,
+            a_win_cnt,
+            jigo_cnt,
+        }
+    }
+
+    fn run<P: PlayoutPolicy>(
+        &mut self,
+        policy: &mut P,
+        playout_cnt: usize,
+        expected_moves: Option<usize>,
+    ) -> String {
+        let stats = self.run_stats(policy, playout_cnt);
+
+        // Assert expected move count if provided
+        assert_eq!(expected_moves.unwrap_or(stats.move_cnt), stats.move_cnt);
+
+        let perf_cc_per_move = match stats.perf_cc_per_move {
+            Some(cc) => format!("{:.1}", cc),
+            None => "N/A".to_string(),
+        };
+        let mut extra = String::new();
+        if let Some(misses) = stats.perf_cache_misses_per_move {
+            extra += &format!("\nCache misses/move (perf counter): {:.2}", misses);
+        }
+        if let Some(allocs) = stats.allocs_per_playout {
+            extra += &format!("\nAllocations/playout: {:.3}", allocs);
+        }
+        format!(
+            "\n{} playouts \n\
+             in {:.6} seconds => {:.3} kpps\n\
+             CC/move (time*freq, perf counter): {:.1} / {}  @  CPU freq: {:.3} GHz ({})\n\
+             {}/{}/{} (black wins / white wins / jigo)\n\
+             AVG moves/playout = {:.6}{}",
+            stats.playout_cnt,
+            stats.seconds,
+            stats.kpps,
+            stats.cc_per_move,
+            perf_cc_per_move,
+            stats.cpu_freq_ghz,
+            stats.freq_source,
+            stats.black_win_cnt,
+            stats.white_win_cnt,
+            stats.jigo_cnt,
+            stats.move_cnt as f32 / stats.playout_cnt as f32,
+            extra
+        )
+    }
+
+    fn run_stats<P: PlayoutPolicy>(
+        &mut self,
+        policy: &mut P,
+        playout_cnt: usize,
+    ) -> BenchmarkStats {
         self.move_count = 0;
-        self.random = FastRandom::new(123);
+        self.random = FastRandom::new(self.seed);
 
-        let mut win_cnt = PlayerMap::<usize>::new();
-        win_cnt[Player::Black] = 0;
-        win_cnt[Player::White] = 0;
+        let mut outcomes = OutcomeCounts::default();
 
         // Initialize perf counter
         let mut perf_counter = PerfCounter::new();
+        let mut cache_miss_counter = PerfCounter::cache_misses();
 
         // Start both timing methods
         perf_counter.start();
+        cache_miss_counter.start();
+        let start_alloc_cnt = thread_alloc_cnt();
         let start = Instant::now();
 
-        self.do_playouts(playout_cnt, &mut win_cnt);
+        self.do_playouts(policy, playout_cnt, &mut outcomes);
 
         // Stop timing and read counter
         let duration = start.elapsed();
         // Stop and then read the perf counter
         perf_counter.stop();
+        cache_miss_counter.stop();
         let perf_cycles = perf_counter.read();
+        let cache_misses = cache_miss_counter.read();
+        let alloc_cnt = thread_alloc_cnt()
+            .zip(start_alloc_cnt)
+            .map(|(end, start)| end - start);
 
         let seconds_total = duration.as_secs_f32();
-        let playouts_finished = win_cnt[Player::Black] + win_cnt[Player::White];
         let kpps = (playout_cnt as f32) / seconds_total / 1000.0;
 
-        // Try to read CPU frequency
-        let cpu_freq_ghz = get_cpu_frequency_ghz();
+        let (cpu_freq_ghz, freq_source) = cpu_frequency_ghz();
         let total_clock_cycles = seconds_total as f64 * cpu_freq_ghz * 1e9;
-        let cc_per_move = total_clock_cycles / self.move_count as f64;
 
-        // Calculate CC/move from perf counter if valid
-        let perf_cc_per_move = if perf_counter.is_valid() {
-            format!("{:.1}", perf_cycles as f64 / self.move_count as f64)
+        BenchmarkStats {
+            playout_cnt,
+            move_cnt: self.move_count,
+            seconds: seconds_total,
+            kpps,
+            cc_per_move: total_clock_cycles / self.move_count as f64,
+            // CC/move from perf counter if valid
+            perf_cc_per_move: perf_counter
+                .is_valid()
+                .then(|| perf_cycles as f64 / self.move_count as f64),
+            perf_cache_misses_per_move: cache_miss_counter
+                .is_valid()
+                .then(|| cache_misses as f64 / self.move_count as f64),
+            cpu_freq_ghz,
+            freq_source,
+            allocs_per_playout: alloc_cnt.map(|cnt| cnt as f64 / playout_cnt as f64),
+            black_win_cnt: outcomes.black_win_cnt,
+            white_win_cnt: outcomes.white_win_cnt,
+            jigo_cnt: outcomes.jigo_cnt,
+        }
+    }
+
+    fn run_repeated<P: PlayoutPolicy>(
+        &mut self,
+        policy: &mut P,
+        run_cnt: usize,
+        playout_cnt: usize,
+    ) -> RepeatedStats {
+        assert!(run_cnt > 0, "run_repeated needs at least one run");
+        for _ in 0..WARMUP_RUNS {
+            self.run_stats(policy, playout_cnt);
+        }
+        let runs: Vec<BenchmarkStats> = (0..run_cnt)
+            .map(|_| self.run_stats(policy, playout_cnt))
+            .collect();
+        let kpps: Vec<f64> = runs.iter().map(|stats| stats.kpps as f64).collect();
+        let cc_per_move: Vec<f64> = runs
+            .iter()
+            .map(|stats| stats.perf_cc_per_move.unwrap_or(stats.cc_per_move))
+            .collect();
+        RepeatedStats {
+            kpps: Spread::of(&kpps),
+            cc_per_move: Spread::of(&cc_per_move),
+            runs,
+        }
+    }
+
+    fn run_checkpointed<P: PlayoutPolicy>(
+        &mut self,
+        policy: &mut P,
+        playout_cnt: usize,
+        path: &Path,
+        interval: usize,
+        resume: bool,
+    ) -> Result<BenchmarkStats, String> {
+        assert!(interval > 0, "checkpoint interval must be positive");
+        let mut checkpoint = if resume && path.exists() {
+            let checkpoint = BenchCheckpoint::load(path)?;
+            if checkpoint.playout_cnt != playout_cnt {
+                return Err(format!(
+                    "{}: checkpoint of a {} playout run",
+                    path.display(),
+                    checkpoint.playout_cnt
+                ));
+            }
+            checkpoint
         } else {
-            "N/A".to_string()
+            BenchCheckpoint::start(playout_cnt, self.seed)
         };
 
-        let avg_moves = self.move_count as f32 / playouts_finished as f32;
+        while checkpoint.done_cnt < playout_cnt {
+            let cnt = interval.min(playout_cnt - checkpoint.done_cnt);
+            self.random = FastRandom::new(checkpoint.random_seed);
+            self.move_count = 0;
+            let mut outcomes = OutcomeCounts::default();
+            let start = Instant::now();
+            self.do_playouts(policy, cnt, &mut outcomes);
 
-        // Assert expected move count if provided
-        assert_eq!(
-            expected_moves.unwrap_or(self.move_count as usize),
-            self.move_count as usize
-        );
+            checkpoint.seconds += start.elapsed().as_secs_f64();
+            checkpoint.done_cnt += cnt;
+            checkpoint.random_seed = self.random.seed();
+            checkpoint.move_cnt += self.move_count;
+            checkpoint.black_win_cnt += outcomes.black_win_cnt;
+            checkpoint.white_win_cnt += outcomes.white_win_cnt;
+            checkpoint.jigo_cnt += outcomes.jigo_cnt;
+            checkpoint.save(path)?;
+        }
 
-        format!(
-            "\n{} playouts \n\
-             in {:.6} seconds => {:.3} kpps\n\
-             CC/move (time*freq, perf counter): {:.1} / {}  @  CPU freq: {:.3} GHz\n\
-             {}/{} (black wins / white wins)\n\
-             AVG moves/playout = {:.6}",
+        let (cpu_freq_ghz, freq_source) = cpu_frequency_ghz();
+        let seconds = checkpoint.seconds.max(f64::MIN_POSITIVE);
+        Ok(BenchmarkStats {
             playout_cnt,
-            seconds_total,
-            kpps,
-            cc_per_move,
-            perf_cc_per_move,
+            move_cnt: checkpoint.move_cnt,
+            seconds: seconds as f32,
+            kpps: (playout_cnt as f64 / seconds / 1000.0) as f32,
+            cc_per_move: seconds * cpu_freq_ghz * 1e9 / checkpoint.move_cnt as f64,
+            perf_cc_per_move: None,
+            perf_cache_misses_per_move: None,
             cpu_freq_ghz,
-            win_cnt[Player::Black],
-            win_cnt[Player::White],
-            avg_moves
-        )
+            freq_source,
+            allocs_per_playout: None,
+            black_win_cnt: checkpoint.black_win_cnt,
+            white_win_cnt: checkpoint.white_win_cnt,
+            jigo_cnt: checkpoint.jigo_cnt,
+        })
     }
 }
 
-fn get_cpu_frequency_ghz() -> f64 {
-    // Try to read current CPU frequency from /sys
-    if let Ok(contents) =
-        std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_cur_freq")
-    {
-        if let Ok(freq_khz) = contents.trim().parse::<f64>() {
-            return freq_khz / 1_000_000.0; // Convert kHz to GHz
-        }
+// Nanoseconds per Board::clone and per Board::copy_from of a midgame position
+pub fn board_copy_cost(copy_cnt: usize) -> (f64, f64) {
+    let mut board = Board::new();
+    let mut random = FastRandom::new(123);
+    let gammas = Gammas::new();
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.new_playout(&board, &gammas);
+    for _ in 0..40 {
+        let v = sampler.sample_move(&board, &mut random);
+        board.play_legal(board.act_player(), v);
+        sampler.move_played(&board, &gammas);
     }
 
-    // Fallback
-    if let Ok(contents) =
-        std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_cur_freq")
-    {
-        if let Ok(freq_khz) = contents.trim().parse::<f64>() {
-            return freq_khz / 1_000_000.0;
-        }
+    let start = Instant::now();
+    let mut move_sum = 0;
+    for _ in 0..copy_cnt {
+        let copy = std::hint::black_box(&board).clone();
+        move_sum += std::hint::black_box(copy).move_count();
+    }
+    let clone_ns = start.elapsed().as_nanos() as f64 / copy_cnt as f64;
+
+    let mut target = Board::new();
+    let start = Instant::now();
+    for _ in 0..copy_cnt {
+        target.copy_from(std::hint::black_box(&board));
+        move_sum += std::hint::black_box(&target).move_count();
+    }
+    let copy_from_ns = start.elapsed().as_nanos() as f64 / copy_cnt as f64;
+
+    assert_eq!(move_sum, 2 * copy_cnt * board.move_count());
+    (clone_ns, copy_from_ns)
+}
+
+impl Default for Benchmark {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    // Default fallback
+// The calibrated TSC frequency when there is one, else the sysfs reading.
+// Under turbo and per-core scaling sysfs shows cpu0 at the moment of the read,
+// while the TSC ticks at the constant nominal rate, so time*freq is a stable
+// unit across runs; perf_cc_per_move has the real core cycles.
+fn cpu_frequency_ghz() -> (f64, FreqSource) {
+    if let Some(ghz) = tsc_frequency_ghz() {
+        return (ghz, FreqSource::Tsc);
+    }
+    if let Some(ghz) = sysfs_frequency_ghz() {
+        return (ghz, FreqSource::Sysfs);
+    }
     eprintln!("Warning: Could not read CPU frequency, assuming 1.0 GHz");
-    1.0
+    (1.0, FreqSource::Assumed)
+}
+
+lazy_static::lazy_static! {
+    static ref TSC_FREQUENCY_GHZ: Option<f64> = calibrate_tsc();
+}
+
+// TSC ticks per nanosecond, calibrated once per process. None off x86_64 or
+// without an invariant TSC.
+pub fn tsc_frequency_ghz() -> Option<f64> {
+    *TSC_FREQUENCY_GHZ
+}
+
+#[cfg(target_arch = "x86_64")]
+fn calibrate_tsc() -> Option<f64> {
+    use std::arch::x86_64::{__cpuid, _rdtsc};
+    let max_extended_leaf = __cpuid(0x8000_0000).eax;
+    let invariant_tsc =
+        max_extended_leaf >= 0x8000_0007 && __cpuid(0x8000_0007).edx & (1 << 8) != 0;
+    if !invariant_tsc {
+        return None;
+    }
+    // Median of a few 10ms windows, robust to a preemption in one of them
+    let mut ghz: Vec<f64> = (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            // SAFETY: rdtsc is available on every x86_64 CPU
+            let start_tsc = unsafe { _rdtsc() };
+            while start.elapsed().as_micros() < 10_000 {}
+            let tsc = unsafe { _rdtsc() } - start_tsc;
+            tsc as f64 / start.elapsed().as_nanos() as f64
+        })
+        .collect();
+    ghz.sort_by(f64::total_cmp);
+    Some(ghz[ghz.len() / 2])
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn calibrate_tsc() -> Option<f64> {
+    None
+}
+
+fn sysfs_frequency_ghz() -> Option<f64> {
+    ["scaling_cur_freq", "cpuinfo_cur_freq"]
+        .iter()
+        .find_map(|file| {
+            let path = format!("/sys/devices/system/cpu/cpu0/cpufreq/{}", file);
+            let freq_khz = std::fs::read_to_string(path)
+                .ok()?
+                .trim()
+                .parse::<f64>()
+                .ok()?;
+            Some(freq_khz / 1_000_000.0)
+        })
 }
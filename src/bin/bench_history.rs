@@ -0,0 +1,109 @@
+use go_game_board::{BenchHistory, BenchRecord, Benchmark, Config};
+use std::path::Path;
+
+// Usage: bench_history <history.csv> [playouts] [max-regression-percent]
+//                      [--checkpoint <file>] [--resume] [--lazy-atari]
+//                      [--config <file>]
+// Runs the playout benchmark, compares it with the best earlier run in the file
+// and appends the result. Exits with 1 on a throughput regression. With
+// --checkpoint progress is saved every 10000 playouts and --resume continues an
+// interrupted run. --lazy-atari measures boards with Board::set_lazy_atari, kept
+// as a separate config in the history. --config takes the board size, seed,
+// playouts, gammas and sampler constants from a config::Config TOML file,
+// recorded as a config named after the file.
+fn main() {
+    let mut args: Vec<String> = std::env::args().collect();
+    let resume = take_flag(&mut args, "--resume");
+    let lazy_atari = take_flag(&mut args, "--lazy-atari");
+    let checkpoint = take_option(&mut args, "--checkpoint");
+    let config_path = take_option(&mut args, "--config");
+    if !(2..=4).contains(&args.len()) || (resume && checkpoint.is_none()) {
+        usage(&args[0]);
+    }
+    let path = Path::new(&args[1]);
+    let config = match &config_path {
+        Some(path) => Config::load(Path::new(path)).unwrap_or_else(|e| fail(&e)),
+        None => Config::default(),
+    };
+    let playout_cnt: usize = args.get(2).map_or(config.bench.playout_cnt, |s| {
+        s.parse().expect("playouts must be a number")
+    });
+    let max_regression: f64 = args.get(3).map_or(5.0, |s| {
+        s.parse().expect("max-regression-percent must be a number")
+    });
+
+    let history = BenchHistory::load(path).unwrap_or_else(|e| fail(&e));
+
+    let size = config.bench.board_size;
+    let mut builder = Benchmark::builder()
+        .board_size(size, size)
+        .seed(config.bench.seed)
+        .sampler_params(config.sampler)
+        .lazy_atari(lazy_atari);
+    if let Some(gammas_path) = &config.gammas_path {
+        builder = builder.gammas_file(gammas_path);
+    }
+    let mut bench = builder.build().unwrap_or_else(|e| fail(&e));
+    let stats = match checkpoint {
+        Some(checkpoint) => bench
+            .run_checkpointed(playout_cnt, Path::new(&checkpoint), 10000, resume)
+            .unwrap_or_else(|e| fail(&e)),
+        None => bench.run_stats(playout_cnt),
+    };
+    let name = match &config_path {
+        Some(path) => Path::new(path)
+            .file_stem()
+            .map_or("config".into(), |stem| stem.to_string_lossy()),
+        None => "9x9-uniform".into(),
+    };
+    let record_config = if lazy_atari {
+        format!("{}-lazy-atari-{}", name, playout_cnt)
+    } else {
+        format!("{}-{}", name, playout_cnt)
+    };
+    let record = BenchRecord::new(&record_config, &stats);
+    let check = history.check(&record, max_regression / 100.0);
+
+    if let Err(e) = BenchHistory::append(path, &record) {
+        fail(&e);
+    }
+    match check {
+        Ok(report) => println!("{}", report),
+        Err(report) => {
+            eprintln!("{}", report);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let len = args.len();
+    args.retain(|a| a != flag);
+    args.len() != len
+}
+
+// Value of "name <value>" removed from args, None without name
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    match args.iter().position(|a| a == name) {
+        Some(idx) if idx + 1 < args.len() => {
+            let value = args.remove(idx + 1);
+            args.remove(idx);
+            Some(value)
+        }
+        Some(_) => usage(&args[0]),
+        None => None,
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1);
+}
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} <history.csv> [playouts] [max-regression-percent] [--checkpoint <file>] [--resume] [--lazy-atari] [--config <file>]",
+        program
+    );
+    std::process::exit(1);
+}
@@ -0,0 +1,207 @@
+use go_game_board::{
+    run_regression, Config, Gammas, Gtp, GtpState, MonteCarloEngine, RegressionOutcome,
+    RegressionSummary, SamplerParams, DEFAULT_BOARD_SIZE,
+};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Usage: gtp [playouts-per-move] [--config <file>] [--gammas <file>] [--params <file>]
+//            [regress <tst-file>...]
+// Flat Monte Carlo engine speaking GTP on stdin/stdout, 1000 playouts by default.
+// --config reads the rules, komi, gammas, sampler constants and the [gtp]
+// settings from a config::Config TOML file, the other arguments override it.
+// With --gammas the gammas are read from a Gammas::to_text file, which is
+// reloaded before the next command whenever it changes, keeping the game.
+// --params reads the sampler constants from a SamplerParams::to_text file,
+// e.g. written by the tune binary.
+// regress runs GNU Go style regression files instead, see regress.rs, and
+// exits with 1 if any test failed. With the tracing feature, spans and events
+// go to stderr at the level of the GTP_LOG environment variable, info (the
+// per-move search summaries) by default; debug adds the timings of commands
+// and searches, trace those of playout batches.
+fn main() {
+    #[cfg(feature = "tracing")]
+    init_tracing();
+    let mut args: Vec<String> = std::env::args().collect();
+    let regress_files = args
+        .iter()
+        .position(|a| a == "regress")
+        .map(|idx| args.split_off(idx).split_off(1));
+    let mut config = match take_option(&mut args, "--config") {
+        Some(path) => Config::load(Path::new(&path)).unwrap_or_else(|e| fail(&e)),
+        None => Config::default(),
+    };
+    if let Some(path) = take_option(&mut args, "--gammas") {
+        config.gammas_path = Some(PathBuf::from(path));
+    }
+    if let Some(path) = take_option(&mut args, "--params") {
+        config.sampler = SamplerParams::load(Path::new(&path)).unwrap_or_else(|e| fail(&e));
+    }
+    match args.get(1).map(|arg| arg.parse::<usize>()) {
+        None => {}
+        Some(Ok(cnt)) if cnt > 0 && args.len() == 2 => config.gtp.playout_cnt = cnt,
+        Some(_) => usage(&args[0]),
+    }
+
+    let mut gammas = config.gammas().unwrap_or_else(|e| fail(&e));
+    if let Some(files) = regress_files {
+        regress(&gammas, &config, &files);
+    }
+    let gammas_path = config.gammas_path.as_deref();
+    let mut loaded_at = gammas_path.and_then(modified);
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    // The game carried over a reload, and the command that was waiting for it
+    let mut restore: Option<GtpState> = None;
+    let mut pending: Option<String> = None;
+
+    loop {
+        let mut gtp = new_gtp(&gammas, &config);
+        if let Some(state) = restore.take() {
+            gtp.restore(state);
+        }
+        let (next_gammas, state) = loop {
+            let line = match pending.take() {
+                Some(line) => line,
+                None => match lines.next() {
+                    Some(line) => line.expect("Failed to read GTP"),
+                    None => return,
+                },
+            };
+            if let Some(path) = gammas_path {
+                let modified_at = modified(path);
+                if modified_at != loaded_at {
+                    loaded_at = modified_at;
+                    match Gammas::load(path) {
+                        Ok(next_gammas) => {
+                            eprintln!("Reloaded gammas from {}", path.display());
+                            pending = Some(line);
+                            break (next_gammas, gtp.into_state());
+                        }
+                        // E.g. caught in the middle of a write, retried on the next change
+                        Err(e) => eprintln!("Keeping the old gammas: {}", e),
+                    }
+                }
+            }
+            if let Some(response) = gtp.response(&line) {
+                stdout
+                    .write_all(response.as_bytes())
+                    .and_then(|_| stdout.flush())
+                    .expect("Failed to talk GTP");
+            }
+            if gtp.has_quit() {
+                return;
+            }
+        };
+        restore = Some(state);
+        gammas = next_gammas;
+    }
+}
+
+// Each file on a fresh engine, printing the tests that did not pass
+fn regress(gammas: &Gammas, config: &Config, files: &[String]) -> ! {
+    if files.is_empty() {
+        usage("gtp");
+    }
+    let mut total = RegressionSummary::default();
+    for file in files {
+        let text =
+            std::fs::read_to_string(file).unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+        let mut gtp = new_gtp(gammas, config);
+        let base_dir = Path::new(file).parent().unwrap_or(Path::new("."));
+        let results = run_regression(&mut gtp, &text, base_dir);
+        for result in results.iter() {
+            let label = match result.outcome {
+                RegressionOutcome::Pass | RegressionOutcome::KnownFail => continue,
+                RegressionOutcome::Fail => "FAILED",
+                RegressionOutcome::UnexpectedPass => "PASSED",
+            };
+            println!(
+                "{}:{} {} {}, expected [{}], got {}",
+                file, result.id, label, result.command, result.expected, result.answer
+            );
+        }
+        let summary = RegressionSummary::of_results(&results);
+        println!("{}: {}", file, summary);
+        total.merge(&summary);
+    }
+    if files.len() > 1 {
+        println!("Total: {}", total);
+    }
+    std::process::exit(if total.failed > 0 { 1 } else { 0 });
+}
+
+// Engine and game as configured
+fn new_gtp<'a>(gammas: &'a Gammas, config: &Config) -> Gtp<'a> {
+    let mut engine = MonteCarloEngine::new(gammas, config.gtp.playout_cnt, config.gtp.seed);
+    engine.set_thread_count(config.gtp.thread_cnt);
+    engine.set_sampler_params(&config.sampler);
+    let mut gtp = Gtp::new(gammas, Box::new(engine), config.rules);
+    if config.gtp.board_size != DEFAULT_BOARD_SIZE {
+        let response = gtp
+            .response(&format!("boardsize {}", config.gtp.board_size))
+            .unwrap_or_default();
+        if !response.starts_with('=') {
+            fail(&format!(
+                "board_size {}: {}",
+                config.gtp.board_size,
+                response.trim()
+            ));
+        }
+    }
+    gtp
+}
+
+// Value of "name <value>" removed from args, None without name
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    match args.iter().position(|a| a == name) {
+        Some(idx) if idx + 1 < args.len() => {
+            let value = args.remove(idx + 1);
+            args.remove(idx);
+            Some(value)
+        }
+        Some(_) => usage(&args[0]),
+        None => None,
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn init_tracing() {
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    let level = std::env::var("GTP_LOG")
+        .ok()
+        .and_then(|level| level.parse::<tracing::Level>().ok())
+        .unwrap_or(tracing::Level::INFO);
+    // Span timings at debug and trace
+    let span_events = if level >= tracing::Level::DEBUG {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    };
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(level)
+        .with_span_events(span_events)
+        .init();
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1);
+}
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} [playouts-per-move] [--config <file>] [--gammas <file>] [--params <file>] [regress <tst-file>...]",
+        program
+    );
+    std::process::exit(1);
+}
@@ -0,0 +1,64 @@
+use go_game_board::sgf;
+use go_game_board::PatternStats;
+use std::path::{Path, PathBuf};
+
+// Usage: pattern_stats <sgf-directory>
+// Prints the 3x3 and 5x5 pattern frequency tables of all *.sgf files found recursively.
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 2 {
+        eprintln!("Usage: {} <sgf-directory>", args[0]);
+        std::process::exit(1);
+    }
+
+    let mut files = Vec::new();
+    collect_sgf_files(Path::new(&args[1]), &mut files);
+    files.sort();
+
+    let mut stats = PatternStats::new();
+    for path in files.iter() {
+        let result = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| sgf::parse_game(&text).map_err(|e| e.to_string()))
+            .and_then(|game| {
+                let replayed = stats.add_game(&game).map_err(|e| e.to_string())?;
+                if replayed < game.moves.len() {
+                    eprintln!(
+                        "{}: illegal move {}, rest of the game skipped",
+                        path.display(),
+                        replayed + 1
+                    );
+                }
+                Ok(())
+            });
+        if let Err(e) = result {
+            eprintln!("{}: {}", path.display(), e);
+        }
+    }
+
+    let stdout = std::io::stdout();
+    stats
+        .write_table(&mut stdout.lock())
+        .expect("Failed to write pattern table");
+}
+
+fn collect_sgf_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}: {}", dir.display(), e);
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sgf_files(&path, files);
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("sgf"))
+        {
+            files.push(path);
+        }
+    }
+}
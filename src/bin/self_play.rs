@@ -0,0 +1,74 @@
+use go_game_board::{self_play_with_params, Config, Gammas, TrainingWriter};
+use std::path::Path;
+
+// Usage: self_play <games> <output> [gammas-file] [--config <file>]
+// Plays Sampler self-play games, 9x9 by default, and writes MM training data,
+// one record per move with the patterns of all legal moves, see
+// training::TrainingWriter. An output ending in .gz is gzip compressed (gzip
+// feature). --config takes the board size, seed, gammas and sampler constants
+// from a config::Config TOML file, a gammas-file argument overrides its gammas.
+fn main() {
+    let mut args: Vec<String> = std::env::args().collect();
+    let config = match args.iter().position(|a| a == "--config") {
+        Some(idx) if idx + 1 < args.len() => {
+            let path = args.remove(idx + 1);
+            args.remove(idx);
+            Config::load(Path::new(&path)).unwrap_or_else(|e| fail(&e))
+        }
+        Some(_) => usage(&args[0]),
+        None => Config::default(),
+    };
+    if args.len() != 3 && args.len() != 4 {
+        usage(&args[0]);
+    }
+    let Ok(game_cnt) = args[1].parse::<usize>() else {
+        usage(&args[0]);
+    };
+    let gammas = match args.get(3) {
+        Some(path) => Gammas::load(Path::new(path)),
+        None => config.gammas(),
+    }
+    .unwrap_or_else(|e| fail(&e));
+    let mut writer = TrainingWriter::create(Path::new(&args[2])).unwrap_or_else(|e| fail(&e));
+
+    let mut write_error = None;
+    let size = config.self_play.board_size;
+    let seed = config.self_play.seed;
+    self_play_with_params(
+        &gammas,
+        &config.sampler,
+        size,
+        size,
+        game_cnt,
+        seed,
+        |position| {
+            if write_error.is_none() {
+                write_error = writer.write(&position).err();
+            }
+        },
+    );
+    if let Some(e) = write_error {
+        fail(&format!("Failed to write {}: {}", args[2], e));
+    }
+    eprintln!(
+        "{} positions of {} games",
+        writer.position_count(),
+        game_cnt
+    );
+    writer
+        .finish()
+        .unwrap_or_else(|e| fail(&format!("Failed to write {}: {}", args[2], e)));
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1);
+}
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} <games> <output> [gammas-file] [--config <file>]",
+        program
+    );
+    std::process::exit(1);
+}
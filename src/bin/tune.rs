@@ -0,0 +1,49 @@
+use go_game_board::{Benchmark, Gammas, SamplerParams, SpsaConfig, SpsaTuner};
+use std::path::Path;
+
+// Usage: tune <iterations> <output> [gammas-file]
+// Tunes the sampler constants by SPSA on 9x9 playout policy games, starting
+// from the output file if it exists, and saves them there after every 10
+// iterations, for gtp --params.
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 && args.len() != 4 {
+        usage(&args[0]);
+    }
+    let Ok(iteration_cnt) = args[1].parse::<usize>() else {
+        usage(&args[0]);
+    };
+    let output = Path::new(&args[2]);
+    let gammas = match args.get(3) {
+        Some(path) => Gammas::load(Path::new(path)).unwrap_or_else(|e| fail(&e)),
+        None => Gammas::new(),
+    };
+    let start = if output.exists() {
+        SamplerParams::load(output).unwrap_or_else(|e| fail(&e))
+    } else {
+        SamplerParams::default()
+    };
+    let mut bench = Benchmark::builder()
+        .board_size(9, 9)
+        .build()
+        .unwrap_or_else(|e| fail(&e));
+
+    let mut tuner = SpsaTuner::new(start, SpsaConfig::default());
+    for idx in 0..iteration_cnt {
+        let result = tuner.step(&mut bench, &gammas);
+        eprintln!("{}: {} {:?}", idx + 1, result, tuner.params());
+        if (idx + 1) % 10 == 0 || idx + 1 == iteration_cnt {
+            tuner.params().save(output).unwrap_or_else(|e| fail(&e));
+        }
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1);
+}
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} <iterations> <output> [gammas-file]", program);
+    std::process::exit(1);
+}
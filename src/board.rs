@@ -1,12 +1,34 @@
-use crate::hash::{Hash, Hash3x3, ZOBRIST};
+use crate::error::BoardError;
+use crate::fast_random::FastRandom;
+use crate::gammas::Gammas;
+use crate::hash::{Hash, Hash3x3, CONTEXT_ZOBRIST, ZOBRIST};
 use crate::nat_set::NatSet;
+use crate::observer::BoardObserver;
+use crate::playout::{estimate_score, ScoreEstimate};
+use crate::score::{PlayoutOutcome, Score};
 use crate::types::{
-    color_is_player, color_to_player, color_to_showboard_char, vertex_nbr, vertex_of_coords_full,
-    Color, Dir, Nat, Player, PlayerMap, Vertex, VertexMap, MAX_BOARD_SIZE,
+    check_komi, color_is_player, color_to_player, color_to_showboard_char, vertex_nbr,
+    vertex_of_coords_full, vertex_to_gtp, Color, Coord, Dir, Move, Nat, PackedVertex, Player,
+    PlayerMap, Vertex, VertexMap, DEFAULT_BOARD_SIZE, MAX_BOARD_SIZE, MAX_KOMI,
 };
 use arrayvec::ArrayVec;
 
 const K_AREA: usize = MAX_BOARD_SIZE * MAX_BOARD_SIZE;
+// A chain slot for every point that can hold a stone, after the sentinel
+const CHAIN_SLOTS: usize = K_AREA + 1;
+// Chain slot of empty and off-board points. Its data is a dummy that takes
+// the liberty updates of those points, so updates need no branch on color.
+const NO_CHAIN: u16 = 0;
+
+// Chain::shift_lib delta of a stone placed next to a point of each color
+const LIB_DELTA: [u32; Color::COUNT] = [u32::MAX, u32::MAX, 1, u32::MAX];
+
+// Dir::all() split in its direct and diagonal halves
+const SIMPLE4: [Dir; 4] = [Dir::N, Dir::E, Dir::S, Dir::W];
+const DIAGONAL4: [Dir; 4] = [Dir::NW, Dir::NE, Dir::SE, Dir::SW];
+
+// The neighbor colors of a Hash3x3, without the atari bits
+const COLOR_BITS: usize = 0xffff;
 
 // Neighbor counter using bitfield like C++
 #[derive(Copy, Clone, Debug)]
@@ -60,7 +82,7 @@ impl NbrCounter {
         ];
         self.bitfield = self
             .bitfield
-            .wrapping_sub(player_inc_tab[usize::from(player) as usize]);
+            .wrapping_sub(player_inc_tab[usize::from(player)]);
     }
 
     pub fn off_board_inc(&mut self) {
@@ -78,35 +100,24 @@ impl NbrCounter {
     #[allow(dead_code)]
     pub fn player_cnt(&self, pl: Player) -> u32 {
         let f_mask = (1 << Self::F_SIZE) - 1;
-        (self.bitfield >> Self::F_SHIFT[usize::from(pl) as usize]) & f_mask
+        (self.bitfield >> Self::F_SHIFT[usize::from(pl)]) & f_mask
     }
 
     pub fn player_cnt_is_max(&self, pl: Player) -> bool {
         let player_cnt_is_max_mask = [Self::MAX << Self::F_SHIFT[0], Self::MAX << Self::F_SHIFT[1]];
-        (player_cnt_is_max_mask[usize::from(pl) as usize] & self.bitfield)
-            == player_cnt_is_max_mask[usize::from(pl) as usize]
+        (player_cnt_is_max_mask[usize::from(pl)] & self.bitfield)
+            == player_cnt_is_max_mask[usize::from(pl)]
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct Chain {
     pub lib_cnt: u32,
     pub lib_sum: u32,
     pub lib_sum2: u32,
-    pub size: u32,
-    pub atari_v: Vertex,
-}
-
-impl Default for Chain {
-    fn default() -> Self {
-        Chain {
-            lib_cnt: 0,
-            lib_sum: 0,
-            lib_sum2: 0,
-            size: 0,
-            atari_v: Vertex::none(),
-        }
-    }
+    pub size: u16,
+    // The first stone, which names the chain in ChainHandle
+    stone: u16,
 }
 
 impl Chain {
@@ -115,7 +126,10 @@ impl Chain {
         self.lib_sum = 0;
         self.lib_sum2 = 0;
         self.size = 0;
-        self.atari_v = Vertex::none();
+    }
+
+    pub fn stone(&self) -> Vertex {
+        Vertex::from(self.stone as usize)
     }
 
     pub fn reset_off_board(&mut self) {
@@ -123,7 +137,6 @@ impl Chain {
         self.lib_sum = 1;
         self.lib_sum2 = 1;
         self.size = 100;
-        self.atari_v = Vertex::none();
     }
 
     pub fn add_lib(&mut self, v: Vertex) {
@@ -142,6 +155,17 @@ impl Chain {
             .wrapping_sub(usize::from(v) as u32 * usize::from(v) as u32);
     }
 
+    // add_lib for delta 1, sub_lib for delta u32::MAX, without branching
+    #[inline(always)]
+    pub fn shift_lib(&mut self, v: Vertex, delta: u32) {
+        let v = usize::from(v) as u32;
+        self.lib_cnt = self.lib_cnt.wrapping_add(delta);
+        self.lib_sum = self.lib_sum.wrapping_add(delta.wrapping_mul(v));
+        self.lib_sum2 = self
+            .lib_sum2
+            .wrapping_add(delta.wrapping_mul(v.wrapping_mul(v)));
+    }
+
     pub fn merge(&mut self, other: &Chain) {
         self.lib_cnt += other.lib_cnt;
         self.lib_sum += other.lib_sum;
@@ -153,14 +177,116 @@ impl Chain {
         self.lib_cnt == 0
     }
 
+    // In u64, the products overflow u32 on 25x25 boards
     pub fn is_in_atari(&self) -> bool {
-        self.lib_cnt * self.lib_sum2 == self.lib_sum * self.lib_sum
+        self.lib_cnt as u64 * self.lib_sum2 as u64 == (self.lib_sum as u64).pow(2)
     }
 
-    #[allow(dead_code)]
-    pub fn atari_vertex(&self) -> Vertex {
-        assert!(self.is_in_atari());
-        self.atari_v
+    // The last liberty, None unless the chain is in atari. Computed from the
+    // liberty sums, so it is never stale.
+    pub fn atari_vertex(&self) -> Option<Vertex> {
+        (self.lib_cnt > 0 && self.is_in_atari())
+            .then(|| Vertex::from((self.lib_sum / self.lib_cnt) as usize))
+    }
+}
+
+// Position without the incremental structures, for persisting and sending
+// boards. Rows go from the top, with '#' black, 'O' white and '.' empty.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardSnapshot {
+    pub width: usize,
+    pub height: usize,
+    pub komi: f32,
+    pub rows: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::player"))]
+    pub to_move: Player,
+    // Vertex::none() if there is no ko
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::vertex"))]
+    pub ko: Vertex,
+    pub move_cnt: usize,
+}
+
+// Counters of a position in one value, for telemetry, logs and showboard
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardStats {
+    pub black_stones: usize,
+    pub white_stones: usize,
+    pub empty: usize,
+    pub move_no: usize,
+    // Stones captured by each player
+    pub black_prisoners: usize,
+    pub white_prisoners: usize,
+    // Vertex::none() if there is no ko
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::vertex"))]
+    pub ko: Vertex,
+}
+
+// Names a chain by one of its stones and the stone's play_count, which tells
+// it apart from later stones on the same point. Chain ids change on merges,
+// handles stay valid until the stone is captured: resolve_chain maps any
+// handle of a chain to the chain's current canonical handle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChainHandle {
+    stone: usize,
+    placement: u32,
+}
+
+impl ChainHandle {
+    pub fn stone(self) -> Vertex {
+        Vertex::from(self.stone)
+    }
+}
+
+// A large field of Board, on the heap with the boxed-board feature. Boxed, a
+// Board is a few hundred bytes and cheap to move or keep in deep recursion,
+// at the cost of a pointer hop on each access.
+struct Storage<T> {
+    #[cfg(feature = "boxed-board")]
+    inner: Box<T>,
+    #[cfg(not(feature = "boxed-board"))]
+    inner: T,
+}
+
+impl<T> Storage<T> {
+    fn new(value: T) -> Self {
+        #[cfg(feature = "boxed-board")]
+        let value = Box::new(value);
+        Storage { inner: value }
+    }
+}
+
+impl<T: Default> Default for Storage<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+// clone_from reuses the box
+impl<T: Clone> Clone for Storage<T> {
+    fn clone(&self) -> Self {
+        Storage {
+            inner: self.inner.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+    }
+}
+
+impl<T> std::ops::Deref for Storage<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> std::ops::DerefMut for Storage<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
     }
 }
 
@@ -177,29 +303,63 @@ pub struct Board {
     // Positional hash
     hash: Hash,
 
-    player_v_cnt: PlayerMap<u32>,
-    chain_next_v: VertexMap<Vertex>,
-    chain_id: VertexMap<Vertex>,
-    chain: VertexMap<Chain>,
-
-    nbr_cnt: VertexMap<NbrCounter>,
-
-    empty_v_cnt: u32,
-    empty_v: [Vertex; K_AREA],
-    empty_pos: VertexMap<u32>,
-
-    play_count: VertexMap<u32>,
-
-    hash3x3: VertexMap<Hash3x3>,
-    hash3x3_changed: ArrayVec<Vertex, K_AREA>,
-    tmp_vertex_set: NatSet<{ Vertex::COUNT }, Vertex>,
+    // Stones of each player, unordered, same swap-remove scheme as the empty list
+    player_v_cnt: PlayerMap<u16>,
+    player_v: Storage<PlayerMap<[PackedVertex; K_AREA]>>,
+    player_pos: Storage<VertexMap<u16>>,
+    // Stones captured by each player, suicided stones count for the opponent
+    prisoners: PlayerMap<u32>,
+    chain_next_v: Storage<VertexMap<PackedVertex>>,
+    // Slot in chain of the chain of every stone, NO_CHAIN elsewhere
+    chain_id: Storage<VertexMap<u16>>,
+    // Dense arena of the chains on the board, slots of captured and merged
+    // chains are reused from chain_free
+    chain: Storage<[Chain; CHAIN_SLOTS]>,
+    chain_free: Storage<[u16; CHAIN_SLOTS]>,
+    chain_free_cnt: u16,
+
+    nbr_cnt: Storage<VertexMap<NbrCounter>>,
+
+    empty_v_cnt: u16,
+    empty_v: Storage<[Vertex; K_AREA]>,
+    empty_pos: Storage<VertexMap<u16>>,
+
+    play_count: Storage<VertexMap<u32>>,
+
+    hash3x3: Storage<VertexMap<Hash3x3>>,
+    // Atari bits are not kept in hash3x3 but computed by hash3x3_at
+    lazy_atari: bool,
+    hash3x3_changed: Storage<ArrayVec<Vertex, K_AREA>>,
+    tmp_vertex_set: Storage<NatSet<{ Vertex::COUNT }, Vertex>>,
 }
 
 impl Board {
     pub fn new() -> Self {
+        Self::small()
+    }
+
+    // 9x9
+    pub fn small() -> Self {
         Self::with_size(9, 9)
     }
 
+    // 19x19
+    pub fn standard() -> Self {
+        Self::with_size(DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE)
+    }
+
+    // Board on the heap, for keeping many positions or recursing deeply. Boards
+    // are built in place only with boxed-board, otherwise new_boxed moves a
+    // full Board through this frame once.
+    pub fn new_boxed(width: usize, height: usize) -> Box<Self> {
+        Box::new(Self::with_size(width, height))
+    }
+
+    pub fn try_with_size(width: usize, height: usize) -> Result<Self, BoardError> {
+        check_size(width, height)?;
+        Ok(Self::with_size(width, height))
+    }
+
     pub fn with_size(width: usize, height: usize) -> Self {
         assert!(
             width > 0 && width <= MAX_BOARD_SIZE,
@@ -224,27 +384,43 @@ impl Board {
             hash: Hash::new(),
 
             player_v_cnt: PlayerMap::new(),
-            chain_next_v: VertexMap::new_with(Vertex::none()),
-            chain_id: VertexMap::new_with(Vertex::none()),
-            chain: VertexMap::new(),
+            player_v: Storage::new(PlayerMap::new_with([Vertex::none().into(); K_AREA])),
+            player_pos: Storage::default(),
+            prisoners: PlayerMap::new(),
+            chain_next_v: Storage::new(VertexMap::new_with(Vertex::none().into())),
+            chain_id: Storage::default(),
+            chain: Storage::new([Chain::default(); CHAIN_SLOTS]),
+            chain_free: Storage::new([NO_CHAIN; CHAIN_SLOTS]),
+            chain_free_cnt: 0,
 
-            nbr_cnt: VertexMap::new(),
+            nbr_cnt: Storage::default(),
 
             empty_v_cnt: 0,
-            empty_v: [Vertex::none(); K_AREA],
-            empty_pos: VertexMap::new(),
+            empty_v: Storage::new([Vertex::none(); K_AREA]),
+            empty_pos: Storage::default(),
 
-            play_count: VertexMap::new(),
+            play_count: Storage::default(),
 
-            hash3x3: VertexMap::new(),
-            hash3x3_changed: ArrayVec::new(),
-            tmp_vertex_set: NatSet::<{ Vertex::COUNT }, Vertex>::new(),
+            hash3x3: Storage::default(),
+            lazy_atari: false,
+            hash3x3_changed: Storage::default(),
+            tmp_vertex_set: Storage::default(),
         };
 
         board.clear();
         board
     }
 
+    // Empty board of another size, keeping komi and the atari bit mode.
+    // Unchanged on a bad size.
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), BoardError> {
+        check_size(width, height)?;
+        self.board_width = width;
+        self.board_height = height;
+        self.clear();
+        Ok(())
+    }
+
     pub fn clear(&mut self) {
         self.move_no = 0;
         self.last_player = Player::White;
@@ -253,12 +429,19 @@ impl Board {
         // Initialize all vertices
         for v in Vertex::all() {
             self.color_at[v] = Color::OffBoard;
-            self.chain_next_v[v] = v;
-            self.chain_id[v] = v;
+            self.chain_next_v[v] = v.into();
+            self.chain_id[v] = NO_CHAIN;
             self.nbr_cnt[v] = NbrCounter::empty();
             self.play_count[v] = 0;
             self.empty_pos[v] = 0;
-            self.chain[v].reset_off_board();
+            self.player_pos[v] = 0;
+        }
+        self.chain[NO_CHAIN as usize].reset_off_board();
+        // Popped from the end, so slot 1 is used first
+        self.chain_free_cnt = 0;
+        for slot in (1..CHAIN_SLOTS as u16).rev() {
+            self.chain_free[self.chain_free_cnt as usize] = slot;
+            self.chain_free_cnt += 1;
         }
 
         // Clear empty vertex list
@@ -266,9 +449,8 @@ impl Board {
 
         // Set up board positions - only within the actual board size
         for v in Vertex::all() {
-            if self.is_within_board(v) {
+            if self.is_on_board(v) {
                 self.color_at[v] = Color::Empty;
-                self.chain[v].reset();
 
                 // Add to empty list
                 self.empty_pos[v] = self.empty_v_cnt;
@@ -293,6 +475,8 @@ impl Board {
 
         self.player_v_cnt[Player::Black] = 0;
         self.player_v_cnt[Player::White] = 0;
+        self.prisoners[Player::Black] = 0;
+        self.prisoners[Player::White] = 0;
 
         self.last_play[Player::Black] = Vertex::none();
         self.last_play[Player::White] = Vertex::none();
@@ -307,18 +491,53 @@ impl Board {
         self.hash = self.recalc_hash();
     }
 
-    fn is_within_board(&self, v: Vertex) -> bool {
-        let row = v.row() as i32 + 1;
-        let col = v.column() as i32 + 1;
-        row > 0 && row <= self.board_height as i32 && col > 0 && col <= self.board_width as i32
+    // Pass and Vertex::none() are not on the board
+    pub fn is_on_board(&self, v: Vertex) -> bool {
+        self.coord_of(v).is_some()
+    }
+
+    // 1 on the edge, 2 on the second line, ... None off the board
+    pub fn line_of(&self, v: Vertex) -> Option<usize> {
+        self.coord_of(v)
+            .map(|coord| coord.line(self.board_width, self.board_height))
+    }
+
+    // On the first line, corners included
+    pub fn is_edge(&self, v: Vertex) -> bool {
+        self.line_of(v) == Some(1)
+    }
+
+    pub fn is_corner(&self, v: Vertex) -> bool {
+        self.coord_of(v).is_some_and(|coord| {
+            (coord.row() == 0 || coord.row() == self.board_height - 1)
+                && (coord.col() == 0 || coord.col() == self.board_width - 1)
+        })
+    }
+
+    // Checked coordinates of a point of this board
+    pub fn coord(&self, row: usize, col: usize) -> Result<Coord, BoardError> {
+        Coord::new(row, col, self.board_width, self.board_height)
+    }
+
+    // None for pass and points off this board
+    pub fn coord_of(&self, v: Vertex) -> Option<Coord> {
+        Coord::of_vertex(v, self.board_width, self.board_height)
+    }
+
+    pub fn width(&self) -> usize {
+        self.board_width
+    }
+
+    pub fn height(&self) -> usize {
+        self.board_height
     }
 
     pub fn act_player(&self) -> Player {
         self.last_player.opponent()
     }
 
-    pub fn color_at(&self, v: Vertex) -> Color {
-        self.color_at[v]
+    pub fn color_at(&self, v: impl Into<Vertex>) -> Color {
+        self.color_at[v.into()]
     }
 
     pub fn empty_vertex_count(&self) -> usize {
@@ -334,8 +553,13 @@ impl Board {
         self.empty_v[idx]
     }
 
-    #[allow(dead_code)]
-    pub fn is_legal(&self, player: Player, v: Vertex) -> bool {
+    // Empty points in empty list order; hot loops index with empty_vertex
+    pub fn empties(&self) -> impl Iterator<Item = Vertex> + '_ {
+        self.empty_v[..self.empty_v_cnt as usize].iter().copied()
+    }
+
+    pub fn is_legal(&self, player: Player, v: impl Into<Vertex>) -> bool {
+        let v = v.into();
         if v == Vertex::pass() {
             return true;
         }
@@ -349,31 +573,102 @@ impl Board {
             return true;
         }
 
+        self.is_legal_nbr_chains(player, v)
+    }
+
+    // is_legal from the 3x3 pattern of v and the ko point alone, without
+    // looking at the neighbor chains. The atari bits of the pattern mark the
+    // neighbors whose chain has v as its last liberty, and while they are up to
+    // date (see verify_hash3x3) the answer is the same as is_legal: no false
+    // positives or negatives. This holds under cpp-compat too, whose is_legal
+    // subtracts once per adjacent stone from the pseudo-liberties, just as the
+    // atari bits are set. Only stale atari bits could make it wrong: a
+    // missing bit of an own chain allows a suicide, of an opponent chain
+    // forbids a capture. With lazy atari bits the pattern is computed from the
+    // chains, so it is no faster than is_legal.
+    #[inline]
+    pub fn is_legal_fast(&self, player: Player, v: impl Into<Vertex>) -> bool {
+        let v = v.into();
+        if v == Vertex::pass() {
+            return true;
+        }
+        self.color_at[v] == Color::Empty && v != self.ko_v && self.hash3x3_at(v).is_legal(player)
+    }
+
+    // is_legal of every point in one pass over the empty points, false for
+    // occupied and off-board points. Pass is not included.
+    pub fn legal_mask(&self, player: Player) -> VertexMap<bool> {
+        let mut mask = VertexMap::new_with(false);
+        for i in 0..self.empty_v_cnt {
+            let v = self.empty_v[i as usize];
+            mask[v] = self.nbr_cnt[v].empty_cnt() > 0 || self.is_legal_nbr_chains(player, v);
+        }
+        if self.ko_v != Vertex::none() {
+            mask[self.ko_v] = false;
+        }
+        mask
+    }
+
+    // A stone at v with no empty neighbors is legal if it captures (an opponent
+    // chain has v as its last liberty) or connects to an own chain with another
+    // liberty. Pseudo-liberties count v once per adjacent stone, so the check
+    // subtracts the number of stones of the chain next to v.
+    #[cfg(not(feature = "cpp-compat"))]
+    fn is_legal_nbr_chains(&self, player: Player, v: Vertex) -> bool {
+        let mut chain_ids = [NO_CHAIN; 4];
+        let mut colors = [Color::Empty; 4];
+        let mut adjacent_cnt = [0u32; 4];
+        let mut chain_cnt = 0;
+        for_each_4_nbr!(v, nbr_v, {
+            if color_is_player(self.color_at[nbr_v]) {
+                let chain_id = self.chain_id[nbr_v];
+                match chain_ids[..chain_cnt].iter().position(|&id| id == chain_id) {
+                    Some(idx) => adjacent_cnt[idx] += 1,
+                    None => {
+                        chain_ids[chain_cnt] = chain_id;
+                        colors[chain_cnt] = self.color_at[nbr_v];
+                        adjacent_cnt[chain_cnt] = 1;
+                        chain_cnt += 1;
+                    }
+                }
+            }
+        });
+
+        (0..chain_cnt).any(|idx| {
+            let atari = self.chain[chain_ids[idx] as usize].lib_cnt == adjacent_cnt[idx];
+            let is_same_color = color_to_player(colors[idx]) == player;
+            atari != is_same_color
+        })
+    }
+
+    // Literal port of the C++ check
+    #[cfg(feature = "cpp-compat")]
+    fn is_legal_nbr_chains(&self, player: Player, v: Vertex) -> bool {
         // Match C++ logic exactly - decrement once per NEIGHBOR, not per chain
         let mut not_suicide = false;
 
         // C++ decrements each neighbor's chain, even if same chain appears multiple times
-        let mut temp_libs = [0i32; 625]; // Use i32 to handle multiple decrements
+        let mut temp_libs = [0i32; CHAIN_SLOTS]; // Use i32 to handle multiple decrements
 
-        // Initialize with original liberties
+        // Initialize with original liberties. Empty and off-board points
+        // share the NO_CHAIN dummy, C++ gave each its own, but only stones
+        // are looked at below.
         for_each_4_nbr!(v, nbr_v, {
-            let chain_id = self.chain_id[nbr_v];
-            if temp_libs[usize::from(chain_id) as usize] == 0 {
-                temp_libs[usize::from(chain_id) as usize] = self.chain[chain_id].lib_cnt as i32;
+            let chain_id = self.chain_id[nbr_v] as usize;
+            if temp_libs[chain_id] == 0 {
+                temp_libs[chain_id] = self.chain[chain_id].lib_cnt as i32;
             }
         });
 
         // Decrement once per neighbor (C++ behavior)
         for_each_4_nbr!(v, nbr_v, {
-            let chain_id = self.chain_id[nbr_v];
-            temp_libs[usize::from(chain_id) as usize] -= 1;
+            temp_libs[self.chain_id[nbr_v] as usize] -= 1;
         });
 
         // Check each neighbor
         for_each_4_nbr!(v, nbr_v, {
             if color_is_player(self.color_at[nbr_v]) {
-                let chain_id = self.chain_id[nbr_v];
-                let atari = temp_libs[usize::from(chain_id) as usize] == 0;
+                let atari = temp_libs[self.chain_id[nbr_v] as usize] == 0;
                 let is_same_color = color_to_player(self.color_at[nbr_v]) == player;
 
                 // C++ logic: atari != (color_at[nbr_v].ToPlayer() == player)
@@ -384,7 +679,62 @@ impl Board {
         not_suicide
     }
 
+    // Empty vertex where a stone would have no liberties and capture nothing
+    pub fn is_suicide(&self, player: Player, v: impl Into<Vertex>) -> bool {
+        let v = v.into();
+        v != Vertex::pass()
+            && self.color_at[v] == Color::Empty
+            && v != self.ko_v
+            && !self.is_legal(player, v)
+    }
+
+    // Checked play_legal for untrusted moves
+    pub fn try_play(&mut self, player: Player, v: impl Into<Vertex>) -> Result<(), BoardError> {
+        let v = v.into();
+        if v != Vertex::pass() {
+            if !self.is_on_board(v) {
+                return Err(BoardError::OffBoard(v));
+            }
+            if self.color_at[v] != Color::Empty {
+                return Err(BoardError::Occupied(v));
+            }
+            if v == self.ko_v {
+                return Err(BoardError::Ko(v));
+            }
+            if !self.is_legal(player, v) {
+                return Err(BoardError::Suicide(v));
+            }
+        }
+        self.play_legal(player, v);
+        Ok(())
+    }
+
+    // try_play of a Move
+    pub fn play_move(&mut self, mv: Move) -> Result<(), BoardError> {
+        self.try_play(mv.player, mv.vertex)
+    }
+
+    // Plays the moves in order. Stops at the first illegal one with its index,
+    // the moves before it stay played.
+    pub fn replay(&mut self, moves: &[Move]) -> Result<(), (usize, BoardError)> {
+        for (idx, &mv) in moves.iter().enumerate() {
+            self.play_move(mv).map_err(|e| (idx, e))?;
+        }
+        Ok(())
+    }
+
     pub fn play_legal(&mut self, player: Player, v: Vertex) {
+        self.play_legal_observed(player, v, &mut ());
+    }
+
+    // play_legal reporting the changes to observer
+    #[inline]
+    pub fn play_legal_observed<O: BoardObserver>(
+        &mut self,
+        player: Player,
+        v: Vertex,
+        observer: &mut O,
+    ) {
         // Clear tracking state
         self.tmp_vertex_set.clear();
         self.hash3x3_changed.clear();
@@ -395,11 +745,18 @@ impl Board {
 
         if v == Vertex::pass() {
             self.ko_v = Vertex::none();
+            if O::ENABLED {
+                observer.on_ko_set(self.ko_v);
+                observer.on_move_played(player, v);
+            }
             return;
         }
 
         self.play_count[v] += 1;
         self.place_stone(player, v);
+        if O::ENABLED {
+            observer.on_stone_placed(player, v);
+        }
 
         // Now handle neighbors similar to C++ update_neighbour
         let color = Color::from(player);
@@ -411,10 +768,13 @@ impl Board {
             if color_is_player(nbr_color) {
                 if nbr_color != color {
                     // Enemy chain
-                    let nbr_chain_id = self.chain_id[nbr_v];
-                    if self.chain[nbr_chain_id].is_captured() {
-                        captured_cnt += self.chain[nbr_chain_id].size;
+                    let nbr_chain = &self.chain[self.chain_id[nbr_v] as usize];
+                    if nbr_chain.is_captured() {
+                        captured_cnt += nbr_chain.size;
                         last_captured_v = nbr_v;
+                        if O::ENABLED {
+                            self.notify_chain_captured(nbr_v, observer);
+                        }
                         self.remove_chain(nbr_v);
                     } else {
                         // Reduced liberty of opponent - check for atari
@@ -422,9 +782,10 @@ impl Board {
                     }
                 } else {
                     // Same color - merge chains if needed
-                    let nbr_chain_id = self.chain_id[nbr_v];
-                    if self.chain_id[v] != nbr_chain_id {
-                        if self.chain[self.chain_id[v]].size > self.chain[nbr_chain_id].size {
+                    let nbr_chain_id = self.chain_id[nbr_v] as usize;
+                    let chain_id = self.chain_id[v] as usize;
+                    if chain_id != nbr_chain_id {
+                        if self.chain[chain_id].size > self.chain[nbr_chain_id].size {
                             self.merge_chains(v, nbr_v);
                         } else {
                             self.merge_chains(nbr_v, v);
@@ -434,18 +795,51 @@ impl Board {
             }
         });
 
+        self.prisoners[player] += captured_cnt as u32;
+
         // Update ko
-        if captured_cnt == 1
-            && self.chain[self.chain_id[v]].size == 1
-            && self.chain[self.chain_id[v]].lib_cnt == 1
+        let chain_id = self.chain_id[v] as usize;
+        if captured_cnt == 1 && self.chain[chain_id].size == 1 && self.chain[chain_id].lib_cnt == 1
         {
             self.ko_v = last_captured_v;
         } else {
             self.ko_v = Vertex::none();
         }
+        if O::ENABLED {
+            observer.on_ko_set(self.ko_v);
+        }
+
+        // Suicide - is_legal moves never get here, only rules allowing suicide
+        if self.chain[chain_id].is_captured() {
+            self.prisoners[player.opponent()] += self.chain[chain_id].size as u32;
+            if O::ENABLED {
+                self.notify_chain_captured(v, observer);
+            }
+            self.remove_chain(v);
+            if O::ENABLED {
+                observer.on_move_played(player, v);
+            }
+            return;
+        }
 
         // Check for atari of the played chain
         self.maybe_in_atari(v);
+        if O::ENABLED {
+            observer.on_move_played(player, v);
+        }
+    }
+
+    fn notify_chain_captured<O: BoardObserver>(&self, v: Vertex, observer: &mut O) {
+        let mut stones = ArrayVec::<Vertex, K_AREA>::new();
+        let mut current = v;
+        loop {
+            stones.push(current);
+            current = Vertex::from(self.chain_next_v[current]);
+            if current == v {
+                break;
+            }
+        }
+        observer.on_chain_captured(color_to_player(self.color_at[v]), &stones);
     }
 
     fn place_stone(&mut self, player: Player, v: Vertex) {
@@ -466,50 +860,59 @@ impl Board {
         // Place stone
         let color = Color::from(player);
         self.color_at[v] = color;
+        self.player_pos[v] = self.player_v_cnt[player];
+        self.player_v[player][self.player_v_cnt[player] as usize] = v.into();
         self.player_v_cnt[player] += 1;
 
         // Update positional hash
         self.hash ^= ZOBRIST.of_player_vertex(player, v);
 
-        // Update hash3x3 for all neighbors
-        for dir in Dir::all() {
-            let nbr = vertex_nbr(v, dir);
-            self.hash3x3[nbr].set_color_at(dir.opposite(), color);
-            if !self.tmp_vertex_set.is_marked(nbr) && self.color_at[nbr] == Color::Empty {
-                self.hash3x3_changed.push(nbr);
-                self.tmp_vertex_set.mark(nbr);
-            }
-        }
-
         // Initialize chain
-        self.chain_id[v] = v;
-        self.chain_next_v[v] = v;
-        self.chain[v].reset();
-        self.chain[v].size = 1;
-
-        // Process all neighbors in one loop like C++
-        for_each_4_nbr!(v, nbr_v, {
+        self.chain_free_cnt -= 1;
+        let slot = self.chain_free[self.chain_free_cnt as usize];
+        self.chain_id[v] = slot;
+        self.chain_next_v[v] = v.into();
+        let chain = &mut self.chain[slot as usize];
+        chain.reset();
+        chain.size = 1;
+        chain.stone = usize::from(v) as u16;
+
+        // One kernel for the direct neighbors: each sees the new color in its
+        // 3x3 pattern and loses an empty neighbor, an empty one is a liberty
+        // of the new stone and any other loses v as a liberty. Empty and
+        // off-board points sub from the NO_CHAIN dummy, like the dummy chains
+        // of C++ chain_at, so the chain is picked with selects instead of
+        // branching on the color. Diagonal neighbors
+        // only see the new color. Direct neighbors go first, so
+        // hash3x3_changed keeps the Dir order the sampler's summation order
+        // depends on.
+        for dir in SIMPLE4 {
+            let nbr_v = vertex_nbr(v, dir);
             let nbr_color = self.color_at[nbr_v];
-
-            // Update neighbor counts - ALL neighbors lose an empty neighbor
+            let is_empty = nbr_color == Color::Empty;
+            self.set_nbr_color(nbr_v, dir, color);
             self.nbr_cnt[nbr_v].player_inc(player);
+            let chain_id = if is_empty { slot } else { self.chain_id[nbr_v] };
+            let lib = if is_empty { nbr_v } else { v };
+            self.chain[chain_id as usize].shift_lib(lib, LIB_DELTA[nbr_color as usize]);
+        }
+        for dir in DIAGONAL4 {
+            self.set_nbr_color(vertex_nbr(v, dir), dir, color);
+        }
+    }
 
-            if nbr_color == Color::Empty {
-                // Add liberty for the new stone
-                self.chain[v].add_lib(nbr_v);
-            } else {
-                // Subtract liberty from neighbor chains (both player and off-board)
-                if color_is_player(nbr_color) {
-                    let nbr_chain_id = self.chain_id[nbr_v];
-                    self.chain[nbr_chain_id].sub_lib(v);
-                } else if nbr_color == Color::OffBoard {
-                    // For off-board, C++ uses chain_at which accesses chain[nbr_v]
-                    self.chain[nbr_v].sub_lib(v);
-                }
-            }
-        });
+    // The 3x3 pattern of nbr_v, in direction dir from a changed point, gets
+    // color there
+    #[inline(always)]
+    fn set_nbr_color(&mut self, nbr_v: Vertex, dir: Dir, color: Color) {
+        self.hash3x3[nbr_v].set_color_at(dir.opposite(), color);
+        if self.color_at[nbr_v] == Color::Empty && !self.tmp_vertex_set.is_marked(nbr_v) {
+            self.hash3x3_changed.push(nbr_v);
+            self.tmp_vertex_set.mark(nbr_v);
+        }
     }
 
+    // The stones of the added chain are relabeled, so it should be the smaller
     fn merge_chains(&mut self, v_base: Vertex, v_add: Vertex) {
         let base_id = self.chain_id[v_base];
         let add_id = self.chain_id[v_add];
@@ -519,18 +922,18 @@ impl Board {
         }
 
         // Merge chain data - copy to avoid borrow issue
-        let add_chain = self.chain[add_id].clone();
-        self.chain[base_id].merge(&add_chain);
+        let add_chain = self.chain[add_id as usize];
+        self.chain[base_id as usize].merge(&add_chain);
 
-        // Update chain IDs
-        let mut current = v_add;
+        let mut act_v = v_add;
         loop {
-            self.chain_id[current] = base_id;
-            current = self.chain_next_v[current];
-            if current == v_add {
+            self.chain_id[act_v] = base_id;
+            act_v = Vertex::from(self.chain_next_v[act_v]);
+            if act_v == v_add {
                 break;
             }
         }
+        self.free_chain(add_id);
 
         // Merge linked lists
         let base_next = self.chain_next_v[v_base];
@@ -539,20 +942,31 @@ impl Board {
         self.chain_next_v[v_add] = base_next;
     }
 
+    fn free_chain(&mut self, slot: u16) {
+        self.chain_free[self.chain_free_cnt as usize] = slot;
+        self.chain_free_cnt += 1;
+    }
+
+    // Chain data of the stone at v
+    #[inline(always)]
+    fn chain_of(&self, v: Vertex) -> &Chain {
+        &self.chain[self.chain_id[v] as usize]
+    }
+
     fn maybe_in_atari(&mut self, v: Vertex) {
         // Update atari bits in hash3x3
         if self.color_at[v] == Color::Empty || self.color_at[v] == Color::OffBoard {
             return;
         }
         let chain_id = self.chain_id[v];
-        if !self.chain[chain_id].is_in_atari() {
+        let chain = self.chain_of(v);
+        if !chain.is_in_atari() {
             return;
         }
 
         // Calculate atari vertex from lib_sum / lib_cnt (like C++)
-        let chain = &self.chain[chain_id];
         assert!(
-            chain.lib_sum % chain.lib_cnt == 0,
+            chain.lib_sum.is_multiple_of(chain.lib_cnt),
             "lib_sum % lib_cnt should be 0"
         );
         let av = Vertex::from((chain.lib_sum / chain.lib_cnt) as usize);
@@ -560,15 +974,17 @@ impl Board {
             return; // Safety check
         }
 
-        self.chain[chain_id].atari_v = av;
-
         // Set atari bits based on which neighbors belong to the same chain
-        self.hash3x3[av].set_atari_bits(
-            self.chain_id[vertex_nbr(av, Dir::N)] == chain_id,
-            self.chain_id[vertex_nbr(av, Dir::E)] == chain_id,
-            self.chain_id[vertex_nbr(av, Dir::S)] == chain_id,
-            self.chain_id[vertex_nbr(av, Dir::W)] == chain_id,
-        );
+        if !self.lazy_atari {
+            let same_chain = |dir| self.chain_id[vertex_nbr(av, dir)] == chain_id;
+            let (n, e, s, w) = (
+                same_chain(Dir::N),
+                same_chain(Dir::E),
+                same_chain(Dir::S),
+                same_chain(Dir::W),
+            );
+            self.hash3x3[av].set_atari_bits(n, e, s, w);
+        }
 
         if !self.tmp_vertex_set.is_marked(av) {
             self.hash3x3_changed.push(av);
@@ -582,17 +998,17 @@ impl Board {
             return;
         }
         let chain_id = self.chain_id[v];
-        if self.chain[chain_id].is_captured() {
+        let chain = self.chain_of(v);
+        if chain.is_captured() {
             return;
         }
-        if !self.chain[chain_id].is_in_atari() {
+        if !chain.is_in_atari() {
             return;
         }
 
         // Calculate atari vertex from lib_sum / lib_cnt (like C++)
-        let chain = &self.chain[chain_id];
         assert!(
-            chain.lib_sum % chain.lib_cnt == 0,
+            chain.lib_sum.is_multiple_of(chain.lib_cnt),
             "lib_sum % lib_cnt should be 0"
         );
         let av = Vertex::from((chain.lib_sum / chain.lib_cnt) as usize);
@@ -600,15 +1016,17 @@ impl Board {
             return; // Safety check
         }
 
-        self.chain[chain_id].atari_v = Vertex::none();
-
         // Unset atari bits
-        self.hash3x3[av].unset_atari_bits(
-            self.chain_id[vertex_nbr(av, Dir::N)] == chain_id,
-            self.chain_id[vertex_nbr(av, Dir::E)] == chain_id,
-            self.chain_id[vertex_nbr(av, Dir::S)] == chain_id,
-            self.chain_id[vertex_nbr(av, Dir::W)] == chain_id,
-        );
+        if !self.lazy_atari {
+            let same_chain = |dir| self.chain_id[vertex_nbr(av, dir)] == chain_id;
+            let (n, e, s, w) = (
+                same_chain(Dir::N),
+                same_chain(Dir::E),
+                same_chain(Dir::S),
+                same_chain(Dir::W),
+            );
+            self.hash3x3[av].unset_atari_bits(n, e, s, w);
+        }
 
         if !self.tmp_vertex_set.is_marked(av) {
             self.hash3x3_changed.push(av);
@@ -620,6 +1038,7 @@ impl Board {
         let color = self.color_at[v];
         assert!(color_is_player(color));
         let player = color_to_player(color);
+        let slot = self.chain_id[v];
 
         // First pass: remove all stones
         let mut current = v;
@@ -633,8 +1052,11 @@ impl Board {
 
             // Remove stone
             self.color_at[act_v] = Color::Empty;
-            self.chain_id[act_v] = act_v;
+            self.chain_id[act_v] = NO_CHAIN;
             self.player_v_cnt[player] -= 1;
+            let last_v = self.player_v[player][self.player_v_cnt[player] as usize];
+            self.player_pos[last_v] = self.player_pos[act_v];
+            self.player_v[player][self.player_pos[act_v] as usize] = last_v;
 
             // Update positional hash
             self.hash ^= ZOBRIST.of_player_vertex(player, act_v);
@@ -646,22 +1068,18 @@ impl Board {
                 self.tmp_vertex_set.mark(act_v);
             }
 
-            // Update hash3x3 for all neighbors
-            for dir in Dir::all() {
+            // Update hash3x3 of all neighbors, and neighbor counts of the
+            // direct ones in the same pass
+            for dir in SIMPLE4 {
                 let nbr = vertex_nbr(act_v, dir);
-                self.hash3x3[nbr].set_color_at(dir.opposite(), Color::Empty);
-                if !self.tmp_vertex_set.is_marked(nbr) && self.color_at[nbr] == Color::Empty {
-                    self.hash3x3_changed.push(nbr);
-                    self.tmp_vertex_set.mark(nbr);
-                }
+                self.set_nbr_color(nbr, dir, Color::Empty);
+                self.nbr_cnt[nbr].player_dec(player);
+            }
+            for dir in DIAGONAL4 {
+                self.set_nbr_color(vertex_nbr(act_v, dir), dir, Color::Empty);
             }
 
-            // Update neighbor counts
-            for_each_4_nbr!(act_v, nbr_v, {
-                self.nbr_cnt[nbr_v].player_dec(player);
-            });
-
-            current = self.chain_next_v[current];
+            current = Vertex::from(self.chain_next_v[current]);
             if current == v {
                 break;
             }
@@ -677,100 +1095,252 @@ impl Board {
                 let _nbr_color = self.color_at[nbr_v];
                 // Must call maybe_in_atari_end BEFORE adding liberty (like C++)
                 self.maybe_in_atari_end(nbr_v);
-                self.chain[self.chain_id[nbr_v]].add_lib(act_v);
+                self.chain[self.chain_id[nbr_v] as usize].add_lib(act_v);
             });
 
-            let next = self.chain_next_v[current];
-            self.chain_next_v[current] = current;
-            current = next;
+            current = std::mem::replace(&mut self.chain_next_v[current], current.into()).into();
 
             if current == v {
                 break;
             }
         }
+        self.free_chain(slot);
     }
 
     #[allow(dead_code)]
     pub fn print_all_maps(&self) {
-        // Print color_at
-        println!("color_at:");
-        let mut str_map = VertexMap::<String>::new();
-        for v in Vertex::all() {
-            str_map[v] = color_to_showboard_char(self.color_at[v]).to_string();
+        for (name, str_map) in self.debug_maps() {
+            println!("{}:", name);
+            println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
         }
-        println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
+    }
 
-        // Print chain_id
-        println!("chain_id:");
-        for v in Vertex::all() {
-            str_map[v] = format!("{}", usize::from(self.chain_id[v]) % 100);
+    // The maps of print_all_maps without the sentinels: a "name:" line followed
+    // by a row of space separated values for every board row
+    pub fn debug_maps_text(&self) -> String {
+        let mut text = String::new();
+        for (name, str_map) in self.debug_maps() {
+            text.push_str(name);
+            text.push_str(":\n");
+            for row in 0..self.height() {
+                let values: Vec<&str> = (0..self.width())
+                    .map(|col| str_map[Vertex::from_coords(row as isize, col as isize)].as_str())
+                    .collect();
+                text.push_str(&values.join(" "));
+                text.push('\n');
+            }
         }
-        println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
+        text
+    }
 
-        // Print chain_next_v
-        println!("chain_next_v:");
-        for v in Vertex::all() {
-            str_map[v] = format!("{}", usize::from(self.chain_next_v[v]) % 100);
+    fn debug_maps(&self) -> Vec<(&'static str, VertexMap<String>)> {
+        let map = |f: &dyn Fn(Vertex) -> String| {
+            let mut str_map = VertexMap::<String>::new();
+            for v in Vertex::all() {
+                str_map[v] = f(v);
+            }
+            str_map
+        };
+        let of_stone = |f: &dyn Fn(&Chain) -> String| {
+            map(&|v| {
+                if color_is_player(self.color_at[v]) {
+                    f(self.chain_of(v))
+                } else {
+                    "-".to_string()
+                }
+            })
+        };
+        vec![
+            (
+                "color_at",
+                map(&|v| color_to_showboard_char(self.color_at[v]).to_string()),
+            ),
+            ("chain_id", map(&|v| format!("{}", self.chain_id[v] % 100))),
+            (
+                "chain_next_v",
+                map(&|v| format!("{}", usize::from(self.chain_next_v[v]) % 100)),
+            ),
+            (
+                "nbr_cnt.empty_cnt()",
+                map(&|v| format!("{}", self.nbr_cnt[v].empty_cnt())),
+            ),
+            (
+                "hash3x3",
+                map(&|v| format!("{}", usize::from(self.hash3x3[v]))),
+            ),
+            (
+                "empty_pos",
+                map(&|v| {
+                    if self.color_at[v] == Color::Empty && self.is_on_board(v) {
+                        format!("{}", self.empty_pos[v])
+                    } else {
+                        "-".to_string()
+                    }
+                }),
+            ),
+            ("play_count", map(&|v| format!("{}", self.play_count[v]))),
+            (
+                "eye_score",
+                map(&|v| {
+                    if self.color_at[v] == Color::Empty {
+                        format!("{}", self.eye_score(v))
+                    } else {
+                        "-".to_string()
+                    }
+                }),
+            ),
+            (
+                "chain.lib_cnt",
+                of_stone(&|chain| format!("{}", chain.lib_cnt)),
+            ),
+            ("chain.size", of_stone(&|chain| format!("{}", chain.size))),
+        ]
+    }
+
+    // Incremental data of the chain at v, None unless v holds a stone
+    pub fn chain_at(&self, v: impl Into<Vertex>) -> Option<&Chain> {
+        let v = v.into();
+        color_is_player(self.color_at[v]).then(|| self.chain_of(v))
+    }
+
+    // Canonical handle of the chain at v, None if there is no stone
+    pub fn chain_handle(&self, v: Vertex) -> Option<ChainHandle> {
+        if !color_is_player(self.color_at[v]) {
+            return None;
         }
-        println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
+        let stone = self.chain_of(v).stone();
+        Some(ChainHandle {
+            stone: stone.into(),
+            placement: self.play_count[stone],
+        })
+    }
 
-        // Print nbr_cnt.empty_cnt()
-        println!("nbr_cnt.empty_cnt():");
-        for v in Vertex::all() {
-            str_map[v] = format!("{}", self.nbr_cnt[v].empty_cnt());
+    // Current canonical handle of the chain holding the stone of the handle,
+    // None once that stone has been captured or removed. Handles of chains
+    // merged since resolve to the same value.
+    pub fn resolve_chain(&self, handle: ChainHandle) -> Option<ChainHandle> {
+        let v = handle.stone();
+        if self.play_count[v] != handle.placement {
+            return None;
         }
-        println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
+        self.chain_handle(v)
+    }
 
-        // Print hash3x3
-        println!("hash3x3:");
-        for v in Vertex::all() {
-            str_map[v] = format!("{}", usize::from(self.hash3x3[v]));
+    // The liberty by which the chain of the handle can escape (or be
+    // captured), None if the chain is not in atari or has been captured
+    pub fn atari_escape_vertex(&self, chain: ChainHandle) -> Option<Vertex> {
+        let handle = self.resolve_chain(chain)?;
+        self.chain_at(handle.stone())?.atari_vertex()
+    }
+
+    // Stones of the chain at v in the order of its circular list
+    pub fn chain_stones(&self, v: Vertex) -> impl Iterator<Item = Vertex> + '_ {
+        let first = color_is_player(self.color_at[v]).then_some(v);
+        std::iter::successors(first, move |&act_v| {
+            let next_v = Vertex::from(self.chain_next_v[act_v]);
+            (next_v != v).then_some(next_v)
+        })
+    }
+
+    pub fn hash3x3_at(&self, v: Vertex) -> Hash3x3 {
+        let hash = self.hash3x3[v];
+        if self.lazy_atari && self.color_at[v] == Color::Empty {
+            return self.with_atari_bits(hash, v);
         }
-        println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
+        hash
+    }
 
-        // Print empty_pos
-        println!("empty_pos:");
-        for v in Vertex::all() {
-            if self.color_at[v] == Color::Empty && self.is_within_board(v) {
-                str_map[v] = format!("{}", self.empty_pos[v]);
-            } else {
-                str_map[v] = "-".to_string();
+    // hash with the atari bits of the neighbor chains whose last liberty is v
+    #[inline]
+    fn with_atari_bits(&self, mut hash: Hash3x3, v: Vertex) -> Hash3x3 {
+        // Neighbor chains whose last liberty is v, which they all have
+        let in_atari = |dir| {
+            let nbr = vertex_nbr(v, dir);
+            if !color_is_player(self.color_at[nbr]) {
+                return false;
             }
-        }
-        println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
+            let chain = self.chain_of(nbr);
+            chain.is_in_atari() && chain.lib_sum / chain.lib_cnt == usize::from(v) as u32
+        };
+        hash.set_atari_bits(
+            in_atari(Dir::N),
+            in_atari(Dir::E),
+            in_atari(Dir::S),
+            in_atari(Dir::W),
+        );
+        hash
+    }
 
-        // Print play_count
-        println!("play_count:");
-        for v in Vertex::all() {
-            str_map[v] = format!("{}", self.play_count[v]);
+    // Compares the incrementally updated hash3x3 of every point with one
+    // computed from scratch, for debugging changes to the update code. Only
+    // the colors are compared on stones, which keep stale atari bits from when
+    // the point was empty.
+    pub fn verify_hash3x3(&self) -> Result<(), String> {
+        for v in Vertex::all().filter(|&v| self.is_on_board(v)) {
+            let mut expected = Hash3x3::of_board(&self.color_at, v);
+            let mut actual = self.hash3x3[v];
+            if self.color_at[v] != Color::Empty {
+                actual = Hash3x3::from(usize::from(actual) & COLOR_BITS);
+            } else if !self.lazy_atari {
+                expected = self.with_atari_bits(expected, v);
+            }
+            if actual != expected {
+                return Err(format!(
+                    "hash3x3 at {} is {:#07x}, from scratch {:#07x}",
+                    vertex_to_gtp(v, self.board_height),
+                    usize::from(actual),
+                    usize::from(expected)
+                ));
+            }
         }
-        println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
+        Ok(())
+    }
 
-        // Print chain.lib_cnt
-        println!("chain.lib_cnt:");
-        for v in Vertex::all() {
-            if color_is_player(self.color_at[v]) {
-                str_map[v] = format!("{}", self.chain[self.chain_id[v]].lib_cnt);
-            } else {
-                str_map[v] = "-".to_string();
+    // Number of empty points with each canonical 3x3 pattern, atari bits
+    // included, most frequent first
+    pub fn pattern_histogram(&self) -> Vec<(Hash3x3, usize)> {
+        let mut patterns: Vec<usize> = self
+            .empties()
+            .map(|v| usize::from(self.hash3x3_at(v).canonical()))
+            .collect();
+        patterns.sort_unstable();
+        let mut histogram: Vec<(Hash3x3, usize)> = Vec::new();
+        for raw in patterns {
+            match histogram.last_mut() {
+                Some((hash, cnt)) if usize::from(*hash) == raw => *cnt += 1,
+                _ => histogram.push((Hash3x3::from(raw), 1)),
             }
         }
-        println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
+        histogram.sort_by_key(|&(hash, cnt)| (std::cmp::Reverse(cnt), usize::from(hash)));
+        histogram
+    }
 
-        // Print chain.size
-        println!("chain.size:");
+    // With lazy atari the atari bits of hash3x3_at are computed on every call
+    // from the neighbor chains instead of being stored after each move.
+    // Liberties of chains entering or leaving atari are still reported by
+    // changed_vertices. Sampler looks up every changed point, so this halves
+    // the uniform playout speed; it pays off only for callers reading few
+    // hashes.
+    pub fn set_lazy_atari(&mut self, lazy: bool) {
+        if lazy == self.lazy_atari {
+            return;
+        }
+        self.lazy_atari = lazy;
         for v in Vertex::all() {
-            if color_is_player(self.color_at[v]) {
-                str_map[v] = format!("{}", self.chain[self.chain_id[v]].size);
-            } else {
-                str_map[v] = "-".to_string();
+            self.hash3x3[v].reset_atari_bits();
+        }
+        if !lazy {
+            // Also drops bits left on points that are no longer atari liberties
+            for v in Vertex::all() {
+                self.maybe_in_atari(v);
             }
+            self.tmp_vertex_set.clear();
+            self.hash3x3_changed.clear();
         }
-        println!("{}", vmap_to_ascii_art_with_sentinels(&str_map));
     }
 
-    pub fn hash3x3_at(&self, v: Vertex) -> Hash3x3 {
-        self.hash3x3[v]
+    pub fn lazy_atari(&self) -> bool {
+        self.lazy_atari
     }
 
     pub fn hash3x3_changed_count(&self) -> usize {
@@ -781,6 +1351,20 @@ impl Board {
         self.hash3x3_changed[ii]
     }
 
+    // Empty vertices whose hash3x3_at changed in the last play_legal, each
+    // listed once. Includes the points emptied by captures, empty after a pass.
+    pub fn changed_vertices(&self) -> impl Iterator<Item = Vertex> + '_ {
+        self.hash3x3_changed.iter().copied()
+    }
+
+    // changed_vertices preceded by the played vertex unless it was a pass:
+    // every vertex whose color or pattern the last play_legal changed
+    pub fn touched_vertices(&self) -> impl Iterator<Item = Vertex> + '_ {
+        let last_v = self.last_play[self.last_player];
+        let played = (last_v != Vertex::pass() && last_v != Vertex::none()).then_some(last_v);
+        played.into_iter().chain(self.changed_vertices())
+    }
+
     pub fn ko_vertex(&self) -> Vertex {
         self.ko_v
     }
@@ -790,6 +1374,119 @@ impl Board {
         self.hash
     }
 
+    // Same size, stones, player to move and ko point. Move history, komi and
+    // prisoners are not part of the position. Board only knows simple ko, so
+    // superko has to be checked against the history, see Game.
+    pub fn same_position(&self, other: &Board) -> bool {
+        self.board_width == other.board_width
+            && self.board_height == other.board_height
+            && self.hash == other.hash
+            && self.act_player() == other.act_player()
+            && self.ko_v == other.ko_v
+            && Vertex::all().all(|v| self.color_at[v] == other.color_at[v])
+    }
+
+    // Positional hash after player plays the legal move v, without playing it
+    pub fn hash_after(&self, player: Player, v: Vertex) -> Hash {
+        debug_assert!(self.is_legal(player, v));
+        let mut hash = self.hash;
+        if v == Vertex::pass() {
+            return hash;
+        }
+        hash ^= ZOBRIST.of_player_vertex(player, v);
+
+        // An opponent chain is captured when all its pseudo-liberties are at v
+        let opponent = player.opponent();
+        let mut chain_ids = [NO_CHAIN; 4];
+        let mut adjacent_cnt = [0u32; 4];
+        let mut chain_cnt = 0;
+        for_each_4_nbr!(v, nbr_v, {
+            if self.color_at[nbr_v] == Color::from(opponent) {
+                let chain_id = self.chain_id[nbr_v];
+                match chain_ids[..chain_cnt].iter().position(|&id| id == chain_id) {
+                    Some(idx) => adjacent_cnt[idx] += 1,
+                    None => {
+                        chain_ids[chain_cnt] = chain_id;
+                        adjacent_cnt[chain_cnt] = 1;
+                        chain_cnt += 1;
+                    }
+                }
+            }
+        });
+        for idx in 0..chain_cnt {
+            let chain = &self.chain[chain_ids[idx] as usize];
+            if chain.lib_cnt == adjacent_cnt[idx] {
+                for stone_v in self.chain_stones(chain.stone()) {
+                    hash ^= ZOBRIST.of_player_vertex(opponent, stone_v);
+                }
+            }
+        }
+        hash
+    }
+
+    // Hash of the colors of the 5x5 area centered on the last move, points off
+    // the board included, the same wherever on the board the area is. Zero
+    // after a pass or before the first move. Combine with last_vertex() for
+    // tables of absolute replies.
+    pub fn last_move_context_hash(&self) -> Hash {
+        let last_v = self.last_vertex();
+        if last_v == Vertex::pass() || last_v == Vertex::none() {
+            return Hash::new();
+        }
+        self.context_hash_at(last_v)
+    }
+
+    // Hash of the colors of the 5x5 area centered on v, as in
+    // last_move_context_hash.
+    pub fn context_hash_at(&self, v: Vertex) -> Hash {
+        let mut hash = Hash::new();
+        self.for_each_context_color(v, |row_offset, col_offset, color| {
+            hash ^= CONTEXT_ZOBRIST.of_offset_color(row_offset, col_offset, color);
+        });
+        hash
+    }
+
+    // Smallest context_hash_at over the 8 rotations and reflections of the
+    // area, so symmetric shapes get the same hash.
+    pub fn canonical_context_hash_at(&self, v: Vertex) -> Hash {
+        let mut hashes = [Hash::new(); 8];
+        self.for_each_context_color(v, |r, c, color| {
+            let images = [
+                (r, c),
+                (c, -r),
+                (-r, -c),
+                (-c, r),
+                (r, -c),
+                (-c, -r),
+                (-r, c),
+                (c, r),
+            ];
+            for (hash, (r, c)) in hashes.iter_mut().zip(images) {
+                *hash ^= CONTEXT_ZOBRIST.of_offset_color(r, c, color);
+            }
+        });
+        hashes.into_iter().min_by_key(|hash| hash.as_u64()).unwrap()
+    }
+
+    fn for_each_context_color(&self, v: Vertex, mut f: impl FnMut(isize, isize, Color)) {
+        let (row, col) = (v.row(), v.column());
+        for row_offset in -2..=2 {
+            for col_offset in -2..=2 {
+                let (r, c) = (row + row_offset, col + col_offset);
+                let color = if r >= 0
+                    && c >= 0
+                    && (r as usize) < self.board_height
+                    && (c as usize) < self.board_width
+                {
+                    self.color_at[Vertex::from_coords(r, c)]
+                } else {
+                    Color::OffBoard
+                };
+                f(row_offset, col_offset, color);
+            }
+        }
+    }
+
     fn recalc_hash(&self) -> Hash {
         let mut new_hash = Hash::new();
         new_hash.set_zero();
@@ -815,28 +1512,43 @@ impl Board {
         }
     }
 
+    // Last move of the player, Vertex::none() if it has not moved yet
+    pub fn last_play(&self, player: Player) -> Vertex {
+        self.last_play[player]
+    }
+
     pub fn both_player_pass(&self) -> bool {
         self.last_play[Player::Black] == Vertex::pass()
             && self.last_play[Player::White] == Vertex::pass()
     }
 
+    // Jigo (integer komi only) counts as a white win, as in the C++ engine, see
+    // playout_outcome
     pub fn playout_winner(&self) -> Player {
-        let score = self.playout_score();
-        // In C++: Player::OfRaw(score <= 0)
-        // Returns White (1) if score <= 0, Black (0) if score > 0
-        if score <= 0 {
-            Player::White
-        } else {
-            Player::Black
-        }
+        self.exact_playout_score().winner().unwrap_or(Player::White)
+    }
+
+    // Winner by exact_playout_score, or jigo
+    pub fn playout_outcome(&self) -> PlayoutOutcome {
+        PlayoutOutcome::of_score(self.exact_playout_score())
     }
 
+    // C++ compatible integer score with komi rounded by ceil(-komi), so 6.5 and
+    // 6.0 komi give the same score. Snapshots keep using it.
     pub fn playout_score(&self) -> i32 {
         let stone_score = self.stone_score();
         let eye_score = self.calculate_eye_score();
         stone_score + eye_score
     }
 
+    // Stones plus single point eyes minus komi, exact to half a point
+    pub fn exact_playout_score(&self) -> Score {
+        let points = self.player_v_cnt[Player::Black] as i32
+            - self.player_v_cnt[Player::White] as i32
+            + self.calculate_eye_score();
+        Score::of_points(points) - Score::of_f32(self.komi)
+    }
+
     fn stone_score(&self) -> i32 {
         // komi_inverse + black_stones - white_stones
         // In C++, komi_inverse = ceil(-komi)
@@ -864,52 +1576,422 @@ impl Board {
         (black_eye as i32) - (white_eye as i32)
     }
 
+    // Per vertex eye score of playout_score: 1 for empty points surrounded by
+    // black stones only, -1 for white, 0 elsewhere. Empty points Tromp-Taylor
+    // counts for a player but this map does not are where the scores disagree.
+    pub fn eye_score_map(&self) -> VertexMap<i32> {
+        let mut map = VertexMap::new_with(0);
+        for i in 0..self.empty_v_cnt {
+            let v = self.empty_v[i as usize];
+            map[v] = self.eye_score(v);
+        }
+        map
+    }
+
+    // Area owner of a vertex at the end of a playout: 1 black, -1 white, 0 neither
+    pub(crate) fn owner_score(&self, v: Vertex) -> i32 {
+        match self.color_at[v] {
+            Color::Black => 1,
+            Color::White => -1,
+            Color::Empty => self.eye_score(v),
+            Color::OffBoard => 0,
+        }
+    }
+
+    // Monte Carlo estimate of the final score, None for no playouts, see
+    // playout::estimate_score
+    pub fn estimate_score(
+        &self,
+        gammas: &Gammas,
+        playout_cnt: usize,
+        random: &mut FastRandom,
+    ) -> Option<ScoreEstimate> {
+        estimate_score(self, gammas, playout_cnt, random)
+    }
+
+    pub fn komi(&self) -> f32 {
+        self.komi
+    }
+
+    pub fn try_set_komi(&mut self, komi: f32) -> Result<(), BoardError> {
+        check_komi(komi)?;
+        self.komi = komi;
+        Ok(())
+    }
+
+    pub fn set_komi(&mut self, komi: f32) {
+        assert!(
+            check_komi(komi).is_ok(),
+            "Komi must be finite and within {}",
+            MAX_KOMI
+        );
+        self.komi = komi;
+    }
+
+    pub fn stone_count(&self, player: Player) -> usize {
+        self.player_v_cnt[player] as usize
+    }
+
+    // Stones captured by the player since the last clear
+    pub fn prisoners(&self, player: Player) -> usize {
+        self.prisoners[player] as usize
+    }
+
+    pub fn stats(&self) -> BoardStats {
+        BoardStats {
+            black_stones: self.stone_count(Player::Black),
+            white_stones: self.stone_count(Player::White),
+            empty: self.empty_vertex_count(),
+            move_no: self.move_no,
+            black_prisoners: self.prisoners(Player::Black),
+            white_prisoners: self.prisoners(Player::White),
+            ko: self.ko_v,
+        }
+    }
+
+    // Stones placed or set up on each vertex since the last clear, counting every
+    // recapture
+    pub fn play_count_map(&self) -> &VertexMap<u32> {
+        &self.play_count
+    }
+
+    // Stones of the player in no particular order
+    pub fn stones(&self, player: Player) -> impl Iterator<Item = Vertex> + '_ {
+        self.player_v[player][..self.player_v_cnt[player] as usize]
+            .iter()
+            .map(|&v| Vertex::from(v))
+    }
+
     pub fn move_count(&self) -> usize {
         self.move_no
     }
 
-    pub fn load(&mut self, source: &Board) {
-        *self = source.clone();
+    // Places stones outside the normal move alternation (SGF AB/AW, problem
+    // setups), replacing whatever is on the vertices. Fails without changing the
+    // board if a vertex is off the board or a chain would have no liberties.
+    pub fn setup(&mut self, player: Player, vertices: &[Vertex]) -> Result<(), BoardError> {
+        let mut colors = self.color_at.clone();
+        for &v in vertices {
+            if !self.is_on_board(v) {
+                return Err(BoardError::OffBoard(v));
+            }
+            colors[v] = Color::from(player);
+        }
+        self.rebuild(&colors)
     }
 
-    #[allow(dead_code)]
-    pub fn tromp_taylor_score(&self) -> f32 {
-        let mut score = self.komi;
+    // Makes player the one to move (SGF PL, problems where white starts). The
+    // positional hash stays, histories of (hash, last player) pairs see the
+    // change through last_player. Handing the move to the other player drops
+    // the ko point, it only binds the player right after the capture.
+    pub fn set_to_move(&mut self, player: Player) {
+        if player == self.act_player() {
+            return;
+        }
+        self.last_player = player.opponent();
+        self.ko_v = Vertex::none();
+    }
+
+    // Takes a stone off the board (SGF AE), Err if there is no stone
+    pub fn remove_stone(&mut self, v: impl Into<Vertex>) -> Result<(), BoardError> {
+        let v = v.into();
+        if !color_is_player(self.color_at[v]) {
+            return Err(BoardError::NoStone(v));
+        }
+        let mut colors = self.color_at.clone();
+        colors[v] = Color::Empty;
+        self.rebuild(&colors)
+    }
+
+    // Replaces the position with the stones of a raw color map, e.g. after a
+    // GUI, FFI caller or SGF setup edited colors directly. Points off the board
+    // are ignored. Chains, liberties, empty and stone lists, neighbor counts and
+    // hashes are rebuilt from scratch; the move count is kept. ko is
+    // Vertex::none() or an empty point. Fails without changing the board.
+    pub fn rebuild_from_colors(
+        &mut self,
+        colors: &VertexMap<Color>,
+        to_move: Player,
+        ko: Vertex,
+    ) -> Result<(), BoardError> {
+        let mut on_board = VertexMap::new_with(Color::Empty);
+        for v in Vertex::all() {
+            if self.is_on_board(v) {
+                on_board[v] = colors[v];
+            }
+        }
+        if ko != Vertex::none() {
+            if !self.is_on_board(ko) {
+                return Err(BoardError::OffBoard(ko));
+            }
+            if on_board[ko] != Color::Empty {
+                return Err(BoardError::Occupied(ko));
+            }
+        }
+        self.rebuild(&on_board)?;
+        self.ko_v = ko;
+        self.last_player = to_move.opponent();
+        Ok(())
+    }
+
+    // Rebuilds chains, empty lists and hashes from scratch for the given stones.
+    // Move history, prisoners and the player to move are kept, the ko is
+    // cleared.
+    fn rebuild(&mut self, colors: &VertexMap<Color>) -> Result<(), BoardError> {
+        let backup = self.clone();
+        let move_no = self.move_no;
+        let last_player = self.last_player;
+        let last_play = self.last_play.clone();
+        let play_count = self.play_count.clone();
+        let prisoners = self.prisoners.clone();
+
+        self.clear();
+        self.move_no = move_no;
+        self.last_player = last_player;
+        self.last_play = last_play;
+        self.play_count = play_count;
+        self.prisoners = prisoners;
 
         for v in Vertex::all() {
-            if !self.is_within_board(v) {
+            if !color_is_player(colors[v]) {
                 continue;
             }
+            // Stones set up on a point count as placed there, for ChainHandle
+            if backup.color_at[v] != colors[v] {
+                self.play_count[v] += 1;
+            }
+            let player = color_to_player(colors[v]);
+            self.place_stone(player, v);
+            for_each_4_nbr!(v, nbr_v, {
+                if self.color_at[nbr_v] == colors[v] {
+                    self.merge_chains(nbr_v, v);
+                }
+            });
+        }
 
-            let color = self.color_at[v];
-            if color == Color::Black {
-                score += 1.0;
-            } else if color == Color::White {
-                score -= 1.0;
-            } else if color == Color::Empty {
-                // Check if it's surrounded by only one color
-                let mut black_neighbors = false;
-                let mut white_neighbors = false;
+        for v in Vertex::all() {
+            if color_is_player(self.color_at[v]) && self.chain_of(v).is_captured() {
+                *self = backup;
+                return Err(BoardError::NoLiberties(v));
+            }
+        }
+        for v in Vertex::all() {
+            self.maybe_in_atari(v);
+        }
+        self.tmp_vertex_set.clear();
+        self.hash3x3_changed.clear();
+        Ok(())
+    }
 
-                for_each_4_nbr!(v, nbr_v, {
-                    let nbr_color = self.color_at[nbr_v];
-                    if nbr_color == Color::Black {
-                        black_neighbors = true;
-                    } else if nbr_color == Color::White {
-                        white_neighbors = true;
-                    }
-                });
+    pub fn snapshot(&self) -> BoardSnapshot {
+        let rows = (0..self.board_height)
+            .map(|row| {
+                (0..self.board_width)
+                    .map(|col| {
+                        let v = Vertex::from_coords(row as isize, col as isize);
+                        color_to_showboard_char(self.color_at[v])
+                    })
+                    .collect()
+            })
+            .collect();
+        BoardSnapshot {
+            width: self.board_width,
+            height: self.board_height,
+            komi: self.komi,
+            rows,
+            to_move: self.act_player(),
+            ko: self.ko_v,
+            move_cnt: self.move_no,
+        }
+    }
 
-                if black_neighbors && !white_neighbors {
-                    score += 1.0;
-                } else if white_neighbors && !black_neighbors {
-                    score -= 1.0;
+    // Inverse of snapshot. Move history (last plays, play counts) is not restored.
+    pub fn from_snapshot(snapshot: &BoardSnapshot) -> Result<Board, BoardError> {
+        let mut board = Board::try_with_size(snapshot.width, snapshot.height)?;
+        if snapshot.rows.len() != snapshot.height {
+            return Err(BoardError::BadSnapshot(format!(
+                "{} rows on a board {} high",
+                snapshot.rows.len(),
+                snapshot.height
+            )));
+        }
+        let mut colors = VertexMap::new_with(Color::Empty);
+        for (row, line) in snapshot.rows.iter().enumerate() {
+            if line.chars().count() != snapshot.width {
+                return Err(BoardError::BadSnapshot(format!(
+                    "row {} is not {} wide",
+                    row, snapshot.width
+                )));
+            }
+            for (col, c) in line.chars().enumerate() {
+                let v = Vertex::from_coords(row as isize, col as isize);
+                colors[v] = match c {
+                    '#' => Color::Black,
+                    'O' => Color::White,
+                    '.' => Color::Empty,
+                    _ => return Err(BoardError::BadSnapshot(format!("bad point '{}'", c))),
+                };
+            }
+        }
+        board
+            .rebuild_from_colors(&colors, snapshot.to_move, snapshot.ko)
+            .map_err(|e| match e {
+                BoardError::OffBoard(_) | BoardError::Occupied(_) => {
+                    BoardError::BadSnapshot("ko is not an empty vertex".to_string())
+                }
+                e => e,
+            })?;
+        board.komi = snapshot.komi;
+        board.move_no = snapshot.move_cnt;
+        Ok(board)
+    }
+
+    pub fn load(&mut self, source: &Board) {
+        self.copy_from(source);
+    }
+
+    // Like clone_from, but in place and without touching the scratch vertex set,
+    // which play_legal clears before use
+    pub fn copy_from(&mut self, source: &Board) {
+        self.move_no = source.move_no;
+        self.komi = source.komi;
+        self.color_at.clone_from(&source.color_at);
+        self.ko_v = source.ko_v;
+        self.last_player = source.last_player;
+        self.last_play.clone_from(&source.last_play);
+        self.board_width = source.board_width;
+        self.board_height = source.board_height;
+        self.hash = source.hash;
+        self.player_v_cnt.clone_from(&source.player_v_cnt);
+        self.player_v.clone_from(&source.player_v);
+        self.player_pos.clone_from(&source.player_pos);
+        self.prisoners.clone_from(&source.prisoners);
+        self.chain_next_v.clone_from(&source.chain_next_v);
+        self.chain_id.clone_from(&source.chain_id);
+        self.chain.clone_from(&source.chain);
+        self.chain_free.clone_from(&source.chain_free);
+        self.chain_free_cnt = source.chain_free_cnt;
+        self.nbr_cnt.clone_from(&source.nbr_cnt);
+        self.empty_v_cnt = source.empty_v_cnt;
+        self.empty_v.clone_from(&source.empty_v);
+        self.empty_pos.clone_from(&source.empty_pos);
+        self.play_count.clone_from(&source.play_count);
+        self.hash3x3.clone_from(&source.hash3x3);
+        self.lazy_atari = source.lazy_atari;
+        self.hash3x3_changed.clone_from(&source.hash3x3_changed);
+    }
+
+    // Black minus white area: stones plus empty regions reaching only one color
+    pub fn area_score(&self) -> i32 {
+        let mut visited = NatSet::<{ Vertex::COUNT }, Vertex>::new();
+        let mut region = Vec::new();
+        let mut score = 0;
+
+        for v in Vertex::all() {
+            match self.color_at[v] {
+                Color::Black => score += 1,
+                Color::White => score -= 1,
+                Color::Empty if !visited.is_marked(v) => {
+                    // Flood fill the empty region, noting which colors it touches
+                    let mut size = 0;
+                    let mut reaches = PlayerMap::new_with(false);
+                    visited.mark(v);
+                    region.push(v);
+                    while let Some(act_v) = region.pop() {
+                        size += 1;
+                        for_each_4_nbr!(act_v, nbr_v, {
+                            let nbr_color = self.color_at[nbr_v];
+                            if color_is_player(nbr_color) {
+                                reaches[color_to_player(nbr_color)] = true;
+                            } else if nbr_color == Color::Empty && !visited.is_marked(nbr_v) {
+                                visited.mark(nbr_v);
+                                region.push(nbr_v);
+                            }
+                        });
+                    }
+                    if reaches[Player::Black] && !reaches[Player::White] {
+                        score += size;
+                    } else if reaches[Player::White] && !reaches[Player::Black] {
+                        score -= size;
+                    }
                 }
+                _ => {}
             }
         }
 
         score
     }
+
+    // Area score minus komi, positive is a black win
+    pub fn tromp_taylor_score(&self) -> f32 {
+        self.area_score() as f32 - self.komi
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check_size(width: usize, height: usize) -> Result<(), BoardError> {
+    if width == 0 || width > MAX_BOARD_SIZE || height == 0 || height > MAX_BOARD_SIZE {
+        return Err(BoardError::BadSize { width, height });
+    }
+    Ok(())
+}
+
+// Recycles boxed boards for tree search, so taking a copy of a position costs a
+// copy_from instead of an allocation and a full clone
+#[derive(Default)]
+pub struct BoardPool {
+    // The boxes handed out by acquire, small ones with boxed-board
+    #[allow(clippy::vec_box)]
+    free: Vec<Box<Board>>,
+}
+
+impl BoardPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Copy of source, reusing a released board if there is one
+    pub fn acquire(&mut self, source: &Board) -> Box<Board> {
+        match self.free.pop() {
+            Some(mut board) => {
+                board.copy_from(source);
+                board
+            }
+            None => Box::new(source.clone()),
+        }
+    }
+
+    pub fn release(&mut self, board: Box<Board>) {
+        self.free.push(board);
+    }
+
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+}
+
+// Position equality, see Board::same_position
+impl PartialEq for Board {
+    fn eq(&self, other: &Board) -> bool {
+        self.same_position(other)
+    }
+}
+
+impl Eq for Board {}
+
+impl std::hash::Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.board_width.hash(state);
+        self.board_height.hash(state);
+        self.hash.as_u64().hash(state);
+        usize::from(self.act_player()).hash(state);
+        usize::from(self.ko_v).hash(state);
+    }
 }
 
 impl Clone for Board {
@@ -925,17 +2007,23 @@ impl Clone for Board {
             board_height: self.board_height,
             hash: self.hash,
             player_v_cnt: self.player_v_cnt.clone(),
+            player_v: self.player_v.clone(),
+            player_pos: self.player_pos.clone(),
+            prisoners: self.prisoners.clone(),
             chain_next_v: self.chain_next_v.clone(),
             chain_id: self.chain_id.clone(),
             chain: self.chain.clone(),
+            chain_free: self.chain_free.clone(),
+            chain_free_cnt: self.chain_free_cnt,
             nbr_cnt: self.nbr_cnt.clone(),
             empty_v_cnt: self.empty_v_cnt,
             empty_v: self.empty_v.clone(),
             empty_pos: self.empty_pos.clone(),
             play_count: self.play_count.clone(),
             hash3x3: self.hash3x3.clone(),
+            lazy_atari: self.lazy_atari,
             hash3x3_changed: self.hash3x3_changed.clone(),
-            tmp_vertex_set: NatSet::<{ Vertex::COUNT }, Vertex>::new(), // Don't need to clone this
+            tmp_vertex_set: Storage::default(), // Don't need to clone this
         }
     }
 }
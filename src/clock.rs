@@ -0,0 +1,157 @@
+use crate::board::Board;
+use std::time::Duration;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimeControl {
+    Absolute {
+        main_time: Duration,
+    },
+    // After main time, each move must fit in period_time or one of the periods is lost
+    ByoYomi {
+        main_time: Duration,
+        period_time: Duration,
+        periods: u32,
+    },
+    // After main time, period_stones moves must be played within each period_time
+    Canadian {
+        main_time: Duration,
+        period_time: Duration,
+        period_stones: u32,
+    },
+}
+
+impl TimeControl {
+    fn main_time(&self) -> Duration {
+        match *self {
+            TimeControl::Absolute { main_time }
+            | TimeControl::ByoYomi { main_time, .. }
+            | TimeControl::Canadian { main_time, .. } => main_time,
+        }
+    }
+}
+
+// Remaining time of one player
+#[derive(Clone, Debug)]
+pub struct Clock {
+    control: TimeControl,
+    main_left: Duration,
+    period_left: Duration,
+    // Byo-yomi periods or Canadian stones left in the current period
+    period_cnt_left: u32,
+    is_expired: bool,
+}
+
+// Fraction of overtime periods we allow ourselves to use, the rest is network/GUI lag margin
+const OVERTIME_SAFETY: f64 = 0.8;
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        let (period_left, period_cnt_left) = match control {
+            TimeControl::Absolute { .. } => (Duration::ZERO, 0),
+            TimeControl::ByoYomi {
+                period_time,
+                periods,
+                ..
+            } => (period_time, periods),
+            TimeControl::Canadian {
+                period_time,
+                period_stones,
+                ..
+            } => (period_time, period_stones),
+        };
+        Clock {
+            control,
+            main_left: control.main_time(),
+            period_left,
+            period_cnt_left,
+            is_expired: false,
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    pub fn main_time_left(&self) -> Duration {
+        self.main_left
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.is_expired
+    }
+
+    // Overrides the state from an external source (e.g. GTP time_left: time and
+    // stones/periods left, with 0 stones meaning main time)
+    pub fn set_time_left(&mut self, time_left: Duration, cnt_left: u32) {
+        if cnt_left == 0 {
+            self.main_left = time_left;
+        } else {
+            self.main_left = Duration::ZERO;
+            self.period_left = time_left;
+            self.period_cnt_left = cnt_left;
+        }
+        self.is_expired = false;
+    }
+
+    // Thinking time for the next move, when about moves_left more moves are expected
+    pub fn time_for_move(&self, moves_left: usize) -> Duration {
+        if self.is_expired {
+            return Duration::ZERO;
+        }
+        let main_share = self.main_left / moves_left.max(1) as u32;
+        let overtime = match self.control {
+            TimeControl::Absolute { .. } => Duration::ZERO,
+            TimeControl::ByoYomi { .. } => self.period_left.mul_f64(OVERTIME_SAFETY),
+            TimeControl::Canadian { .. } => {
+                (self.period_left / self.period_cnt_left.max(1)).mul_f64(OVERTIME_SAFETY)
+            }
+        };
+        main_share + overtime
+    }
+
+    // Charges the time used by one move
+    pub fn spend(&mut self, elapsed: Duration) {
+        if elapsed <= self.main_left {
+            self.main_left -= elapsed;
+            return;
+        }
+        let over = elapsed - self.main_left;
+        self.main_left = Duration::ZERO;
+
+        match self.control {
+            TimeControl::Absolute { .. } => self.is_expired = true,
+            TimeControl::ByoYomi { period_time, .. } => {
+                // Every started period beyond the first one is lost
+                let lost = (over.as_nanos() / period_time.as_nanos().max(1)) as u32;
+                if lost >= self.period_cnt_left {
+                    self.period_cnt_left = 0;
+                    self.is_expired = true;
+                } else {
+                    self.period_cnt_left -= lost;
+                }
+            }
+            TimeControl::Canadian {
+                period_time,
+                period_stones,
+                ..
+            } => {
+                if over > self.period_left {
+                    self.period_left = Duration::ZERO;
+                    self.is_expired = true;
+                    return;
+                }
+                self.period_left -= over;
+                self.period_cnt_left -= 1;
+                if self.period_cnt_left == 0 {
+                    self.period_left = period_time;
+                    self.period_cnt_left = period_stones;
+                }
+            }
+        }
+    }
+}
+
+// Rough number of own moves still to be played, used to split the main time
+pub fn expected_moves_left(board: &Board) -> usize {
+    (board.empty_vertex_count() / 2).max(10)
+}
@@ -0,0 +1,229 @@
+use crate::benchmark::DEFAULT_BENCH_SEED;
+use crate::gammas::Gammas;
+use crate::rules::Rules;
+use crate::tune::SamplerParams;
+use crate::types::{check_komi, DEFAULT_BOARD_SIZE, MAX_BOARD_SIZE};
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+// Settings of an experiment, read from a TOML file by the gtp, bench_history
+// and self_play binaries with --config, e.g.
+//
+//   rules = "japanese"
+//   komi = 6.5
+//   gammas = "gammas.txt"
+//
+//   [gtp]
+//   playouts = 5000
+//   threads = 4
+//
+//   [bench]
+//   playouts = 200000
+//   board_size = 19
+//
+//   [self_play]
+//   board_size = 9
+//   seed = 7
+//
+//   [sampler]
+//   capture_bonus = 3.0
+//
+// Every key is optional, unknown keys are errors so that a typo does not fall
+// back to a default unnoticed. The [sampler] keys are those of tune::PARAMS.
+// Relative paths are taken from the directory of the file. Command line
+// arguments of the binaries override the file.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    // Named by rules, with komi replacing the komi of the named rules
+    pub rules: Rules,
+    // Uniform gammas if None
+    pub gammas_path: Option<PathBuf>,
+    pub sampler: SamplerParams,
+    pub gtp: GtpConfig,
+    pub bench: BenchConfig,
+    pub self_play: SelfPlayConfig,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GtpConfig {
+    // Per move
+    pub playout_cnt: usize,
+    pub thread_cnt: usize,
+    pub board_size: usize,
+    pub seed: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BenchConfig {
+    pub playout_cnt: usize,
+    pub board_size: usize,
+    pub seed: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SelfPlayConfig {
+    pub board_size: usize,
+    pub seed: u32,
+}
+
+// The defaults of the binaries without a config
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            rules: Rules::chinese(),
+            gammas_path: None,
+            sampler: SamplerParams::default(),
+            gtp: GtpConfig {
+                playout_cnt: 1000,
+                thread_cnt: 1,
+                board_size: DEFAULT_BOARD_SIZE,
+                seed: 1,
+            },
+            bench: BenchConfig {
+                playout_cnt: 100000,
+                board_size: 9,
+                seed: DEFAULT_BENCH_SEED,
+            },
+            self_play: SelfPlayConfig {
+                board_size: 9,
+                seed: 1,
+            },
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        Self::from_toml(&text, base_dir).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Relative paths of text are taken from base_dir
+    pub fn from_toml(text: &str, base_dir: &Path) -> Result<Self, String> {
+        let table: Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let mut config = Config::default();
+
+        // Before komi, which overrides the komi of the rules
+        if let Some(value) = table.get("rules") {
+            let name = string("rules", value)?;
+            config.rules =
+                Rules::by_name(name).ok_or_else(|| format!("rules: unknown rules {}", name))?;
+        }
+        for (key, value) in table.iter() {
+            match key.as_str() {
+                "rules" => {}
+                "komi" => {
+                    let komi = number(key, value)? as f32;
+                    check_komi(komi).map_err(|e| format!("komi: {}", e))?;
+                    config.rules.komi = komi;
+                }
+                "gammas" => config.gammas_path = Some(base_dir.join(string(key, value)?)),
+                "sampler" => {
+                    for (name, value) in section(key, value)? {
+                        let path = format!("sampler.{}", name);
+                        config
+                            .sampler
+                            .set(name, number(&path, value)?)
+                            .map_err(|e| format!("{}: {}", path, e))?;
+                    }
+                }
+                "gtp" => {
+                    let gtp = &mut config.gtp;
+                    for (name, value) in section(key, value)? {
+                        let path = format!("gtp.{}", name);
+                        match name.as_str() {
+                            "playouts" => gtp.playout_cnt = count(&path, value)?,
+                            "threads" => gtp.thread_cnt = count(&path, value)?,
+                            "board_size" => gtp.board_size = board_size(&path, value)?,
+                            "seed" => gtp.seed = seed(&path, value)?,
+                            _ => return Err(format!("unknown key {}", path)),
+                        }
+                    }
+                }
+                "bench" => {
+                    let bench = &mut config.bench;
+                    for (name, value) in section(key, value)? {
+                        let path = format!("bench.{}", name);
+                        match name.as_str() {
+                            "playouts" => bench.playout_cnt = count(&path, value)?,
+                            "board_size" => bench.board_size = board_size(&path, value)?,
+                            "seed" => bench.seed = seed(&path, value)?,
+                            _ => return Err(format!("unknown key {}", path)),
+                        }
+                    }
+                }
+                "self_play" => {
+                    let self_play = &mut config.self_play;
+                    for (name, value) in section(key, value)? {
+                        let path = format!("self_play.{}", name);
+                        match name.as_str() {
+                            "board_size" => self_play.board_size = board_size(&path, value)?,
+                            "seed" => self_play.seed = seed(&path, value)?,
+                            _ => return Err(format!("unknown key {}", path)),
+                        }
+                    }
+                }
+                _ => return Err(format!("unknown key {}", key)),
+            }
+        }
+        Ok(config)
+    }
+
+    // Gammas::load of gammas_path, uniform gammas without one
+    pub fn gammas(&self) -> Result<Gammas, String> {
+        match &self.gammas_path {
+            Some(path) => Gammas::load(path),
+            None => Ok(Gammas::new()),
+        }
+    }
+}
+
+fn section<'a>(key: &str, value: &'a Value) -> Result<&'a Table, String> {
+    value
+        .as_table()
+        .ok_or_else(|| format!("{}: expected a table", key))
+}
+
+fn string<'a>(key: &str, value: &'a Value) -> Result<&'a str, String> {
+    value
+        .as_str()
+        .ok_or_else(|| format!("{}: expected a string", key))
+}
+
+// Integers are accepted for floats
+fn number(key: &str, value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Float(x) => Ok(*x),
+        Value::Integer(x) => Ok(*x as f64),
+        _ => Err(format!("{}: expected a number", key)),
+    }
+}
+
+// Positive integer
+fn count(key: &str, value: &Value) -> Result<usize, String> {
+    match value.as_integer() {
+        Some(x) if x > 0 => Ok(x as usize),
+        _ => Err(format!("{}: expected a positive integer", key)),
+    }
+}
+
+// Up to MAX_BOARD_SIZE, Board::with_size panics on larger sizes
+fn board_size(key: &str, value: &Value) -> Result<usize, String> {
+    match value.as_integer() {
+        Some(x) if (1..=MAX_BOARD_SIZE as i64).contains(&x) => Ok(x as usize),
+        _ => Err(format!(
+            "{}: expected an integer in 1..={}",
+            key, MAX_BOARD_SIZE
+        )),
+    }
+}
+
+fn seed(key: &str, value: &Value) -> Result<u32, String> {
+    value
+        .as_integer()
+        .and_then(|x| u32::try_from(x).ok())
+        .ok_or_else(|| format!("{}: expected an integer in 0..2^32", key))
+}
@@ -0,0 +1,210 @@
+use crate::board::Board;
+use crate::fast_random::FastRandom;
+use crate::gammas::Gammas;
+use crate::nat_set::NatSet;
+use crate::tactics::chain;
+use crate::types::{
+    color_is_player, color_to_player, Color, Nat, Player, PlayerMap, Vertex, VertexMap,
+    VertexNeighbors,
+};
+
+// Moves to play before passing at the end of an area scored game, so that the
+// Tromp-Taylor score of the final position matches the ownership estimate:
+// nothing gets captured after we pass, dead stones are off the board and
+// neutral points are filled.
+
+// Stones owned by the opponent at least this much count as dead
+pub const DEAD_OWNERSHIP: f64 = 0.7;
+
+// Kinds of cleanup moves, most urgent first
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum CleanupKind {
+    // Extends an own chain in atari
+    Defend,
+    // Takes a liberty of dead opponent stones
+    CaptureDead,
+    // Fills a neutral point next to own stones
+    FillDame,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CleanupMove {
+    pub v: Vertex,
+    pub kind: CleanupKind,
+}
+
+// Cleanup moves of player sorted by kind, then vertex. ownership is as in
+// ScoreEstimate (1.0 black). Moves leaving the played chain in atari are never
+// suggested, unless they capture. Empty when player can safely pass.
+pub fn cleanup_moves(
+    board: &Board,
+    player: Player,
+    ownership: &VertexMap<f64>,
+) -> Vec<CleanupMove> {
+    let own = Color::from(player);
+    let mut moves = Vec::new();
+    let mut seen_chain = NatSet::<{ Vertex::COUNT }, Vertex>::new();
+
+    for v in Vertex::all() {
+        let color = board.color_at(v);
+        if !color_is_player(color) || seen_chain.is_marked(v) {
+            continue;
+        }
+        let (stones, libs) = chain(board, v);
+        for s in Vertex::all().filter(|&s| stones.is_marked(s)) {
+            seen_chain.mark(s);
+        }
+        if color == own {
+            if libs.len() == 1 && is_safe(board, player, libs[0]) {
+                moves.push(CleanupMove {
+                    v: libs[0],
+                    kind: CleanupKind::Defend,
+                });
+            }
+        } else if ownership[v] * sign(player) >= DEAD_OWNERSHIP {
+            for &lib in libs.iter() {
+                if is_safe(board, player, lib) {
+                    moves.push(CleanupMove {
+                        v: lib,
+                        kind: CleanupKind::CaptureDead,
+                    });
+                }
+            }
+        }
+    }
+
+    for v in neutral_points(board, ownership) {
+        let touches_own = v.neighbors4().any(|nbr| board.color_at(nbr) == own);
+        if touches_own && is_safe(board, player, v) {
+            moves.push(CleanupMove {
+                v,
+                kind: CleanupKind::FillDame,
+            });
+        }
+    }
+
+    moves.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then(usize::from(a.v).cmp(&usize::from(b.v)))
+    });
+    // Keep the most urgent kind of every vertex
+    let mut listed = NatSet::<{ Vertex::COUNT }, Vertex>::new();
+    moves.retain(|m| {
+        let first = !listed.is_marked(m.v);
+        listed.mark(m.v);
+        first
+    });
+    moves
+}
+
+// Stones of chains owned by the opponent at least DEAD_OWNERSHIP, ownership as
+// in cleanup_moves
+pub fn dead_stones(board: &Board, ownership: &VertexMap<f64>) -> Vec<Vertex> {
+    let mut dead = Vec::new();
+    let mut seen_chain = NatSet::<{ Vertex::COUNT }, Vertex>::new();
+    for v in Vertex::all() {
+        let color = board.color_at(v);
+        if !color_is_player(color) || seen_chain.is_marked(v) {
+            continue;
+        }
+        let stones = chain(board, v).0;
+        let is_dead = ownership[v] * sign(color_to_player(color)) <= -DEAD_OWNERSHIP;
+        for s in Vertex::all().filter(|&s| stones.is_marked(s)) {
+            seen_chain.mark(s);
+            if is_dead {
+                dead.push(s);
+            }
+        }
+    }
+    dead
+}
+
+// Legal and the played chain keeps two liberties, or the move captures
+fn is_safe(board: &Board, player: Player, v: Vertex) -> bool {
+    if !board.is_legal(player, v) {
+        return false;
+    }
+    let mut next = board.clone();
+    next.play_legal(player, v);
+    let captured = v
+        .neighbors4()
+        .any(|nbr| color_is_player(board.color_at(nbr)) && next.color_at(nbr) == Color::Empty);
+    next.color_at(v) == Color::from(player) && (captured || chain(&next, v).1.len() >= 2)
+}
+
+// Empty points of regions reaching live stones of both colors, dead stones
+// count as their captor's
+fn neutral_points(board: &Board, ownership: &VertexMap<f64>) -> Vec<Vertex> {
+    let mut points = Vec::new();
+    let mut visited = NatSet::<{ Vertex::COUNT }, Vertex>::new();
+    for v in Vertex::all() {
+        if board.color_at(v) != Color::Empty || visited.is_marked(v) {
+            continue;
+        }
+        let mut region = vec![v];
+        let mut reaches = PlayerMap::new_with(false);
+        visited.mark(v);
+        let mut idx = 0;
+        while idx < region.len() {
+            let act_v = region[idx];
+            idx += 1;
+            for nbr in act_v.neighbors4() {
+                match board.color_at(nbr) {
+                    Color::Empty => {
+                        if !visited.is_marked(nbr) {
+                            visited.mark(nbr);
+                            region.push(nbr);
+                        }
+                    }
+                    Color::OffBoard => {}
+                    color => {
+                        let mut owner = color_to_player(color);
+                        if ownership[nbr] * sign(owner) <= -DEAD_OWNERSHIP {
+                            owner = owner.opponent();
+                        }
+                        reaches[owner] = true;
+                    }
+                }
+            }
+        }
+        if reaches[Player::Black] && reaches[Player::White] {
+            points.extend(region);
+        }
+    }
+    points
+}
+
+fn sign(player: Player) -> f64 {
+    match player {
+        Player::Black => 1.0,
+        Player::White => -1.0,
+    }
+}
+
+// Tromp-Taylor score of the board after taking off the stones the playouts of
+// estimate_score find dead, for games ended by passing before the dead
+// stones were captured. The playouts start from the stones alone, so the
+// passes that ended the game do not end them.
+pub fn score_with_dead_removal(
+    board: &Board,
+    gammas: &Gammas,
+    playout_cnt: usize,
+    random: &mut FastRandom,
+) -> f32 {
+    let mut scratch = Board::with_size(board.width(), board.height());
+    scratch.set_komi(board.komi());
+    let mut colors = board.color_at.clone();
+    scratch
+        .rebuild_from_colors(&colors, board.act_player(), Vertex::none())
+        .expect("the stones of a board are a valid position");
+    if let Some(estimate) = scratch.estimate_score(gammas, playout_cnt, random) {
+        for v in dead_stones(board, &estimate.ownership) {
+            colors[v] = Color::Empty;
+        }
+    }
+    scratch
+        .rebuild_from_colors(&colors, board.act_player(), Vertex::none())
+        .expect("removing stones keeps the position valid");
+    scratch.tromp_taylor_score()
+}
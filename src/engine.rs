@@ -0,0 +1,384 @@
+use crate::board::Board;
+use crate::clock::{expected_moves_left, Clock};
+use crate::fast_random::FastRandom;
+use crate::gammas::Gammas;
+use crate::playout::PlayoutPool;
+use crate::policy::PlayoutPolicy;
+use crate::sampler::Sampler;
+use crate::tune::SamplerParams;
+use crate::types::{Vertex, VertexMap};
+use std::time::{Duration, Instant};
+
+// Anything that can choose a move in a position
+pub trait Engine {
+    fn name(&self) -> String;
+    fn genmove(&mut self, board: &Board) -> Vertex;
+
+    // Thinks within the time the clock allows and charges the clock
+    fn genmove_with_clock(&mut self, board: &Board, clock: &mut Clock) -> Vertex {
+        let start = Instant::now();
+        let v = self.genmove(board);
+        clock.spend(start.elapsed());
+        v
+    }
+
+    // Estimated win rate of the player who got the last genmove move, if the
+    // engine estimates one
+    fn last_winrate(&self) -> Option<f64> {
+        None
+    }
+
+    // Searches board without playing a move, the visited first moves best
+    // first. Empty for engines that do not search.
+    fn analyze(&mut self, _board: &Board) -> Vec<MoveStats> {
+        Vec::new()
+    }
+}
+
+// A first move of a search, for analysis front ends
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MoveStats {
+    pub v: Vertex,
+    pub visits: u32,
+    // Smoothed win rate of the player to move, as genmove ranks the moves
+    pub winrate: f64,
+}
+
+// When an engine gives up: its win rate stays below threshold for
+// consecutive_moves of its moves in a row, none of them before min_move_no
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ResignPolicy {
+    pub threshold: f64,
+    pub consecutive_moves: usize,
+    pub min_move_no: usize,
+}
+
+impl Default for ResignPolicy {
+    fn default() -> Self {
+        ResignPolicy {
+            threshold: 0.1,
+            consecutive_moves: 3,
+            min_move_no: 20,
+        }
+    }
+}
+
+// Applies a ResignPolicy to the moves of one engine in one game
+#[derive(Clone, Debug)]
+pub struct ResignTracker {
+    policy: ResignPolicy,
+    low_cnt: usize,
+}
+
+impl ResignTracker {
+    pub fn new(policy: ResignPolicy) -> Self {
+        ResignTracker { policy, low_cnt: 0 }
+    }
+
+    // Call after every move of the engine, move_no counting moves before it.
+    // Returns whether to resign. No win rate counts as not low.
+    pub fn should_resign(&mut self, move_no: usize, winrate: Option<f64>) -> bool {
+        match winrate {
+            Some(rate) if move_no >= self.policy.min_move_no && rate < self.policy.threshold => {
+                self.low_cnt += 1
+            }
+            _ => self.low_cnt = 0,
+        }
+        self.low_cnt >= self.policy.consecutive_moves
+    }
+}
+
+// Plays a single move sampled from the playout policy
+pub struct SamplerEngine<'a> {
+    gammas: &'a Gammas,
+    sampler: Sampler,
+    random: FastRandom,
+}
+
+impl<'a> SamplerEngine<'a> {
+    pub fn new(gammas: &'a Gammas, seed: u32) -> Self {
+        SamplerEngine {
+            gammas,
+            sampler: Sampler::new(&Board::new(), gammas),
+            random: FastRandom::new(seed),
+        }
+    }
+}
+
+impl Engine for SamplerEngine<'_> {
+    fn name(&self) -> String {
+        "sampler".to_string()
+    }
+
+    fn genmove(&mut self, board: &Board) -> Vertex {
+        self.sampler.new_playout(board, self.gammas);
+        self.sampler.sample_move(board, &mut self.random)
+    }
+}
+
+// Flat Monte Carlo: plays the first move of the playouts with the best win rate
+pub struct MonteCarloEngine<'a> {
+    gammas: &'a Gammas,
+    pool: PlayoutPool,
+    seed: u32,
+    playout_cnt: usize,
+    playouts_per_second: f64,
+    last_winrate: Option<f64>,
+    deterministic: bool,
+    sampler_params: SamplerParams,
+}
+
+// With several threads playouts are dispatched in batches of this many per thread
+const BATCH_PER_THREAD: usize = 16;
+
+impl<'a> MonteCarloEngine<'a> {
+    pub fn new(gammas: &'a Gammas, playout_cnt: usize, seed: u32) -> Self {
+        MonteCarloEngine {
+            gammas,
+            pool: PlayoutPool::new(gammas, 1, seed),
+            seed,
+            playout_cnt,
+            playouts_per_second: 0.0,
+            last_winrate: None,
+            deterministic: false,
+            sampler_params: SamplerParams::default(),
+        }
+    }
+
+    pub fn set_thread_count(&mut self, thread_cnt: usize) {
+        self.pool = PlayoutPool::new(self.gammas, thread_cnt, self.seed);
+        self.pool.set_deterministic(self.deterministic);
+        self.pool.set_sampler_params(&self.sampler_params);
+    }
+
+    // Sampler constants of the playouts, e.g. tuned by SpsaTuner
+    pub fn set_sampler_params(&mut self, params: &SamplerParams) {
+        self.sampler_params = *params;
+        self.pool.set_sampler_params(params);
+    }
+
+    // Same moves for any thread count given the same playout count, see
+    // PlayoutPool::set_deterministic
+    pub fn set_deterministic(&mut self, enabled: bool) {
+        self.deterministic = enabled;
+        self.pool.set_deterministic(enabled);
+    }
+
+    // Measured during the last search
+    pub fn playouts_per_second(&self) -> f64 {
+        self.playouts_per_second
+    }
+
+    fn search(
+        &mut self,
+        board: &Board,
+        playout_limit: usize,
+        time_limit: Option<Duration>,
+    ) -> Vec<MoveStats> {
+        let pl = board.act_player();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!(
+            "search",
+            engine = "flat-mc",
+            move_no = board.move_count() + 1,
+            player = ?pl,
+            threads = self.pool.thread_count()
+        )
+        .entered();
+        let mut win_cnt = VertexMap::<u32>::new();
+        let mut visit_cnt = VertexMap::<u32>::new();
+        let batch_size = match self.pool.thread_count() {
+            1 => 1,
+            thread_cnt => thread_cnt * BATCH_PER_THREAD,
+        };
+
+        let start = Instant::now();
+        let mut playout_idx = 0;
+        while playout_idx < playout_limit {
+            if time_limit.is_some_and(|limit| playout_idx > 0 && start.elapsed() >= limit) {
+                break;
+            }
+            let cnt = batch_size.min(playout_limit - playout_idx);
+            for result in self.pool.run_many(board, cnt, self.gammas) {
+                visit_cnt[result.first_v] += 1;
+                if result.winner == pl {
+                    win_cnt[result.first_v] += 1;
+                }
+            }
+            playout_idx += cnt;
+        }
+        self.playouts_per_second = playout_idx as f64 / start.elapsed().as_secs_f64();
+
+        let stats = move_stats(board, &win_cnt, &visit_cnt);
+        self.last_winrate = best_first_move(&stats).1;
+        #[cfg(feature = "tracing")]
+        trace_search_summary(board, &stats, playout_idx, start);
+        stats
+    }
+}
+
+// Per-move summary of a search: the chosen move, its win rate and visits,
+// the runner-up and the playout rate
+#[cfg(feature = "tracing")]
+fn trace_search_summary(board: &Board, stats: &[MoveStats], playout_cnt: usize, start: Instant) {
+    let gtp = |v: Vertex| crate::types::vertex_to_gtp(v, board.height());
+    let (best_v, winrate) = best_first_move(stats);
+    let runner_up = stats.iter().skip(1).max_by_key(|s| s.visits);
+    let elapsed = start.elapsed();
+    tracing::info!(
+        mv = gtp(best_v),
+        winrate = winrate.unwrap_or(f64::NAN),
+        visits = stats.first().map_or(0, |s| s.visits),
+        runner_up = runner_up.map_or_else(|| "-".to_string(), |s| gtp(s.v)),
+        playouts = playout_cnt,
+        elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+        playouts_per_second = playout_cnt as f64 / elapsed.as_secs_f64(),
+        "search finished"
+    );
+}
+
+// The moves some playout started with, by smoothed win rate, best first. Ties
+// keep the board.empties order.
+fn move_stats(
+    board: &Board,
+    win_cnt: &VertexMap<u32>,
+    visit_cnt: &VertexMap<u32>,
+) -> Vec<MoveStats> {
+    let mut stats: Vec<MoveStats> = board
+        .empties()
+        .filter(|&v| visit_cnt[v] > 0)
+        .map(|v| MoveStats {
+            v,
+            visits: visit_cnt[v],
+            // Laplace smoothing keeps rarely sampled moves from winning by luck
+            winrate: (win_cnt[v] as f64 + 1.0) / (visit_cnt[v] as f64 + 2.0),
+        })
+        .collect();
+    stats.sort_by(|a, b| b.winrate.total_cmp(&a.winrate));
+    stats
+}
+
+// The best move of move_stats and its win rate, pass if no playout started
+// with a move
+fn best_first_move(stats: &[MoveStats]) -> (Vertex, Option<f64>) {
+    stats
+        .first()
+        .map_or((Vertex::pass(), None), |best| (best.v, Some(best.winrate)))
+}
+
+impl Engine for MonteCarloEngine<'_> {
+    fn name(&self) -> String {
+        format!("flat-mc-{}", self.playout_cnt)
+    }
+
+    fn genmove(&mut self, board: &Board) -> Vertex {
+        let stats = self.search(board, self.playout_cnt, None);
+        best_first_move(&stats).0
+    }
+
+    // Searches until the allotted time is used up instead of a fixed playout count
+    fn genmove_with_clock(&mut self, board: &Board, clock: &mut Clock) -> Vertex {
+        let start = Instant::now();
+        let budget = clock.time_for_move(expected_moves_left(board));
+        let stats = self.search(board, usize::MAX, Some(budget));
+        clock.spend(start.elapsed());
+        best_first_move(&stats).0
+    }
+
+    fn last_winrate(&self) -> Option<f64> {
+        self.last_winrate
+    }
+
+    fn analyze(&mut self, board: &Board) -> Vec<MoveStats> {
+        let last_winrate = self.last_winrate;
+        let stats = self.search(board, self.playout_cnt, None);
+        self.last_winrate = last_winrate;
+        stats
+    }
+}
+
+// Flat Monte Carlo like MonteCarloEngine with any playout policy, single
+// threaded
+pub struct PolicyEngine<P: PlayoutPolicy> {
+    policy: P,
+    board: Board,
+    random: FastRandom,
+    playout_cnt: usize,
+    last_winrate: Option<f64>,
+}
+
+impl<P: PlayoutPolicy> PolicyEngine<P> {
+    pub fn new(policy: P, playout_cnt: usize, seed: u32) -> Self {
+        PolicyEngine {
+            policy,
+            board: Board::new(),
+            random: FastRandom::new(seed),
+            playout_cnt,
+            last_winrate: None,
+        }
+    }
+}
+
+impl<P: PlayoutPolicy> PolicyEngine<P> {
+    fn search(&mut self, board: &Board) -> Vec<MoveStats> {
+        let pl = board.act_player();
+        #[cfg(feature = "tracing")]
+        let (_span, start) = (
+            tracing::info_span!(
+                "search",
+                engine = "policy-mc",
+                move_no = board.move_count() + 1,
+                player = ?pl
+            )
+            .entered(),
+            Instant::now(),
+        );
+        let mut win_cnt = VertexMap::<u32>::new();
+        let mut visit_cnt = VertexMap::<u32>::new();
+        for _ in 0..self.playout_cnt {
+            self.board.load(board);
+            self.policy.new_playout(&self.board);
+            let mut first_v = Vertex::none();
+            while !self.board.both_player_pass() {
+                let v = self.policy.sample(&self.board, &mut self.random);
+                if first_v == Vertex::none() {
+                    first_v = v;
+                }
+                self.board.play_legal(self.board.act_player(), v);
+                self.policy.move_played(&self.board);
+            }
+            visit_cnt[first_v] += 1;
+            if self.board.playout_winner() == pl {
+                win_cnt[first_v] += 1;
+            }
+        }
+
+        let stats = move_stats(board, &win_cnt, &visit_cnt);
+        self.last_winrate = best_first_move(&stats).1;
+        #[cfg(feature = "tracing")]
+        trace_search_summary(board, &stats, self.playout_cnt, start);
+        stats
+    }
+}
+
+impl<P: PlayoutPolicy> Engine for PolicyEngine<P> {
+    fn name(&self) -> String {
+        format!("policy-mc-{}", self.playout_cnt)
+    }
+
+    fn genmove(&mut self, board: &Board) -> Vertex {
+        let stats = self.search(board);
+        best_first_move(&stats).0
+    }
+
+    fn last_winrate(&self) -> Option<f64> {
+        self.last_winrate
+    }
+
+    fn analyze(&mut self, board: &Board) -> Vec<MoveStats> {
+        let last_winrate = self.last_winrate;
+        let stats = self.search(board);
+        self.last_winrate = last_winrate;
+        stats
+    }
+}
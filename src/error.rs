@@ -0,0 +1,43 @@
+use crate::sgf::SgfError;
+use crate::types::{Color, CoordError, Vertex, MAX_BOARD_SIZE, MAX_KOMI};
+
+// Errors of the try_ variants of board operations. Vertices are printed as
+// their raw index, the board size is not known here.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum BoardError {
+    #[error("board size {width}x{height} is not between 1x1 and {max}x{max}", max = MAX_BOARD_SIZE)]
+    BadSize { width: usize, height: usize },
+    #[error("coordinates {row}-{column} are outside the board")]
+    BadCoords { row: i32, column: i32 },
+    #[error("vertex {} is not on the board", usize::from(*.0))]
+    OffBoard(Vertex),
+    #[error("vertex {} is occupied", usize::from(*.0))]
+    Occupied(Vertex),
+    #[error("vertex {} is banned by ko", usize::from(*.0))]
+    Ko(Vertex),
+    #[error("move at {} is suicide", usize::from(*.0))]
+    Suicide(Vertex),
+    #[error("no stone at {}", usize::from(*.0))]
+    NoStone(Vertex),
+    #[error("chain at {} has no liberties", usize::from(*.0))]
+    NoLiberties(Vertex),
+    #[error("bad board snapshot: {0}")]
+    BadSnapshot(String),
+    #[error("color {0:?} is not a player")]
+    NotAPlayer(Color),
+    #[error("komi {0} is not a number between -{max} and {max}", max = MAX_KOMI)]
+    BadKomi(String),
+    #[error("{choices} move choices for {boards} boards")]
+    ChoiceCount { boards: usize, choices: usize },
+}
+
+// Anything that can go wrong reading untrusted input (GTP, SGF)
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error(transparent)]
+    Coord(#[from] CoordError),
+    #[error(transparent)]
+    Sgf(#[from] SgfError),
+    #[error(transparent)]
+    Board(#[from] BoardError),
+}
@@ -8,6 +8,11 @@ impl FastRandom {
         FastRandom { seed }
     }
 
+    // Current state, FastRandom::new of it continues the same sequence
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
     pub fn get_next_uint(&mut self) -> u32 {
         let lo = 16807u32.wrapping_mul(self.seed & 0xffff);
         let hi = 16807u32.wrapping_mul(self.seed >> 16);
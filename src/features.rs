@@ -0,0 +1,98 @@
+use crate::board::Board;
+use crate::types::{color_is_player, Color, Coord, Nat, Vertex, VertexMap, VertexNeighbors};
+use std::collections::VecDeque;
+
+// Per-vertex distance features for the sampler and for network encoders
+
+// Lines beyond this one are all reported as MAX_LINE
+pub const MAX_LINE: u8 = 5;
+// CFG distance of vertices beyond the limit, off-board vertices and all
+// vertices when there is no last move
+pub const CFG_FAR: u8 = u8::MAX;
+
+pub struct DistanceFeatures {
+    width: usize,
+    height: usize,
+    max_cfg_distance: u8,
+    line: VertexMap<u8>,
+    cfg_distance: VertexMap<u8>,
+}
+
+impl DistanceFeatures {
+    // CFG distances above max_cfg_distance are reported as CFG_FAR
+    pub fn new(max_cfg_distance: u8) -> Self {
+        DistanceFeatures {
+            width: 0,
+            height: 0,
+            max_cfg_distance,
+            line: VertexMap::new(),
+            cfg_distance: VertexMap::new_with(CFG_FAR),
+        }
+    }
+
+    // Line map is only recomputed when the board size changes
+    pub fn update(&mut self, board: &Board) {
+        if (board.width(), board.height()) != (self.width, self.height) {
+            self.width = board.width();
+            self.height = board.height();
+            self.line = line_map(self.width, self.height);
+        }
+        self.update_cfg_distance(board);
+    }
+
+    // 1 on the edge, 2 on the second line, ... capped at MAX_LINE, 0 off the board
+    pub fn line(&self) -> &VertexMap<u8> {
+        &self.line
+    }
+
+    // Common fate graph distance from the last move: a chain counts as a single
+    // node, so all its stones are as far as the nearest one
+    pub fn cfg_distance(&self) -> &VertexMap<u8> {
+        &self.cfg_distance
+    }
+
+    fn update_cfg_distance(&mut self, board: &Board) {
+        self.cfg_distance = VertexMap::new_with(CFG_FAR);
+        let start = board.last_vertex();
+        if start == Vertex::pass() || start == Vertex::none() {
+            return;
+        }
+
+        // 0-1 BFS, moving within a chain is free
+        let mut queue = VecDeque::new();
+        self.cfg_distance[start] = 0;
+        queue.push_back(start);
+        while let Some(v) = queue.pop_front() {
+            let dist = self.cfg_distance[v];
+            let color = board.color_at(v);
+            for nbr in v.neighbors4() {
+                let nbr_color = board.color_at(nbr);
+                if nbr_color == Color::OffBoard {
+                    continue;
+                }
+                let same_chain = color_is_player(color) && nbr_color == color;
+                let nbr_dist = if same_chain { dist } else { dist + 1 };
+                if nbr_dist > self.max_cfg_distance || nbr_dist >= self.cfg_distance[nbr] {
+                    continue;
+                }
+                self.cfg_distance[nbr] = nbr_dist;
+                if same_chain {
+                    queue.push_front(nbr);
+                } else {
+                    queue.push_back(nbr);
+                }
+            }
+        }
+    }
+}
+
+// Line numbers for an empty board of the given size, see DistanceFeatures::line
+pub fn line_map(width: usize, height: usize) -> VertexMap<u8> {
+    let mut line = VertexMap::new();
+    for v in Vertex::all() {
+        if let Some(coord) = Coord::of_vertex(v, width, height) {
+            line[v] = coord.line(width, height).min(MAX_LINE as usize) as u8;
+        }
+    }
+    line
+}
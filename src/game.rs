@@ -0,0 +1,426 @@
+use crate::board::Board;
+use crate::error::BoardError;
+use crate::hash::Hash;
+use crate::rules::{KoRule, Rules, Scoring};
+use crate::score::Score;
+use crate::types::{
+    board_symmetry_count, check_komi, symmetric_coords, Color, Move, Player, Vertex,
+};
+
+// A board with rules and move history
+#[derive(Clone)]
+pub struct Game {
+    board: Board,
+    // Position before the first move, undo replays the moves from it
+    start: Board,
+    rules: Rules,
+    moves: Vec<Move>,
+    // Positional hash and last player of every position so far, for superko
+    history: Vec<(Hash, Player)>,
+    handicap: usize,
+    width: usize,
+    height: usize,
+    cycle_detection: Option<CycleDetection>,
+    // Length of the cycle that voided the game
+    void_cycle: Option<usize>,
+}
+
+// Long repetition cycles (triple ko, eternal life) that some rulesets score as
+// no result: the game is void once a position with the same player to move
+// occurs repetitions times, each time at most max_cycle_len moves after the
+// previous occurrence. Mostly useful under KoRule::Simple, superko rules forbid
+// the repeating move.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CycleDetection {
+    pub max_cycle_len: usize,
+    pub repetitions: usize,
+}
+
+impl Default for CycleDetection {
+    fn default() -> Self {
+        CycleDetection {
+            max_cycle_len: 12,
+            repetitions: 3,
+        }
+    }
+}
+
+// Key of a position for opening books and evaluation caches that also keeps
+// apart the rules it is evaluated under. The position hash does not use the
+// Zobrist table, so keys stay valid across versions, and is the same for all
+// rotations and reflections of the position.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct GameKey {
+    pub position: u64,
+    pub width: usize,
+    pub height: usize,
+    pub black_to_move: bool,
+    pub scoring: Scoring,
+    pub ko: KoRule,
+    pub suicide_allowed: bool,
+    // Komi in half points rounded to nearest, so 6.5 and 7.5 differ but float
+    // noise does not
+    pub komi_half_points: i32,
+}
+
+impl GameKey {
+    pub fn new(board: &Board, rules: &Rules, komi: f32) -> Self {
+        GameKey {
+            position: canonical_position_hash(board),
+            width: board.width(),
+            height: board.height(),
+            black_to_move: board.act_player() == Player::Black,
+            scoring: rules.scoring,
+            ko: rules.ko,
+            suicide_allowed: rules.suicide_allowed,
+            komi_half_points: (komi * 2.0).round() as i32,
+        }
+    }
+}
+
+// FNV-1a of the point colors and the ko point in row-major order, for the
+// symmetry giving the smallest hash
+fn canonical_position_hash(board: &Board) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    let (width, height) = (board.width(), board.height());
+    let ko = board.ko_vertex();
+    (0..board_symmetry_count(width, height))
+        .map(|sym| {
+            let mut hash = FNV_OFFSET;
+            for row in 0..height {
+                for col in 0..width {
+                    let (r, c) = symmetric_coords(sym, width, height, row, col);
+                    let v = Vertex::from_coords(r as isize, c as isize);
+                    let code = match board.color_at(v) {
+                        Color::Black => 1,
+                        Color::White => 2,
+                        _ if v == ko => 3,
+                        _ => 0,
+                    };
+                    hash = (hash ^ code).wrapping_mul(FNV_PRIME);
+                }
+            }
+            hash
+        })
+        .min()
+        .unwrap()
+}
+
+// Komi of handicap games, replacing the rules' even-game komi
+pub const HANDICAP_KOMI: f32 = 0.5;
+
+impl Game {
+    pub fn new(width: usize, height: usize, rules: Rules) -> Self {
+        Self::from_position(Board::with_size(width, height), rules)
+    }
+
+    // Game continuing from a set-up position, e.g. SGF AB/AW stones, with the
+    // rules' komi. The board's move history is not part of the game.
+    pub fn from_position(mut board: Board, rules: Rules) -> Self {
+        board.set_komi(rules.komi);
+        let history = vec![(board.positional_hash(), board.last_player())];
+        let (width, height) = (board.width(), board.height());
+        Game {
+            start: board.clone(),
+            board,
+            rules,
+            moves: Vec::new(),
+            history,
+            handicap: 0,
+            width,
+            height,
+            cycle_detection: None,
+            void_cycle: None,
+        }
+    }
+
+    // Starts over on a board of another size with the given rules, reusing the
+    // board. Cycle detection stays as set. Unchanged on a bad size.
+    pub fn reset(&mut self, width: usize, height: usize, rules: Rules) -> Result<(), BoardError> {
+        self.board.resize(width, height)?;
+        self.board.set_komi(rules.komi);
+        self.start.clone_from(&self.board);
+        self.rules = rules;
+        self.moves.clear();
+        self.history.clear();
+        self.history
+            .push((self.board.positional_hash(), self.board.last_player()));
+        self.handicap = 0;
+        self.width = width;
+        self.height = height;
+        self.void_cycle = None;
+        Ok(())
+    }
+
+    // Places the standard GTP handicap stones, white moves next.
+    // Only possible before the first move.
+    pub fn set_fixed_handicap(&mut self, stone_cnt: usize) -> bool {
+        if !self.moves.is_empty() || self.handicap != 0 {
+            return false;
+        }
+        let Some(vertices) = fixed_handicap_vertices(self.width, self.height, stone_cnt) else {
+            return false;
+        };
+
+        for v in vertices {
+            self.board.play_legal(Player::Black, v);
+        }
+        self.handicap = stone_cnt;
+        self.rules.komi = HANDICAP_KOMI;
+        self.board.set_komi(self.effective_komi());
+        self.start.clone_from(&self.board);
+        self.history = vec![(self.board.positional_hash(), self.board.last_player())];
+        true
+    }
+
+    // Sets the player to move next, e.g. white first in a problem. The last
+    // position of the history is updated so situational superko sees it as
+    // made by the other player.
+    pub fn set_to_move(&mut self, player: Player) {
+        self.board.set_to_move(player);
+        if let Some(last) = self.history.last_mut() {
+            *last = (self.board.positional_hash(), self.board.last_player());
+        }
+        if self.moves.is_empty() {
+            self.start.set_to_move(player);
+        }
+    }
+
+    // Changes the komi keeping the position and the moves. Unchanged on a
+    // komi check_komi rejects.
+    pub fn set_komi(&mut self, komi: f32) -> Result<(), BoardError> {
+        check_komi(komi)?;
+        self.rules.komi = komi;
+        self.board.set_komi(self.effective_komi());
+        self.start.set_komi(self.effective_komi());
+        Ok(())
+    }
+
+    pub fn handicap(&self) -> usize {
+        self.handicap
+    }
+
+    // Komi plus handicap compensation
+    pub fn effective_komi(&self) -> f32 {
+        self.rules.komi + self.rules.compensation_points(self.handicap)
+    }
+
+    // Position before the first move: setup or handicap stones and the player
+    // to move
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    // GameKey of the current position with the rules and effective komi
+    pub fn key(&self) -> GameKey {
+        GameKey::new(&self.board, &self.rules, self.effective_komi())
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    // Stones captured by the player
+    pub fn prisoners(&self, player: Player) -> usize {
+        self.board.prisoners(player)
+    }
+
+    pub fn is_legal(&self, mv: Move) -> bool {
+        if mv.vertex == Vertex::pass() {
+            return true;
+        }
+        let allowed = self.board.is_legal(mv.player, mv.vertex)
+            || (self.rules.suicide_allowed && self.board.is_suicide(mv.player, mv.vertex));
+        if !allowed {
+            return false;
+        }
+        self.repeated_position(mv).is_none()
+    }
+
+    // Hashes of all positions of the game with the player who made each one
+    // (0 is the start, i is after i moves)
+    pub fn position_history(&self) -> &[(Hash, Player)] {
+        &self.history
+    }
+
+    // Index of the earliest position equal to hash under the superko rule, with
+    // last_player having made it. None under simple ko.
+    pub fn find_position(&self, hash: Hash, last_player: Player) -> Option<usize> {
+        match self.rules.ko {
+            KoRule::Simple => None,
+            KoRule::PositionalSuperko => self.history.iter().position(|&(h, _)| h == hash),
+            KoRule::SituationalSuperko => self
+                .history
+                .iter()
+                .position(|&(h, pl)| h == hash && pl == last_player),
+        }
+    }
+
+    // Index in the position history of the earliest position the move would
+    // repeat under the superko rule. The game and its board stay untouched.
+    pub fn repeated_position(&self, mv: Move) -> Option<usize> {
+        if self.rules.ko == KoRule::Simple || mv.vertex == Vertex::pass() {
+            return None;
+        }
+        let hash = if self.board.is_legal(mv.player, mv.vertex) {
+            self.board.hash_after(mv.player, mv.vertex)
+        } else {
+            // Multi-stone suicide
+            let mut next = self.board.clone();
+            next.play_legal(mv.player, mv.vertex);
+            next.positional_hash()
+        };
+        self.find_position(hash, mv.player)
+    }
+
+    // Returns false and leaves the game unchanged if the move is illegal
+    pub fn play(&mut self, mv: Move) -> bool {
+        if !self.is_legal(mv) {
+            return false;
+        }
+
+        self.board.play_legal(mv.player, mv.vertex);
+        self.moves.push(mv);
+        self.history
+            .push((self.board.positional_hash(), self.board.last_player()));
+        if self.void_cycle.is_none() {
+            self.void_cycle = self.detect_cycle(self.history.len() - 1);
+        }
+        true
+    }
+
+    // Takes back the last move, replaying the others on the start position;
+    // a set_to_move after the first move is lost. False without moves.
+    pub fn undo(&mut self) -> bool {
+        if self.moves.pop().is_none() {
+            return false;
+        }
+        self.history.pop();
+        self.board.load(&self.start);
+        for mv in self.moves.iter() {
+            self.board.play_legal(mv.player, mv.vertex);
+        }
+        self.void_cycle = (1..self.history.len()).find_map(|last| self.detect_cycle(last));
+        true
+    }
+
+    // Off by default
+    pub fn set_cycle_detection(&mut self, detection: Option<CycleDetection>) {
+        if let Some(detection) = detection {
+            assert!(detection.repetitions >= 2, "a cycle needs two repetitions");
+        }
+        self.cycle_detection = detection;
+    }
+
+    // Length of the repetition cycle that made the game void, if any
+    pub fn void_cycle(&self) -> Option<usize> {
+        self.void_cycle
+    }
+
+    // Cycle ending at the position history[last]
+    fn detect_cycle(&self, last: usize) -> Option<usize> {
+        let detection = self.cycle_detection?;
+        let mut occurrence = last;
+        let mut cycle_len = 0;
+        for _ in 1..detection.repetitions {
+            let earlier = (occurrence.saturating_sub(detection.max_cycle_len)..occurrence)
+                .rev()
+                .find(|&i| self.history[i] == self.history[last])?;
+            cycle_len = cycle_len.max(occurrence - earlier);
+            occurrence = earlier;
+        }
+        Some(cycle_len)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.board.both_player_pass() || self.void_cycle.is_some()
+    }
+
+    // Final score assuming dead stones were removed, positive is a black win
+    pub fn score(&self) -> f32 {
+        self.exact_score().to_f32()
+    }
+
+    // score in half points, komi rounded to the nearest half point
+    pub fn exact_score(&self) -> Score {
+        let area = self.board.area_score();
+        let points = match self.rules.scoring {
+            Scoring::Area => area,
+            Scoring::Territory => {
+                let stones = self.board.stone_count(Player::Black) as i32
+                    - self.board.stone_count(Player::White) as i32;
+                let prisoners =
+                    self.prisoners(Player::Black) as i32 - self.prisoners(Player::White) as i32;
+                area - stones + prisoners
+            }
+        };
+        Score::of_points(points) - Score::of_f32(self.effective_komi())
+    }
+
+    // Score in SGF RE[] notation: "B+3.5", "W+0.5", "0" for jigo or "Void"
+    // after a repetition cycle
+    pub fn result(&self) -> String {
+        if self.void_cycle.is_some() {
+            return "Void".to_string();
+        }
+        self.exact_score().to_string()
+    }
+}
+
+// GTP fixed handicap placement: star points on the 4th line (3rd below 13x13),
+// corners first, then sides, center for odd counts.
+pub fn fixed_handicap_vertices(
+    width: usize,
+    height: usize,
+    stone_cnt: usize,
+) -> Option<Vec<Vertex>> {
+    if !(2..=9).contains(&stone_cnt) || width < 7 || height < 7 {
+        return None;
+    }
+    // More than 4 stones need the center lines, and 7x7 has at most 4 as in
+    // the GTP spec
+    let room_for_sides = width.min(height) > 7;
+    if stone_cnt > 4 && (width.is_multiple_of(2) || height.is_multiple_of(2) || !room_for_sides) {
+        return None;
+    }
+
+    let edge = |size: usize| if size >= 13 { 3 } else { 2 };
+    let (left, right) = (edge(width), width - 1 - edge(width));
+    let (top, bottom) = (edge(height), height - 1 - edge(height));
+    let (mid_row, mid_col) = (height / 2, width / 2);
+
+    let mut coords = vec![(bottom, left), (top, right)];
+    if stone_cnt >= 3 {
+        coords.push((top, left));
+    }
+    if stone_cnt >= 4 {
+        coords.push((bottom, right));
+    }
+    if stone_cnt >= 6 {
+        coords.push((mid_row, left));
+        coords.push((mid_row, right));
+    }
+    if stone_cnt >= 8 {
+        coords.push((bottom, mid_col));
+        coords.push((top, mid_col));
+    }
+    if stone_cnt % 2 == 1 && stone_cnt >= 5 {
+        coords.push((mid_row, mid_col));
+    }
+
+    Some(
+        coords
+            .into_iter()
+            .map(|(row, col)| Vertex::from_coords(row as isize, col as isize))
+            .collect(),
+    )
+}
@@ -1,34 +1,358 @@
+use crate::board::Board;
 use crate::hash::{Hash3x3, Hash3x3Map};
-use crate::types::{Nat, Player, PlayerMap};
+use crate::types::{Nat, Player, PlayerMap, Vertex};
+use std::path::Path;
 
 pub const GAMMAS_ACCURACY: f64 = 1.0e-10;
 
+// Value encodings of binary gamma tables. F16 and F32 files are smaller but
+// lose precision (F16 keeps about 3 digits, flushes gammas below 2^-24 to 0
+// and saturates above 65504). Only F64 tables can be memory mapped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GammasFormat {
+    F64,
+    F32,
+    F16,
+}
+
+impl GammasFormat {
+    fn value_size(self) -> usize {
+        match self {
+            GammasFormat::F64 => 8,
+            GammasFormat::F32 => 4,
+            GammasFormat::F16 => 2,
+        }
+    }
+}
+
+// Binary tables are the magic, a format byte and padding to HEADER_SIZE, then
+// the little endian gammas of both players for every hash in Hash3x3 order
+const MAGIC: &[u8; 8] = b"GGGAMMAS";
+const HEADER_SIZE: usize = 16;
+
+enum Table {
+    Owned(Hash3x3Map<PlayerMap<f64>>),
+    // Shared read-only F64 file, copied into an owned table on the first set
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
 pub struct Gammas {
-    gammas: Hash3x3Map<PlayerMap<f64>>,
+    table: Table,
 }
 
 impl Gammas {
     pub fn new() -> Self {
         let mut gammas = Gammas {
-            gammas: Hash3x3Map::new(),
+            table: Table::Owned(Hash3x3Map::new()),
         };
         gammas.reset_to_uniform();
         gammas
     }
 
     pub fn reset_to_uniform(&mut self) {
+        let table = self.owned_table();
         for hash in Hash3x3::all() {
             for pl in Player::all() {
-                self.gammas[hash][pl] = if hash.is_legal(pl) && !hash.is_eyelike(pl) {
-                    1.0
-                } else {
-                    0.0
-                };
+                table[hash][pl] = uniform_gamma(hash, pl);
             }
         }
     }
 
     pub fn get(&self, hash: Hash3x3, pl: Player) -> f64 {
-        self.gammas[hash][pl]
+        match &self.table {
+            Table::Owned(table) => table[hash][pl],
+            #[cfg(feature = "mmap")]
+            Table::Mapped(map) => {
+                let offset = HEADER_SIZE + value_index(hash, pl) * 8;
+                f64::from_le_bytes(map[offset..offset + 8].try_into().unwrap())
+            }
+        }
+    }
+
+    // Starts loading the gammas of hash into the cache. The table is 16MB, so
+    // lookups after a move mostly miss; prefetching all of them first overlaps
+    // the misses.
+    #[inline(always)]
+    pub fn prefetch(&self, hash: Hash3x3) {
+        match &self.table {
+            Table::Owned(table) => prefetch(&table[hash]),
+            #[cfg(feature = "mmap")]
+            Table::Mapped(map) => {
+                prefetch(&map[HEADER_SIZE + value_index(hash, Player::Black) * 8])
+            }
+        }
+    }
+
+    // Gamma of one pattern for one player, the other player's is unchanged
+    pub fn set(&mut self, hash: Hash3x3, pl: Player, gamma: f64) {
+        self.owned_table()[hash][pl] = gamma;
+    }
+
+    // set for every rotation and reflection of the pattern
+    pub fn set_symmetric(&mut self, hash: Hash3x3, pl: Player, gamma: f64) {
+        let table = self.owned_table();
+        for sym in hash.symmetries() {
+            table[sym][pl] = gamma;
+        }
+    }
+
+    fn owned_table(&mut self) -> &mut Hash3x3Map<PlayerMap<f64>> {
+        #[cfg(feature = "mmap")]
+        if let Table::Mapped(_) = self.table {
+            let mut table = Hash3x3Map::<PlayerMap<f64>>::new();
+            for hash in Hash3x3::all() {
+                for pl in Player::all() {
+                    table[hash][pl] = self.get(hash, pl);
+                }
+            }
+            self.table = Table::Owned(table);
+        }
+        match &mut self.table {
+            Table::Owned(table) => table,
+            #[cfg(feature = "mmap")]
+            Table::Mapped(_) => unreachable!(),
+        }
+    }
+
+    // Full table, 16MB as F64, 4MB as F16
+    pub fn to_binary(&self, format: GammasFormat) -> Vec<u8> {
+        let value_cnt = Hash3x3::COUNT * Player::COUNT;
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + value_cnt * format.value_size());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(format as u8);
+        bytes.resize(HEADER_SIZE, 0);
+        for hash in Hash3x3::all() {
+            for pl in Player::all() {
+                let gamma = self.get(hash, pl);
+                match format {
+                    GammasFormat::F64 => bytes.extend_from_slice(&gamma.to_le_bytes()),
+                    GammasFormat::F32 => bytes.extend_from_slice(&(gamma as f32).to_le_bytes()),
+                    GammasFormat::F16 => bytes.extend_from_slice(&f16_of_f64(gamma).to_le_bytes()),
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn of_binary(bytes: &[u8]) -> Result<Self, String> {
+        let format = binary_format(bytes)?;
+        let size = format.value_size();
+        let mut table = Hash3x3Map::<PlayerMap<f64>>::new();
+        for hash in Hash3x3::all() {
+            for pl in Player::all() {
+                let offset = HEADER_SIZE + value_index(hash, pl) * size;
+                let raw = &bytes[offset..offset + size];
+                table[hash][pl] = match format {
+                    GammasFormat::F64 => f64::from_le_bytes(raw.try_into().unwrap()),
+                    GammasFormat::F32 => f32::from_le_bytes(raw.try_into().unwrap()) as f64,
+                    GammasFormat::F16 => f64_of_f16(u16::from_le_bytes(raw.try_into().unwrap())),
+                };
+            }
+        }
+        Ok(Gammas {
+            table: Table::Owned(table),
+        })
+    }
+
+    pub fn save_binary(&self, path: &Path, format: GammasFormat) -> Result<(), String> {
+        std::fs::write(path, self.to_binary(format))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn load_binary(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::of_binary(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Reads gammas straight from an F64 file, so processes mapping the same
+    // file share one copy of the table. The file must not change while mapped.
+    #[cfg(feature = "mmap")]
+    pub fn map_binary(path: &Path) -> Result<Self, String> {
+        let error = |e: String| format!("{}: {}", path.display(), e);
+        let file = std::fs::File::open(path).map_err(|e| error(e.to_string()))?;
+        // Safety: the table is only read, and callers promise not to modify
+        // the file while it is mapped
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| error(e.to_string()))?;
+        match binary_format(&map).map_err(error)? {
+            GammasFormat::F64 => Ok(Gammas {
+                table: Table::Mapped(map),
+            }),
+            format => Err(error(format!("{:?} tables can not be mapped", format))),
+        }
+    }
+
+    // Whether get reads a memory mapped file
+    pub fn is_mapped(&self) -> bool {
+        !matches!(self.table, Table::Owned(_))
+    }
+
+    // One "<hash in hex> <B|W> <gamma>" line for every gamma differing from
+    // the uniform table
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for hash in Hash3x3::all() {
+            for (pl, name) in [(Player::Black, "B"), (Player::White, "W")] {
+                let gamma = self.get(hash, pl);
+                if gamma != uniform_gamma(hash, pl) {
+                    text.push_str(&format!("{:x} {} {}\n", usize::from(hash), name, gamma));
+                }
+            }
+        }
+        text
+    }
+
+    // Uniform table with the gammas of to_text lines; '#' starts a comment
+    pub fn of_text(text: &str) -> Result<Self, String> {
+        let mut gammas = Gammas::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("");
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let bad_line = || format!("line {}: bad gamma '{}'", idx + 1, line.trim());
+            let [hash, pl, gamma] = fields[..] else {
+                return Err(bad_line());
+            };
+            let hash = usize::from_str_radix(hash, 16)
+                .ok()
+                .filter(|&raw| raw < Hash3x3::COUNT)
+                .ok_or_else(bad_line)?;
+            let pl = match pl {
+                "B" => Player::Black,
+                "W" => Player::White,
+                _ => return Err(bad_line()),
+            };
+            let gamma: f64 = gamma
+                .parse()
+                .ok()
+                .filter(|g: &f64| g.is_finite() && *g >= 0.0)
+                .ok_or_else(bad_line)?;
+            gammas.set(Hash3x3::from(hash), pl, gamma);
+        }
+        Ok(gammas)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::of_text(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_text()).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Legal moves of pl with a non-zero gamma, highest gamma first (ties by vertex),
+    // e.g. for progressive widening. Proximity bonuses are not applied.
+    pub fn ranked_moves(&self, board: &Board, pl: Player) -> Vec<(Vertex, f64)> {
+        let mut moves = Vec::with_capacity(board.empty_vertex_count());
+        for v in board.empties() {
+            let gamma = self.get(board.hash3x3_at(v), pl);
+            if gamma >= GAMMAS_ACCURACY && board.is_legal(pl, v) {
+                moves.push((v, gamma));
+            }
+        }
+        moves.sort_by(|a, b| {
+            b.1.total_cmp(&a.1)
+                .then(usize::from(a.0).cmp(&usize::from(b.0)))
+        });
+        moves
+    }
+}
+
+#[inline(always)]
+fn prefetch<T>(value: &T) {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: prefetching is a hint and never faults, even on a bad address
+    unsafe {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(value as *const T as *const i8);
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = value;
+}
+
+fn value_index(hash: Hash3x3, pl: Player) -> usize {
+    usize::from(hash) * Player::COUNT + usize::from(pl)
+}
+
+fn binary_format(bytes: &[u8]) -> Result<GammasFormat, String> {
+    if bytes.len() < HEADER_SIZE || &bytes[..MAGIC.len()] != MAGIC {
+        return Err("not a binary gamma table".to_string());
+    }
+    let format = match bytes[MAGIC.len()] {
+        0 => GammasFormat::F64,
+        1 => GammasFormat::F32,
+        2 => GammasFormat::F16,
+        raw => return Err(format!("unknown gamma format {}", raw)),
+    };
+    let expected = HEADER_SIZE + Hash3x3::COUNT * Player::COUNT * format.value_size();
+    if bytes.len() != expected {
+        return Err(format!(
+            "{:?} table has {} bytes instead of {}",
+            format,
+            bytes.len(),
+            expected
+        ));
+    }
+    Ok(format)
+}
+
+// IEEE half precision, rounding to nearest
+fn f16_of_f64(x: f64) -> u16 {
+    let bits = (x as f32).to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let mant = bits & 0x7f_ffff;
+    if x.is_nan() {
+        return 0x7e00;
+    }
+    if exp >= 31 {
+        // Saturate at the largest finite value
+        return sign | 0x7bff;
+    }
+    if exp <= 0 {
+        if exp < -10 {
+            return sign;
+        }
+        let mant = (mant | 0x80_0000) >> (1 - exp);
+        return sign | ((mant >> 13) + ((mant >> 12) & 1)) as u16;
+    }
+    let half = sign | ((exp as u32) << 10) as u16 | (mant >> 13) as u16;
+    // A carry out of the mantissa correctly bumps the exponent, except above
+    // 65504 where it would reach infinity
+    let rounded = half + ((mant >> 12) & 1) as u16;
+    if rounded & 0x7fff > 0x7bff {
+        return sign | 0x7bff;
+    }
+    rounded
+}
+
+fn f64_of_f16(half: u16) -> f64 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = ((half >> 10) & 0x1f) as i32;
+    let mant = (half & 0x3ff) as f64;
+    sign * match exp {
+        0 => mant * 2f64.powi(-24),
+        31 if mant == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1.0 + mant / 1024.0) * 2f64.powi(exp - 15),
+    }
+}
+
+// Every legal move that does not fill an own eye is equally likely
+fn uniform_gamma(hash: Hash3x3, pl: Player) -> f64 {
+    if hash.is_legal(pl) && !hash.is_eyelike(pl) {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Default for Gammas {
+    fn default() -> Self {
+        Self::new()
     }
 }
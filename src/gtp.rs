@@ -0,0 +1,559 @@
+use crate::clock::{Clock, TimeControl};
+use crate::engine::Engine;
+use crate::fast_random::FastRandom;
+use crate::game::Game;
+use crate::gammas::Gammas;
+use crate::playout::ScoreEstimate;
+use crate::rules::Rules;
+use crate::sgf;
+use crate::tactics::chain;
+use crate::types::{
+    color_to_showboard_char, vertex_of_gtp, vertex_to_gtp, Color, Move, Player, PlayerMap, Vertex,
+    DEFAULT_BOARD_SIZE,
+};
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+// Go Text Protocol front end of an engine. Besides the standard commands there
+// are gg- commands exposing board internals for remote debugging, answered as
+// plain text lines.
+
+const COMMANDS: &[&str] = &[
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "quit",
+    "boardsize",
+    "clear_board",
+    "komi",
+    "play",
+    "genmove",
+    "reg_genmove",
+    "lz-analyze",
+    "kata-analyze",
+    "undo",
+    "time_settings",
+    "kgs-time_settings",
+    "time_left",
+    "loadsgf",
+    "showboard",
+    "gg-showboard-maps",
+    "gg-showboard-one-color",
+    "gg-hash",
+    "gg-chain-info",
+    "final_score",
+    "gg-ownership",
+    "gg-score-estimate",
+];
+
+// Playouts behind final_score
+const FINAL_SCORE_PLAYOUT_CNT: usize = 1000;
+
+pub struct Gtp<'a> {
+    gammas: &'a Gammas,
+    engine: Box<dyn Engine + 'a>,
+    game: Game,
+    rules: Rules,
+    // None without time settings, then genmove thinks for a fixed effort
+    clocks: Option<PlayerMap<Clock>>,
+    random: FastRandom,
+    quit: bool,
+}
+
+// Everything a Gtp knows about the game, without the engine and gammas, to
+// carry the game over to a Gtp with reloaded gammas
+pub struct GtpState {
+    game: Game,
+    rules: Rules,
+    clocks: Option<PlayerMap<Clock>>,
+    random: FastRandom,
+}
+
+impl<'a> Gtp<'a> {
+    pub fn new(gammas: &'a Gammas, engine: Box<dyn Engine + 'a>, rules: Rules) -> Self {
+        Gtp {
+            gammas,
+            engine,
+            game: Game::new(DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE, rules),
+            rules,
+            clocks: None,
+            random: FastRandom::new(1),
+            quit: false,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn into_state(self) -> GtpState {
+        GtpState {
+            game: self.game,
+            rules: self.rules,
+            clocks: self.clocks,
+            random: self.random,
+        }
+    }
+
+    // Continues the game of state, as if its commands had been given here
+    pub fn restore(&mut self, state: GtpState) {
+        self.game = state.game;
+        self.rules = state.rules;
+        self.clocks = state.clocks;
+        self.random = state.random;
+    }
+
+    pub fn clock(&self, pl: Player) -> Option<&Clock> {
+        self.clocks.as_ref().map(|clocks| &clocks[pl])
+    }
+
+    // After a quit command, for front ends reading commands themselves
+    pub fn has_quit(&self) -> bool {
+        self.quit
+    }
+
+    // Answers commands until quit or the end of input
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, output: &mut W) -> std::io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            let Some(response) = self.response(&line) else {
+                continue;
+            };
+            output.write_all(response.as_bytes())?;
+            output.flush()?;
+            if self.quit {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Full response to a command line ("= ...\n\n" or "? ...\n\n"), None for
+    // empty and comment lines
+    pub fn response(&mut self, line: &str) -> Option<String> {
+        let line = line.split('#').next().unwrap_or("");
+        let mut words = line.split_whitespace().peekable();
+        let id = words
+            .next_if(|word| word.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or("");
+        let command = words.next()?;
+        let args: Vec<&str> = words.collect();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("gtp_command", command, id).entered();
+        let (status, text) = match self.execute(command, &args) {
+            Ok(text) => ('=', text),
+            Err(text) => ('?', text),
+        };
+        #[cfg(feature = "tracing")]
+        match status {
+            '=' => tracing::debug!(args = ?args, response = text.trim_end(), "gtp ok"),
+            _ => tracing::warn!(args = ?args, error = text.as_str(), "gtp error"),
+        }
+        let separator = if text.is_empty() || text.starts_with('\n') {
+            ""
+        } else {
+            " "
+        };
+        Some(format!(
+            "{}{}{}{}\n\n",
+            status,
+            id,
+            separator,
+            text.trim_end()
+        ))
+    }
+
+    pub fn execute(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        match command {
+            "protocol_version" => Ok("2".to_string()),
+            "name" => Ok("go_game_board".to_string()),
+            "version" => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            "known_command" => {
+                let name = arg(args, 0)?;
+                Ok(COMMANDS.contains(&name).to_string())
+            }
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "quit" => {
+                self.quit = true;
+                Ok(String::new())
+            }
+            "boardsize" => {
+                let size: usize = parse(arg(args, 0)?)?;
+                self.game
+                    .reset(size, size, self.rules)
+                    .map_err(|_| "unacceptable size".to_string())?;
+                self.reset_clocks();
+                Ok(String::new())
+            }
+            "clear_board" => {
+                self.new_game();
+                Ok(String::new())
+            }
+            "komi" => {
+                let komi = parse(arg(args, 0)?)?;
+                self.game.set_komi(komi).map_err(|e| e.to_string())?;
+                self.rules.komi = komi;
+                Ok(String::new())
+            }
+            "play" => {
+                let player = self.player(arg(args, 0)?)?;
+                let v = self.vertex(arg(args, 1)?)?;
+                if self.game.play(Move::of_player_vertex(player, v)) {
+                    Ok(String::new())
+                } else {
+                    Err("illegal move".to_string())
+                }
+            }
+            // Out of turn the given color is set to move, see Game::set_to_move
+            "genmove" => {
+                let player = self.player(arg(args, 0)?)?;
+                if player != self.game.board().act_player() {
+                    self.game.set_to_move(player);
+                }
+                let v = match self.clocks.as_mut() {
+                    Some(clocks) => self
+                        .engine
+                        .genmove_with_clock(self.game.board(), &mut clocks[player]),
+                    None => self.engine.genmove(self.game.board()),
+                };
+                if !self.game.play(Move::of_player_vertex(player, v)) {
+                    return Err("engine generated an illegal move".to_string());
+                }
+                Ok(self.gtp_vertex(v))
+            }
+            // genmove without playing the move, for regression suites. It
+            // thinks as long as genmove would but leaves the clock untouched.
+            "reg_genmove" => {
+                let player = self.player(arg(args, 0)?)?;
+                let mut board = self.game.board().clone();
+                board.set_to_move(player);
+                let v = match self.clocks.as_ref() {
+                    Some(clocks) => self
+                        .engine
+                        .genmove_with_clock(&board, &mut clocks[player].clone()),
+                    None => self.engine.genmove(&board),
+                };
+                Ok(self.gtp_vertex(v))
+            }
+            "lz-analyze" | "kata-analyze" => self.analyze(command == "lz-analyze", args),
+            "undo" => {
+                if self.game.undo() {
+                    Ok(String::new())
+                } else {
+                    Err("cannot undo".to_string())
+                }
+            }
+            // Canadian byo-yomi; no byo-yomi time means absolute time, byo-yomi
+            // time without stones no time limit
+            "time_settings" => {
+                let main_time = seconds(arg(args, 0)?)?;
+                let period_time = seconds(arg(args, 1)?)?;
+                let period_stones: u32 = parse(arg(args, 2)?)?;
+                let control = if period_time.is_zero() {
+                    Some(TimeControl::Absolute { main_time })
+                } else if period_stones == 0 {
+                    None
+                } else {
+                    Some(TimeControl::Canadian {
+                        main_time,
+                        period_time,
+                        period_stones,
+                    })
+                };
+                self.set_time_control(control);
+                Ok(String::new())
+            }
+            "kgs-time_settings" => {
+                let control = match arg(args, 0)? {
+                    "none" => None,
+                    "absolute" => Some(TimeControl::Absolute {
+                        main_time: seconds(arg(args, 1)?)?,
+                    }),
+                    "byoyomi" => Some(TimeControl::ByoYomi {
+                        main_time: seconds(arg(args, 1)?)?,
+                        period_time: seconds(arg(args, 2)?)?,
+                        periods: parse(arg(args, 3)?)?,
+                    }),
+                    "canadian" => Some(TimeControl::Canadian {
+                        main_time: seconds(arg(args, 1)?)?,
+                        period_time: seconds(arg(args, 2)?)?,
+                        period_stones: parse(arg(args, 3)?)?,
+                    }),
+                    other => return Err(format!("invalid argument {}", other)),
+                };
+                self.set_time_control(control);
+                Ok(String::new())
+            }
+            // Ignored without time settings
+            "time_left" => {
+                let player = self.player(arg(args, 0)?)?;
+                let time_left = seconds(arg(args, 1)?)?;
+                let cnt_left: u32 = parse(arg(args, 2)?)?;
+                if let Some(clocks) = self.clocks.as_mut() {
+                    clocks[player].set_time_left(time_left, cnt_left);
+                }
+                Ok(String::new())
+            }
+            "loadsgf" => {
+                let move_number = match args.get(1) {
+                    Some(number) => Some(parse(number)?),
+                    None => None,
+                };
+                self.load_sgf(arg(args, 0)?, move_number)
+            }
+            "showboard" => Ok(format!("\n{}", self.showboard(false))),
+            "gg-showboard-one-color" => Ok(format!("\n{}", self.showboard(true))),
+            "gg-showboard-maps" => Ok(format!("\n{}", self.game.board().debug_maps_text())),
+            "gg-hash" => Ok(format!(
+                "{:016x}",
+                self.game.board().positional_hash().as_u64()
+            )),
+            "gg-chain-info" => {
+                let v = self.vertex(arg(args, 0)?)?;
+                self.chain_info(v)
+            }
+            // Mean playout score, "B+3.5", "W+0.5" or "0"
+            "final_score" => {
+                let estimate = self.estimate_score(FINAL_SCORE_PLAYOUT_CNT)?;
+                let mean = (estimate.mean * 10.0).round() / 10.0;
+                Ok(if mean > 0.0 {
+                    format!("B+{}", mean)
+                } else if mean < 0.0 {
+                    format!("W+{}", -mean)
+                } else {
+                    "0".to_string()
+                })
+            }
+            "gg-ownership" => {
+                let playout_cnt: usize = parse(arg(args, 0)?)?;
+                Ok(format!("\n{}", self.ownership(playout_cnt)?))
+            }
+            // Score from black's side over the given number of playouts
+            "gg-score-estimate" => {
+                let playout_cnt: usize = parse(arg(args, 0)?)?;
+                let estimate = self.estimate_score(playout_cnt)?;
+                Ok(format!(
+                    "mean {:.2}\nstddev {:.2}",
+                    estimate.mean, estimate.stddev
+                ))
+            }
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    // Position of an SGF file before its move move_number (counting from 1), or
+    // after all moves, with the file's komi. Answers the color to move.
+    fn load_sgf(&mut self, path: &str, move_number: Option<usize>) -> Result<String, String> {
+        let text = std::fs::read_to_string(path).map_err(|_| "cannot load file".to_string())?;
+        let sgf = sgf::parse_game(&text).map_err(|e| e.to_string())?;
+        let start = sgf.start_position().map_err(|e| e.to_string())?;
+        let mut rules = self.rules;
+        rules.komi = sgf.komi;
+        let mut game = Game::from_position(start, rules);
+        let move_cnt = move_number.map_or(sgf.moves.len(), |number| {
+            number.saturating_sub(1).min(sgf.moves.len())
+        });
+        for (move_idx, &mv) in sgf.moves[..move_cnt].iter().enumerate() {
+            if !game.play(mv) {
+                return Err(format!("illegal move {} in file", move_idx + 1));
+            }
+        }
+        self.rules = rules;
+        self.game = game;
+        Ok(match self.game.board().act_player() {
+            Player::Black => "black".to_string(),
+            Player::White => "white".to_string(),
+        })
+    }
+
+    fn new_game(&mut self) {
+        let board = self.game.board();
+        let (width, height) = (board.width(), board.height());
+        self.game
+            .reset(width, height, self.rules)
+            .expect("the current size is valid");
+        self.reset_clocks();
+    }
+
+    fn set_time_control(&mut self, control: Option<TimeControl>) {
+        self.clocks = control.map(|control| PlayerMap::new_with(Clock::new(control)));
+    }
+
+    // Full time again for both players, for a new game
+    fn reset_clocks(&mut self) {
+        let control = self
+            .clocks
+            .as_ref()
+            .map(|clocks| clocks[Player::Black].control());
+        self.set_time_control(control);
+    }
+
+    fn player(&self, s: &str) -> Result<Player, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "b" | "black" => Ok(Player::Black),
+            "w" | "white" => Ok(Player::White),
+            _ => Err("invalid color".to_string()),
+        }
+    }
+
+    fn vertex(&self, s: &str) -> Result<Vertex, String> {
+        let board = self.game.board();
+        vertex_of_gtp(&s.to_ascii_uppercase(), board.width(), board.height())
+            .ok_or_else(|| "invalid vertex".to_string())
+    }
+
+    fn gtp_vertex(&self, v: Vertex) -> String {
+        vertex_to_gtp(v, self.game.board().height())
+    }
+
+    // One color shows every stone as X, for one-color Go
+    fn showboard(&self, one_color: bool) -> String {
+        let board = self.game.board();
+        let mut text = String::new();
+        for row in 0..board.height() {
+            let colors: Vec<String> = (0..board.width())
+                .map(|col| {
+                    let v = Vertex::from_coords(row as isize, col as isize);
+                    match board.color_at(v) {
+                        Color::Black | Color::White if one_color => "X".to_string(),
+                        color => color_to_showboard_char(color).to_string(),
+                    }
+                })
+                .collect();
+            text.push_str(&format!(
+                "{:2} {}\n",
+                board.height() - row,
+                colors.join(" ")
+            ));
+        }
+        let letters: Vec<String> = (0..board.width())
+            .map(|col| self.gtp_vertex(Vertex::from_coords(0, col as isize))[..1].to_string())
+            .collect();
+        text.push_str(&format!("   {}\n", letters.join(" ")));
+        let stats = board.stats();
+        text.push_str(&format!(
+            "move {}, captured by black {}, by white {}",
+            stats.move_no, stats.black_prisoners, stats.white_prisoners
+        ));
+        if stats.ko != Vertex::none() {
+            text.push_str(&format!(", ko {}", self.gtp_vertex(stats.ko)));
+        }
+        text.push('\n');
+        text
+    }
+
+    // key value lines; liberties are exact, pseudo_liberties the incremental count
+    fn chain_info(&self, v: Vertex) -> Result<String, String> {
+        let board = self.game.board();
+        let Some(chain_data) = board.chain_at(v) else {
+            return Err("no stone at vertex".to_string());
+        };
+        let color = if board.color_at(v) == Color::Black {
+            "black"
+        } else {
+            "white"
+        };
+        let mut stones: Vec<Vertex> = board.chain_stones(v).collect();
+        stones.sort_by_key(|&s| usize::from(s));
+        let mut libs = chain(board, v).1;
+        libs.sort_by_key(|&l| usize::from(l));
+        let list = |vs: &[Vertex]| {
+            vs.iter()
+                .map(|&x| self.gtp_vertex(x))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let atari = if chain_data.is_in_atari() {
+            self.gtp_vertex(libs[0])
+        } else {
+            "none".to_string()
+        };
+        Ok(format!(
+            "color {}\nsize {}\nliberties {}\npseudo_liberties {}\natari {}\nstones {}\nliberty_list {}",
+            color,
+            chain_data.size,
+            libs.len(),
+            chain_data.lib_cnt,
+            atari,
+            list(&stones),
+            list(&libs)
+        ))
+    }
+
+    // One search of the engine answered with a single line of 1-ply
+    // variations, "info move D4 visits 120 winrate 5312 order 0 pv D4 info
+    // ...", instead of a stream until the next command. Arguments are an
+    // optional color to move and an interval, which is ignored. Leela Zero
+    // gives win rates in 1/10000, KataGo as fractions.
+    fn analyze(&mut self, leela_zero: bool, args: &[&str]) -> Result<String, String> {
+        let mut board = self.game.board().clone();
+        for &word in args {
+            if let Ok(player) = self.player(word) {
+                board.set_to_move(player);
+            } else if word != "interval" && word.parse::<u32>().is_err() {
+                return Err(format!("invalid argument {}", word));
+            }
+        }
+        let infos: Vec<String> = self
+            .engine
+            .analyze(&board)
+            .iter()
+            .enumerate()
+            .map(|(order, stats)| {
+                let mv = self.gtp_vertex(stats.v);
+                let winrate = if leela_zero {
+                    format!("{}", (stats.winrate * 10000.0).round())
+                } else {
+                    format!("{:.6}", stats.winrate)
+                };
+                format!(
+                    "info move {} visits {} winrate {} order {} pv {}",
+                    mv, stats.visits, winrate, order, mv
+                )
+            })
+            .collect();
+        Ok(format!("\n{}", infos.join(" ")))
+    }
+
+    // Expected owner of every point from black's side, top row first
+    fn ownership(&mut self, playout_cnt: usize) -> Result<String, String> {
+        let estimate = self.estimate_score(playout_cnt)?;
+        let board = self.game.board();
+        let mut text = String::new();
+        for row in 0..board.height() {
+            let values: Vec<String> = (0..board.width())
+                .map(|col| {
+                    let v = Vertex::from_coords(row as isize, col as isize);
+                    format!("{:5.2}", estimate.ownership[v])
+                })
+                .collect();
+            text.push_str(&values.join(" "));
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    fn estimate_score(&mut self, playout_cnt: usize) -> Result<ScoreEstimate, String> {
+        self.game
+            .board()
+            .estimate_score(self.gammas, playout_cnt, &mut self.random)
+            .ok_or_else(|| "need at least one playout".to_string())
+    }
+}
+
+fn arg<'s>(args: &[&'s str], idx: usize) -> Result<&'s str, String> {
+    args.get(idx)
+        .copied()
+        .ok_or_else(|| "missing argument".to_string())
+}
+
+fn parse<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid argument {}", s))
+}
+
+// GTP times are whole seconds
+fn seconds(s: &str) -> Result<Duration, String> {
+    parse(s).map(Duration::from_secs)
+}
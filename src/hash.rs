@@ -17,17 +17,23 @@ impl<T: Default + Clone> Hash3x3Map<T> {
     }
 }
 
+impl<T: Default + Clone> Default for Hash3x3Map<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> std::ops::Index<Hash3x3> for Hash3x3Map<T> {
     type Output = T;
 
     fn index(&self, idx: Hash3x3) -> &Self::Output {
-        &self.data[usize::from(idx) as usize]
+        &self.data[usize::from(idx)]
     }
 }
 
 impl<T> std::ops::IndexMut<Hash3x3> for Hash3x3Map<T> {
     fn index_mut(&mut self, idx: Hash3x3) -> &mut Self::Output {
-        &mut self.data[usize::from(idx) as usize]
+        &mut self.data[usize::from(idx)]
     }
 }
 
@@ -101,6 +107,43 @@ impl Hash3x3 {
         (self.0 & (1 << (16 + usize::from(dir)))) != 0
     }
 
+    fn permuted(&self, perm: impl Fn(Dir) -> Dir) -> Self {
+        let mut result = Hash3x3(0);
+        for dir in Dir::all() {
+            result.set_color_at(perm(dir), self.color_at(dir));
+            if dir.is_simple4() && self.is_in_atari(dir) {
+                result.0 |= 1 << (16 + usize::from(perm(dir)));
+            }
+        }
+        result
+    }
+
+    pub fn rotated(&self) -> Self {
+        self.permuted(|dir| dir.rotated())
+    }
+
+    pub fn mirrored(&self) -> Self {
+        self.permuted(|dir| dir.mirrored())
+    }
+
+    // All 8 rotations and reflections of the pattern (may contain duplicates)
+    pub fn symmetries(&self) -> [Hash3x3; 8] {
+        let mut result = [*self; 8];
+        for i in 1..4 {
+            result[i] = result[i - 1].rotated();
+        }
+        for i in 4..8 {
+            result[i] = result[i - 4].mirrored();
+        }
+        result
+    }
+
+    // Representative of the symmetry class - the smallest raw value
+    pub fn canonical(&self) -> Self {
+        let raw = self.symmetries().iter().map(|h| h.0).min().unwrap();
+        Hash3x3(raw)
+    }
+
     pub fn is_legal(&self, pl: Player) -> bool {
         let mut color_cnt = ColorMap::<u32>::new();
         let mut atari_cnt = PlayerMap::<u32>::new();
@@ -161,7 +204,7 @@ impl Hash3x3 {
 }
 
 // Zobrist hash for the whole board position
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Default, Hash)]
 pub struct Hash {
     hash: u64,
 }
@@ -175,12 +218,16 @@ impl Hash {
         self.hash = 0;
     }
 
+    pub fn as_u64(&self) -> u64 {
+        self.hash
+    }
+
     pub fn randomize(&mut self, fr: &mut FastRandom) {
         // Match C++ initialization exactly
-        self.hash = (fr.get_next_uint() as u64) << (0 * 16)
-            ^ (fr.get_next_uint() as u64) << (1 * 16)
-            ^ (fr.get_next_uint() as u64) << (2 * 16)
-            ^ (fr.get_next_uint() as u64) << (3 * 16);
+        self.hash = (fr.get_next_uint() as u64)
+            ^ (fr.get_next_uint() as u64) << 16
+            ^ (fr.get_next_uint() as u64) << 32
+            ^ (fr.get_next_uint() as u64) << 48;
     }
 }
 
@@ -216,7 +263,7 @@ impl Zobrist {
         // Match C++ iteration order: ForEachNat(Player, pl) { ForEachNat(Vertex, v) { ... } }
         for pl_raw in 0..2 {
             let pl = Player::from(pl_raw);
-            for v_raw in 0..Vertex::COUNT as usize {
+            for v_raw in 0..Vertex::COUNT {
                 let v = Vertex::from(v_raw);
                 let mv = Move::of_player_vertex(pl, v);
                 zobrist.hashes[mv].randomize(&mut rng);
@@ -231,7 +278,46 @@ impl Zobrist {
     }
 }
 
+impl Default for Zobrist {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Zobrist table of the 25 points of a 5x5 area, by offset from its center
+pub struct ContextZobrist {
+    hashes: [[Hash; 4]; 25],
+}
+
+impl ContextZobrist {
+    pub fn new() -> Self {
+        let mut zobrist = ContextZobrist {
+            hashes: [[Hash::new(); 4]; 25],
+        };
+        let mut rng = FastRandom::new(525);
+        for cell in zobrist.hashes.iter_mut() {
+            for hash in cell.iter_mut() {
+                hash.randomize(&mut rng);
+            }
+        }
+        zobrist
+    }
+
+    // Offsets range over -2..=2
+    pub fn of_offset_color(&self, row_offset: isize, col_offset: isize, color: Color) -> Hash {
+        debug_assert!(row_offset.abs() <= 2 && col_offset.abs() <= 2);
+        self.hashes[((row_offset + 2) * 5 + col_offset + 2) as usize][usize::from(color)]
+    }
+}
+
+impl Default for ContextZobrist {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Global Zobrist instance
 lazy_static::lazy_static! {
     pub static ref ZOBRIST: Zobrist = Zobrist::new();
+    pub static ref CONTEXT_ZOBRIST: ContextZobrist = ContextZobrist::new();
 }
@@ -0,0 +1,153 @@
+use crate::game::Game;
+use crate::types::{color_to_showboard_char, vertex_to_gtp, Color, Nat, Player, Vertex, VertexMap};
+use std::ops::Range;
+
+// Numbered move diagrams of a game record. Every diagram shows the position
+// before its first move with the moves of its range numbered on top (stones
+// captured within the range keep their number). Moves on an already numbered
+// point and passes are listed below the diagram, e.g. "15 at 9" (or "15 at
+// D4" for the point of a stone shown unnumbered).
+
+impl Game {
+    // Ranges of move indices of the diagrams, the last one may be shorter
+    pub fn kifu_ranges(&self, moves_per_diagram: usize) -> Vec<Range<usize>> {
+        assert!(moves_per_diagram > 0, "moves_per_diagram must be positive");
+        let move_cnt = self.moves().len();
+        (0..move_cnt)
+            .step_by(moves_per_diagram)
+            .map(|first| first..(first + moves_per_diagram).min(move_cnt))
+            .collect()
+    }
+
+    // Text diagrams, '#' and 'O' are stones played before the range
+    pub fn to_kifu_ascii(&self, moves_per_diagram: usize) -> String {
+        let height = self.board().height();
+        let mut text = String::new();
+        for diagram in self.kifu_diagrams(moves_per_diagram) {
+            text.push_str(&format!(
+                "Moves {}-{}\n",
+                diagram.range.start + 1,
+                diagram.range.end
+            ));
+            text.push_str("   ");
+            for col in 0..self.board().width() {
+                let v = Vertex::from_coords(0, col as isize);
+                text.push_str(&format!("{:>4}", &vertex_to_gtp(v, height)[..1]));
+            }
+            text.push('\n');
+            for row in 0..height {
+                text.push_str(&format!("{:>3}", height - row));
+                for col in 0..self.board().width() {
+                    let v = Vertex::from_coords(row as isize, col as isize);
+                    let cell = match diagram.numbers[v] {
+                        0 => color_to_showboard_char(diagram.start_colors[v]).to_string(),
+                        number => number.to_string(),
+                    };
+                    text.push_str(&format!("{:>4}", cell));
+                }
+                text.push('\n');
+            }
+            if !diagram.notes.is_empty() {
+                text.push_str(&diagram.notes.join(", "));
+                text.push('\n');
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    // LaTeX source for the igo package, one goban per diagram
+    pub fn to_kifu_tex(&self, moves_per_diagram: usize) -> String {
+        let height = self.board().height();
+        let coord = |v: Vertex| vertex_to_gtp(v, height).to_lowercase();
+        let mut text = String::new();
+        for diagram in self.kifu_diagrams(moves_per_diagram) {
+            text.push_str("\\cleargoban\n");
+            text.push_str(&format!(
+                "\\gobansize{{{}}}\n",
+                height.max(self.board().width())
+            ));
+            for (color, command) in [(Color::Black, "black"), (Color::White, "white")] {
+                let stones: Vec<String> = Vertex::all()
+                    .filter(|&v| diagram.start_colors[v] == color)
+                    .map(coord)
+                    .collect();
+                if !stones.is_empty() {
+                    text.push_str(&format!("\\{}{{{}}}\n", command, stones.join(",")));
+                }
+            }
+            for &(number, player, v) in diagram.numbered.iter() {
+                let command = if player == Player::Black {
+                    "black"
+                } else {
+                    "white"
+                };
+                text.push_str(&format!("\\{}[{}]{{{}}}\n", command, number, coord(v)));
+            }
+            text.push_str("\\begin{center}\n\\showfullgoban\\\\\n");
+            text.push_str(&format!(
+                "Moves {}--{}",
+                diagram.range.start + 1,
+                diagram.range.end
+            ));
+            if !diagram.notes.is_empty() {
+                text.push_str(&format!("\\\\\n{}", diagram.notes.join(", ")));
+            }
+            text.push_str("\n\\end{center}\n\n");
+        }
+        text
+    }
+
+    fn kifu_diagrams(&self, moves_per_diagram: usize) -> Vec<KifuDiagram> {
+        let board = self.board();
+        let mut replay = Game::from_position(self.start().clone(), *self.rules());
+
+        let mut diagrams = Vec::new();
+        for range in self.kifu_ranges(moves_per_diagram) {
+            let mut diagram = KifuDiagram {
+                range: range.clone(),
+                start_colors: VertexMap::new_with(Color::OffBoard),
+                numbers: VertexMap::new_with(0),
+                numbered: Vec::new(),
+                notes: Vec::new(),
+            };
+            for v in Vertex::all() {
+                diagram.start_colors[v] = replay.board().color_at(v);
+            }
+            for idx in range {
+                let mv = self.moves()[idx];
+                let number = idx + 1;
+                if mv.vertex == Vertex::pass() {
+                    diagram.notes.push(format!("{}: pass", number));
+                } else if diagram.numbers[mv.vertex] != 0 {
+                    diagram
+                        .notes
+                        .push(format!("{} at {}", number, diagram.numbers[mv.vertex]));
+                } else if diagram.start_colors[mv.vertex] != Color::Empty {
+                    // Retaking the point of a stone captured within the range
+                    let coord = vertex_to_gtp(mv.vertex, board.height());
+                    diagram.notes.push(format!("{} at {}", number, coord));
+                } else {
+                    diagram.numbers[mv.vertex] = number;
+                    diagram.numbered.push((number, mv.player, mv.vertex));
+                }
+                // As genmove out of turn did
+                if mv.player != replay.board().act_player() {
+                    replay.set_to_move(mv.player);
+                }
+                assert!(replay.play(mv), "the moves of a game replay from its start");
+            }
+            diagrams.push(diagram);
+        }
+        diagrams
+    }
+}
+
+struct KifuDiagram {
+    range: Range<usize>,
+    start_colors: VertexMap<Color>,
+    // Move number shown on each vertex, 0 for none
+    numbers: VertexMap<usize>,
+    numbered: Vec<(usize, Player, Vertex)>,
+    notes: Vec<String>,
+}
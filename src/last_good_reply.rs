@@ -0,0 +1,73 @@
+use crate::types::{Move, Player, PlayerMap, Vertex, VertexMap};
+
+// Last good reply with forgetting (LGRF-1 and LGRF-2). After each playout the
+// winner's moves are stored as replies to the preceding move (and the two
+// preceding moves), replies the loser played are forgotten.
+pub struct LastGoodReply {
+    reply1: PlayerMap<VertexMap<Vertex>>,
+    // Indexed by prev2 * Vertex::COUNT + prev1
+    reply2: PlayerMap<Vec<Vertex>>,
+}
+
+impl LastGoodReply {
+    pub fn new() -> Self {
+        LastGoodReply {
+            reply1: PlayerMap::new_with(VertexMap::new_with(Vertex::none())),
+            reply2: PlayerMap::new_with(vec![Vertex::none(); Vertex::COUNT * Vertex::COUNT]),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for pl in [Player::Black, Player::White] {
+            self.reply1[pl] = VertexMap::new_with(Vertex::none());
+            self.reply2[pl].fill(Vertex::none());
+        }
+    }
+
+    // Reply of pl to the moves prev2 (own) and prev1 (opponent's), LGRF-2 first
+    pub fn reply(&self, pl: Player, prev2: Vertex, prev1: Vertex) -> Option<Vertex> {
+        let v = self.reply2[pl][Self::index2(prev2, prev1)];
+        if v != Vertex::none() {
+            return Some(v);
+        }
+        let v = self.reply1[pl][prev1];
+        (v != Vertex::none()).then_some(v)
+    }
+
+    // moves are the moves of one playout, pass moves are never stored
+    pub fn update(&mut self, moves: &[Move], winner: Player) {
+        for (ii, mv) in moves.iter().enumerate().skip(1) {
+            let prev1 = moves[ii - 1].vertex;
+            let prev2 = if ii >= 2 {
+                moves[ii - 2].vertex
+            } else {
+                Vertex::none()
+            };
+            let index2 = Self::index2(prev2, prev1);
+            let pl = mv.player;
+            if pl == winner {
+                if mv.vertex != Vertex::pass() {
+                    self.reply1[pl][prev1] = mv.vertex;
+                    self.reply2[pl][index2] = mv.vertex;
+                }
+            } else {
+                if self.reply1[pl][prev1] == mv.vertex {
+                    self.reply1[pl][prev1] = Vertex::none();
+                }
+                if self.reply2[pl][index2] == mv.vertex {
+                    self.reply2[pl][index2] = Vertex::none();
+                }
+            }
+        }
+    }
+
+    fn index2(prev2: Vertex, prev1: Vertex) -> usize {
+        usize::from(prev2) * Vertex::COUNT + usize::from(prev1)
+    }
+}
+
+impl Default for LastGoodReply {
+    fn default() -> Self {
+        Self::new()
+    }
+}
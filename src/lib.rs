@@ -1,19 +1,100 @@
+pub mod alloc_counter;
+pub mod bench_history;
 pub mod benchmark;
 pub mod board;
+pub mod clock;
+pub mod config;
+pub mod endgame;
+pub mod engine;
+pub mod error;
 pub mod fast_random;
+pub mod features;
+pub mod game;
 pub mod gammas;
+pub mod gtp;
 pub mod hash;
+pub mod kifu;
+pub mod last_good_reply;
+pub mod lockstep;
+pub mod match_runner;
+pub mod movegen;
 pub mod nat_map;
 pub mod nat_set;
+pub mod observer;
+pub mod offload;
+pub mod pattern_stats;
 pub mod perf_counter;
+pub mod persistent_board;
+pub mod playout;
+pub mod policy;
+pub mod prediction;
+pub mod regress;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod rules;
 pub mod sampler;
+pub mod score;
+#[cfg(feature = "serde")]
+pub mod serde_impls;
+pub mod sgf;
+pub mod snapshot;
+pub mod solver;
+pub mod stone_delta;
+pub mod tactics;
+pub mod topology;
+pub mod training;
+pub mod tsumego;
+pub mod tune;
 pub mod types;
+#[cfg(feature = "large-boards")]
+pub mod vertex;
 
 // Re-export main types
-pub use benchmark::Benchmark;
-pub use board::Board;
-pub use gammas::{Gammas, GAMMAS_ACCURACY};
-pub use hash::{Hash, Hash3x3, Hash3x3Map, ZOBRIST};
+pub use bench_history::{BenchHistory, BenchRecord};
+pub use benchmark::{
+    BenchCheckpoint, BenchPolicy, Benchmark, BenchmarkBuilder, BenchmarkStats, ComparisonResult,
+    FreqSource, PolicyConfig, RepeatedStats, Spread, DEFAULT_BENCH_SEED, WARMUP_RUNS,
+};
+pub use board::{Board, BoardPool, BoardSnapshot, BoardStats, ChainHandle};
+pub use clock::{Clock, TimeControl};
+pub use config::{BenchConfig, Config, GtpConfig, SelfPlayConfig};
+pub use endgame::{
+    cleanup_moves, dead_stones, score_with_dead_removal, CleanupKind, CleanupMove, DEAD_OWNERSHIP,
+};
+pub use engine::{
+    Engine, MonteCarloEngine, MoveStats, PolicyEngine, ResignPolicy, ResignTracker, SamplerEngine,
+};
+pub use error::{BoardError, ParseError};
+pub use fast_random::FastRandom;
+pub use features::DistanceFeatures;
+pub use game::{fixed_handicap_vertices, CycleDetection, Game, GameKey};
+pub use gammas::{Gammas, GammasFormat, GAMMAS_ACCURACY};
+pub use gtp::{Gtp, GtpState};
+pub use hash::{ContextZobrist, Hash, Hash3x3, Hash3x3Map, CONTEXT_ZOBRIST, ZOBRIST};
+pub use last_good_reply::LastGoodReply;
+pub use lockstep::{LockstepPlayouts, LockstepReport};
+pub use movegen::{ordered_local_moves, urgent_moves, UrgentKind, UrgentMove};
+pub use observer::BoardObserver;
+pub use offload::{apply_choices, PlayoutBatch};
+pub use pattern_stats::PatternStats;
 pub use perf_counter::PerfCounter;
-pub use sampler::Sampler;
+pub use persistent_board::PersistentBoard;
+pub use playout::{
+    estimate_score, Criticality, PlayoutPool, PlayoutResult, Playouts, ScoreEstimate,
+};
+pub use policy::{MogoPolicy, PlayoutPolicy, SamplerPolicy, UniformPolicy};
+pub use prediction::PredictionStats;
+pub use regress::{run_regression, RegressionOutcome, RegressionResult, RegressionSummary};
+pub use rules::{HandicapCompensation, KoRule, Rules, Scoring};
+pub use sampler::{SampleKind, SampleTrace, Sampler};
+pub use score::{PlayoutOutcome, Score};
+pub use sgf::SgfGame;
+pub use stone_delta::StoneDeltaStats;
+pub use topology::{Graph, Rectangle, Topology, TopologyBoard, Torus};
+pub use training::{
+    self_play, self_play_with_params, FinishWrite, TrainingFile, TrainingPosition, TrainingReader,
+    TrainingWriter,
+};
+pub use tsumego::{TsumegoGoal, TsumegoResult, TsumegoStatus};
+pub use tune::{find_param, Param, SamplerParams, SpsaConfig, SpsaTuner, PARAMS};
 pub use types::*;
@@ -0,0 +1,215 @@
+use crate::board::Board;
+use crate::engine::{Engine, ResignPolicy, ResignTracker};
+use crate::types::{Player, PlayerMap, Vertex};
+
+// Sequential probability ratio test of H0: elo = elo0 against H1: elo = elo1
+#[derive(Copy, Clone, Debug)]
+pub struct SprtConfig {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for SprtConfig {
+    fn default() -> Self {
+        SprtConfig {
+            elo0: 0.0,
+            elo1: 20.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+impl SprtConfig {
+    pub fn lower_bound(&self) -> f64 {
+        (self.beta / (1.0 - self.alpha)).ln()
+    }
+
+    pub fn upper_bound(&self) -> f64 {
+        ((1.0 - self.beta) / self.alpha).ln()
+    }
+
+    // Log-likelihood ratio of the results (games are never drawn with half-point komi)
+    pub fn llr(&self, win_cnt: usize, loss_cnt: usize) -> f64 {
+        let s0 = elo_to_score(self.elo0);
+        let s1 = elo_to_score(self.elo1);
+        win_cnt as f64 * (s1 / s0).ln() + loss_cnt as f64 * ((1.0 - s1) / (1.0 - s0)).ln()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SprtDecision {
+    AcceptH0,
+    AcceptH1,
+    Undecided,
+}
+
+pub struct MatchConfig {
+    pub width: usize,
+    pub height: usize,
+    pub max_games: usize,
+    pub sprt: Option<SprtConfig>,
+    // None plays every game to the end
+    pub resign: Option<ResignPolicy>,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        MatchConfig {
+            width: 9,
+            height: 9,
+            max_games: 1000,
+            sprt: None,
+            resign: None,
+        }
+    }
+}
+
+pub struct MatchReport {
+    pub a_name: String,
+    pub b_name: String,
+    pub game_cnt: usize,
+    pub a_win_cnt: usize,
+    pub llr: Option<f64>,
+    pub decision: SprtDecision,
+}
+
+impl MatchReport {
+    // None without games
+    pub fn a_score(&self) -> Option<f64> {
+        if self.game_cnt == 0 {
+            return None;
+        }
+        Some(self.a_win_cnt as f64 / self.game_cnt as f64)
+    }
+
+    // Elo difference of A over B with a ~95% confidence interval, None without
+    // games
+    pub fn elo(&self) -> Option<(f64, f64, f64)> {
+        let n = self.game_cnt as f64;
+        let p = self.a_score()?;
+        let margin = 1.96 * (p * (1.0 - p) / n).sqrt();
+        Some((
+            score_to_elo(p),
+            score_to_elo(p - margin),
+            score_to_elo(p + margin),
+        ))
+    }
+}
+
+impl std::fmt::Display for MatchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{} vs {}", self.a_name, self.b_name)?;
+        write!(
+            f,
+            "{} games: {} - {}",
+            self.game_cnt,
+            self.a_win_cnt,
+            self.game_cnt - self.a_win_cnt
+        )?;
+        if let (Some(score), Some((elo, low, high))) = (self.a_score(), self.elo()) {
+            write!(f, " ({:.2}%)", 100.0 * score)?;
+            write!(f, "\nElo: {:.1} [{:.1}, {:.1}]", elo, low, high)?;
+        }
+        if let Some(llr) = self.llr {
+            write!(f, "\nSPRT: LLR {:.3}, {:?}", llr, self.decision)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn elo_to_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+pub fn score_to_elo(score: f64) -> f64 {
+    let score = score.clamp(1e-6, 1.0 - 1e-6);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+// Plays games with alternating colors (A is black in even games) until
+// max_games or an SPRT decision
+pub fn run_match(a: &mut dyn Engine, b: &mut dyn Engine, config: &MatchConfig) -> MatchReport {
+    let mut report = MatchReport {
+        a_name: a.name(),
+        b_name: b.name(),
+        game_cnt: 0,
+        a_win_cnt: 0,
+        llr: None,
+        decision: SprtDecision::Undecided,
+    };
+
+    while report.game_cnt < config.max_games {
+        let a_player = if report.game_cnt.is_multiple_of(2) {
+            Player::Black
+        } else {
+            Player::White
+        };
+        let winner =
+            play_game_with_resign(a, b, a_player, config.width, config.height, config.resign);
+
+        report.game_cnt += 1;
+        if winner == a_player {
+            report.a_win_cnt += 1;
+        }
+
+        if let Some(sprt) = config.sprt {
+            let llr = sprt.llr(report.a_win_cnt, report.game_cnt - report.a_win_cnt);
+            report.llr = Some(llr);
+            if llr >= sprt.upper_bound() {
+                report.decision = SprtDecision::AcceptH1;
+                break;
+            }
+            if llr <= sprt.lower_bound() {
+                report.decision = SprtDecision::AcceptH0;
+                break;
+            }
+        }
+    }
+
+    report
+}
+
+// Returns the winner. An illegal move loses the game.
+pub fn play_game(
+    a: &mut dyn Engine,
+    b: &mut dyn Engine,
+    a_player: Player,
+    width: usize,
+    height: usize,
+) -> Player {
+    play_game_with_resign(a, b, a_player, width, height, None)
+}
+
+// play_game where an engine resigns according to resign
+pub fn play_game_with_resign(
+    a: &mut dyn Engine,
+    b: &mut dyn Engine,
+    a_player: Player,
+    width: usize,
+    height: usize,
+    resign: Option<ResignPolicy>,
+) -> Player {
+    let mut board = Board::with_size(width, height);
+    let max_moves = 3 * width * height;
+    let mut trackers = resign.map(|policy| PlayerMap::new_with(ResignTracker::new(policy)));
+
+    while !board.both_player_pass() && board.move_count() < max_moves {
+        let pl = board.act_player();
+        let engine: &mut dyn Engine = if pl == a_player { &mut *a } else { &mut *b };
+        let v = engine.genmove(&board);
+        if let Some(trackers) = trackers.as_mut() {
+            if trackers[pl].should_resign(board.move_count(), engine.last_winrate()) {
+                return pl.opponent();
+            }
+        }
+        if v != Vertex::pass() && !board.is_legal(pl, v) {
+            return pl.opponent();
+        }
+        board.play_legal(pl, v);
+    }
+
+    board.playout_winner()
+}
@@ -0,0 +1,267 @@
+use crate::board::Board;
+use crate::gammas::Gammas;
+use crate::nat_set::NatSet;
+use crate::tactics::{chain, is_ladder_captured};
+use crate::types::{color_is_player, Color, Nat, Player, Vertex, VertexNeighbors};
+
+// Positions tried by the ladder check of each extension
+pub const LADDER_BUDGET: usize = 200;
+
+// Prior factor of ordered_local_moves for the 8 points around the last move
+pub const LOCAL_PROXIMITY_BONUS: f64 = 10.0;
+
+// Kinds of urgent moves, most urgent first
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum UrgentKind {
+    // Captures an opponent chain that has one of our chains in atari
+    CaptureToSave,
+    // Extends a chain in atari to three or more liberties, or to two that do
+    // not lose a ladder
+    AtariEscape,
+    Capture,
+    // Vital point of an enclosed three to five point eye space
+    NakadeVital,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct UrgentMove {
+    pub v: Vertex,
+    pub kind: UrgentKind,
+    // Stones saved or captured, eye space size for nakade
+    pub size: usize,
+}
+
+// Urgent tactical moves of player, legal and without duplicates, sorted by kind
+// and then by size (largest first)
+pub fn urgent_moves(board: &Board, player: Player) -> Vec<UrgentMove> {
+    let mut moves = Vec::new();
+    let own = Color::from(player);
+    let mut seen_chain = NatSet::<{ Vertex::COUNT }, Vertex>::new();
+
+    for v in Vertex::all() {
+        let color = board.color_at(v);
+        if !color_is_player(color) || seen_chain.is_marked(v) {
+            continue;
+        }
+        let (stones, libs) = chain(board, v);
+        let stone_list: Vec<Vertex> = Vertex::all().filter(|&s| stones.is_marked(s)).collect();
+        for &s in stone_list.iter() {
+            seen_chain.mark(s);
+        }
+        if libs.len() != 1 {
+            continue;
+        }
+        let lib = libs[0];
+
+        if color != own {
+            if board.is_legal(player, lib) {
+                push(&mut moves, lib, UrgentKind::Capture, stone_list.len());
+            }
+            continue;
+        }
+
+        // Own chain in atari: capture a neighbor in atari or run
+        for &s in stone_list.iter() {
+            for nbr in s.neighbors4() {
+                let nbr_color = board.color_at(nbr);
+                if color_is_player(nbr_color) && nbr_color != own {
+                    let (_, nbr_libs) = chain(board, nbr);
+                    if nbr_libs.len() == 1 && board.is_legal(player, nbr_libs[0]) {
+                        push(
+                            &mut moves,
+                            nbr_libs[0],
+                            UrgentKind::CaptureToSave,
+                            stone_list.len(),
+                        );
+                    }
+                }
+            }
+        }
+        if board.is_legal(player, lib) && escapes(board, player, v, lib) {
+            push(&mut moves, lib, UrgentKind::AtariEscape, stone_list.len());
+        }
+    }
+
+    nakade_moves(board, player, &mut moves);
+
+    moves.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then(b.size.cmp(&a.size))
+            .then(usize::from(a.v).cmp(&usize::from(b.v)))
+    });
+    moves
+}
+
+// Legal moves of player in region, best first for alpha-beta style local
+// searches: captures (largest first), then extensions of own chains in atari,
+// then ataris, then the rest. Within each group moves are ordered by their
+// gamma (1 without gammas) times LOCAL_PROXIMITY_BONUS next to the last move,
+// ties by vertex. No ladder reading, so cheap enough to call at every node.
+pub fn ordered_local_moves(
+    board: &Board,
+    region: &[Vertex],
+    player: Player,
+    gammas: Option<&Gammas>,
+) -> Vec<Vertex> {
+    let last_v = board.last_vertex();
+    let near_last: Vec<Vertex> = if color_is_player(board.color_at(last_v)) {
+        last_v.neighbors8().collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut moves: Vec<(Vertex, usize, usize, f64)> = Vec::new();
+    for &v in region.iter() {
+        if board.color_at(v) != Color::Empty
+            || !board.is_legal(player, v)
+            || moves.iter().any(|m| m.0 == v)
+        {
+            continue;
+        }
+        let (group, size) = local_move_group(board, player, v);
+        let mut prior = gammas.map_or(1.0, |gammas| gammas.get(board.hash3x3_at(v), player));
+        if near_last.contains(&v) {
+            prior *= LOCAL_PROXIMITY_BONUS;
+        }
+        moves.push((v, group, size, prior));
+    }
+
+    moves.sort_by(|a, b| {
+        a.1.cmp(&b.1)
+            .then(b.2.cmp(&a.2))
+            .then(b.3.total_cmp(&a.3))
+            .then(usize::from(a.0).cmp(&usize::from(b.0)))
+    });
+    moves.into_iter().map(|m| m.0).collect()
+}
+
+// Group of ordered_local_moves, 0 to 3, with the stones captured or saved
+fn local_move_group(board: &Board, player: Player, v: Vertex) -> (usize, usize) {
+    let (mut captured, mut saved, mut atari) = (0, 0, false);
+    let mut seen = Vec::with_capacity(4);
+    for nbr in v.neighbors4() {
+        let Some(nbr_chain) = board.chain_at(nbr) else {
+            continue;
+        };
+        let handle = board.chain_handle(nbr);
+        if seen.contains(&handle) {
+            continue;
+        }
+        seen.push(handle);
+        let in_atari_at_v = nbr_chain.atari_vertex() == Some(v);
+        if board.color_at(nbr) == Color::from(player) {
+            if in_atari_at_v {
+                saved += nbr_chain.size as usize;
+            }
+        } else if in_atari_at_v {
+            captured += nbr_chain.size as usize;
+        } else if !atari {
+            atari = chain(board, nbr).1.len() == 2;
+        }
+    }
+    match (captured, saved, atari) {
+        (c, _, _) if c > 0 => (0, c),
+        (_, s, _) if s > 0 => (1, s),
+        (_, _, true) => (2, 0),
+        _ => (3, 0),
+    }
+}
+
+// Keeps the most urgent entry for every vertex
+fn push(moves: &mut Vec<UrgentMove>, v: Vertex, kind: UrgentKind, size: usize) {
+    match moves.iter_mut().find(|m| m.v == v) {
+        Some(m) => {
+            if (kind, usize::MAX - size) < (m.kind, usize::MAX - m.size) {
+                m.kind = kind;
+                m.size = size;
+            }
+        }
+        None => moves.push(UrgentMove { v, kind, size }),
+    }
+}
+
+fn escapes(board: &Board, player: Player, chain_v: Vertex, lib: Vertex) -> bool {
+    let mut next = board.clone();
+    next.play_legal(player, lib);
+    match chain(&next, chain_v).1.len() {
+        0 | 1 => false,
+        2 => is_ladder_captured(&next, chain_v, LADDER_BUDGET) == Some(false),
+        _ => true,
+    }
+}
+
+fn nakade_moves(board: &Board, player: Player, moves: &mut Vec<UrgentMove>) {
+    for (vital_v, _, size) in nakade_vital_points(board) {
+        if board.is_legal(player, vital_v) {
+            push(moves, vital_v, UrgentKind::NakadeVital, size);
+        }
+    }
+}
+
+// Vital points of empty regions of 3 to 5 points bordered by one color only:
+// the unique point with the most neighbors inside the region, if the shape is
+// one that a single stone there kills (or makes alive). With the border color
+// and the region size.
+pub(crate) fn nakade_vital_points(board: &Board) -> Vec<(Vertex, Color, usize)> {
+    let mut points = Vec::new();
+    let mut visited = NatSet::<{ Vertex::COUNT }, Vertex>::new();
+    for v in Vertex::all() {
+        if board.color_at(v) != Color::Empty || visited.is_marked(v) {
+            continue;
+        }
+        let mut region = vec![v];
+        let mut border = Color::Empty;
+        let mut mixed = false;
+        visited.mark(v);
+        let mut idx = 0;
+        while idx < region.len() {
+            let act_v = region[idx];
+            idx += 1;
+            for nbr in act_v.neighbors4() {
+                match board.color_at(nbr) {
+                    Color::Empty => {
+                        if !visited.is_marked(nbr) {
+                            visited.mark(nbr);
+                            region.push(nbr);
+                        }
+                    }
+                    Color::OffBoard => {}
+                    color => {
+                        mixed |= border != Color::Empty && border != color;
+                        border = color;
+                    }
+                }
+            }
+        }
+        if mixed || border == Color::Empty || !(3..=5).contains(&region.len()) {
+            continue;
+        }
+
+        let degree = |p: Vertex| p.neighbors4().filter(|n| region.contains(n)).count();
+        let max_degree = region.iter().map(|&p| degree(p)).max().unwrap();
+        let centers: Vec<Vertex> = region
+            .iter()
+            .copied()
+            .filter(|&p| degree(p) == max_degree)
+            .collect();
+        let killable = match region.len() {
+            3 => max_degree == 2,
+            4 => max_degree == 3,
+            // Cross or bulky five
+            _ => max_degree == 4 || (max_degree == 3 && has_square(&region)),
+        };
+        if killable && centers.len() == 1 {
+            points.push((centers[0], border, region.len()));
+        }
+    }
+    points
+}
+
+fn has_square(region: &[Vertex]) -> bool {
+    region.iter().any(|&p| {
+        [p.right(), p.down(), p.down().right()]
+            .iter()
+            .all(|q| region.contains(q))
+    })
+}
@@ -1,4 +1,4 @@
-use crate::types::Nat;
+use crate::types::{Nat, PackedVertex, Vertex};
 use std::ops::{Index, IndexMut};
 
 #[derive(Clone)]
@@ -30,14 +30,14 @@ impl<const SIZE: usize, N: Nat, T> Index<N> for NatMap<SIZE, N, T> {
 
     fn index(&self, idx: N) -> &Self::Output {
         let index: usize = idx.into();
-        &self.data[index as usize]
+        &self.data[index]
     }
 }
 
 impl<const SIZE: usize, N: Nat, T> IndexMut<N> for NatMap<SIZE, N, T> {
     fn index_mut(&mut self, idx: N) -> &mut Self::Output {
         let index: usize = idx.into();
-        &mut self.data[index as usize]
+        &mut self.data[index]
     }
 }
 
@@ -49,3 +49,18 @@ where
         Self::new()
     }
 }
+
+// Vertex maps are indexed by packed vertices directly
+impl<T> Index<PackedVertex> for NatMap<{ Vertex::COUNT }, Vertex, T> {
+    type Output = T;
+
+    fn index(&self, v: PackedVertex) -> &T {
+        &self.data[usize::from(v)]
+    }
+}
+
+impl<T> IndexMut<PackedVertex> for NatMap<{ Vertex::COUNT }, Vertex, T> {
+    fn index_mut(&mut self, v: PackedVertex) -> &mut T {
+        &mut self.data[usize::from(v)]
+    }
+}
@@ -1,5 +1,6 @@
 use crate::types::Nat;
 
+#[derive(Clone)]
 pub struct NatSet<const SIZE: usize, T: Nat> {
     marked: [bool; SIZE],
     _phantom: std::marker::PhantomData<T>,
@@ -19,11 +20,17 @@ impl<const SIZE: usize, T: Nat> NatSet<SIZE, T> {
 
     pub fn mark(&mut self, item: T) {
         let index: usize = item.into();
-        self.marked[index as usize] = true;
+        self.marked[index] = true;
     }
 
     pub fn is_marked(&self, item: T) -> bool {
         let index: usize = item.into();
-        self.marked[index as usize]
+        self.marked[index]
+    }
+}
+
+impl<const SIZE: usize, T: Nat> Default for NatSet<SIZE, T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,26 @@
+use crate::types::{Player, Vertex};
+
+// Receives the changes of Board::play_legal_observed, e.g. to keep history
+// planes or a GUI in sync. All methods default to doing nothing.
+pub trait BoardObserver {
+    // False skips collecting the data for the callbacks altogether
+    const ENABLED: bool = true;
+
+    fn on_stone_placed(&mut self, _player: Player, _v: Vertex) {}
+
+    // Called before the stones are removed, player is their owner. Suicide
+    // reports the mover's own chain.
+    fn on_chain_captured(&mut self, _player: Player, _stones: &[Vertex]) {}
+
+    // Vertex::none() clears the ko, called after every non-pass move and pass
+    fn on_ko_set(&mut self, _ko_v: Vertex) {}
+
+    // Called once at the end of every move, passes included, after the other
+    // callbacks of the move, including those of a suicide
+    fn on_move_played(&mut self, _player: Player, _v: Vertex) {}
+}
+
+// No observer, what Board::play_legal uses
+impl BoardObserver for () {
+    const ENABLED: bool = false;
+}
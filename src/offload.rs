@@ -0,0 +1,155 @@
+use crate::board::Board;
+use crate::error::BoardError;
+use crate::types::{Color, Vertex};
+
+// Flat buffers of the playout state of a batch of same-size boards, for
+// computing move choices outside of Board (GPU playouts, NN policy rollouts).
+// Points are numbered row-major from the top left, row * width + column.
+
+// Choice meaning pass in apply_choices
+pub const PASS_CHOICE: u32 = u32::MAX;
+// PlayoutBatch::ko without a ko point
+pub const NO_KO: u32 = u32::MAX;
+
+// Point colors in PlayoutBatch::colors
+pub const EMPTY_POINT: u8 = 0;
+pub const BLACK_POINT: u8 = 1;
+pub const WHITE_POINT: u8 = 2;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlayoutBatch {
+    pub width: usize,
+    pub height: usize,
+    // width * height per board
+    pub colors: Vec<u8>,
+    // 0 black, 1 white, per board
+    pub to_move: Vec<u8>,
+    // Point banned by simple ko or NO_KO, per board
+    pub ko: Vec<u32>,
+    // Empty points of board i are empties[empty_offsets[i]..empty_offsets[i + 1]]
+    pub empty_offsets: Vec<u32>,
+    pub empties: Vec<u32>,
+    // Hash3x3 index of each entry of empties, for looking up Gammas::get
+    pub patterns: Vec<u32>,
+}
+
+impl PlayoutBatch {
+    pub fn new(width: usize, height: usize) -> Self {
+        PlayoutBatch {
+            width,
+            height,
+            empty_offsets: vec![0],
+            ..Self::default()
+        }
+    }
+
+    pub fn board_count(&self) -> usize {
+        self.to_move.len()
+    }
+
+    // Empties the buffers keeping their capacity
+    pub fn clear(&mut self) {
+        self.colors.clear();
+        self.to_move.clear();
+        self.ko.clear();
+        self.empty_offsets.clear();
+        self.empty_offsets.push(0);
+        self.empties.clear();
+        self.patterns.clear();
+    }
+
+    pub fn push(&mut self, board: &Board) -> Result<(), BoardError> {
+        if (board.width(), board.height()) != (self.width, self.height) {
+            return Err(BoardError::BadSize {
+                width: board.width(),
+                height: board.height(),
+            });
+        }
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let v = Vertex::from_coords(row as isize, col as isize);
+                let point = (row * self.width + col) as u32;
+                self.colors.push(match board.color_at(v) {
+                    Color::Black => BLACK_POINT,
+                    Color::White => WHITE_POINT,
+                    _ => EMPTY_POINT,
+                });
+                if board.color_at(v) == Color::Empty {
+                    self.empties.push(point);
+                    self.patterns.push(usize::from(board.hash3x3_at(v)) as u32);
+                }
+            }
+        }
+        self.to_move.push(usize::from(board.act_player()) as u8);
+        self.ko.push(match board.ko_vertex() {
+            ko if ko == Vertex::none() => NO_KO,
+            ko => self.point_of(ko),
+        });
+        self.empty_offsets.push(self.empties.len() as u32);
+        Ok(())
+    }
+
+    pub fn of_boards(boards: &[Board]) -> Result<Self, BoardError> {
+        let Some(first) = boards.first() else {
+            return Ok(Self::default());
+        };
+        let mut batch = Self::new(first.width(), first.height());
+        for board in boards.iter() {
+            batch.push(board)?;
+        }
+        Ok(batch)
+    }
+
+    // Empty points of board board_idx
+    pub fn board_empties(&self, board_idx: usize) -> &[u32] {
+        let range =
+            self.empty_offsets[board_idx] as usize..self.empty_offsets[board_idx + 1] as usize;
+        &self.empties[range]
+    }
+
+    pub fn point_of(&self, v: Vertex) -> u32 {
+        (v.row() as usize * self.width + v.column() as usize) as u32
+    }
+
+    // Vertex of a point or PASS_CHOICE
+    pub fn vertex_of(&self, choice: u32) -> Result<Vertex, BoardError> {
+        vertex_of_choice(self.width, self.height, choice)
+    }
+}
+
+// Plays choices[i], a point or PASS_CHOICE, for the player to move on boards[i].
+// Stops at the first illegal choice with its index, the boards before it keep
+// their move. Without one choice per board no move is played and the index is
+// that of the first board or choice without a partner.
+pub fn apply_choices(boards: &mut [Board], choices: &[u32]) -> Result<(), (usize, BoardError)> {
+    if boards.len() != choices.len() {
+        return Err((
+            boards.len().min(choices.len()),
+            BoardError::ChoiceCount {
+                boards: boards.len(),
+                choices: choices.len(),
+            },
+        ));
+    }
+    for (idx, (board, &choice)) in boards.iter_mut().zip(choices).enumerate() {
+        let v = vertex_of_choice(board.width(), board.height(), choice).map_err(|e| (idx, e))?;
+        board
+            .try_play(board.act_player(), v)
+            .map_err(|e| (idx, e))?;
+    }
+    Ok(())
+}
+
+fn vertex_of_choice(width: usize, height: usize, choice: u32) -> Result<Vertex, BoardError> {
+    if choice == PASS_CHOICE {
+        return Ok(Vertex::pass());
+    }
+    let (row, col) = (choice as usize / width, choice as usize % width);
+    if row >= height {
+        return Err(BoardError::BadCoords {
+            row: row as i32,
+            column: col as i32,
+        });
+    }
+    Ok(Vertex::from_coords(row as isize, col as isize))
+}
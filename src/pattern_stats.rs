@@ -0,0 +1,160 @@
+use crate::board::Board;
+use crate::error::BoardError;
+use crate::hash::{Hash, Hash3x3, Hash3x3Map};
+use crate::sgf::SgfGame;
+use crate::types::{Move, Nat, Player, PlayerMap};
+use std::collections::HashMap;
+use std::io::Write;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct PatternCount {
+    // Times a move with this pattern was played
+    pub played: u64,
+    // Times a legal move with this pattern was on the board when a move was played
+    pub available: u64,
+}
+
+// Frequency of canonical (rotation/reflection reduced) 3x3 and 5x5 patterns
+// in game records. 5x5 patterns are keyed by Board::canonical_context_hash_at.
+pub struct PatternStats {
+    counts: Hash3x3Map<PlayerMap<PatternCount>>,
+    context_counts: HashMap<Hash, PlayerMap<PatternCount>>,
+    game_cnt: usize,
+    position_cnt: usize,
+}
+
+impl PatternStats {
+    pub fn new() -> Self {
+        PatternStats {
+            counts: Hash3x3Map::new(),
+            context_counts: HashMap::new(),
+            game_cnt: 0,
+            position_cnt: 0,
+        }
+    }
+
+    // Counts patterns before every non-pass move, see SgfGame::replay
+    pub fn add_game(&mut self, game: &SgfGame) -> Result<usize, BoardError> {
+        let replayed = game.replay(|board, mv| self.add_position(board, mv))?;
+        self.game_cnt += 1;
+        Ok(replayed)
+    }
+
+    // Counts all legal moves of mv.player as available and mv as played
+    pub fn add_position(&mut self, board: &Board, mv: Move) {
+        self.position_cnt += 1;
+        for v in board.empties() {
+            if !board.is_legal(mv.player, v) {
+                continue;
+            }
+            let played = (v == mv.vertex) as u64;
+            let count = &mut self.counts[board.hash3x3_at(v).canonical()][mv.player];
+            count.available += 1;
+            count.played += played;
+            let context = board.canonical_context_hash_at(v);
+            let count = &mut self.context_counts.entry(context).or_default()[mv.player];
+            count.available += 1;
+            count.played += played;
+        }
+    }
+
+    pub fn get(&self, hash: Hash3x3, pl: Player) -> PatternCount {
+        self.counts[hash.canonical()][pl]
+    }
+
+    pub fn get_context(&self, hash: Hash, pl: Player) -> PatternCount {
+        self.context_counts
+            .get(&hash)
+            .map_or_else(PatternCount::default, |counts| counts[pl])
+    }
+
+    pub fn game_count(&self) -> usize {
+        self.game_cnt
+    }
+
+    pub fn position_count(&self) -> usize {
+        self.position_cnt
+    }
+
+    // All patterns seen at least once, most played first
+    pub fn entries(&self) -> Vec<(Hash3x3, Player, PatternCount)> {
+        let mut result = Vec::new();
+        for hash in Hash3x3::all() {
+            for pl in Player::all() {
+                let count = self.counts[hash][pl];
+                if count.available > 0 {
+                    result.push((hash, pl, count));
+                }
+            }
+        }
+        result.sort_by_key(|&(hash, pl, count)| {
+            (
+                std::cmp::Reverse(count.played),
+                usize::from(hash),
+                usize::from(pl),
+            )
+        });
+        result
+    }
+
+    // All 5x5 patterns seen, most played first
+    pub fn context_entries(&self) -> Vec<(Hash, Player, PatternCount)> {
+        let mut result = Vec::new();
+        for (&hash, counts) in self.context_counts.iter() {
+            for pl in Player::all() {
+                if counts[pl].available > 0 {
+                    result.push((hash, pl, counts[pl]));
+                }
+            }
+        }
+        result.sort_by_key(|&(hash, pl, count)| {
+            (
+                std::cmp::Reverse(count.played),
+                hash.as_u64(),
+                usize::from(pl),
+            )
+        });
+        result
+    }
+
+    // One "hash player played available" line per pattern, hash in hex,
+    // 3x3 patterns first, then 5x5 ones
+    pub fn write_table(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "# {} games, {} positions",
+            self.game_cnt, self.position_cnt
+        )?;
+        writeln!(out, "# hash player played available")?;
+        for (hash, pl, count) in self.entries() {
+            let pl_char = if pl == Player::Black { 'B' } else { 'W' };
+            writeln!(
+                out,
+                "{:05x} {} {} {}",
+                usize::from(hash),
+                pl_char,
+                count.played,
+                count.available
+            )?;
+        }
+        writeln!(out, "# 5x5 hash player played available")?;
+        for (hash, pl, count) in self.context_entries() {
+            let pl_char = if pl == Player::Black { 'B' } else { 'W' };
+            writeln!(
+                out,
+                "{:016x} {} {} {}",
+                hash.as_u64(),
+                pl_char,
+                count.played,
+                count.available
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for PatternStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
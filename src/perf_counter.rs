@@ -1,20 +1,28 @@
 use perf_event::events::Hardware;
 use perf_event::{Builder, Counter};
 
+// Hardware counter, invalid on machines without perf_event access (no
+// hardware PMU exposed, or perf_event_paranoid too strict)
 pub struct PerfCounter {
     counter: Option<Counter>,
 }
 
 impl PerfCounter {
     pub fn new() -> Self {
+        Self::of_kind(Hardware::CPU_CYCLES, "will use time-based measurement")
+    }
+
+    // Last level cache misses, to see the effect of the board memory layout
+    pub fn cache_misses() -> Self {
+        Self::of_kind(Hardware::CACHE_MISSES, "cache misses will not be reported")
+    }
+
+    fn of_kind(kind: Hardware, fallback: &str) -> Self {
         let counter = Builder::new()
-            .kind(Hardware::CPU_CYCLES)
+            .kind(kind)
             .build()
             .map_err(|e| {
-                eprintln!(
-                    "Warning: Failed to open perf counter ({}), will use time-based measurement",
-                    e
-                );
+                eprintln!("Warning: Failed to open perf counter ({}), {}", e, fallback);
                 e
             })
             .ok();
@@ -53,3 +61,9 @@ impl PerfCounter {
         self.counter.is_some()
     }
 }
+
+impl Default for PerfCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,187 @@
+use crate::board::Board;
+use crate::error::BoardError;
+use crate::hash::{Hash, ZOBRIST};
+use crate::topology::{check_move, ko_after_move, Rectangle};
+use crate::types::{color_to_showboard_char, Color, Player, Vertex, MAX_BOARD_SIZE};
+use std::sync::Arc;
+
+// Immutable board for search trees that keep a position per node. play returns a
+// new board sharing all rows the move did not touch, so a node costs a row of
+// pointers plus the changed rows. Much slower to play on than Board, which
+// should still be used for playouts.
+
+type Row = [Color; MAX_BOARD_SIZE];
+
+#[derive(Clone)]
+pub struct PersistentBoard {
+    width: usize,
+    height: usize,
+    rows: Arc<[Arc<Row>]>,
+    to_move: Player,
+    ko: Vertex,
+    hash: Hash,
+    move_cnt: usize,
+}
+
+impl PersistentBoard {
+    pub fn new(width: usize, height: usize) -> Result<Self, BoardError> {
+        if width == 0 || width > MAX_BOARD_SIZE || height == 0 || height > MAX_BOARD_SIZE {
+            return Err(BoardError::BadSize { width, height });
+        }
+        let empty_row = Arc::new([Color::Empty; MAX_BOARD_SIZE]);
+        Ok(PersistentBoard {
+            width,
+            height,
+            rows: (0..height).map(|_| empty_row.clone()).collect(),
+            to_move: Player::Black,
+            ko: Vertex::none(),
+            hash: Hash::new(),
+            move_cnt: 0,
+        })
+    }
+
+    pub fn of_board(board: &Board) -> Self {
+        let rows = (0..board.height())
+            .map(|row| {
+                let mut colors = [Color::Empty; MAX_BOARD_SIZE];
+                for (col, color) in colors.iter_mut().enumerate().take(board.width()) {
+                    *color = board.color_at(Vertex::from_coords(row as isize, col as isize));
+                }
+                Arc::new(colors)
+            })
+            .collect();
+        PersistentBoard {
+            width: board.width(),
+            height: board.height(),
+            rows,
+            to_move: board.act_player(),
+            ko: board.ko_vertex(),
+            hash: board.positional_hash(),
+            move_cnt: board.move_count(),
+        }
+    }
+
+    // Komi is the Board default
+    pub fn to_board(&self) -> Board {
+        let mut snapshot = Board::with_size(self.width, self.height).snapshot();
+        snapshot.rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                row[..self.width]
+                    .iter()
+                    .map(|&c| color_to_showboard_char(c))
+                    .collect()
+            })
+            .collect();
+        snapshot.to_move = self.to_move;
+        snapshot.ko = self.ko;
+        snapshot.move_cnt = self.move_cnt;
+        Board::from_snapshot(&snapshot).expect("PersistentBoard holds a valid position")
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn act_player(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn ko_vertex(&self) -> Vertex {
+        self.ko
+    }
+
+    pub fn positional_hash(&self) -> Hash {
+        self.hash
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.move_cnt
+    }
+
+    // Color::OffBoard outside the board, including pass and none
+    pub fn color_at(&self, v: Vertex) -> Color {
+        match self.coords(v) {
+            Some((row, col)) => self.rows[row][col],
+            None => Color::OffBoard,
+        }
+    }
+
+    // Number of rows stored in the same memory as in other
+    pub fn shared_row_count(&self, other: &PersistentBoard) -> usize {
+        self.rows
+            .iter()
+            .zip(other.rows.iter())
+            .filter(|(a, b)| Arc::ptr_eq(a, b))
+            .count()
+    }
+
+    pub fn is_legal(&self, v: Vertex) -> bool {
+        self.check_move(v).is_ok()
+    }
+
+    // Move of the player to move, self is unchanged
+    pub fn play(&self, v: Vertex) -> Result<PersistentBoard, BoardError> {
+        let captured = self.check_move(v)?;
+        let mut next = PersistentBoard {
+            rows: self.rows.clone(),
+            to_move: self.to_move.opponent(),
+            ko: Vertex::none(),
+            move_cnt: self.move_cnt + 1,
+            ..*self
+        };
+        if v == Vertex::pass() {
+            return Ok(next);
+        }
+
+        let mut rows: Vec<Arc<Row>> = self.rows.to_vec();
+        let opponent = self.to_move.opponent();
+        let mut set = |v: Vertex, color: Color, hash: &mut Hash| {
+            let (row, col) = self.coords(v).unwrap();
+            Arc::make_mut(&mut rows[row])[col] = color;
+            let pl = if color == Color::Empty {
+                opponent
+            } else {
+                self.to_move
+            };
+            *hash ^= ZOBRIST.of_player_vertex(pl, v);
+        };
+        set(v, Color::from(self.to_move), &mut next.hash);
+        for &captured_v in captured.iter() {
+            set(captured_v, Color::Empty, &mut next.hash);
+        }
+        next.rows = rows.into();
+
+        next.ko = ko_after_move(&self.rectangle(), |v| next.color_at(v), v, &captured);
+        Ok(next)
+    }
+
+    // Stones captured by the move, or why it is illegal
+    fn check_move(&self, v: Vertex) -> Result<Vec<Vertex>, BoardError> {
+        check_move(
+            &self.rectangle(),
+            |v| self.color_at(v),
+            self.to_move,
+            self.ko,
+            v,
+        )
+    }
+
+    fn rectangle(&self) -> Rectangle {
+        Rectangle::new(self.width, self.height).expect("size checked by new")
+    }
+
+    fn coords(&self, v: Vertex) -> Option<(usize, usize)> {
+        if v == Vertex::pass() || v == Vertex::none() {
+            return None;
+        }
+        let (row, col) = (v.row(), v.column());
+        (row >= 0 && col >= 0 && (row as usize) < self.height && (col as usize) < self.width)
+            .then_some((row as usize, col as usize))
+    }
+}
@@ -0,0 +1,554 @@
+use crate::board::Board;
+use crate::fast_random::FastRandom;
+use crate::gammas::Gammas;
+use crate::observer::BoardObserver;
+use crate::sampler::Sampler;
+use crate::score::PlayoutOutcome;
+use crate::tune::SamplerParams;
+use crate::types::{Move, Nat, Player, PlayerMap, Vertex, VertexMap};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayoutResult {
+    // Vertex::none() if the start position was already finished
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::vertex"))]
+    pub first_v: Vertex,
+    // Jigo counts for white as in the C++ engine, outcome has it apart
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::player"))]
+    pub winner: Player,
+    pub outcome: PlayoutOutcome,
+    pub score: i32,
+    pub move_cnt: usize,
+}
+
+// Correlation between owning a vertex and winning, over many playouts
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Criticality {
+    playout_cnt: u32,
+    win_cnt: PlayerMap<u32>,
+    owner_cnt: VertexMap<PlayerMap<u32>>,
+    // Playouts in which the vertex ended owned by the winner
+    winner_owner_cnt: VertexMap<u32>,
+}
+
+impl Criticality {
+    pub fn new() -> Self {
+        Criticality {
+            playout_cnt: 0,
+            win_cnt: PlayerMap::new(),
+            owner_cnt: VertexMap::new(),
+            winner_owner_cnt: VertexMap::new(),
+        }
+    }
+
+    // board is the final position of a playout
+    pub fn add(&mut self, board: &Board, winner: Player) {
+        self.playout_cnt += 1;
+        self.win_cnt[winner] += 1;
+        for v in Vertex::all() {
+            let owner = match board.owner_score(v) {
+                1 => Player::Black,
+                -1 => Player::White,
+                _ => continue,
+            };
+            self.owner_cnt[v][owner] += 1;
+            if owner == winner {
+                self.winner_owner_cnt[v] += 1;
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &Criticality) {
+        self.playout_cnt += other.playout_cnt;
+        for pl in Player::all() {
+            self.win_cnt[pl] += other.win_cnt[pl];
+        }
+        for v in Vertex::all() {
+            for pl in Player::all() {
+                self.owner_cnt[v][pl] += other.owner_cnt[v][pl];
+            }
+            self.winner_owner_cnt[v] += other.winner_owner_cnt[v];
+        }
+    }
+
+    pub fn playout_count(&self) -> usize {
+        self.playout_cnt as usize
+    }
+
+    // Expected owner: 1.0 black, -1.0 white
+    pub fn ownership(&self, v: Vertex) -> f64 {
+        if self.playout_cnt == 0 {
+            return 0.0;
+        }
+        let owner_cnt = &self.owner_cnt[v];
+        (owner_cnt[Player::Black] as f64 - owner_cnt[Player::White] as f64)
+            / self.playout_cnt as f64
+    }
+
+    // Coulom's criticality: P(winner owns v) minus what it would be if owning v
+    // and winning were independent. Near 0 for settled or irrelevant vertices.
+    pub fn criticality(&self, v: Vertex) -> f64 {
+        if self.playout_cnt == 0 {
+            return 0.0;
+        }
+        let n = self.playout_cnt as f64;
+        let mut independent = 0.0;
+        for pl in Player::all() {
+            independent += (self.owner_cnt[v][pl] as f64 / n) * (self.win_cnt[pl] as f64 / n);
+        }
+        self.winner_owner_cnt[v] as f64 / n - independent
+    }
+}
+
+impl Default for Criticality {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Monte Carlo estimate of the final score, positive is good for black
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreEstimate {
+    pub mean: f64,
+    pub stddev: f64,
+    // Expected owner of each vertex: 1.0 black, -1.0 white
+    pub ownership: VertexMap<f64>,
+}
+
+// Mean and spread of the final score over playout_cnt playouts from start,
+// positive is good for black. None for no playouts.
+pub fn estimate_score(
+    start: &Board,
+    gammas: &Gammas,
+    playout_cnt: usize,
+    random: &mut FastRandom,
+) -> Option<ScoreEstimate> {
+    if playout_cnt == 0 {
+        return None;
+    }
+    let mut board = start.clone();
+    let mut sampler = Sampler::new(&board, gammas);
+    let mut ownership = VertexMap::<f64>::new();
+    let mut score_sum = 0.0;
+    let mut score_sq_sum = 0.0;
+
+    for _ in 0..playout_cnt {
+        board.load(start);
+        sampler.new_playout(&board, gammas);
+        while !board.both_player_pass() {
+            let pl = board.act_player();
+            let v = sampler.sample_move(&board, random);
+            board.play_legal(pl, v);
+            sampler.move_played(&board, gammas);
+        }
+
+        let mut area = 0;
+        for v in Vertex::all() {
+            let owner = board.owner_score(v);
+            ownership[v] += owner as f64;
+            area += owner;
+        }
+        let score = area as f64 - start.komi() as f64;
+        score_sum += score;
+        score_sq_sum += score * score;
+    }
+
+    let n = playout_cnt as f64;
+    for v in Vertex::all() {
+        ownership[v] /= n;
+    }
+    let mean = score_sum / n;
+    Some(ScoreEstimate {
+        mean,
+        stddev: (score_sq_sum / n - mean * mean).max(0.0).sqrt(),
+        ownership,
+    })
+}
+
+// Runs playouts on its own board, sampler and random generator
+pub struct Playouts {
+    board: Board,
+    sampler: Sampler,
+    random: FastRandom,
+
+    // Exponentially decayed per-vertex counts of moves played in playouts
+    play_heat: VertexMap<f64>,
+    play_heat_decay: Option<f64>,
+
+    // Moves of the current playout, only recorded for LGRF
+    moves: Vec<Move>,
+}
+
+impl Playouts {
+    pub fn new(gammas: &Gammas, seed: u32) -> Self {
+        let board = Board::new();
+        let sampler = Sampler::new(&board, gammas);
+        Playouts {
+            board,
+            sampler,
+            random: FastRandom::new(seed),
+            play_heat: VertexMap::new(),
+            play_heat_decay: None,
+            moves: Vec::new(),
+        }
+    }
+
+    // After every playout the heat map is multiplied by decay and the moves of
+    // the playout are added. Disabled (and free) by default.
+    pub fn set_play_heat_decay(&mut self, decay: Option<f64>) {
+        self.play_heat_decay = decay;
+        self.play_heat = VertexMap::new();
+    }
+
+    pub fn play_heat(&self) -> &VertexMap<f64> {
+        &self.play_heat
+    }
+
+    // Playouts learn last good replies from their own outcomes, see LastGoodReply
+    pub fn set_last_good_reply(&mut self, enabled: bool) {
+        self.sampler.set_last_good_reply(enabled);
+    }
+
+    pub fn set_sampler_params(&mut self, params: &SamplerParams) {
+        params.apply(&mut self.sampler);
+    }
+
+    pub fn run(&mut self, start: &Board, gammas: &Gammas) -> PlayoutResult {
+        self.run_observed(start, gammas, &mut ())
+    }
+
+    // run reporting the moves of the playout to observer, e.g. StoneDeltaStats
+    pub fn run_observed<O: BoardObserver>(
+        &mut self,
+        start: &Board,
+        gammas: &Gammas,
+        observer: &mut O,
+    ) -> PlayoutResult {
+        self.board.load(start);
+        self.sampler.new_playout(&self.board, gammas);
+
+        let record_moves = self.sampler.last_good_reply().is_some();
+        self.moves.clear();
+
+        let mut first_v = Vertex::none();
+        while !self.board.both_player_pass() {
+            let pl = self.board.act_player();
+            let v = self.sampler.sample_move(&self.board, &mut self.random);
+            if first_v == Vertex::none() {
+                first_v = v;
+            }
+            self.board.play_legal_observed(pl, v, observer);
+            self.sampler.move_played(&self.board, gammas);
+            if record_moves {
+                self.moves.push(Move::of_player_vertex(pl, v));
+            }
+        }
+
+        let winner = self.board.playout_winner();
+        if record_moves {
+            self.sampler.playout_finished(&self.moves, winner);
+        }
+
+        if let Some(decay) = self.play_heat_decay {
+            let start_count = start.play_count_map();
+            let end_count = self.board.play_count_map();
+            for v in Vertex::all() {
+                let played = end_count[v] - start_count[v];
+                self.play_heat[v] = self.play_heat[v] * decay + played as f64;
+            }
+        }
+
+        PlayoutResult {
+            first_v,
+            winner,
+            outcome: self.board.playout_outcome(),
+            score: self.board.playout_score(),
+            move_cnt: self.board.move_count() - start.move_count(),
+        }
+    }
+
+    // run starting from FastRandom::new(seed) if given
+    pub fn run_seeded(
+        &mut self,
+        start: &Board,
+        gammas: &Gammas,
+        seed: Option<u32>,
+    ) -> PlayoutResult {
+        if let Some(seed) = seed {
+            self.random = FastRandom::new(seed);
+        }
+        self.run(start, gammas)
+    }
+
+    // Endless playouts from start, e.g. playouts.results(&board, &gammas).take(100)
+    pub fn results<'a>(
+        &'a mut self,
+        start: &'a Board,
+        gammas: &'a Gammas,
+    ) -> impl Iterator<Item = PlayoutResult> + 'a {
+        std::iter::repeat_with(move || self.run(start, gammas))
+    }
+
+    // Monte Carlo win rate of the player to move at start over playout_cnt
+    // playouts, jigo counting as half a win
+    pub fn evaluate(&mut self, start: &Board, gammas: &Gammas, playout_cnt: usize) -> f64 {
+        assert!(playout_cnt > 0, "evaluate needs at least one playout");
+        let pl = start.act_player();
+        let mut wins = 0.0;
+        for _ in 0..playout_cnt {
+            wins += match self.run(start, gammas).outcome.winner() {
+                Some(winner) if winner == pl => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+        }
+        wins / playout_cnt as f64
+    }
+
+    // Final position of the last playout
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+}
+
+// Playout workers, one per thread, each with its own Board and Sampler
+pub struct PlayoutPool {
+    workers: Vec<Playouts>,
+    seed: u32,
+    deterministic: bool,
+    last_good_reply: bool,
+    // Playouts run so far, numbering them in deterministic mode
+    next_index: u64,
+}
+
+impl PlayoutPool {
+    // Worker i uses seed + i
+    pub fn new(gammas: &Gammas, thread_cnt: usize, seed: u32) -> Self {
+        assert!(thread_cnt > 0, "PlayoutPool needs at least one thread");
+        PlayoutPool {
+            workers: (0..thread_cnt)
+                .map(|i| Playouts::new(gammas, seed.wrapping_add(i as u32)))
+                .collect(),
+            seed,
+            deterministic: false,
+            last_good_reply: false,
+            next_index: 0,
+        }
+    }
+
+    // In deterministic mode the n-th playout of the pool is seeded with
+    // playout_seed(seed, n), so results (in order) do not depend on the thread
+    // count or scheduling. Costs a reseed per playout. Restarts the numbering.
+    // Last good replies are learned per worker, so they are off in
+    // deterministic mode.
+    pub fn set_deterministic(&mut self, enabled: bool) {
+        self.deterministic = enabled;
+        self.next_index = 0;
+        self.update_last_good_reply();
+    }
+
+    // Every worker learns its own last good replies, see
+    // Playouts::set_last_good_reply. No effect in deterministic mode.
+    pub fn set_last_good_reply(&mut self, enabled: bool) {
+        self.last_good_reply = enabled;
+        self.update_last_good_reply();
+    }
+
+    fn update_last_good_reply(&mut self) {
+        let enabled = self.last_good_reply && !self.deterministic;
+        for worker in self.workers.iter_mut() {
+            worker.set_last_good_reply(enabled);
+        }
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn set_sampler_params(&mut self, params: &SamplerParams) {
+        for worker in self.workers.iter_mut() {
+            worker.set_sampler_params(params);
+        }
+    }
+
+    // One playout from every position, results in the order of positions
+    pub fn run_batch(&mut self, positions: &[Board], gammas: &Gammas) -> Vec<PlayoutResult> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "playout_batch",
+            count = positions.len(),
+            threads = self.workers.len()
+        )
+        .entered();
+        let seeds = self.take_seeds(positions.len());
+        if self.workers.len() == 1 || positions.len() <= 1 {
+            let worker = &mut self.workers[0];
+            return positions
+                .iter()
+                .enumerate()
+                .map(|(i, p)| worker.run_seeded(p, gammas, seeds.seed(i as u64)))
+                .collect();
+        }
+
+        let chunk_size = positions.len().div_ceil(self.workers.len());
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = positions
+                .chunks(chunk_size)
+                .zip(self.workers.iter_mut())
+                .enumerate()
+                .map(|(chunk_idx, (chunk, worker))| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .enumerate()
+                            .map(|(i, p)| {
+                                let idx = (chunk_idx * chunk_size + i) as u64;
+                                worker.run_seeded(p, gammas, seeds.seed(idx))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("Playout worker panicked"))
+                .collect()
+        })
+    }
+
+    // playout_cnt playouts from the same position, split evenly between the workers
+    pub fn run_many(
+        &mut self,
+        start: &Board,
+        playout_cnt: usize,
+        gammas: &Gammas,
+    ) -> Vec<PlayoutResult> {
+        self.run_split(playout_cnt, |worker, indices, seeds| {
+            indices
+                .map(|idx| worker.run_seeded(start, gammas, seeds.seed(idx)))
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    // run_many that also collects ownership and criticality of the final positions
+    pub fn run_many_with_criticality(
+        &mut self,
+        start: &Board,
+        playout_cnt: usize,
+        gammas: &Gammas,
+    ) -> (Vec<PlayoutResult>, Criticality) {
+        let mut results = Vec::with_capacity(playout_cnt);
+        let mut criticality = Criticality::new();
+        let parts = self.run_split(playout_cnt, |worker, indices, seeds| {
+            let mut part_criticality = Criticality::new();
+            let part_results: Vec<_> = indices
+                .map(|idx| {
+                    let result = worker.run_seeded(start, gammas, seeds.seed(idx));
+                    part_criticality.add(worker.board(), result.winner);
+                    result
+                })
+                .collect();
+            (part_results, part_criticality)
+        });
+        for (part_results, part_criticality) in parts {
+            results.extend(part_results);
+            criticality.merge(&part_criticality);
+        }
+        (results, criticality)
+    }
+
+    // Calls run(worker, indices, seeds) on every worker thread with its share of
+    // playout_cnt, indices being consecutive ranges in worker order
+    fn run_split<R: Send>(
+        &mut self,
+        playout_cnt: usize,
+        run: impl Fn(&mut Playouts, std::ops::Range<u64>, PlayoutSeeds) -> R + Sync,
+    ) -> Vec<R> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::trace_span!(
+            "playouts",
+            count = playout_cnt,
+            threads = self.workers.len()
+        );
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+        let seeds = self.take_seeds(playout_cnt);
+        if self.workers.len() == 1 || playout_cnt <= 1 {
+            return vec![run(&mut self.workers[0], 0..playout_cnt as u64, seeds)];
+        }
+
+        let thread_cnt = self.workers.len();
+        let run = &run;
+        std::thread::scope(|scope| {
+            let mut first = 0;
+            let handles: Vec<_> = self
+                .workers
+                .iter_mut()
+                .enumerate()
+                .map(|(i, worker)| {
+                    let cnt = playout_cnt / thread_cnt + usize::from(i < playout_cnt % thread_cnt);
+                    let indices = first as u64..(first + cnt) as u64;
+                    first += cnt;
+                    #[cfg(feature = "tracing")]
+                    let span = &span;
+                    scope.spawn(move || {
+                        // Threads do not inherit the current span
+                        #[cfg(feature = "tracing")]
+                        let _span = tracing::trace_span!(
+                            parent: span,
+                            "playout_worker",
+                            worker = i,
+                            count = cnt
+                        )
+                        .entered();
+                        run(worker, indices, seeds)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("Playout worker panicked"))
+                .collect()
+        })
+    }
+
+    // Seeds of the next playout_cnt playouts, indexed from 0
+    fn take_seeds(&mut self, playout_cnt: usize) -> PlayoutSeeds {
+        let seeds = PlayoutSeeds {
+            seed: self.seed,
+            first_index: self.deterministic.then_some(self.next_index),
+        };
+        self.next_index += playout_cnt as u64;
+        seeds
+    }
+}
+
+// Seeds of a range of deterministic playouts, or none to keep the worker's
+// random state
+#[derive(Copy, Clone)]
+struct PlayoutSeeds {
+    seed: u32,
+    first_index: Option<u64>,
+}
+
+impl PlayoutSeeds {
+    fn seed(&self, idx: u64) -> Option<u32> {
+        self.first_index
+            .map(|first| playout_seed(self.seed, first + idx))
+    }
+}
+
+// Seed of playout index of a pool seeded with seed: a SplitMix64 mix mapped to
+// the valid FastRandom states 1..2^31-1
+pub fn playout_seed(seed: u32, index: u64) -> u32 {
+    let mut z = ((seed as u64) << 32 ^ index).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z % 0x7fff_fffe) as u32 + 1
+}
@@ -0,0 +1,264 @@
+use crate::board::Board;
+use crate::fast_random::FastRandom;
+use crate::gammas::Gammas;
+use crate::hash::Hash3x3;
+use crate::sampler::Sampler;
+use crate::types::{color_is_player, Color, Dir, Nat, Player, Vertex, VertexNeighbors};
+
+// Chooses the moves of playouts. new_playout is called with the start position,
+// then sample and move_played alternate until both players pass.
+pub trait PlayoutPolicy {
+    fn new_playout(&mut self, board: &Board);
+    // A legal move of board.act_player() or pass
+    fn sample(&mut self, board: &Board, random: &mut FastRandom) -> Vertex;
+    fn move_played(&mut self, board: &Board);
+}
+
+// The gamma-product policy of Sampler with its gammas
+pub struct SamplerPolicy<'a> {
+    sampler: Sampler,
+    gammas: &'a Gammas,
+}
+
+impl<'a> SamplerPolicy<'a> {
+    pub fn new(sampler: Sampler, gammas: &'a Gammas) -> Self {
+        SamplerPolicy { sampler, gammas }
+    }
+
+    pub fn sampler_mut(&mut self) -> &mut Sampler {
+        &mut self.sampler
+    }
+}
+
+impl PlayoutPolicy for SamplerPolicy<'_> {
+    fn new_playout(&mut self, board: &Board) {
+        self.sampler.new_playout(board, self.gammas);
+    }
+
+    fn sample(&mut self, board: &Board, random: &mut FastRandom) -> Vertex {
+        self.sampler.sample_move(board, random)
+    }
+
+    fn move_played(&mut self, board: &Board) {
+        self.sampler.move_played(board, self.gammas);
+    }
+}
+
+// Baseline playout policy without patterns or gammas: every legal move that
+// does not fill an own eye is equally likely, pass when there is none. Slower
+// than Sampler, as it looks at all empty points on every move.
+#[derive(Default)]
+pub struct UniformPolicy {
+    candidates: Vec<Vertex>,
+}
+
+impl UniformPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PlayoutPolicy for UniformPolicy {
+    fn new_playout(&mut self, _board: &Board) {}
+
+    fn sample(&mut self, board: &Board, random: &mut FastRandom) -> Vertex {
+        let pl = board.act_player();
+        self.candidates.clear();
+        for v in board.empties() {
+            if board.is_legal(pl, v) && !board.hash3x3_at(v).is_eyelike(pl) {
+                self.candidates.push(v);
+            }
+        }
+        if self.candidates.is_empty() {
+            return Vertex::pass();
+        }
+        let idx = random.get_next_uint() as usize % self.candidates.len();
+        self.candidates[idx]
+    }
+
+    fn move_played(&mut self, _board: &Board) {}
+}
+
+// Mogo's 3x3 patterns (Gelly et al. 2006) as in michi, centered on the move.
+// X and O are the two colors in either assignment, x is not X, o is not O,
+// '.' empty, ' ' off board and '?' anything.
+const MOGO_PATTERNS: [[&str; 3]; 13] = [
+    // Hane: enclosing, non-cutting, magari, katatsuke
+    ["XOX", "...", "???"],
+    ["XO.", "...", "?.?"],
+    ["XO?", "X..", "x.?"],
+    [".O.", "X..", "..."],
+    // Cuts: unprotected, peeped, de, keima
+    ["XO?", "O.o", "?o?"],
+    ["XO?", "O.X", "???"],
+    ["?X?", "O.O", "ooo"],
+    ["OX?", "o.O", "???"],
+    // Side: chase, block side cut, block side connection, sagari, cut
+    ["X.?", "O.?", "   "],
+    ["OX?", "X.O", "   "],
+    ["?X?", "x.O", "   "],
+    ["?XO", "x.x", "   "],
+    ["?OX", "X.O", "   "],
+];
+
+lazy_static::lazy_static! {
+    // Indexed by the color bits of a Hash3x3
+    static ref MOGO_PATTERN_TABLE: Vec<bool> = (0..1usize << 16)
+        .map(|raw| {
+            Hash3x3::from(raw)
+                .symmetries()
+                .iter()
+                .any(|hash| MOGO_PATTERNS.iter().any(|pattern| matches_pattern(*hash, pattern)))
+        })
+        .collect();
+}
+
+fn matches_pattern(hash: Hash3x3, pattern: &[&str; 3]) -> bool {
+    let cell = |dir: Dir| {
+        let (row, col) = match dir {
+            Dir::NW => (0, 0),
+            Dir::N => (0, 1),
+            Dir::NE => (0, 2),
+            Dir::W => (1, 0),
+            Dir::E => (1, 2),
+            Dir::SW => (2, 0),
+            Dir::S => (2, 1),
+            Dir::SE => (2, 2),
+        };
+        pattern[row].as_bytes()[col]
+    };
+    [(Color::Black, Color::White), (Color::White, Color::Black)]
+        .iter()
+        .any(|&(x, o)| {
+            Dir::all().all(|dir| {
+                let color = hash.color_at(dir);
+                match cell(dir) {
+                    b'X' => color == x,
+                    b'O' => color == o,
+                    b'x' => color != x,
+                    b'o' => color != o,
+                    b'.' => color == Color::Empty,
+                    b' ' => color == Color::OffBoard,
+                    _ => true,
+                }
+            })
+        })
+}
+
+// Mogo style sequential playout policy: capture an opponent chain in atari,
+// else extend an own chain put in atari by the last move, else play a 3x3
+// pattern next to the last move, else a uniform random move. Each step picks
+// uniformly among its candidates.
+#[derive(Default)]
+pub struct MogoPolicy {
+    candidates: Vec<Vertex>,
+    fallback: UniformPolicy,
+}
+
+impl MogoPolicy {
+    pub fn new() -> Self {
+        lazy_static::initialize(&MOGO_PATTERN_TABLE);
+        Self::default()
+    }
+
+    // Whether the 3x3 neighborhood of the point matches one of the patterns
+    pub fn is_pattern(hash: Hash3x3) -> bool {
+        MOGO_PATTERN_TABLE[usize::from(hash) & 0xffff]
+    }
+
+    fn pick(&self, random: &mut FastRandom) -> Option<Vertex> {
+        if self.candidates.is_empty() {
+            return None;
+        }
+        let idx = random.get_next_uint() as usize % self.candidates.len();
+        Some(self.candidates[idx])
+    }
+
+    fn playable(board: &Board, pl: Player, v: Vertex) -> bool {
+        board.color_at(v) == Color::Empty
+            && board.is_legal(pl, v)
+            && !board.hash3x3_at(v).is_eyelike(pl)
+    }
+
+    // Liberties of opponent chains in atari
+    fn captures(&mut self, board: &Board, pl: Player) {
+        let opponent = Color::from(pl.opponent());
+        self.candidates.clear();
+        for v in board.empties() {
+            let hash = board.hash3x3_at(v);
+            let captures = [Dir::N, Dir::E, Dir::S, Dir::W]
+                .iter()
+                .any(|&dir| hash.color_at(dir) == opponent && hash.is_in_atari(dir));
+            if captures && board.is_legal(pl, v) {
+                self.candidates.push(v);
+            }
+        }
+    }
+
+    // Liberties of own chains next to the last move that are in atari, when
+    // extending there gives at least two liberties of its own
+    fn escapes(&mut self, board: &Board, pl: Player) {
+        self.candidates.clear();
+        let last_v = board.last_vertex();
+        if !color_is_player(board.color_at(last_v)) {
+            return;
+        }
+        for nbr in last_v.neighbors4() {
+            if board.color_at(nbr) != Color::from(pl) {
+                continue;
+            }
+            let Some(lib) = board.chain_at(nbr).and_then(|chain| chain.atari_vertex()) else {
+                continue;
+            };
+            let new_lib_cnt = lib
+                .neighbors4()
+                .filter(|&l| board.color_at(l) == Color::Empty)
+                .count();
+            if new_lib_cnt >= 2 && Self::playable(board, pl, lib) && !self.candidates.contains(&lib)
+            {
+                self.candidates.push(lib);
+            }
+        }
+    }
+
+    // Points around the last move matching a pattern
+    fn pattern_moves(&mut self, board: &Board, pl: Player) {
+        self.candidates.clear();
+        let last_v = board.last_vertex();
+        if !color_is_player(board.color_at(last_v)) {
+            return;
+        }
+        for v in last_v.neighbors8() {
+            if Self::playable(board, pl, v) && Self::is_pattern(board.hash3x3_at(v)) {
+                self.candidates.push(v);
+            }
+        }
+    }
+}
+
+impl PlayoutPolicy for MogoPolicy {
+    fn new_playout(&mut self, board: &Board) {
+        self.fallback.new_playout(board);
+    }
+
+    fn sample(&mut self, board: &Board, random: &mut FastRandom) -> Vertex {
+        let pl = board.act_player();
+        self.captures(board, pl);
+        if let Some(v) = self.pick(random) {
+            return v;
+        }
+        self.escapes(board, pl);
+        if let Some(v) = self.pick(random) {
+            return v;
+        }
+        self.pattern_moves(board, pl);
+        if let Some(v) = self.pick(random) {
+            return v;
+        }
+        self.fallback.sample(board, random)
+    }
+
+    fn move_played(&mut self, board: &Board) {
+        self.fallback.move_played(board);
+    }
+}
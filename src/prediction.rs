@@ -0,0 +1,105 @@
+use crate::board::Board;
+use crate::error::BoardError;
+use crate::gammas::Gammas;
+use crate::sgf::SgfGame;
+use crate::types::Move;
+
+// How well the gamma model alone (no proximity bonus) predicts moves from game records
+#[derive(Clone, Debug, Default)]
+pub struct PredictionStats {
+    pub move_cnt: usize,
+    // Ties with the played move are split evenly, so uniform gammas score like random guessing
+    pub top1_hits: f64,
+    pub top5_hits: f64,
+    // Sum of ln(probability of the played move) over moves with non-zero
+    // probability, see mean_log_likelihood for all moves
+    pub log_likelihood: f64,
+    // Played moves the model considered impossible (gamma 0)
+    pub zero_prob_cnt: usize,
+}
+
+impl PredictionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_game(&mut self, game: &SgfGame, gammas: &Gammas) -> Result<usize, BoardError> {
+        game.replay(|board, mv| self.add_position(board, gammas, mv))
+    }
+
+    pub fn add_position(&mut self, board: &Board, gammas: &Gammas, mv: Move) {
+        let played_gamma = gammas.get(board.hash3x3_at(mv.vertex), mv.player);
+        let mut total = 0.0;
+        let mut greater_cnt = 0;
+        let mut equal_cnt = 0;
+
+        for v in board.empties() {
+            if !board.is_legal(mv.player, v) {
+                continue;
+            }
+            let gamma = gammas.get(board.hash3x3_at(v), mv.player);
+            total += gamma;
+            if gamma > played_gamma {
+                greater_cnt += 1;
+            } else if gamma == played_gamma {
+                equal_cnt += 1;
+            }
+        }
+
+        self.move_cnt += 1;
+        self.top1_hits += top_k_hit(1, greater_cnt, equal_cnt);
+        self.top5_hits += top_k_hit(5, greater_cnt, equal_cnt);
+        if played_gamma > 0.0 {
+            self.log_likelihood += (played_gamma / total).ln();
+        } else {
+            self.zero_prob_cnt += 1;
+        }
+    }
+
+    // Zero without moves, as are the other means
+    pub fn top1_accuracy(&self) -> f64 {
+        self.mean(self.top1_hits)
+    }
+
+    pub fn top5_accuracy(&self) -> f64 {
+        self.mean(self.top5_hits)
+    }
+
+    // Over all moves, so -inf once a played move had zero probability
+    pub fn mean_log_likelihood(&self) -> f64 {
+        if self.zero_prob_cnt > 0 {
+            return f64::NEG_INFINITY;
+        }
+        self.mean(self.log_likelihood)
+    }
+
+    fn mean(&self, sum: f64) -> f64 {
+        if self.move_cnt == 0 {
+            return 0.0;
+        }
+        sum / self.move_cnt as f64
+    }
+}
+
+// Probability that the played move is among the first k when ties are broken randomly
+fn top_k_hit(k: usize, greater_cnt: usize, equal_cnt: usize) -> f64 {
+    if greater_cnt >= k {
+        0.0
+    } else {
+        ((k - greater_cnt) as f64 / equal_cnt as f64).min(1.0)
+    }
+}
+
+impl std::fmt::Display for PredictionStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} moves: top-1 {:.2}%, top-5 {:.2}%, log-likelihood/move {:.4} ({} zero-probability moves)",
+            self.move_cnt,
+            100.0 * self.top1_accuracy(),
+            100.0 * self.top5_accuracy(),
+            self.mean_log_likelihood(),
+            self.zero_prob_cnt
+        )
+    }
+}
@@ -0,0 +1,189 @@
+use crate::gtp::Gtp;
+use std::path::Path;
+
+// Runner for GNU Go style regression files (.tst): GTP commands, each
+// numbered command optionally followed by a "#? [expected]" line, e.g.
+//
+//   loadsgf games/ladder.sgf 12
+//   1 reg_genmove white
+//   #? [C3|D4]
+//
+// The expected answer is a |-separated list of alternatives, compared case
+// insensitively with the whole answer; a leading ! expects none of them, a
+// trailing * marks a known failure. Relative loadsgf paths are taken from the
+// directory of the .tst file.
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RegressionOutcome {
+    Pass,
+    Fail,
+    // Marked with * and failing as expected
+    KnownFail,
+    // Marked with * but passing
+    UnexpectedPass,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegressionResult {
+    // Number of the GTP command
+    pub id: String,
+    pub command: String,
+    // The pattern between the brackets
+    pub expected: String,
+    // Answer of the engine, "? ..." for GTP errors
+    pub answer: String,
+    pub outcome: RegressionOutcome,
+}
+
+// Runs the commands of a .tst file on gtp, one result per "#?" line
+pub fn run_regression(gtp: &mut Gtp, text: &str, base_dir: &Path) -> Vec<RegressionResult> {
+    let mut results = Vec::new();
+    // Id, command and answer of the last numbered command
+    let mut last: Option<(String, String, Result<String, String>)> = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(expectation) = line.strip_prefix("#?") {
+            let Some((id, command, answer)) = last.take() else {
+                continue;
+            };
+            let (expected, known_fail) = parse_expectation(expectation);
+            let passed = answer
+                .as_ref()
+                .is_ok_and(|answer| answer_matches(&expected, answer));
+            let outcome = match (passed, known_fail) {
+                (true, false) => RegressionOutcome::Pass,
+                (false, false) => RegressionOutcome::Fail,
+                (false, true) => RegressionOutcome::KnownFail,
+                (true, true) => RegressionOutcome::UnexpectedPass,
+            };
+            results.push(RegressionResult {
+                id,
+                command,
+                expected,
+                answer: answer.unwrap_or_else(|e| format!("? {}", e)),
+                outcome,
+            });
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = resolve_loadsgf(line, base_dir);
+        let mut words = line.split_whitespace();
+        let id = words
+            .clone()
+            .next()
+            .filter(|word| word.chars().all(|c| c.is_ascii_digit()));
+        if id.is_some() {
+            words.next();
+        }
+        let command: Vec<&str> = words.collect();
+        let Some(response) = gtp.response(&line) else {
+            continue;
+        };
+        last = id.map(|id| (id.to_string(), command.join(" "), parse_response(&response)));
+    }
+    results
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct RegressionSummary {
+    pub passed: usize,
+    pub failed: usize,
+    pub known_failed: usize,
+    pub unexpectedly_passed: usize,
+}
+
+impl RegressionSummary {
+    pub fn of_results(results: &[RegressionResult]) -> Self {
+        let mut summary = Self::default();
+        for result in results {
+            match result.outcome {
+                RegressionOutcome::Pass => summary.passed += 1,
+                RegressionOutcome::Fail => summary.failed += 1,
+                RegressionOutcome::KnownFail => summary.known_failed += 1,
+                RegressionOutcome::UnexpectedPass => summary.unexpectedly_passed += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn merge(&mut self, other: &RegressionSummary) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.known_failed += other.known_failed;
+        self.unexpectedly_passed += other.unexpectedly_passed;
+    }
+}
+
+impl std::fmt::Display for RegressionSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} passed, {} failed, {} known failures, {} unexpected passes",
+            self.passed, self.failed, self.known_failed, self.unexpectedly_passed
+        )
+    }
+}
+
+// Pattern between the brackets and whether it is marked as a known failure
+fn parse_expectation(text: &str) -> (String, bool) {
+    let text = text.trim();
+    let (text, known_fail) = match text.strip_suffix('*') {
+        Some(text) => (text.trim_end(), true),
+        None => (text, false),
+    };
+    let pattern = text
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+        .unwrap_or(text);
+    (pattern.trim().to_string(), known_fail)
+}
+
+fn answer_matches(pattern: &str, answer: &str) -> bool {
+    let (pattern, negated) = match pattern.strip_prefix('!') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let answer = answer.trim();
+    let found = pattern
+        .split('|')
+        .any(|alternative| alternative.trim().eq_ignore_ascii_case(answer));
+    found != negated
+}
+
+// Answer text of "=id text" or the error of "?id text"
+fn parse_response(response: &str) -> Result<String, String> {
+    let (status, rest) = response.split_at(1);
+    let text = rest
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .trim()
+        .to_string();
+    if status == "=" {
+        Ok(text)
+    } else {
+        Err(text)
+    }
+}
+
+fn resolve_loadsgf(line: &str, base_dir: &Path) -> String {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let command_idx = match words.first() {
+        Some(word) if word.chars().all(|c| c.is_ascii_digit()) => 1,
+        _ => 0,
+    };
+    if words.get(command_idx) != Some(&"loadsgf") {
+        return line.to_string();
+    }
+    let Some(&path) = words.get(command_idx + 1) else {
+        return line.to_string();
+    };
+    if Path::new(path).is_absolute() {
+        return line.to_string();
+    }
+    let mut resolved: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+    resolved[command_idx + 1] = base_dir.join(path).to_string_lossy().into_owned();
+    resolved.join(" ")
+}
@@ -0,0 +1,213 @@
+use crate::board::Board;
+use crate::game::fixed_handicap_vertices;
+use crate::types::{vertex_to_gtp, Color, Vertex, VertexMap};
+use std::fmt::Write;
+
+// SVG drawings of a board for documentation and GUI prototypes. Rows are drawn
+// from the top like BoardSnapshot rows, with GTP coordinates around the grid.
+
+#[derive(Clone, Copy)]
+pub struct RenderOptions<'a> {
+    // Distance between lines in SVG units
+    pub cell_size: f64,
+    pub coordinates: bool,
+    // Circle on the last stone played
+    pub last_move: bool,
+    // Square on the point banned by ko
+    pub ko: bool,
+    // Shades every point by its expected owner (1.0 black, -1.0 white), e.g.
+    // ScoreEstimate::ownership
+    pub ownership: Option<&'a VertexMap<f64>>,
+    // One-color Go: every stone gray, for looking at policies without the
+    // colors
+    pub one_color: bool,
+}
+
+impl Default for RenderOptions<'_> {
+    fn default() -> Self {
+        RenderOptions {
+            cell_size: 24.0,
+            coordinates: true,
+            last_move: true,
+            ko: true,
+            ownership: None,
+            one_color: false,
+        }
+    }
+}
+
+pub fn board_to_svg(board: &Board, options: &RenderOptions) -> String {
+    let (width, height) = (board.width(), board.height());
+    let cell = options.cell_size;
+    let margin = if options.coordinates {
+        cell * 1.2
+    } else {
+        cell * 0.6
+    };
+    let x = |col: usize| num(margin + col as f64 * cell);
+    let y = |row: usize| num(margin + row as f64 * cell);
+    let svg_width = num(2.0 * margin + (width - 1) as f64 * cell);
+    let svg_height = num(2.0 * margin + (height - 1) as f64 * cell);
+    let on_board = |v: Vertex| board.coord_of(v).map(|coord| (coord.row(), coord.col()));
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = svg_width,
+        h = svg_height
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect width="{}" height="{}" fill="#dcb35c"/>"##,
+        svg_width, svg_height
+    );
+
+    // Grid and star points
+    for row in 0..height {
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{}" y1="{y}" x2="{}" y2="{y}" stroke="black"/>"#,
+            x(0),
+            x(width - 1),
+            y = y(row)
+        );
+    }
+    for col in 0..width {
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="black"/>"#,
+            y(0),
+            y(height - 1),
+            x = x(col)
+        );
+    }
+    let star_cnt = match (width % 2 == 1 && height % 2 == 1, width.min(height) >= 13) {
+        (true, true) => 9,
+        (true, false) => 5,
+        _ => 4,
+    };
+    for v in fixed_handicap_vertices(width, height, star_cnt).unwrap_or_default() {
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="black"/>"#,
+            x(v.column() as usize),
+            y(v.row() as usize),
+            num(cell * 0.1)
+        );
+    }
+
+    if options.coordinates {
+        let font = num(cell * 0.5);
+        for col in 0..width {
+            let letter = &vertex_to_gtp(Vertex::from_coords(0, col as isize), height)[..1];
+            for text_y in [
+                num(margin - cell * 0.7),
+                num(svg_height - margin + cell * 0.7),
+            ] {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    x(col),
+                    text_y,
+                    font,
+                    letter
+                );
+            }
+        }
+        for row in 0..height {
+            for text_x in [
+                num(margin - cell * 0.7),
+                num(svg_width - margin + cell * 0.7),
+            ] {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    text_x,
+                    y(row),
+                    font,
+                    height - row
+                );
+            }
+        }
+    }
+
+    if let Some(ownership) = options.ownership {
+        for row in 0..height {
+            for col in 0..width {
+                let owner = ownership[Vertex::from_coords(row as isize, col as isize)];
+                if owner == 0.0 {
+                    continue;
+                }
+                let fill = if owner > 0.0 { "black" } else { "white" };
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{cell}" height="{cell}" fill="{}" fill-opacity="{:.3}"/>"#,
+                    num(x(col) - cell / 2.0),
+                    num(y(row) - cell / 2.0),
+                    fill,
+                    0.6 * owner.abs().min(1.0),
+                    cell = cell
+                );
+            }
+        }
+    }
+
+    for row in 0..height {
+        for col in 0..width {
+            let fill = match board.color_at(Vertex::from_coords(row as isize, col as isize)) {
+                Color::Black | Color::White if options.one_color => "gray",
+                Color::Black => "black",
+                Color::White => "white",
+                _ => continue,
+            };
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="black"/>"#,
+                x(col),
+                y(row),
+                num(cell * 0.47),
+                fill
+            );
+        }
+    }
+
+    if options.last_move {
+        let stroke = match board.color_at(board.last_vertex()) {
+            Color::Black => Some("white"),
+            Color::White if options.one_color => Some("white"),
+            Color::White => Some("black"),
+            _ => None,
+        };
+        if let (Some((row, col)), Some(stroke)) = (on_board(board.last_vertex()), stroke) {
+            let _ = writeln!(
+                svg,
+                r#"<circle class="last-move" cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+                x(col),
+                y(row),
+                num(cell * 0.25),
+                stroke
+            );
+        }
+    }
+
+    if options.ko {
+        if let Some((row, col)) = on_board(board.ko_vertex()) {
+            let _ = writeln!(
+                svg,
+                r#"<rect class="ko" x="{}" y="{}" width="{s}" height="{s}" fill="none" stroke="black" stroke-width="2"/>"#,
+                num(x(col) - cell * 0.25),
+                num(y(row) - cell * 0.25),
+                s = num(cell * 0.5)
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Coordinates rounded to 1/100 unit, keeping the output short
+fn num(x: f64) -> f64 {
+    (x * 100.0).round() / 100.0
+}
@@ -0,0 +1,122 @@
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scoring {
+    // Stones plus surrounded empty points
+    Area,
+    // Surrounded empty points plus prisoners
+    Territory,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KoRule {
+    // Only the immediate recapture is forbidden
+    Simple,
+    // No move may recreate an earlier board position
+    PositionalSuperko,
+    // No move may recreate an earlier position with the same player to move
+    SituationalSuperko,
+}
+
+// Extra points for white in handicap games with n handicap stones
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HandicapCompensation {
+    None,
+    // n points (Chinese)
+    Full,
+    // n - 1 points (AGA)
+    MinusOne,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rules {
+    pub scoring: Scoring,
+    pub ko: KoRule,
+    // Multi-stone suicide; single-stone suicide is then still forbidden by superko
+    pub suicide_allowed: bool,
+    pub komi: f32,
+    pub handicap_compensation: HandicapCompensation,
+}
+
+impl Rules {
+    pub fn chinese() -> Self {
+        Rules {
+            scoring: Scoring::Area,
+            ko: KoRule::PositionalSuperko,
+            suicide_allowed: false,
+            komi: 7.5,
+            handicap_compensation: HandicapCompensation::Full,
+        }
+    }
+
+    pub fn japanese() -> Self {
+        Rules {
+            scoring: Scoring::Territory,
+            ko: KoRule::Simple,
+            suicide_allowed: false,
+            komi: 6.5,
+            handicap_compensation: HandicapCompensation::None,
+        }
+    }
+
+    pub fn aga() -> Self {
+        Rules {
+            scoring: Scoring::Area,
+            ko: KoRule::SituationalSuperko,
+            suicide_allowed: false,
+            komi: 7.5,
+            handicap_compensation: HandicapCompensation::MinusOne,
+        }
+    }
+
+    pub fn tromp_taylor() -> Self {
+        Rules {
+            scoring: Scoring::Area,
+            ko: KoRule::PositionalSuperko,
+            suicide_allowed: true,
+            komi: 7.5,
+            handicap_compensation: HandicapCompensation::None,
+        }
+    }
+
+    pub fn new_zealand() -> Self {
+        Rules {
+            scoring: Scoring::Area,
+            ko: KoRule::SituationalSuperko,
+            suicide_allowed: true,
+            komi: 7.0,
+            handicap_compensation: HandicapCompensation::None,
+        }
+    }
+}
+
+impl Rules {
+    // Rules of the name: chinese, japanese, aga, tromp-taylor or new-zealand
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "chinese" => Some(Self::chinese()),
+            "japanese" => Some(Self::japanese()),
+            "aga" => Some(Self::aga()),
+            "tromp-taylor" => Some(Self::tromp_taylor()),
+            "new-zealand" => Some(Self::new_zealand()),
+            _ => None,
+        }
+    }
+
+    // Points white gets on top of komi in a game with this many handicap stones
+    pub fn compensation_points(&self, handicap: usize) -> f32 {
+        match self.handicap_compensation {
+            HandicapCompensation::None => 0.0,
+            HandicapCompensation::Full => handicap as f32,
+            HandicapCompensation::MinusOne => handicap.saturating_sub(1) as f32,
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::chinese()
+    }
+}
@@ -1,70 +1,315 @@
 use crate::board::Board;
 use crate::fast_random::FastRandom;
 use crate::gammas::{Gammas, GAMMAS_ACCURACY};
+use crate::last_good_reply::LastGoodReply;
+use crate::movegen::urgent_moves;
 use crate::nat_set::NatSet;
-use crate::types::{vertex_nbr, Color, Dir, Nat, Player, PlayerMap, Vertex, VertexMap};
+use crate::types::{vertex_nbr, Color, Dir, Move, Nat, Player, PlayerMap, Vertex, VertexMap};
+use arrayvec::ArrayVec;
+
+// Bound of Sampler::local_vertices: the 8 neighbors of the last move and the
+// liberty of its chain in atari. Raise it when the local area grows.
+const MAX_LOCAL_VERTICES: usize = 9;
+
+// Gammas as the sampler sums them: f64 by default, with fixed-point-gammas u64
+// fixed point, whose sums and samples are exact integer arithmetic and give the
+// same playouts on every platform and compiler
+#[cfg(not(feature = "fixed-point-gammas"))]
+mod weight {
+    use crate::fast_random::FastRandom;
+
+    pub type Weight = f64;
+    pub const ZERO: Weight = 0.0;
+
+    #[inline(always)]
+    pub fn of_gamma(gamma: f64) -> Weight {
+        gamma
+    }
+
+    #[inline(always)]
+    pub fn to_f64(weight: Weight) -> f64 {
+        weight
+    }
+
+    #[inline(always)]
+    pub fn scaled(weight: Weight, factor: f64) -> Weight {
+        weight * factor
+    }
+
+    // Uniform in [0, total)
+    #[inline(always)]
+    pub fn draw(random: &mut FastRandom, total: Weight) -> Weight {
+        random.next_double(total)
+    }
+
+    // Whether a running sum of weights has reached the drawn sample
+    #[inline(always)]
+    pub fn covers(sum: Weight, sample: Weight) -> bool {
+        sum >= sample
+    }
+
+    // Sums below it are not sampled from
+    pub fn pass_threshold(epsilon: f64) -> Weight {
+        epsilon
+    }
+}
+
+// 32.32 fixed point: gammas below 2^-32 count as 0 and a board of 361 points
+// fits gammas up to about 10^7
+#[cfg(feature = "fixed-point-gammas")]
+mod weight {
+    use crate::fast_random::FastRandom;
+
+    pub type Weight = u64;
+    pub const ZERO: Weight = 0;
+    const ONE: f64 = (1u64 << 32) as f64;
+
+    // Multiplying by a power of two is exact, the cast truncates
+    #[inline(always)]
+    pub fn of_gamma(gamma: f64) -> Weight {
+        (gamma * ONE) as u64
+    }
+
+    #[inline(always)]
+    pub fn to_f64(weight: Weight) -> f64 {
+        weight as f64 / ONE
+    }
+
+    #[inline(always)]
+    pub fn scaled(weight: Weight, factor: f64) -> Weight {
+        ((weight as u128 * of_gamma(factor) as u128) >> 32) as u64
+    }
+
+    // Uniform in [0, total) from one 31 bit draw, like next_double
+    #[inline(always)]
+    pub fn draw(random: &mut FastRandom, total: Weight) -> Weight {
+        ((random.get_next_uint() as u128 * total as u128) >> 31) as u64
+    }
+
+    // Strict, so that a zero weight never covers a zero sample
+    #[inline(always)]
+    pub fn covers(sum: Weight, sample: Weight) -> bool {
+        sum > sample
+    }
+
+    // Sums are exact, so only a zero sum means no gammas
+    pub fn pass_threshold(epsilon: f64) -> Weight {
+        of_gamma(epsilon).max(1)
+    }
+}
+
+use weight::Weight;
+
+// How sample_move chose a move
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SampleKind {
+    Pass,
+    GoodReply,
+    Urgent,
+    // Gamma sampling among the neighbors of the last move
+    Local,
+    // Gamma sampling among the other empty points
+    NonLocal,
+}
+
+// One sample_move decision of a traced playout. The gamma totals are after
+// the proximity bonus and 0 for moves chosen without gamma sampling, gamma is
+// the one the chosen move was sampled with.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SampleTrace {
+    pub move_no: usize,
+    pub mv: Move,
+    pub kind: SampleKind,
+    pub local_gamma: f64,
+    pub non_local_gamma: f64,
+    pub gamma: f64,
+}
+
+// Log line, e.g. "12 B[cd] Local 2.500+41.000 gamma 0.400": move number,
+// move, kind, local and non-local gamma totals and the gamma of the move
+impl std::fmt::Display for SampleTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {:?} {:.3}+{:.3} gamma {:.3}",
+            self.move_no, self.mv, self.kind, self.local_gamma, self.non_local_gamma, self.gamma
+        )
+    }
+}
 
 pub struct Sampler {
-    act_gamma: VertexMap<PlayerMap<f64>>,
-    act_gamma_sum: PlayerMap<f64>,
+    act_gamma: PlayerMap<VertexMap<Weight>>,
+    act_gamma_sum: PlayerMap<Weight>,
+    legacy_pass: bool,
+    pass_epsilon: f64,
     proximity_bonus: [f64; 2],
+    capture_bonus: f64,
+    pass_gamma: Weight,
+    row_major: bool,
+    last_good_reply: Option<LastGoodReply>,
+    urgent_moves: bool,
 
     is_in_local: NatSet<{ Vertex::COUNT }, Vertex>,
-    local_vertices: Vec<Vertex>,
-    local_gamma: VertexMap<f64>,
-    total_non_local_gamma: f64,
-    total_local_gamma: f64,
+    local_vertices: ArrayVec<Vertex, MAX_LOCAL_VERTICES>,
+    local_gamma: VertexMap<Weight>,
+    total_non_local_gamma: Weight,
+    total_local_gamma: Weight,
 
     ko_v: Vertex,
+    // Decisions of the current playout when tracing
+    trace: Option<Vec<SampleTrace>>,
 }
 
 impl Sampler {
     pub fn new(_board: &Board, _gammas: &Gammas) -> Self {
         let mut sampler = Sampler {
-            act_gamma: VertexMap::new(),
+            act_gamma: PlayerMap::new_with(VertexMap::new()),
             act_gamma_sum: PlayerMap::new(),
+            legacy_pass: false,
+            pass_epsilon: GAMMAS_ACCURACY,
             proximity_bonus: [10.0, 10.0],
+            capture_bonus: 1.0,
+            pass_gamma: weight::ZERO,
+            row_major: false,
+            last_good_reply: None,
+            urgent_moves: false,
 
             is_in_local: NatSet::<{ Vertex::COUNT }, Vertex>::new(),
-            local_vertices: Vec::with_capacity(100),
+            local_vertices: ArrayVec::new(),
             local_gamma: VertexMap::new(),
-            total_non_local_gamma: 0.0,
-            total_local_gamma: 0.0,
+            total_non_local_gamma: weight::ZERO,
+            total_local_gamma: weight::ZERO,
 
             ko_v: Vertex::none(),
+            trace: None,
         };
 
         // Initialize act_gamma
         for pl in Player::all() {
             for v in Vertex::all() {
-                sampler.act_gamma[v][pl] = 0.0;
+                sampler.act_gamma[pl][v] = weight::ZERO;
             }
-            sampler.act_gamma_sum[pl] = 0.0;
+            sampler.act_gamma_sum[pl] = weight::ZERO;
         }
 
         sampler
     }
 
+    // Multipliers of the gammas of direct and diagonal neighbors of the last move
+    pub fn set_proximity_bonus(&mut self, direct: f64, diagonal: f64) {
+        self.proximity_bonus = [direct, diagonal];
+    }
+
+    // Multiplier of the gamma of the last liberty of the last move's chain when
+    // that chain is in atari, i.e. of capturing it. 1.0 (off) by default.
+    pub fn set_capture_bonus(&mut self, bonus: f64) {
+        self.capture_bonus = bonus;
+    }
+
+    // Weight of passing in the gamma sampling step, against the (bonused)
+    // gammas of the empty points. 0.0 by default: players pass only when no
+    // point has a gamma.
+    pub fn set_pass_gamma(&mut self, gamma: f64) {
+        self.pass_gamma = weight::of_gamma(gamma);
+    }
+
+    // By default empty vertices are scanned in board.empty_vertex order, which
+    // depends on the capture history. Row-major order makes samples depend only on
+    // the position (and the random state) at the cost of scanning the whole board.
+    // The gamma sums are then recomputed in row-major order after every move, so
+    // their rounding does not depend on the move order either.
+    pub fn set_row_major_order(&mut self, row_major: bool) {
+        self.row_major = row_major;
+    }
+
+    // A gamma sum below pass_epsilon may be rounding noise left after the last
+    // move was played, or a few tiny gammas. The empty points are then counted
+    // and the player passes only if none has a gamma. Legacy pass skips the
+    // count and passes right away, as the C++ version does.
+    pub fn set_legacy_pass(&mut self, enabled: bool) {
+        self.legacy_pass = enabled;
+    }
+
+    // GAMMAS_ACCURACY by default
+    pub fn set_pass_epsilon(&mut self, epsilon: f64) {
+        self.pass_epsilon = epsilon;
+    }
+
+    // Stored replies are played before falling back to gamma sampling
+    pub fn set_last_good_reply(&mut self, enabled: bool) {
+        self.last_good_reply = enabled.then(LastGoodReply::new);
+    }
+
+    // The most urgent tactical move (movegen::urgent_moves) is played before
+    // gamma sampling. Scans the whole board every move, off by default.
+    pub fn set_urgent_moves(&mut self, enabled: bool) {
+        self.urgent_moves = enabled;
+    }
+
+    // Records the decisions of sample_move from the next new_playout on, e.g.
+    // enabled before the playouts to diagnose. Off by default.
+    pub fn set_trace(&mut self, enabled: bool) {
+        self.trace = enabled.then(Vec::new);
+    }
+
+    // Decisions of the current playout, empty when not tracing
+    pub fn trace(&self) -> &[SampleTrace] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    // The trace, one line per decision
+    pub fn write_trace(&self, out: &mut impl std::io::Write) -> std::io::Result<()> {
+        for decision in self.trace() {
+            writeln!(out, "{}", decision)?;
+        }
+        Ok(())
+    }
+
+    // Points with a non-zero gamma for the player in the current playout, in
+    // vertex order, before the proximity bonus of the last move
+    pub fn distribution(&self, player: Player) -> impl Iterator<Item = (Vertex, f64)> + '_ {
+        Vertex::all()
+            .map(move |v| (v, weight::to_f64(self.act_gamma[player][v])))
+            .filter(|&(_, gamma)| gamma > 0.0)
+    }
+
+    pub fn last_good_reply(&self) -> Option<&LastGoodReply> {
+        self.last_good_reply.as_ref()
+    }
+
+    // Called with the moves of a finished playout, no-op without LGRF
+    pub fn playout_finished(&mut self, moves: &[Move], winner: Player) {
+        if let Some(lgr) = self.last_good_reply.as_mut() {
+            lgr.update(moves, winner);
+        }
+    }
+
     pub fn new_playout(&mut self, board: &Board, gammas: &Gammas) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.clear();
+        }
         // Prepare act_gamma and act_gamma_sum
         for pl in Player::all() {
-            self.act_gamma_sum[pl] = 0.0;
+            self.act_gamma_sum[pl] = weight::ZERO;
             for v in Vertex::all() {
-                self.act_gamma[v][pl] = 0.0;
+                self.act_gamma[pl][v] = weight::ZERO;
             }
 
             for ii in 0..board.empty_vertex_count() {
                 let v = board.empty_vertex(ii);
-                self.act_gamma[v][pl] = gammas.get(board.hash3x3_at(v), pl);
-                self.act_gamma_sum[pl] += self.act_gamma[v][pl];
+                self.act_gamma[pl][v] = weight::of_gamma(gammas.get(board.hash3x3_at(v), pl));
+                self.act_gamma_sum[pl] += self.act_gamma[pl][v];
             }
         }
 
         let act_pl = board.act_player();
         self.ko_v = board.ko_vertex();
         if self.ko_v != Vertex::none() {
-            self.act_gamma_sum[act_pl] -= self.act_gamma[self.ko_v][act_pl];
-            self.act_gamma[self.ko_v][act_pl] = 0.0;
+            self.act_gamma_sum[act_pl] -= self.act_gamma[act_pl][self.ko_v];
+            self.act_gamma[act_pl][self.ko_v] = weight::ZERO;
+        }
+        if self.row_major {
+            self.sum_row_major(board);
         }
     }
 
@@ -73,26 +318,27 @@ impl Sampler {
         let last_v = board.last_vertex();
 
         // Restore gamma after ko_ban lifted
-        let _old_gamma = self.act_gamma[self.ko_v][last_pl];
+        let _old_gamma = self.act_gamma[last_pl][self.ko_v];
         let hash = board.hash3x3_at(self.ko_v);
-        let new_gamma = gammas.get(hash, last_pl);
-        self.act_gamma[self.ko_v][last_pl] = new_gamma;
+        let new_gamma = weight::of_gamma(gammas.get(hash, last_pl));
+        self.act_gamma[last_pl][self.ko_v] = new_gamma;
         self.act_gamma_sum[last_pl] += new_gamma;
 
+        for v in board.changed_vertices() {
+            gammas.prefetch(board.hash3x3_at(v));
+        }
+
         for pl in Player::all() {
             // One new occupied intersection
-            let _old_val = self.act_gamma[last_v][pl];
-            self.act_gamma_sum[pl] -= self.act_gamma[last_v][pl];
-            self.act_gamma[last_v][pl] = 0.0;
+            let _old_val = self.act_gamma[pl][last_v];
+            self.act_gamma_sum[pl] -= self.act_gamma[pl][last_v];
+            self.act_gamma[pl][last_v] = weight::ZERO;
 
             // All new gammas
-            let n = board.hash3x3_changed_count();
-            for ii in 0..n {
-                let v = board.hash3x3_changed(ii);
-
-                self.act_gamma_sum[pl] -= self.act_gamma[v][pl];
-                self.act_gamma[v][pl] = gammas.get(board.hash3x3_at(v), pl);
-                self.act_gamma_sum[pl] += self.act_gamma[v][pl];
+            for v in board.changed_vertices() {
+                self.act_gamma_sum[pl] -= self.act_gamma[pl][v];
+                self.act_gamma[pl][v] = weight::of_gamma(gammas.get(board.hash3x3_at(v), pl));
+                self.act_gamma_sum[pl] += self.act_gamma[pl][v];
             }
         }
 
@@ -100,39 +346,204 @@ impl Sampler {
         let act_pl = board.act_player();
         self.ko_v = board.ko_vertex();
 
-        self.act_gamma_sum[act_pl] -= self.act_gamma[self.ko_v][act_pl];
-        self.act_gamma[self.ko_v][act_pl] = 0.0;
+        self.act_gamma_sum[act_pl] -= self.act_gamma[act_pl][self.ko_v];
+        self.act_gamma[act_pl][self.ko_v] = weight::ZERO;
+        if self.row_major {
+            self.sum_row_major(board);
+        }
+
+        #[cfg(feature = "sampler-check")]
+        if let Err(e) = self.check_incremental(board, gammas) {
+            panic!(
+                "Sampler out of sync after move {}: {}",
+                board.move_count(),
+                e
+            );
+        }
+    }
+
+    // Same summation order for equal positions, whatever the move order
+    fn sum_row_major(&mut self, board: &Board) {
+        for pl in Player::all() {
+            self.act_gamma_sum[pl] = weight::ZERO;
+            for v in Vertex::all() {
+                if board.color_at(v) == Color::Empty {
+                    self.act_gamma_sum[pl] += self.act_gamma[pl][v];
+                }
+            }
+        }
+    }
+
+    // Compares the incrementally updated gammas with those of a new_playout on
+    // the same position: equal for every point, sums equal up to rounding
+    pub fn check_incremental(&self, board: &Board, gammas: &Gammas) -> Result<(), String> {
+        let mut fresh = Sampler::new(board, gammas);
+        fresh.set_row_major_order(self.row_major);
+        fresh.new_playout(board, gammas);
+        for pl in Player::all() {
+            for v in Vertex::all() {
+                let (act, expected) = (self.act_gamma[pl][v], fresh.act_gamma[pl][v]);
+                if act != expected {
+                    return Err(format!(
+                        "{:?} gamma at {:?} is {}, from scratch {}",
+                        pl,
+                        v,
+                        weight::to_f64(act),
+                        weight::to_f64(expected)
+                    ));
+                }
+            }
+            let (act, expected) = (
+                weight::to_f64(self.act_gamma_sum[pl]),
+                weight::to_f64(fresh.act_gamma_sum[pl]),
+            );
+            if (act - expected).abs() > GAMMAS_ACCURACY * expected.max(1.0) {
+                return Err(format!(
+                    "{:?} gamma sum is {}, from scratch {}",
+                    pl, act, expected
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Probabilities of the moves of the gamma sampling step of sample_move:
+    // act_gamma with the proximity and capture bonuses around the last move,
+    // normalized, and pass with the pass gamma. Empty when there is nothing to
+    // sample.
+    pub fn move_distribution(&self, board: &Board) -> Vec<(Vertex, f64)> {
+        let pl = board.act_player();
+        let mut bonus_gamma = VertexMap::new_with(0.0);
+        for v in Vertex::all() {
+            bonus_gamma[v] = weight::to_f64(self.act_gamma[pl][v]);
+        }
+        let last_v = board.last_vertex();
+        if board.color_at(last_v) != Color::OffBoard {
+            for d in Dir::all() {
+                bonus_gamma[vertex_nbr(last_v, d)] *= self.proximity_bonus[d.proximity()];
+            }
+            if let Some(lib) = self.capture_vertex(board) {
+                bonus_gamma[lib] *= self.capture_bonus;
+            }
+        }
+        let pass_gamma = weight::to_f64(self.pass_gamma);
+        let total: f64 = board.empties().map(|v| bonus_gamma[v]).sum::<f64>() + pass_gamma;
+        if total <= 0.0 {
+            return Vec::new();
+        }
+        let mut distribution: Vec<(Vertex, f64)> = board
+            .empties()
+            .filter(|&v| bonus_gamma[v] > 0.0)
+            .map(|v| (v, bonus_gamma[v] / total))
+            .collect();
+        if pass_gamma > 0.0 {
+            distribution.push((Vertex::pass(), pass_gamma / total));
+        }
+        distribution
     }
 
     pub fn sample_move(&mut self, board: &Board, random: &mut FastRandom) -> Vertex {
+        let (v, kind) = self.sample_move_kind(board, random);
+        if self.trace.is_some() {
+            self.record(board, v, kind);
+        }
+        v
+    }
+
+    #[inline]
+    fn sample_move_kind(&mut self, board: &Board, random: &mut FastRandom) -> (Vertex, SampleKind) {
         let pl = board.act_player();
 
-        if self.act_gamma_sum[pl] < GAMMAS_ACCURACY {
-            return Vertex::pass();
+        if self.act_gamma_sum[pl] < weight::pass_threshold(self.pass_epsilon)
+            && (self.legacy_pass || self.nonzero_gamma_count(board, pl) == 0)
+        {
+            return (Vertex::pass(), SampleKind::Pass);
+        }
+
+        if let Some(v) = self.good_reply(board) {
+            return (v, SampleKind::GoodReply);
+        }
+
+        if self.urgent_moves {
+            if let Some(urgent) = urgent_moves(board, pl).first() {
+                return (urgent.v, SampleKind::Urgent);
+            }
         }
 
         self.calculate_local_gammas(board);
 
         // Draw sample
-        let total_gamma = self.total_non_local_gamma + self.total_local_gamma;
-        let sample = random.next_double(total_gamma);
+        let move_gamma = self.total_non_local_gamma + self.total_local_gamma;
+        let sample = weight::draw(random, move_gamma + self.pass_gamma);
 
         // Local move?
         if sample < self.total_local_gamma {
-            self.sample_local_move(sample)
+            (self.sample_local_move(sample), SampleKind::Local)
+        } else if self.pass_gamma > weight::ZERO && sample >= move_gamma {
+            (Vertex::pass(), SampleKind::Pass)
         } else {
             let sample = sample - self.total_local_gamma;
-            self.sample_non_local_move(board, sample)
+            (
+                self.sample_non_local_move(board, sample),
+                SampleKind::NonLocal,
+            )
+        }
+    }
+
+    fn record(&mut self, board: &Board, v: Vertex, kind: SampleKind) {
+        let pl = board.act_player();
+        let sampled = matches!(kind, SampleKind::Local | SampleKind::NonLocal);
+        let (local_gamma, non_local_gamma) = if sampled {
+            (
+                weight::to_f64(self.total_local_gamma),
+                weight::to_f64(self.total_non_local_gamma),
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        let gamma = match kind {
+            SampleKind::Pass => 0.0,
+            SampleKind::Local => weight::to_f64(self.local_gamma[v]),
+            _ => weight::to_f64(self.act_gamma[pl][v]),
+        };
+        let decision = SampleTrace {
+            move_no: board.move_count() + 1,
+            mv: Move::of_player_vertex(pl, v),
+            kind,
+            local_gamma,
+            non_local_gamma,
+            gamma,
+        };
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(decision);
         }
     }
 
+    fn nonzero_gamma_count(&self, board: &Board, pl: Player) -> usize {
+        board
+            .empties()
+            .filter(|&v| self.act_gamma[pl][v] > weight::ZERO)
+            .count()
+    }
+
+    // Stored reply if it is legal and not ruled out by its pattern (e.g. own eye)
+    fn good_reply(&self, board: &Board) -> Option<Vertex> {
+        let pl = board.act_player();
+        let lgr = self.last_good_reply.as_ref()?;
+        let v = lgr.reply(pl, board.last_play(pl), board.last_vertex())?;
+        (board.color_at(v) == Color::Empty
+            && self.act_gamma[pl][v] > weight::ZERO
+            && board.is_legal(pl, v))
+        .then_some(v)
+    }
+
     fn calculate_local_gammas(&mut self, board: &Board) {
         let pl = board.act_player();
 
         self.is_in_local.clear();
         self.local_vertices.clear();
         self.total_non_local_gamma = self.act_gamma_sum[pl];
-        self.total_local_gamma = 0.0;
+        self.total_local_gamma = weight::ZERO;
 
         let last_v = board.last_vertex();
 
@@ -140,7 +551,12 @@ impl Sampler {
             for d in Dir::all() {
                 let nbr = vertex_nbr(last_v, d);
                 self.ensure_local(nbr, pl);
-                self.local_gamma[nbr] *= self.proximity_bonus[d.proximity()];
+                self.local_gamma[nbr] =
+                    weight::scaled(self.local_gamma[nbr], self.proximity_bonus[d.proximity()]);
+            }
+            if let Some(lib) = self.capture_vertex(board) {
+                self.ensure_local(lib, pl);
+                self.local_gamma[lib] = weight::scaled(self.local_gamma[lib], self.capture_bonus);
             }
         }
 
@@ -150,37 +566,64 @@ impl Sampler {
         }
     }
 
+    // Liberty of the last move's chain in atari, None if it is not in atari or
+    // the capture bonus is off
+    fn capture_vertex(&self, board: &Board) -> Option<Vertex> {
+        if self.capture_bonus == 1.0 {
+            return None;
+        }
+        board.chain_at(board.last_vertex())?.atari_vertex()
+    }
+
     fn ensure_local(&mut self, v: Vertex, pl: Player) {
         if !self.is_in_local.is_marked(v) {
             self.is_in_local.mark(v);
+            debug_assert!(
+                !self.local_vertices.is_full(),
+                "more than {} local vertices",
+                MAX_LOCAL_VERTICES
+            );
             self.local_vertices.push(v);
-            self.local_gamma[v] = self.act_gamma[v][pl];
-            self.total_non_local_gamma -= self.act_gamma[v][pl];
+            self.local_gamma[v] = self.act_gamma[pl][v];
+            self.total_non_local_gamma -= self.act_gamma[pl][v];
         }
     }
 
-    fn sample_local_move(&self, sample: f64) -> Vertex {
-        let mut local_gamma_sum = 0.0;
+    fn sample_local_move(&self, sample: Weight) -> Vertex {
+        let mut local_gamma_sum = weight::ZERO;
         for ii in 0..self.local_vertices.len() {
             let nbr = self.local_vertices[ii];
             local_gamma_sum += self.local_gamma[nbr];
-            if local_gamma_sum >= sample {
+            if weight::covers(local_gamma_sum, sample) {
                 return nbr;
             }
         }
         panic!("Should not reach here");
     }
 
-    fn sample_non_local_move(&self, board: &Board, sample: f64) -> Vertex {
+    fn sample_non_local_move(&self, board: &Board, sample: Weight) -> Vertex {
         let pl = board.act_player();
-        let mut sum = 0.0;
+        let mut sum = weight::ZERO;
+
+        if self.row_major {
+            for v in Vertex::all() {
+                if board.color_at(v) != Color::Empty || self.is_in_local.is_marked(v) {
+                    continue;
+                }
+                sum += self.act_gamma[pl][v];
+                if sum > sample {
+                    return v;
+                }
+            }
+            return Vertex::pass();
+        }
 
         for ii in 0..board.empty_vertex_count() {
             let v = board.empty_vertex(ii);
             if self.is_in_local.is_marked(v) {
                 continue;
             }
-            sum += self.act_gamma[v][pl];
+            sum += self.act_gamma[pl][v];
             if sum > sample {
                 return v;
             }
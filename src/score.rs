@@ -0,0 +1,110 @@
+use crate::types::Player;
+
+// Score from black's side counted in half points, so .5 komi is exact and a
+// zero score is a jigo. Arithmetic saturates.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Score(i32);
+
+impl Score {
+    pub const ZERO: Score = Score(0);
+
+    pub fn of_points(points: i32) -> Self {
+        Score(2 * points)
+    }
+
+    pub fn of_half_points(half_points: i32) -> Self {
+        Score(half_points)
+    }
+
+    // Rounded to the nearest half point, e.g. komi. Saturates out of range,
+    // NaN is zero.
+    pub fn of_f32(points: f32) -> Self {
+        Score((2.0 * points).round() as i32)
+    }
+
+    pub fn half_points(self) -> i32 {
+        self.0
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / 2.0
+    }
+
+    pub fn is_jigo(self) -> bool {
+        self.0 == 0
+    }
+
+    // None for jigo
+    pub fn winner(self) -> Option<Player> {
+        match self.0 {
+            0 => None,
+            s if s > 0 => Some(Player::Black),
+            _ => Some(Player::White),
+        }
+    }
+}
+
+impl std::ops::Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score(self.0.saturating_add(other.0))
+    }
+}
+
+impl std::ops::Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score(self.0.saturating_sub(other.0))
+    }
+}
+
+impl std::ops::Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score(self.0.saturating_neg())
+    }
+}
+
+// SGF RE[] notation: "B+3.5", "W+2" or "0" for jigo
+impl std::fmt::Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let margin = Score(self.0.saturating_abs()).to_f32();
+        match self.winner() {
+            None => write!(f, "0"),
+            Some(Player::Black) => write!(f, "B+{}", margin),
+            Some(Player::White) => write!(f, "W+{}", margin),
+        }
+    }
+}
+
+// Result of a finished playout by its exact score, keeping jigo apart
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlayoutOutcome {
+    BlackWin,
+    WhiteWin,
+    Jigo,
+}
+
+impl PlayoutOutcome {
+    pub fn of_score(score: Score) -> Self {
+        match score.winner() {
+            Some(Player::Black) => PlayoutOutcome::BlackWin,
+            Some(Player::White) => PlayoutOutcome::WhiteWin,
+            None => PlayoutOutcome::Jigo,
+        }
+    }
+
+    // None for jigo
+    pub fn winner(self) -> Option<Player> {
+        match self {
+            PlayoutOutcome::BlackWin => Some(Player::Black),
+            PlayoutOutcome::WhiteWin => Some(Player::White),
+            PlayoutOutcome::Jigo => None,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// Serde support for the go_game_types re-exports, which have no serde impls
+// of their own. Use with #[serde(with = "...")]. Vertices are encoded as their
+// raw index, players and colors as lowercase names.
+
+use crate::nat_map::NatMap;
+use crate::types::Nat;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+pub mod vertex {
+    use super::*;
+    use crate::types::Vertex;
+
+    pub fn serialize<S: Serializer>(v: &Vertex, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(usize::from(*v) as u32)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vertex, D::Error> {
+        let raw = u32::deserialize(deserializer)? as usize;
+        if raw >= Vertex::COUNT {
+            return Err(de::Error::custom(format!(
+                "vertex index {} out of range",
+                raw
+            )));
+        }
+        Ok(Vertex::from(raw))
+    }
+}
+
+pub mod player {
+    use super::*;
+    use crate::types::Player;
+
+    pub fn serialize<S: Serializer>(pl: &Player, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match pl {
+            Player::Black => "black",
+            Player::White => "white",
+        })
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Player, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "black" => Ok(Player::Black),
+            "white" => Ok(Player::White),
+            other => Err(de::Error::unknown_variant(other, &["black", "white"])),
+        }
+    }
+}
+
+pub mod color {
+    use super::*;
+    use crate::types::Color;
+
+    const NAMES: &[&str] = &["black", "white", "empty", "off_board"];
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(NAMES[usize::from(*color)])
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match NAMES.iter().position(|&n| n == name) {
+            Some(idx) => Ok(Color::from(idx)),
+            None => Err(de::Error::unknown_variant(&name, NAMES)),
+        }
+    }
+}
+
+// Maps are plain sequences of all SIZE values
+impl<const SIZE: usize, N: Nat, T: Serialize> Serialize for NatMap<SIZE, N, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(SIZE))?;
+        for idx in 0..SIZE {
+            seq.serialize_element(&self[N::from(idx)])?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, const SIZE: usize, N: Nat, T: Deserialize<'de> + Default + Clone> Deserialize<'de>
+    for NatMap<SIZE, N, T>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MapVisitor<const SIZE: usize, N, T>(std::marker::PhantomData<(N, T)>);
+
+        impl<'de, const SIZE: usize, N: Nat, T: Deserialize<'de> + Default + Clone> Visitor<'de>
+            for MapVisitor<SIZE, N, T>
+        {
+            type Value = NatMap<SIZE, N, T>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a sequence of {} values", SIZE)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut map = NatMap::new();
+                for idx in 0..SIZE {
+                    map[N::from(idx)] = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(idx, &self))?;
+                }
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(SIZE + 1, &self));
+                }
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_seq(MapVisitor::<SIZE, N, T>(std::marker::PhantomData))
+    }
+}
@@ -0,0 +1,354 @@
+use crate::board::Board;
+use crate::error::BoardError;
+use crate::types::{
+    check_komi, vertex_of_sgf, Color, Move, Player, Vertex, VertexMap, DEFAULT_BOARD_SIZE,
+    MAX_BOARD_SIZE,
+};
+
+// Minimal SGF reader - only the main line (first variation) of the first game tree is kept
+
+#[derive(Clone, Debug)]
+pub struct SgfError {
+    // Byte offset of syntax errors, None for semantic ones
+    pub pos: Option<usize>,
+    pub msg: String,
+}
+
+impl std::fmt::Display for SgfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.pos {
+            Some(pos) => write!(f, "SGF error at byte {}: {}", pos, self.msg),
+            None => write!(f, "SGF error: {}", self.msg),
+        }
+    }
+}
+
+impl std::error::Error for SgfError {}
+
+#[derive(Clone, Debug, Default)]
+pub struct SgfNode {
+    pub props: Vec<(String, Vec<String>)>,
+}
+
+impl SgfNode {
+    pub fn get(&self, ident: &str) -> Option<&[String]> {
+        self.props
+            .iter()
+            .find(|(id, _)| id == ident)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    pub fn first(&self, ident: &str) -> Option<&str> {
+        self.get(ident)
+            .and_then(|values| values.first())
+            .map(|s| s.as_str())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SgfGame {
+    pub width: usize,
+    pub height: usize,
+    pub komi: f32,
+    // Stones added by AB/AW in the root node
+    pub setup: Vec<Move>,
+    // PL of the root node, black moves first without it
+    pub to_move: Option<Player>,
+    pub moves: Vec<Move>,
+}
+
+impl SgfGame {
+    // Plays the game on a fresh board with the setup stones, calling on_move with the position before
+    // every non-pass move. Returns the number of moves replayed, which is less
+    // than the game length if an illegal move was found.
+    pub fn replay(&self, mut on_move: impl FnMut(&Board, Move)) -> Result<usize, BoardError> {
+        let mut board = self.start_position()?;
+        for (move_idx, &mv) in self.moves.iter().enumerate() {
+            if mv.vertex != Vertex::pass() {
+                if !board.is_legal(mv.player, mv.vertex) {
+                    return Ok(move_idx);
+                }
+                on_move(&board, mv);
+            }
+            board.play_legal(mv.player, mv.vertex);
+        }
+
+        Ok(self.moves.len())
+    }
+
+    // The position after all moves, with the game's komi
+    pub fn final_position(&self) -> Result<Board, BoardError> {
+        let mut board = self.start_position()?;
+        board.replay(&self.moves).map_err(|(_, e)| e)?;
+        board.set_komi(self.komi);
+        Ok(board)
+    }
+
+    // The setup stones with the PL player to move, before the first move
+    pub fn start_position(&self) -> Result<Board, BoardError> {
+        let mut board = Board::try_with_size(self.width, self.height)?;
+        let mut colors = VertexMap::new_with(Color::Empty);
+        for mv in self.setup.iter() {
+            if board.coord_of(mv.vertex).is_none() {
+                return Err(BoardError::OffBoard(mv.vertex));
+            }
+            colors[mv.vertex] = Color::from(mv.player);
+        }
+        let to_move = self.to_move.unwrap_or(Player::Black);
+        board.rebuild_from_colors(&colors, to_move, Vertex::none())?;
+        Ok(board)
+    }
+}
+
+pub fn parse_game(text: &str) -> Result<SgfGame, SgfError> {
+    let nodes = parse_main_line(text)?;
+    let root = nodes.first().ok_or_else(|| invalid("no nodes"))?;
+
+    let (width, height) = match root.first("SZ") {
+        None => (DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE),
+        Some(sz) => parse_size(sz).ok_or_else(|| invalid(&format!("bad SZ[{}]", sz)))?,
+    };
+    if width > MAX_BOARD_SIZE || height > MAX_BOARD_SIZE {
+        return Err(invalid(&format!("board {}x{} is too large", width, height)));
+    }
+
+    let komi = match root.first("KM") {
+        None => 0.0,
+        Some(km) => km
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|&komi| check_komi(komi).is_ok())
+            .ok_or_else(|| invalid(&format!("bad KM[{}]", km)))?,
+    };
+
+    let mut game = SgfGame {
+        width,
+        height,
+        komi,
+        setup: Vec::new(),
+        to_move: None,
+        moves: Vec::new(),
+    };
+
+    for (node_idx, node) in nodes.iter().enumerate() {
+        for (ident, values) in node.props.iter() {
+            let player = match ident.as_str() {
+                "B" | "AB" => Player::Black,
+                "W" | "AW" => Player::White,
+                "AE" => {
+                    return Err(invalid("AE setup is not supported"));
+                }
+                "PL" => {
+                    if node_idx != 0 {
+                        return Err(invalid("PL outside the root node"));
+                    }
+                    let value = values.first().map(|s| s.trim()).unwrap_or("");
+                    game.to_move = Some(match value {
+                        "B" | "b" => Player::Black,
+                        "W" | "w" => Player::White,
+                        _ => return Err(invalid(&format!("bad PL[{}]", value))),
+                    });
+                    continue;
+                }
+                _ => continue,
+            };
+            if ident.len() == 2 {
+                if node_idx != 0 {
+                    return Err(invalid("setup stones outside the root node"));
+                }
+                for value in values.iter() {
+                    for vertex in parse_point_list(value, width, height)? {
+                        game.setup.push(Move::of_player_vertex(player, vertex));
+                    }
+                }
+            } else {
+                let value = values.first().map(|s| s.as_str()).unwrap_or("");
+                let vertex = vertex_of_sgf(value, width, height)
+                    .ok_or_else(|| invalid(&format!("bad move {}[{}]", ident, value)))?;
+                game.moves.push(Move::of_player_vertex(player, vertex));
+            }
+        }
+    }
+
+    Ok(game)
+}
+
+// Point lists may use the FF[4] compressed "aa:cc" rectangle form
+fn parse_point_list(value: &str, width: usize, height: usize) -> Result<Vec<Vertex>, SgfError> {
+    let bad = || invalid(&format!("bad point [{}]", value));
+    let (from, to) = match value.split_once(':') {
+        Some((from, to)) => (from, to),
+        None => (value, value),
+    };
+    let from = vertex_of_sgf(from, width, height).ok_or_else(bad)?;
+    let to = vertex_of_sgf(to, width, height).ok_or_else(bad)?;
+    if from == Vertex::pass() || to == Vertex::pass() {
+        return Err(bad());
+    }
+
+    let mut result = Vec::new();
+    for row in from.row().min(to.row())..=from.row().max(to.row()) {
+        for column in from.column().min(to.column())..=from.column().max(to.column()) {
+            result.push(Vertex::from_coords(row, column));
+        }
+    }
+    Ok(result)
+}
+
+fn parse_size(sz: &str) -> Option<(usize, usize)> {
+    let (width, height) = match sz.split_once(':') {
+        Some((w, h)) => (w.trim().parse().ok()?, h.trim().parse().ok()?),
+        None => {
+            let size = sz.trim().parse().ok()?;
+            (size, size)
+        }
+    };
+    if width == 0 || height == 0 {
+        return None;
+    }
+    Some((width, height))
+}
+
+pub fn parse_main_line(text: &str) -> Result<Vec<SgfNode>, SgfError> {
+    let mut parser = Parser {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    let mut nodes = Vec::new();
+    parser.skip_ws();
+    parser.parse_game_tree(&mut nodes)?;
+    Ok(nodes)
+}
+
+fn error(pos: usize, msg: &str) -> SgfError {
+    SgfError {
+        pos: Some(pos),
+        msg: msg.to_string(),
+    }
+}
+
+fn invalid(msg: &str) -> SgfError {
+    SgfError {
+        pos: None,
+        msg: msg.to_string(),
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), SgfError> {
+        self.skip_ws();
+        if self.peek() != Some(c) {
+            return Err(error(self.pos, &format!("expected '{}'", c as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_game_tree(&mut self, nodes: &mut Vec<SgfNode>) -> Result<(), SgfError> {
+        self.expect(b'(')?;
+        loop {
+            self.skip_ws();
+            if self.peek() != Some(b';') {
+                break;
+            }
+            self.pos += 1;
+            nodes.push(self.parse_node()?);
+        }
+
+        // The first variation continues the main line, the rest are dropped
+        let mut is_main = true;
+        loop {
+            self.skip_ws();
+            if self.peek() != Some(b'(') {
+                break;
+            }
+            if is_main {
+                self.parse_game_tree(nodes)?;
+            } else {
+                self.parse_game_tree(&mut Vec::new())?;
+            }
+            is_main = false;
+        }
+        self.expect(b')')
+    }
+
+    fn parse_node(&mut self) -> Result<SgfNode, SgfError> {
+        let mut node = SgfNode::default();
+        loop {
+            self.skip_ws();
+            let start = self.pos;
+            let mut ident = String::new();
+            // Lowercase letters are allowed (and ignored) in old FF[3] identifiers
+            while let Some(c) = self.peek().filter(|c| c.is_ascii_alphabetic()) {
+                if c.is_ascii_uppercase() {
+                    ident.push(c as char);
+                }
+                self.pos += 1;
+            }
+            if self.pos == start {
+                return Ok(node);
+            }
+            if ident.is_empty() {
+                return Err(error(
+                    start,
+                    "property identifier without uppercase letters",
+                ));
+            }
+
+            let mut values = Vec::new();
+            loop {
+                self.skip_ws();
+                if self.peek() != Some(b'[') {
+                    break;
+                }
+                self.pos += 1;
+                values.push(self.parse_value()?);
+            }
+            if values.is_empty() {
+                return Err(error(self.pos, &format!("property {} has no value", ident)));
+            }
+            node.props.push((ident, values));
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<String, SgfError> {
+        let start = self.pos;
+        let mut value = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(error(start, "unterminated property value")),
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(String::from_utf8_lossy(&value).into_owned());
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    if let Some(c) = self.peek() {
+                        value.push(c);
+                        self.pos += 1;
+                    }
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::board::Board;
+use crate::gammas::Gammas;
+use crate::playout::Playouts;
+use std::path::Path;
+
+// Deterministic playout snapshots for checking that changes to the board keep
+// its exact behavior. A snapshot lists, for consecutive playouts from an empty
+// board with a fixed seed, the final position hash, score and length.
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotEntry {
+    pub hash: u64,
+    pub score: i32,
+    pub move_cnt: usize,
+}
+
+// Golden files are kept per build mode, so the default mode may diverge from
+// the C++ engine without touching the cpp-compat expectations, and the
+// fixed-point-gammas playouts from the f64 ones. large-boards plays the same
+// moves with other Zobrist hashes, as vertex indices change.
+pub const GOLDEN_MODE: &str = match (
+    cfg!(feature = "cpp-compat"),
+    cfg!(feature = "fixed-point-gammas"),
+    cfg!(feature = "large-boards"),
+) {
+    (false, false, false) => "default",
+    (true, false, false) => "cpp-compat",
+    (false, true, false) => "fixed-point",
+    (true, true, false) => "cpp-compat-fixed-point",
+    (false, false, true) => "large-boards",
+    (true, false, true) => "cpp-compat-large-boards",
+    (false, true, true) => "fixed-point-large-boards",
+    (true, true, true) => "cpp-compat-fixed-point-large-boards",
+};
+
+// Set to regenerate golden files instead of comparing against them
+pub const UPDATE_GOLDEN_ENV: &str = "UPDATE_GOLDEN";
+
+pub fn playout_snapshot(
+    width: usize,
+    height: usize,
+    gammas: &Gammas,
+    seed: u32,
+    playout_cnt: usize,
+) -> Vec<SnapshotEntry> {
+    let start = Board::with_size(width, height);
+    let mut playouts = Playouts::new(gammas, seed);
+    (0..playout_cnt)
+        .map(|_| {
+            let result = playouts.run(&start, gammas);
+            SnapshotEntry {
+                hash: playouts.board().positional_hash().as_u64(),
+                score: result.score,
+                move_cnt: result.move_cnt,
+            }
+        })
+        .collect()
+}
+
+// One "hash score move_cnt" line per playout, hash in hex
+pub fn format_snapshot(entries: &[SnapshotEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| format!("{:016x} {} {}\n", e.hash, e.score, e.move_cnt))
+        .collect()
+}
+
+pub fn parse_snapshot(text: &str) -> Result<Vec<SnapshotEntry>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_idx, line)| {
+            let bad = || format!("line {}: bad snapshot entry '{}'", line_idx + 1, line);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 3 {
+                return Err(bad());
+            }
+            Ok(SnapshotEntry {
+                hash: u64::from_str_radix(fields[0], 16).map_err(|_| bad())?,
+                score: fields[1].parse().map_err(|_| bad())?,
+                move_cnt: fields[2].parse().map_err(|_| bad())?,
+            })
+        })
+        .collect()
+}
+
+// Compares with the golden file, reporting the first differing playout. With
+// UPDATE_GOLDEN set in the environment the file is (re)written instead.
+pub fn check_golden(path: &Path, actual: &[SnapshotEntry]) -> Result<(), String> {
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        return std::fs::write(path, format_snapshot(actual))
+            .map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        format!(
+            "{}: {} (run with {}=1 to create it)",
+            path.display(),
+            e,
+            UPDATE_GOLDEN_ENV
+        )
+    })?;
+    let expected = parse_snapshot(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if let Some(idx) = (0..expected.len().min(actual.len())).find(|&i| expected[i] != actual[i]) {
+        return Err(format!(
+            "{}: playout {} differs, expected {:?}, got {:?}",
+            path.display(),
+            idx,
+            expected[idx],
+            actual[idx]
+        ));
+    }
+    if expected.len() != actual.len() {
+        return Err(format!(
+            "{}: expected {} playouts, got {}",
+            path.display(),
+            expected.len(),
+            actual.len()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use crate::game::Game;
+use crate::rules::{Rules, Scoring};
+use crate::types::{board_symmetry_count, symmetric_coords, Color, Move, Player, Vertex};
+use std::collections::HashMap;
+
+// Perfect play on tiny boards by alpha-beta search with a transposition table
+// over symmetry-reduced positions. Meant as an oracle for rules, ko and scoring.
+// Results that depend on the path to a position (superko, the move limit) are
+// not stored, so the table never mixes up positions with different histories.
+// On tiny boards most results do depend on it and the search is exponential:
+// 2x2 takes milliseconds, 3x2 minutes.
+
+// Games are cut off after this many moves per vertex and scored as they stand
+pub const MOVE_LIMIT_PER_VERTEX: usize = 4;
+
+// Largest board area the solver accepts, a hard cap rather than a size it
+// solves quickly: 3x2 already takes minutes
+pub const MAX_SOLVER_AREA: usize = 6;
+
+#[derive(Clone, Debug)]
+pub struct SolveResult {
+    // Final score under perfect play, positive is a black win, komi included
+    pub score: f32,
+    // Vertex::none() if the game is already over
+    pub best_move: Vertex,
+    pub node_cnt: usize,
+}
+
+pub fn solve(width: usize, height: usize, rules: Rules) -> SolveResult {
+    solve_game(&Game::new(width, height, rules))
+}
+
+// Solves the game from its current position with its move history
+pub fn solve_game(game: &Game) -> SolveResult {
+    let (width, height) = (game.board().width(), game.board().height());
+    assert!(
+        width * height <= MAX_SOLVER_AREA,
+        "Board {}x{} is too large to solve",
+        width,
+        height
+    );
+
+    let mut solver = Solver {
+        width,
+        height,
+        max_move_cnt: game.moves().len() + MOVE_LIMIT_PER_VERTEX * width * height,
+        tt: HashMap::new(),
+        node_cnt: 0,
+    };
+    // Territory scores count prisoners, at most one per move still to come
+    let prisoners = match game.rules().scoring {
+        Scoring::Area => 0,
+        Scoring::Territory => {
+            game.prisoners(Player::Black) + game.prisoners(Player::White) + solver.max_move_cnt
+        }
+    };
+    let bound = (width * height + prisoners) as f32 + game.effective_komi().abs() + 1.0;
+    let (value, best_move, _) = solver.search(game, -bound, bound);
+    let black_to_move = game.board().act_player() == Player::Black;
+    SolveResult {
+        score: if black_to_move { value } else { -value },
+        best_move,
+        node_cnt: solver.node_cnt,
+    }
+}
+
+// See Solver::key
+type Key = (u64, usize, i32, bool, bool);
+
+#[derive(Copy, Clone, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+struct Solver {
+    width: usize,
+    height: usize,
+    max_move_cnt: usize,
+    // Value for the player to move
+    tt: HashMap<Key, (f32, Bound)>,
+    node_cnt: usize,
+}
+
+impl Solver {
+    // Negamax, returns the value for the player to move, the best move and the
+    // earliest history index the value depends on (usize::MAX for none)
+    fn search(&mut self, game: &Game, mut alpha: f32, beta: f32) -> (f32, Vertex, usize) {
+        self.node_cnt += 1;
+        let board = game.board();
+        let pl = board.act_player();
+        let sign = if pl == Player::Black { 1.0 } else { -1.0 };
+        if game.is_over() {
+            return (sign * game.score(), Vertex::none(), usize::MAX);
+        }
+        if game.moves().len() >= self.max_move_cnt {
+            // Depends on the length of the path
+            return (sign * game.score(), Vertex::none(), 0);
+        }
+
+        let key = self.key(game);
+        if let Some(&(value, bound)) = self.tt.get(&key) {
+            let usable = match bound {
+                Bound::Exact => true,
+                Bound::Lower => value >= beta,
+                Bound::Upper => value <= alpha,
+            };
+            if usable {
+                return (value, Vertex::none(), usize::MAX);
+            }
+        }
+
+        let alpha_start = alpha;
+        let mut best = (f32::NEG_INFINITY, Vertex::none());
+        let mut depends_on = usize::MAX;
+        let moves = board.empties().chain(std::iter::once(Vertex::pass()));
+        for v in moves {
+            let mv = Move::of_player_vertex(pl, v);
+            if !game.is_legal(mv) {
+                if let Some(idx) = game.repeated_position(mv) {
+                    depends_on = depends_on.min(idx);
+                }
+                continue;
+            }
+            let mut child = game.clone();
+            child.play(mv);
+            let (child_value, _, child_depends_on) = self.search(&child, -beta, -alpha);
+            depends_on = depends_on.min(child_depends_on);
+            let value = -child_value;
+            if value > best.0 {
+                best = (value, v);
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        // Positions from this one on are recreated by any path reaching it
+        if depends_on >= game.moves().len() {
+            let bound = if best.0 <= alpha_start {
+                Bound::Upper
+            } else if best.0 >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.tt.insert(key, (best.0, bound));
+        }
+        (best.0, best.1, depends_on)
+    }
+
+    // Smallest encoding of the position over the board symmetries, with the
+    // matching ko vertex, plus the prisoner difference when the score counts
+    // prisoners, the player to move and whether the last move passed
+    fn key(&self, game: &Game) -> Key {
+        let board = game.board();
+        let ko = board.ko_vertex();
+        let mut best = (u64::MAX, usize::MAX);
+        for sym in 0..board_symmetry_count(self.width, self.height) {
+            let mut code = 0u64;
+            let mut ko_idx = 0;
+            for row in 0..self.height {
+                for col in 0..self.width {
+                    let idx = row * self.width + col;
+                    let (r, c) = symmetric_coords(sym, self.width, self.height, row, col);
+                    let v = Vertex::from_coords(r as isize, c as isize);
+                    code = code * 3
+                        + match board.color_at(v) {
+                            Color::Black => 1,
+                            Color::White => 2,
+                            _ => 0,
+                        };
+                    if v == ko {
+                        ko_idx = idx + 1;
+                    }
+                }
+            }
+            best = best.min((code, ko_idx));
+        }
+        let prisoners = match game.rules().scoring {
+            Scoring::Area => 0,
+            Scoring::Territory => {
+                game.prisoners(Player::Black) as i32 - game.prisoners(Player::White) as i32
+            }
+        };
+        let last_pass = board.last_vertex() == Vertex::pass();
+        let black_to_move = board.act_player() == Player::Black;
+        (best.0, best.1, prisoners, black_to_move, last_pass)
+    }
+}
@@ -0,0 +1,124 @@
+use crate::board::Board;
+use crate::gammas::Gammas;
+use crate::observer::BoardObserver;
+use crate::playout::Playouts;
+use crate::types::{Player, PlayerMap, Vertex};
+
+// Stone counts of playouts move by move, for telling whether a playout policy
+// systematically favors one color. Fed as the BoardObserver of
+// Playouts::run_observed, with start_playout before every playout. An
+// unbiased policy from the empty board keeps the mean black minus white delta
+// near the alternation's own +1/0 sawtooth; a drift shows a bias.
+#[derive(Clone, Default)]
+pub struct StoneDeltaStats {
+    // Sum over playouts of black minus white stones gained since the start,
+    // after each move of the playout
+    delta_sum: Vec<i64>,
+    // Playouts that made at least that many moves
+    reached_cnt: Vec<u64>,
+    placed: PlayerMap<u64>,
+    // Stones of the player taken off the board
+    captured: PlayerMap<u64>,
+    playout_cnt: u64,
+    // Current playout
+    delta: i64,
+    move_idx: usize,
+}
+
+impl StoneDeltaStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Runs playout_cnt playouts from start, recording all of them
+    pub fn collect(
+        playouts: &mut Playouts,
+        start: &Board,
+        gammas: &Gammas,
+        playout_cnt: usize,
+    ) -> Self {
+        let mut stats = Self::new();
+        for _ in 0..playout_cnt {
+            stats.start_playout();
+            playouts.run_observed(start, gammas, &mut stats);
+        }
+        stats
+    }
+
+    pub fn start_playout(&mut self) {
+        self.playout_cnt += 1;
+        self.delta = 0;
+        self.move_idx = 0;
+    }
+
+    pub fn playout_count(&self) -> usize {
+        self.playout_cnt as usize
+    }
+
+    // Length of the longest playout
+    pub fn max_move_count(&self) -> usize {
+        self.delta_sum.len()
+    }
+
+    // Mean black minus white stones gained after move_cnt moves, over the
+    // playouts that made that many moves; 0.0 if none did
+    pub fn mean_delta(&self, move_cnt: usize) -> f64 {
+        if move_cnt == 0 || move_cnt > self.delta_sum.len() {
+            return 0.0;
+        }
+        self.delta_sum[move_cnt - 1] as f64 / self.reached_cnt[move_cnt - 1] as f64
+    }
+
+    pub fn placed(&self, player: Player) -> u64 {
+        self.placed[player]
+    }
+
+    pub fn captured(&self, player: Player) -> u64 {
+        self.captured[player]
+    }
+
+    pub fn merge(&mut self, other: &StoneDeltaStats) {
+        if self.delta_sum.len() < other.delta_sum.len() {
+            self.delta_sum.resize(other.delta_sum.len(), 0);
+            self.reached_cnt.resize(other.reached_cnt.len(), 0);
+        }
+        for (idx, (&sum, &cnt)) in other.delta_sum.iter().zip(&other.reached_cnt).enumerate() {
+            self.delta_sum[idx] += sum;
+            self.reached_cnt[idx] += cnt;
+        }
+        for pl in [Player::Black, Player::White] {
+            self.placed[pl] += other.placed[pl];
+            self.captured[pl] += other.captured[pl];
+        }
+        self.playout_cnt += other.playout_cnt;
+    }
+}
+
+fn signed(player: Player, stone_cnt: usize) -> i64 {
+    match player {
+        Player::Black => stone_cnt as i64,
+        Player::White => -(stone_cnt as i64),
+    }
+}
+
+impl BoardObserver for StoneDeltaStats {
+    fn on_stone_placed(&mut self, player: Player, _v: Vertex) {
+        self.placed[player] += 1;
+        self.delta += signed(player, 1);
+    }
+
+    fn on_chain_captured(&mut self, player: Player, stones: &[Vertex]) {
+        self.captured[player] += stones.len() as u64;
+        self.delta -= signed(player, stones.len());
+    }
+
+    fn on_move_played(&mut self, _player: Player, _v: Vertex) {
+        if self.move_idx == self.delta_sum.len() {
+            self.delta_sum.push(0);
+            self.reached_cnt.push(0);
+        }
+        self.delta_sum[self.move_idx] += self.delta;
+        self.reached_cnt[self.move_idx] += 1;
+        self.move_idx += 1;
+    }
+}
@@ -0,0 +1,287 @@
+use crate::board::Board;
+use crate::movegen::{nakade_vital_points, LADDER_BUDGET};
+use crate::nat_set::NatSet;
+use crate::topology::{flood_chain, Rectangle};
+use crate::types::{color_is_player, color_to_player, Color, Nat, Player, Vertex, VertexNeighbors};
+
+// Shallow local reading of tactical shapes
+
+type VertexSet = NatSet<{ Vertex::COUNT }, Vertex>;
+
+// Whether the stones at v1 and v2 (same color) stay connected even if the opponent
+// moves first. Two shared liberties (diagonal, bamboo joint) are miai, other
+// shapes are read out, trying at most budget positions. Running out of budget
+// counts as cuttable. None unless v1 and v2 are stones of the same color.
+pub fn can_connect(board: &Board, v1: Vertex, v2: Vertex, budget: usize) -> Option<bool> {
+    let color = board.color_at(v1);
+    if !color_is_player(color) || board.color_at(v2) != color {
+        return None;
+    }
+    let mut budget = budget;
+    Some(cutter_to_move(board, v1, v2, &mut budget))
+}
+
+// Whether the chain at v, with two liberties and the opponent to move, dies in a
+// ladder: the attacker keeps it in atari until it cannot escape. Capturing an
+// attacking stone counts as an escape, so does running out of budget. None
+// unless v is a stone.
+pub fn is_ladder_captured(board: &Board, v: Vertex, budget: usize) -> Option<bool> {
+    if !color_is_player(board.color_at(v)) {
+        return None;
+    }
+    let mut budget = budget;
+    Some(ladder_attacker_to_move(board, v, &mut budget))
+}
+
+fn ladder_attacker_to_move(board: &Board, v: Vertex, budget: &mut usize) -> bool {
+    let (_, libs) = chain(board, v);
+    if libs.len() != 2 {
+        return libs.len() < 2;
+    }
+    let attacker = color_to_player(board.color_at(v)).opponent();
+    for &lib in libs.iter() {
+        if *budget == 0 {
+            return false;
+        }
+        *budget -= 1;
+        if !board.is_legal(attacker, lib) {
+            continue;
+        }
+        let mut next = board.clone();
+        next.play_legal(attacker, lib);
+        if ladder_defender_to_move(&next, v, budget) {
+            return true;
+        }
+    }
+    false
+}
+
+fn ladder_defender_to_move(board: &Board, v: Vertex, budget: &mut usize) -> bool {
+    let color = board.color_at(v);
+    let (stones, libs) = chain(board, v);
+    if libs.len() != 1 {
+        return libs.is_empty();
+    }
+    let defender = color_to_player(color);
+    for stone in Vertex::all().filter(|&s| stones.is_marked(s)) {
+        for nbr in stone.neighbors4() {
+            let nbr_color = board.color_at(nbr);
+            if color_is_player(nbr_color) && nbr_color != color && chain(board, nbr).1.len() == 1 {
+                return false;
+            }
+        }
+    }
+
+    if !board.is_legal(defender, libs[0]) {
+        return true;
+    }
+    let mut next = board.clone();
+    next.play_legal(defender, libs[0]);
+    match chain(&next, v).1.len() {
+        0 | 1 => true,
+        2 => ladder_attacker_to_move(&next, v, budget),
+        _ => false,
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum KoThreatKind {
+    // Puts opponent chains in atari
+    Atari,
+    // Leaves an opponent chain with two liberties that it loses in a ladder
+    Ladder,
+    // Vital point of an opponent eye space, threatening the group's life
+    Nakade,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct KoThreat {
+    pub v: Vertex,
+    pub kind: KoThreatKind,
+    // Rough value: stones threatened, eye space size for nakade
+    pub size: usize,
+}
+
+// Moves of player that the opponent should answer, for fighting a ko instead of
+// resolving it. Captures are not threats and neither are moves that leave the
+// played chain in atari. Sorted by size (largest first), then vertex.
+pub fn ko_threats(board: &Board, player: Player) -> Vec<KoThreat> {
+    let own = Color::from(player);
+    let opponent = Color::from(player.opponent());
+    let mut threats = Vec::new();
+    let nakade: Vec<(Vertex, usize)> = nakade_vital_points(board)
+        .into_iter()
+        .filter(|&(_, border, _)| border == opponent)
+        .map(|(vital_v, _, size)| (vital_v, size))
+        .collect();
+
+    for v in Vertex::all() {
+        if board.color_at(v) != Color::Empty || !board.is_legal(player, v) {
+            continue;
+        }
+        let captures = v
+            .neighbors4()
+            .any(|nbr| board.color_at(nbr) == opponent && chain(board, nbr).1 == [v]);
+        if captures {
+            continue;
+        }
+        let mut next = board.clone();
+        next.play_legal(player, v);
+        if next.color_at(v) != own || chain(&next, v).1.len() < 2 {
+            continue;
+        }
+
+        let mut seen = VertexSet::new();
+        let mut atari_size = 0;
+        let mut ladder_size = 0;
+        for nbr in v.neighbors4() {
+            if next.color_at(nbr) != opponent || seen.is_marked(nbr) {
+                continue;
+            }
+            let (stones, libs) = chain(&next, nbr);
+            let size = Vertex::all().filter(|&s| stones.is_marked(s)).count();
+            for s in Vertex::all().filter(|&s| stones.is_marked(s)) {
+                seen.mark(s);
+            }
+            match libs.len() {
+                1 => atari_size += size,
+                2 if is_ladder_captured(&next, nbr, LADDER_BUDGET) == Some(true) => {
+                    ladder_size += size
+                }
+                _ => {}
+            }
+        }
+
+        let threat = if atari_size > 0 {
+            Some((KoThreatKind::Atari, atari_size))
+        } else if ladder_size > 0 {
+            Some((KoThreatKind::Ladder, ladder_size))
+        } else {
+            nakade
+                .iter()
+                .find(|&&(vital_v, _)| vital_v == v)
+                .map(|&(_, size)| (KoThreatKind::Nakade, size))
+        };
+        if let Some((kind, size)) = threat {
+            threats.push(KoThreat { v, kind, size });
+        }
+    }
+
+    threats.sort_by(|a, b| {
+        b.size
+            .cmp(&a.size)
+            .then(usize::from(a.v).cmp(&usize::from(b.v)))
+    });
+    threats
+}
+
+struct ChainPair {
+    same_chain: bool,
+    common_libs: Vec<Vertex>,
+    // Points where a stone would reduce the gap between the chains: common
+    // liberties and liberties of one chain next to a liberty of the other
+    links: Vec<Vertex>,
+}
+
+fn chain_pair(board: &Board, v1: Vertex, v2: Vertex) -> Option<ChainPair> {
+    let color = board.color_at(v1);
+    if !color_is_player(color) || board.color_at(v2) != color {
+        // One of the chains was captured
+        return None;
+    }
+    let (stones1, libs1) = chain(board, v1);
+    if stones1.is_marked(v2) {
+        return Some(ChainPair {
+            same_chain: true,
+            common_libs: Vec::new(),
+            links: Vec::new(),
+        });
+    }
+    let (_, libs2) = chain(board, v2);
+
+    let mut lib_set2 = VertexSet::new();
+    libs2.iter().for_each(|&v| lib_set2.mark(v));
+    let mut lib_set1 = VertexSet::new();
+    libs1.iter().for_each(|&v| lib_set1.mark(v));
+
+    let common_libs: Vec<Vertex> = libs1
+        .iter()
+        .copied()
+        .filter(|&v| lib_set2.is_marked(v))
+        .collect();
+    let mut links = common_libs.clone();
+    for (libs, other) in [(&libs1, &lib_set2), (&libs2, &lib_set1)] {
+        for &lib in libs.iter() {
+            if !links.contains(&lib) && lib.neighbors4().any(|nbr| other.is_marked(nbr)) {
+                links.push(lib);
+            }
+        }
+    }
+    Some(ChainPair {
+        same_chain: false,
+        common_libs,
+        links,
+    })
+}
+
+fn cutter_to_move(board: &Board, v1: Vertex, v2: Vertex, budget: &mut usize) -> bool {
+    let Some(pair) = chain_pair(board, v1, v2) else {
+        return false;
+    };
+    if pair.same_chain || pair.common_libs.len() >= 2 {
+        return true;
+    }
+    if pair.links.is_empty() || *budget == 0 {
+        return false;
+    }
+    *budget -= 1;
+
+    let cutter = color_to_player(board.color_at(v1)).opponent();
+    for &v in pair.links.iter() {
+        if !board.is_legal(cutter, v) {
+            continue;
+        }
+        let mut child = board.clone();
+        child.play_legal(cutter, v);
+        if !connector_to_move(&child, v1, v2, budget) {
+            return false;
+        }
+    }
+    true
+}
+
+fn connector_to_move(board: &Board, v1: Vertex, v2: Vertex, budget: &mut usize) -> bool {
+    let Some(pair) = chain_pair(board, v1, v2) else {
+        return false;
+    };
+    if pair.same_chain || !pair.common_libs.is_empty() {
+        return true;
+    }
+    if *budget == 0 {
+        return false;
+    }
+    *budget -= 1;
+
+    let connector = color_to_player(board.color_at(v1));
+    for &v in pair.links.iter() {
+        if !board.is_legal(connector, v) {
+            continue;
+        }
+        let mut child = board.clone();
+        child.play_legal(connector, v);
+        if cutter_to_move(&child, v1, v2, budget) {
+            return true;
+        }
+    }
+    false
+}
+
+// Stones of the chain at v as a set and its liberties
+pub(crate) fn chain(board: &Board, v: Vertex) -> (VertexSet, Vec<Vertex>) {
+    let rectangle =
+        Rectangle::new(board.width(), board.height()).expect("a board has a valid size");
+    let (stone_list, libs) = flood_chain(&rectangle, |v| board.color_at(v), v);
+    let mut stones = VertexSet::new();
+    stone_list.into_iter().for_each(|s| stones.mark(s));
+    (stones, libs)
+}
@@ -0,0 +1,433 @@
+use crate::error::BoardError;
+use crate::fast_random::FastRandom;
+use crate::hash::{Hash, ZOBRIST};
+use crate::nat_set::NatSet;
+use crate::types::{
+    color_is_player, color_to_player, Color, Player, PlayerMap, Vertex, VertexMap, VertexNeighbors,
+    MAX_BOARD_SIZE,
+};
+
+// Boards on other neighbor graphs than the rectangle, for research: tori or
+// any graph given by its edges. TopologyBoard is generic over the
+// Topology, so every topology gets its own monomorphized code. TopologyBoard
+// finds chains by flood fill, with the move helpers at the end of this file
+// that PersistentBoard and the tactical reading share; it is far slower than
+// Board.
+//
+// Board and vertex_nbr are deliberately not generic over Topology: Board's
+// incremental liberties, 3x3 patterns and the Sampler all rely on the
+// off-board sentinel ring around the rectangle, which a torus or graph does
+// not have. Rectangle is the TopologyBoard equivalent of Board, for checking
+// the other topologies against it.
+
+pub trait Topology {
+    // Points of the board, in a fixed order
+    fn points(&self) -> impl Iterator<Item = Vertex> + '_;
+
+    fn contains(&self, v: Vertex) -> bool;
+
+    // Calls f on every neighbor of the point v. A neighbor may come more than
+    // once, e.g. on a torus 2 points wide.
+    fn for_each_neighbor(&self, v: Vertex, f: impl FnMut(Vertex));
+}
+
+// The usual board, neighbors through the sentinel layout of Vertex
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Rectangle {
+    width: usize,
+    height: usize,
+}
+
+impl Rectangle {
+    pub fn new(width: usize, height: usize) -> Result<Self, BoardError> {
+        check_size(width, height, 1)?;
+        Ok(Rectangle { width, height })
+    }
+}
+
+impl Topology for Rectangle {
+    fn points(&self) -> impl Iterator<Item = Vertex> + '_ {
+        grid_points(self.width, self.height)
+    }
+
+    fn contains(&self, v: Vertex) -> bool {
+        grid_contains(self.width, self.height, v)
+    }
+
+    #[inline(always)]
+    fn for_each_neighbor(&self, v: Vertex, mut f: impl FnMut(Vertex)) {
+        for nbr in v.neighbors4() {
+            if self.contains(nbr) {
+                f(nbr);
+            }
+        }
+    }
+}
+
+// Rectangle with opposite edges glued, every point has four neighbors
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Torus {
+    width: usize,
+    height: usize,
+}
+
+impl Torus {
+    // At least 2x2, so no point is its own neighbor
+    pub fn new(width: usize, height: usize) -> Result<Self, BoardError> {
+        check_size(width, height, 2)?;
+        Ok(Torus { width, height })
+    }
+}
+
+impl Topology for Torus {
+    fn points(&self) -> impl Iterator<Item = Vertex> + '_ {
+        grid_points(self.width, self.height)
+    }
+
+    fn contains(&self, v: Vertex) -> bool {
+        grid_contains(self.width, self.height, v)
+    }
+
+    #[inline(always)]
+    fn for_each_neighbor(&self, v: Vertex, mut f: impl FnMut(Vertex)) {
+        let (width, height) = (self.width as isize, self.height as isize);
+        let (row, col) = (v.row(), v.column());
+        f(Vertex::from_coords((row + height - 1) % height, col));
+        f(Vertex::from_coords(row, (col + 1) % width));
+        f(Vertex::from_coords((row + 1) % height, col));
+        f(Vertex::from_coords(row, (col + width - 1) % width));
+    }
+}
+
+// Any undirected graph, points are the vertices named by the edges
+#[derive(Clone)]
+pub struct Graph {
+    points: Vec<Vertex>,
+    is_point: NatSet<{ Vertex::COUNT }, Vertex>,
+    neighbors: VertexMap<Vec<Vertex>>,
+}
+
+impl Graph {
+    // Vertices must be on a MAX_BOARD_SIZE board, loops and duplicate edges are
+    // dropped
+    pub fn new(edges: &[(Vertex, Vertex)]) -> Result<Self, BoardError> {
+        let on_board = |v: Vertex| grid_contains(MAX_BOARD_SIZE, MAX_BOARD_SIZE, v);
+        let mut graph = Graph {
+            points: Vec::new(),
+            is_point: NatSet::new(),
+            neighbors: VertexMap::new_with(Vec::new()),
+        };
+        for &(a, b) in edges {
+            for v in [a, b] {
+                if !on_board(v) {
+                    return Err(BoardError::OffBoard(v));
+                }
+                if !graph.is_point.is_marked(v) {
+                    graph.is_point.mark(v);
+                    graph.points.push(v);
+                }
+            }
+            if a != b && !graph.neighbors[a].contains(&b) {
+                graph.neighbors[a].push(b);
+                graph.neighbors[b].push(a);
+            }
+        }
+        Ok(graph)
+    }
+}
+
+impl Topology for Graph {
+    fn points(&self) -> impl Iterator<Item = Vertex> + '_ {
+        self.points.iter().copied()
+    }
+
+    fn contains(&self, v: Vertex) -> bool {
+        self.is_point.is_marked(v)
+    }
+
+    fn for_each_neighbor(&self, v: Vertex, f: impl FnMut(Vertex)) {
+        self.neighbors[v].iter().copied().for_each(f);
+    }
+}
+
+fn check_size(width: usize, height: usize, min: usize) -> Result<(), BoardError> {
+    if width < min || width > MAX_BOARD_SIZE || height < min || height > MAX_BOARD_SIZE {
+        return Err(BoardError::BadSize { width, height });
+    }
+    Ok(())
+}
+
+fn grid_points(width: usize, height: usize) -> impl Iterator<Item = Vertex> {
+    (0..height).flat_map(move |row| {
+        (0..width).map(move |col| Vertex::from_coords(row as isize, col as isize))
+    })
+}
+
+fn grid_contains(width: usize, height: usize, v: Vertex) -> bool {
+    if v == Vertex::pass() || v == Vertex::none() {
+        return false;
+    }
+    let (row, col) = (v.row(), v.column());
+    row >= 0 && col >= 0 && (row as usize) < height && (col as usize) < width
+}
+
+// Position on a Topology under simple ko, suicide forbidden
+#[derive(Clone)]
+pub struct TopologyBoard<T: Topology> {
+    topology: T,
+    color_at: VertexMap<Color>,
+    to_move: Player,
+    ko: Vertex,
+    hash: Hash,
+    move_cnt: usize,
+    pass_cnt: usize,
+}
+
+impl<T: Topology> TopologyBoard<T> {
+    pub fn new(topology: T) -> Self {
+        let mut color_at = VertexMap::new_with(Color::OffBoard);
+        for v in topology.points() {
+            color_at[v] = Color::Empty;
+        }
+        TopologyBoard {
+            topology,
+            color_at,
+            to_move: Player::Black,
+            ko: Vertex::none(),
+            hash: Hash::new(),
+            move_cnt: 0,
+            pass_cnt: 0,
+        }
+    }
+
+    pub fn topology(&self) -> &T {
+        &self.topology
+    }
+
+    // Color::OffBoard outside the topology, including pass and none
+    pub fn color_at(&self, v: Vertex) -> Color {
+        self.color_at[v]
+    }
+
+    pub fn act_player(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn ko_vertex(&self) -> Vertex {
+        self.ko
+    }
+
+    pub fn positional_hash(&self) -> Hash {
+        self.hash
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.move_cnt
+    }
+
+    pub fn both_player_pass(&self) -> bool {
+        self.pass_cnt >= 2
+    }
+
+    pub fn is_legal(&self, v: Vertex) -> bool {
+        self.check_move(v).is_ok()
+    }
+
+    // Legal moves of the player to move, pass excluded
+    pub fn legal_moves(&self) -> Vec<Vertex> {
+        self.topology
+            .points()
+            .filter(|&v| self.is_legal(v))
+            .collect()
+    }
+
+    // Move of the player to move, unchanged board on Err
+    pub fn play(&mut self, v: Vertex) -> Result<(), BoardError> {
+        let captured = self.check_move(v)?;
+        let player = self.to_move;
+        self.to_move = player.opponent();
+        self.ko = Vertex::none();
+        self.move_cnt += 1;
+        if v == Vertex::pass() {
+            self.pass_cnt += 1;
+            return Ok(());
+        }
+        self.pass_cnt = 0;
+
+        self.color_at[v] = Color::from(player);
+        self.hash ^= ZOBRIST.of_player_vertex(player, v);
+        for &captured_v in captured.iter() {
+            self.color_at[captured_v] = Color::Empty;
+            self.hash ^= ZOBRIST.of_player_vertex(player.opponent(), captured_v);
+        }
+
+        self.ko = ko_after_move(&self.topology, |v| self.color_at[v], v, &captured);
+        Ok(())
+    }
+
+    // Uniformly random legal move that does not fill an eye of the player to
+    // move (an empty point whose neighbors are all its stones), pass if none.
+    // Random play can repeat a ko cycle forever, callers cap the game length.
+    pub fn play_random(&mut self, random: &mut FastRandom) -> Vertex {
+        let player = Color::from(self.to_move);
+        let candidates: Vec<Vertex> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|&v| {
+                let mut is_eye = true;
+                self.topology
+                    .for_each_neighbor(v, |nbr| is_eye &= self.color_at[nbr] == player);
+                !is_eye
+            })
+            .collect();
+        let v = match candidates.len() {
+            0 => Vertex::pass(),
+            cnt => candidates[random.get_next_uint() as usize % cnt],
+        };
+        self.play(v).expect("candidate moves are legal");
+        v
+    }
+
+    // Black minus white area: stones plus empty regions reaching only one color
+    pub fn area_score(&self) -> i32 {
+        let mut visited = NatSet::<{ Vertex::COUNT }, Vertex>::new();
+        let mut region = Vec::new();
+        let mut score = 0;
+        for v in self.topology.points() {
+            match self.color_at[v] {
+                Color::Black => score += 1,
+                Color::White => score -= 1,
+                Color::Empty if !visited.is_marked(v) => {
+                    let mut size = 0;
+                    let mut reaches = PlayerMap::new_with(false);
+                    visited.mark(v);
+                    region.push(v);
+                    while let Some(act_v) = region.pop() {
+                        size += 1;
+                        self.topology.for_each_neighbor(act_v, |nbr| {
+                            let nbr_color = self.color_at[nbr];
+                            if color_is_player(nbr_color) {
+                                reaches[color_to_player(nbr_color)] = true;
+                            } else if !visited.is_marked(nbr) {
+                                visited.mark(nbr);
+                                region.push(nbr);
+                            }
+                        });
+                    }
+                    if reaches[Player::Black] && !reaches[Player::White] {
+                        score += size;
+                    } else if reaches[Player::White] && !reaches[Player::Black] {
+                        score -= size;
+                    }
+                }
+                _ => {}
+            }
+        }
+        score
+    }
+
+    // Stones captured by the move, or why it is illegal
+    fn check_move(&self, v: Vertex) -> Result<Vec<Vertex>, BoardError> {
+        check_move(
+            &self.topology,
+            |v| self.color_at[v],
+            self.to_move,
+            self.ko,
+            v,
+        )
+    }
+}
+
+// Move helpers for boards that keep only colors, under simple ko with suicide
+// forbidden. color_at must give Color::OffBoard outside the topology.
+
+// Stones and liberties of the chain at v, by flood fill
+pub(crate) fn flood_chain<T: Topology>(
+    topology: &T,
+    color_at: impl Fn(Vertex) -> Color,
+    v: Vertex,
+) -> (Vec<Vertex>, Vec<Vertex>) {
+    let color = color_at(v);
+    let mut visited = NatSet::<{ Vertex::COUNT }, Vertex>::new();
+    let mut stones = vec![v];
+    let mut libs = Vec::new();
+    visited.mark(v);
+    let mut idx = 0;
+    while idx < stones.len() {
+        let stone = stones[idx];
+        idx += 1;
+        topology.for_each_neighbor(stone, |nbr| {
+            if visited.is_marked(nbr) {
+                return;
+            }
+            let nbr_color = color_at(nbr);
+            if nbr_color == color {
+                visited.mark(nbr);
+                stones.push(nbr);
+            } else if nbr_color == Color::Empty {
+                visited.mark(nbr);
+                libs.push(nbr);
+            }
+        });
+    }
+    (stones, libs)
+}
+
+// Stones captured by player's move at v, or why it is illegal
+pub(crate) fn check_move<T: Topology>(
+    topology: &T,
+    color_at: impl Fn(Vertex) -> Color,
+    player: Player,
+    ko: Vertex,
+    v: Vertex,
+) -> Result<Vec<Vertex>, BoardError> {
+    if v == Vertex::pass() {
+        return Ok(Vec::new());
+    }
+    match color_at(v) {
+        Color::OffBoard => return Err(BoardError::OffBoard(v)),
+        Color::Empty => {}
+        _ => return Err(BoardError::Occupied(v)),
+    }
+    if v == ko {
+        return Err(BoardError::Ko(v));
+    }
+
+    let mut captured = Vec::new();
+    let mut has_liberty = false;
+    topology.for_each_neighbor(v, |nbr| match color_at(nbr) {
+        Color::Empty => has_liberty = true,
+        Color::OffBoard => {}
+        color => {
+            let (stones, libs) = flood_chain(topology, &color_at, nbr);
+            let last_liberty = libs.len() == 1;
+            if color_to_player(color) == player {
+                has_liberty |= !last_liberty;
+            } else if last_liberty && !captured.contains(&nbr) {
+                captured.extend(stones);
+            }
+        }
+    });
+    if !has_liberty && captured.is_empty() {
+        return Err(BoardError::Suicide(v));
+    }
+    Ok(captured)
+}
+
+// Ko point after the move at v captured the given stones, color_at is the
+// position after the move. Ko: a single stone captured by a single stone left
+// with one liberty.
+pub(crate) fn ko_after_move<T: Topology>(
+    topology: &T,
+    color_at: impl Fn(Vertex) -> Color,
+    v: Vertex,
+    captured: &[Vertex],
+) -> Vertex {
+    if captured.len() == 1 {
+        let (stones, libs) = flood_chain(topology, color_at, v);
+        if stones.len() == 1 && libs.len() == 1 {
+            return captured[0];
+        }
+    }
+    Vertex::none()
+}
@@ -0,0 +1,352 @@
+use crate::board::Board;
+use crate::fast_random::FastRandom;
+use crate::gammas::Gammas;
+use crate::hash::Hash3x3;
+use crate::playout::playout_seed;
+use crate::sampler::Sampler;
+use crate::tune::SamplerParams;
+use crate::types::{Move, Player, Vertex};
+use std::io::{Read, Write};
+use std::path::Path;
+
+// Training data for minorization-maximization (MM) gamma fitting taken straight
+// from self-play: for every position the 3x3 patterns of all legal moves and
+// the pattern of the move played, no SGF round trip needed.
+
+// A stream is the magic followed by the positions: the player byte (0 black,
+// 1 white), the candidate count, the index of the chosen candidate and the
+// candidates sorted by hash, each as the difference to the previous hash and
+// its count. All numbers are LEB128 varints. A 9x9 self-play position takes
+// about 80 bytes, about 25 gzipped.
+const MAGIC: &[u8; 8] = b"GGTRAIN1";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrainingPosition {
+    pub player: Player,
+    // Distinct patterns of the legal moves with the number of points having
+    // them, sorted by hash. Patterns are Hash3x3 values as Gammas keys them,
+    // without canonicalization.
+    pub candidates: Vec<(Hash3x3, u32)>,
+    // Index into candidates of the pattern played
+    pub chosen: usize,
+}
+
+impl TrainingPosition {
+    // None for passes and illegal moves
+    pub fn of_move(board: &Board, mv: Move) -> Option<Self> {
+        if mv.vertex == Vertex::pass() || !board.is_legal(mv.player, mv.vertex) {
+            return None;
+        }
+        let mut hashes: Vec<usize> = board
+            .empties()
+            .filter(|&v| board.is_legal(mv.player, v))
+            .map(|v| usize::from(board.hash3x3_at(v)))
+            .collect();
+        hashes.sort_unstable();
+        let mut candidates: Vec<(Hash3x3, u32)> = Vec::new();
+        for raw in hashes {
+            match candidates.last_mut() {
+                Some((hash, cnt)) if usize::from(*hash) == raw => *cnt += 1,
+                _ => candidates.push((Hash3x3::from(raw), 1)),
+            }
+        }
+        let played = board.hash3x3_at(mv.vertex);
+        let chosen = candidates.iter().position(|&(hash, _)| hash == played)?;
+        Some(TrainingPosition {
+            player: mv.player,
+            candidates,
+            chosen,
+        })
+    }
+}
+
+// Self-play games of the Sampler with the gammas from an empty board, each
+// played to the end with a seed derived from seed and the game index. Calls
+// on_position for every non-pass move and returns the number of positions.
+pub fn self_play(
+    gammas: &Gammas,
+    width: usize,
+    height: usize,
+    game_cnt: usize,
+    seed: u32,
+    on_position: impl FnMut(TrainingPosition),
+) -> usize {
+    let params = SamplerParams::default();
+    self_play_with_params(gammas, &params, width, height, game_cnt, seed, on_position)
+}
+
+// self_play with the Sampler constants of params
+pub fn self_play_with_params(
+    gammas: &Gammas,
+    params: &SamplerParams,
+    width: usize,
+    height: usize,
+    game_cnt: usize,
+    seed: u32,
+    mut on_position: impl FnMut(TrainingPosition),
+) -> usize {
+    let empty = Board::with_size(width, height);
+    let mut board = empty.clone();
+    let mut sampler = Sampler::new(&board, gammas);
+    params.apply(&mut sampler);
+    let mut position_cnt = 0;
+    for game_idx in 0..game_cnt {
+        let mut random = FastRandom::new(playout_seed(seed, game_idx as u64));
+        board.load(&empty);
+        sampler.new_playout(&board, gammas);
+        while !board.both_player_pass() {
+            let player = board.act_player();
+            let v = sampler.sample_move(&board, &mut random);
+            if let Some(position) =
+                TrainingPosition::of_move(&board, Move::of_player_vertex(player, v))
+            {
+                on_position(position);
+                position_cnt += 1;
+            }
+            board.play_legal(player, v);
+            sampler.move_played(&board, gammas);
+        }
+    }
+    position_cnt
+}
+
+pub struct TrainingWriter<W: Write> {
+    out: W,
+    position_cnt: u64,
+}
+
+impl<W: Write> TrainingWriter<W> {
+    pub fn new(mut out: W) -> std::io::Result<Self> {
+        out.write_all(MAGIC)?;
+        Ok(TrainingWriter {
+            out,
+            position_cnt: 0,
+        })
+    }
+
+    pub fn write(&mut self, position: &TrainingPosition) -> std::io::Result<()> {
+        let mut bytes = vec![usize::from(position.player) as u8];
+        write_varint(&mut bytes, position.candidates.len() as u64);
+        write_varint(&mut bytes, position.chosen as u64);
+        let mut prev = 0;
+        for &(hash, cnt) in position.candidates.iter() {
+            write_varint(&mut bytes, (usize::from(hash) - prev) as u64);
+            write_varint(&mut bytes, cnt as u64);
+            prev = usize::from(hash);
+        }
+        self.out.write_all(&bytes)?;
+        self.position_cnt += 1;
+        Ok(())
+    }
+
+    pub fn position_count(&self) -> u64 {
+        self.position_cnt
+    }
+
+    // Completes the stream (flushes, and writes the gzip trailer of a .gz
+    // file) and returns the output
+    pub fn finish(mut self) -> std::io::Result<W>
+    where
+        W: FinishWrite,
+    {
+        self.out.finish_write()?;
+        Ok(self.out)
+    }
+}
+
+// Outputs that need a last step beyond flushing to be complete
+pub trait FinishWrite: Write {
+    fn finish_write(&mut self) -> std::io::Result<()>;
+}
+
+impl FinishWrite for Vec<u8> {
+    fn finish_write(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W: Write> FinishWrite for std::io::BufWriter<W> {
+    fn finish_write(&mut self) -> std::io::Result<()> {
+        self.flush()
+    }
+}
+
+// File written by TrainingWriter::create
+pub enum TrainingFile {
+    Plain(std::io::BufWriter<std::fs::File>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<std::io::BufWriter<std::fs::File>>),
+}
+
+impl Write for TrainingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            TrainingFile::Plain(file) => file.write(buf),
+            #[cfg(feature = "gzip")]
+            TrainingFile::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            TrainingFile::Plain(file) => file.flush(),
+            #[cfg(feature = "gzip")]
+            TrainingFile::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl FinishWrite for TrainingFile {
+    fn finish_write(&mut self) -> std::io::Result<()> {
+        match self {
+            TrainingFile::Plain(file) => file.flush(),
+            #[cfg(feature = "gzip")]
+            TrainingFile::Gzip(encoder) => {
+                encoder.try_finish()?;
+                encoder.get_mut().flush()
+            }
+        }
+    }
+}
+
+impl TrainingWriter<TrainingFile> {
+    // Writes to a file, gzip compressed if the name ends with ".gz", which
+    // needs the gzip feature
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let gzip = path.extension().is_some_and(|ext| ext == "gz");
+        if gzip && !cfg!(feature = "gzip") {
+            return Err("gzip output needs the gzip feature".to_string());
+        }
+        let write_error = |e: std::io::Error| format!("Failed to write {}: {}", path.display(), e);
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let file = std::io::BufWriter::new(file);
+        let out = if gzip {
+            gzip_writer(file).map_err(write_error)?
+        } else {
+            TrainingFile::Plain(file)
+        };
+        TrainingWriter::new(out).map_err(write_error)
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_writer(file: std::io::BufWriter<std::fs::File>) -> std::io::Result<TrainingFile> {
+    Ok(TrainingFile::Gzip(flate2::write::GzEncoder::new(
+        file,
+        flate2::Compression::default(),
+    )))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_writer(_file: std::io::BufWriter<std::fs::File>) -> std::io::Result<TrainingFile> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "gzip output needs the gzip feature",
+    ))
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_reader(file: std::fs::File) -> Result<Box<dyn Read>, String> {
+    Ok(Box::new(flate2::read::GzDecoder::new(file)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_reader(_file: std::fs::File) -> Result<Box<dyn Read>, String> {
+    Err("gzip input needs the gzip feature".to_string())
+}
+
+// Positions of a stream written by TrainingWriter
+pub struct TrainingReader<R: Read> {
+    input: std::io::Bytes<std::io::BufReader<R>>,
+}
+
+impl TrainingReader<Box<dyn Read>> {
+    // Reads a file written by TrainingWriter::create
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let input: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+            gzip_reader(file)?
+        } else {
+            Box::new(file)
+        };
+        TrainingReader::new(input).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    }
+}
+
+impl<R: Read> TrainingReader<R> {
+    pub fn new(mut input: R) -> std::io::Result<Self> {
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a training data stream"));
+        }
+        Ok(TrainingReader {
+            input: std::io::BufReader::new(input).bytes(),
+        })
+    }
+
+    fn read_position(&mut self, player_byte: u8) -> std::io::Result<TrainingPosition> {
+        let player = match player_byte {
+            0 => Player::Black,
+            1 => Player::White,
+            _ => return Err(invalid_data("bad player")),
+        };
+        let candidate_cnt = self.read_varint()? as usize;
+        let chosen = self.read_varint()? as usize;
+        if chosen >= candidate_cnt {
+            return Err(invalid_data("chosen candidate out of range"));
+        }
+        let mut candidates = Vec::with_capacity(candidate_cnt);
+        let mut raw = 0;
+        for _ in 0..candidate_cnt {
+            raw += self.read_varint()? as usize;
+            let cnt = self.read_varint()? as u32;
+            candidates.push((Hash3x3::from(raw), cnt));
+        }
+        Ok(TrainingPosition {
+            player,
+            candidates,
+            chosen,
+        })
+    }
+
+    fn read_varint(&mut self) -> std::io::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self
+                .input
+                .next()
+                .unwrap_or_else(|| Err(invalid_data("truncated position")))?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid_data("varint too long"))
+    }
+}
+
+impl<R: Read> Iterator for TrainingReader<R> {
+    type Item = std::io::Result<TrainingPosition>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.input.next()? {
+            Ok(player_byte) => Some(self.read_position(player_byte)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn invalid_data(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
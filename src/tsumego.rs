@@ -0,0 +1,173 @@
+use crate::board::Board;
+use crate::error::BoardError;
+use crate::hash::Hash;
+use crate::movegen::ordered_local_moves;
+use crate::types::{color_is_player, color_to_player, Color, Player, Vertex};
+use std::collections::HashMap;
+
+// Exhaustive life and death search with all moves restricted to a small region.
+// Killing means capturing the target chain within the search, so life is only
+// proven when the attacker runs out of moves in the region.
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TsumegoGoal {
+    // Capture the chain at the vertex
+    Kill(Vertex),
+    // Keep the chain at the vertex on the board
+    Live(Vertex),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TsumegoStatus {
+    // The goal is reached against every defence
+    Solved,
+    // The goal cannot be reached
+    Failed,
+    // The depth limit was hit before the search could decide
+    Unknown,
+}
+
+#[derive(Clone, Debug)]
+pub struct TsumegoResult {
+    pub status: TsumegoStatus,
+    // Principal variation starting with the player to move, passes included
+    pub pv: Vec<Vertex>,
+    pub node_cnt: usize,
+}
+
+// Solves for the goal with board.act_player() to move. The defender may pass, the
+// attacker may not. Positions repeating one on the current line are not played
+// (superko), the transposition table ignores such path dependence. Err if the
+// target of the goal is not a stone.
+pub fn solve(
+    board: &Board,
+    region: &[Vertex],
+    goal: TsumegoGoal,
+    max_depth: usize,
+) -> Result<TsumegoResult, BoardError> {
+    let (TsumegoGoal::Kill(target) | TsumegoGoal::Live(target)) = goal;
+    if !color_is_player(board.color_at(target)) {
+        return Err(BoardError::NoStone(target));
+    }
+    let defender = color_to_player(board.color_at(target));
+    let goal_player = match goal {
+        TsumegoGoal::Kill(_) => defender.opponent(),
+        TsumegoGoal::Live(_) => defender,
+    };
+
+    let mut solver = Solver {
+        region,
+        target,
+        defender,
+        max_depth,
+        tt: HashMap::new(),
+        path: vec![board.positional_hash()],
+        node_cnt: 0,
+    };
+    let (value, pv) = solver.search(board, 0);
+
+    let to_move_is_goal_player = board.act_player() == goal_player;
+    let status = match value {
+        Value::Unknown => TsumegoStatus::Unknown,
+        Value::Win if to_move_is_goal_player => TsumegoStatus::Solved,
+        Value::Loss if !to_move_is_goal_player => TsumegoStatus::Solved,
+        _ => TsumegoStatus::Failed,
+    };
+    Ok(TsumegoResult {
+        status,
+        pv,
+        node_cnt: solver.node_cnt,
+    })
+}
+
+// Result for the player to move
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Value {
+    Win,
+    Loss,
+    Unknown,
+}
+
+struct Solver<'a> {
+    region: &'a [Vertex],
+    target: Vertex,
+    defender: Player,
+    max_depth: usize,
+    // Keyed by position, player to move and ko vertex, proven values only
+    tt: HashMap<(Hash, usize, usize), bool>,
+    path: Vec<Hash>,
+    node_cnt: usize,
+}
+
+impl Solver<'_> {
+    fn search(&mut self, board: &Board, depth: usize) -> (Value, Vec<Vertex>) {
+        self.node_cnt += 1;
+        let pl = board.act_player();
+
+        if board.color_at(self.target) != Color::from(self.defender) {
+            let value = if pl == self.defender {
+                Value::Loss
+            } else {
+                Value::Win
+            };
+            return (value, Vec::new());
+        }
+
+        let key = (
+            board.positional_hash(),
+            usize::from(pl),
+            usize::from(board.ko_vertex()),
+        );
+        if let Some(&win) = self.tt.get(&key) {
+            return (if win { Value::Win } else { Value::Loss }, Vec::new());
+        }
+        if depth == self.max_depth {
+            return (Value::Unknown, Vec::new());
+        }
+
+        let mut moves = ordered_local_moves(board, self.region, pl, None);
+        if pl == self.defender {
+            moves.push(Vertex::pass());
+        }
+
+        let mut result = Value::Loss;
+        let mut pv = Vec::new();
+        for v in moves {
+            let mut child = board.clone();
+            child.play_legal(pl, v);
+            let hash = child.positional_hash();
+            if v != Vertex::pass() && self.path.contains(&hash) {
+                continue;
+            }
+
+            self.path.push(hash);
+            let (child_value, child_pv) = self.search(&child, depth + 1);
+            self.path.pop();
+
+            let line = || std::iter::once(v).chain(child_pv.iter().copied()).collect();
+            match child_value {
+                Value::Loss => {
+                    result = Value::Win;
+                    pv = line();
+                    break;
+                }
+                Value::Unknown => {
+                    if result == Value::Loss {
+                        result = Value::Unknown;
+                        pv = line();
+                    }
+                }
+                Value::Win => {
+                    if pv.is_empty() {
+                        pv = line();
+                    }
+                }
+            }
+        }
+
+        if result != Value::Unknown {
+            self.tt.insert(key, result == Value::Win);
+        }
+        (result, pv)
+    }
+}
@@ -0,0 +1,279 @@
+use crate::benchmark::{Benchmark, ComparisonResult};
+use crate::board::Board;
+use crate::fast_random::FastRandom;
+use crate::gammas::Gammas;
+use crate::policy::SamplerPolicy;
+use crate::sampler::Sampler;
+use std::path::Path;
+
+// Tuning of the Sampler constants that are not gammas. SamplerParams holds
+// them, PARAMS lists them by name with their ranges, and SpsaTuner improves
+// them by SPSA (simultaneous perturbation stochastic approximation) on the
+// win rate of playout policy games, see Benchmark::compare_with. The result
+// is saved as "name = value" lines, read by the gtp binary with --params.
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SamplerParams {
+    // See Sampler::set_proximity_bonus
+    pub proximity_direct: f64,
+    pub proximity_diagonal: f64,
+    // See Sampler::set_capture_bonus
+    pub capture_bonus: f64,
+    // See Sampler::set_pass_gamma
+    pub pass_gamma: f64,
+}
+
+// The values of a new Sampler
+impl Default for SamplerParams {
+    fn default() -> Self {
+        SamplerParams {
+            proximity_direct: 10.0,
+            proximity_diagonal: 10.0,
+            capture_bonus: 1.0,
+            pass_gamma: 0.0,
+        }
+    }
+}
+
+// A tunable field of SamplerParams
+pub struct Param {
+    pub name: &'static str,
+    pub min: f64,
+    pub max: f64,
+    // SPSA perturbation at the first iteration
+    pub step: f64,
+    get: fn(&SamplerParams) -> f64,
+    set: fn(&mut SamplerParams, f64),
+}
+
+impl Param {
+    pub fn value(&self, params: &SamplerParams) -> f64 {
+        (self.get)(params)
+    }
+
+    fn clamped(&self, value: f64) -> f64 {
+        value.clamp(self.min, self.max)
+    }
+}
+
+pub const PARAMS: [Param; 4] = [
+    Param {
+        name: "proximity_direct",
+        min: 0.5,
+        max: 50.0,
+        step: 2.0,
+        get: |p| p.proximity_direct,
+        set: |p, value| p.proximity_direct = value,
+    },
+    Param {
+        name: "proximity_diagonal",
+        min: 0.5,
+        max: 50.0,
+        step: 2.0,
+        get: |p| p.proximity_diagonal,
+        set: |p, value| p.proximity_diagonal = value,
+    },
+    Param {
+        name: "capture_bonus",
+        min: 0.5,
+        max: 50.0,
+        step: 2.0,
+        get: |p| p.capture_bonus,
+        set: |p, value| p.capture_bonus = value,
+    },
+    Param {
+        name: "pass_gamma",
+        min: 0.0,
+        max: 10.0,
+        step: 0.5,
+        get: |p| p.pass_gamma,
+        set: |p, value| p.pass_gamma = value,
+    },
+];
+
+pub fn find_param(name: &str) -> Option<&'static Param> {
+    PARAMS.iter().find(|param| param.name == name)
+}
+
+impl SamplerParams {
+    pub fn apply(&self, sampler: &mut Sampler) {
+        sampler.set_proximity_bonus(self.proximity_direct, self.proximity_diagonal);
+        sampler.set_capture_bonus(self.capture_bonus);
+        sampler.set_pass_gamma(self.pass_gamma);
+    }
+
+    pub fn policy<'a>(&self, board: &Board, gammas: &'a Gammas) -> SamplerPolicy<'a> {
+        let mut sampler = Sampler::new(board, gammas);
+        self.apply(&mut sampler);
+        SamplerPolicy::new(sampler, gammas)
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        find_param(name).map(|param| param.value(self))
+    }
+
+    // Err for unknown names and values outside the range of the parameter
+    pub fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        let param = find_param(name).ok_or_else(|| format!("unknown parameter {}", name))?;
+        if !(param.min..=param.max).contains(&value) {
+            return Err(format!(
+                "{} = {} outside [{}, {}]",
+                name, value, param.min, param.max
+            ));
+        }
+        (param.set)(self, value);
+        Ok(())
+    }
+
+    // One "name = value" line per parameter, in PARAMS order
+    pub fn to_text(&self) -> String {
+        PARAMS
+            .iter()
+            .map(|param| format!("{} = {}\n", param.name, param.value(self)))
+            .collect()
+    }
+
+    // Reads to_text lines, parameters not mentioned keep their defaults.
+    // Blank lines and lines starting with # are skipped.
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut params = SamplerParams::default();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line
+                .split_once('=')
+                .and_then(|(name, value)| Some((name.trim(), value.trim().parse::<f64>().ok()?)));
+            let Some((name, value)) = parsed else {
+                return Err(format!("line {}: expected name = value", line_idx + 1));
+            };
+            params
+                .set(name, value)
+                .map_err(|e| format!("line {}: {}", line_idx + 1, e))?;
+        }
+        Ok(params)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_text(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_text()).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+// Gains of the SPSA iterations: iteration k (from 0) perturbs every parameter
+// by c_k = step / (k + 1)^perturbation_decay and moves it by up to
+// learning_rate / (k + 1)^learning_rate_decay * c_k, the usual 0.101 and
+// 0.602 decays by default
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpsaConfig {
+    // Games between the two perturbed parameter sets per iteration, even so
+    // both play each color equally often
+    pub game_cnt: usize,
+    pub learning_rate: f64,
+    pub learning_rate_decay: f64,
+    pub perturbation_decay: f64,
+    pub seed: u32,
+}
+
+impl Default for SpsaConfig {
+    fn default() -> Self {
+        SpsaConfig {
+            game_cnt: 200,
+            learning_rate: 2.0,
+            learning_rate_decay: 0.602,
+            perturbation_decay: 0.101,
+            seed: 1,
+        }
+    }
+}
+
+// Each iteration plays params + delta against params - delta, delta a random
+// +-1 per parameter times its perturbation, and moves params towards the
+// winner in proportion to its win rate over 50%. An iteration without games
+// leaves params unchanged.
+pub struct SpsaTuner {
+    config: SpsaConfig,
+    params: SamplerParams,
+    random: FastRandom,
+    iteration: usize,
+}
+
+impl SpsaTuner {
+    pub fn new(start: SamplerParams, config: SpsaConfig) -> Self {
+        SpsaTuner {
+            config,
+            params: start,
+            random: FastRandom::new(config.seed),
+            iteration: 0,
+        }
+    }
+
+    pub fn params(&self) -> &SamplerParams {
+        &self.params
+    }
+
+    // Iterations done so far
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    // One iteration on the start position of bench with playouts on gammas.
+    // Returns the result of the plus side against the minus side.
+    pub fn step(&mut self, bench: &mut Benchmark, gammas: &Gammas) -> ComparisonResult {
+        let k = (self.iteration + 1) as f64;
+        let perturbation = 1.0 / k.powf(self.config.perturbation_decay);
+        let learning_rate = self.config.learning_rate / k.powf(self.config.learning_rate_decay);
+
+        let signs: Vec<f64> = PARAMS
+            .iter()
+            .map(|_| match self.random.get_next_uint() & 1 {
+                0 => -1.0,
+                _ => 1.0,
+            })
+            .collect();
+        let mut plus = self.params;
+        let mut minus = self.params;
+        for (param, sign) in PARAMS.iter().zip(&signs) {
+            let value = param.value(&self.params);
+            let delta = sign * param.step * perturbation;
+            (param.set)(&mut plus, param.clamped(value + delta));
+            (param.set)(&mut minus, param.clamped(value - delta));
+        }
+
+        let board = bench.start_board().clone();
+        let mut plus_policy = plus.policy(&board, gammas);
+        let mut minus_policy = minus.policy(&board, gammas);
+        let result = bench.compare_with(&mut plus_policy, &mut minus_policy, self.config.game_cnt);
+
+        // No games, no evidence for either side
+        let gain = result
+            .a_win_rate()
+            .map_or(0.0, |win_rate| 2.0 * win_rate - 1.0);
+        for (param, sign) in PARAMS.iter().zip(&signs) {
+            let value = param.value(&self.params);
+            let shift = learning_rate * gain * sign * param.step * perturbation;
+            (param.set)(&mut self.params, param.clamped(value + shift));
+        }
+        self.iteration += 1;
+        result
+    }
+
+    // iteration_cnt more steps, returning the tuned parameters
+    pub fn run(
+        &mut self,
+        bench: &mut Benchmark,
+        gammas: &Gammas,
+        iteration_cnt: usize,
+    ) -> SamplerParams {
+        for _ in 0..iteration_cnt {
+            self.step(bench, gammas);
+        }
+        self.params
+    }
+}
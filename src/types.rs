@@ -1,14 +1,40 @@
+use crate::error::BoardError;
 use crate::*;
-pub use go_game_types::{Color, Player, Vertex};
+#[cfg(not(feature = "large-boards"))]
+pub use go_game_types::Vertex;
+pub use go_game_types::{Color, Player};
 
+#[cfg(feature = "large-boards")]
+pub use crate::vertex::Vertex;
+
+#[cfg(not(feature = "large-boards"))]
 pub const MAX_BOARD_SIZE: usize = 19;
+// Largest board GTP coordinates can name, A to Z without I
+#[cfg(feature = "large-boards")]
+pub const MAX_BOARD_SIZE: usize = 25;
+
+// Size of Board::standard and of the GTP and SGF defaults, whatever
+// MAX_BOARD_SIZE is
+pub const DEFAULT_BOARD_SIZE: usize = 19;
+
+// Largest komi magnitude accepted, far beyond any board's score so that Score
+// arithmetic in half points stays in range
+pub const MAX_KOMI: f32 = 1000.0;
+
+pub fn check_komi(komi: f32) -> Result<(), BoardError> {
+    if komi.is_finite() && komi.abs() <= MAX_KOMI {
+        Ok(())
+    } else {
+        Err(BoardError::BadKomi(komi.to_string()))
+    }
+}
 
 // Base trait for natural number types
 pub trait Nat: Copy + Clone + Eq + PartialEq + From<usize> + Into<usize> {
     const COUNT: usize;
 
     fn all() -> impl Iterator<Item = Self> {
-        (0..Self::COUNT as usize).map(Self::from)
+        (0..Self::COUNT).map(Self::from)
     }
 }
 
@@ -26,8 +52,9 @@ impl Nat for Vertex {
 }
 
 // Direction - local type that stays
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub enum Dir {
+    #[default]
     N = 0,
     E = 1,
     S = 2,
@@ -38,12 +65,6 @@ pub enum Dir {
     SW = 7,
 }
 
-impl Default for Dir {
-    fn default() -> Self {
-        Dir::N
-    }
-}
-
 impl From<usize> for Dir {
     fn from(raw: usize) -> Self {
         match raw {
@@ -101,6 +122,34 @@ impl Dir {
         }
     }
 
+    // Direction after rotating the board 90 degrees clockwise
+    pub fn rotated(&self) -> Self {
+        match self {
+            Dir::N => Dir::E,
+            Dir::E => Dir::S,
+            Dir::S => Dir::W,
+            Dir::W => Dir::N,
+            Dir::NW => Dir::NE,
+            Dir::NE => Dir::SE,
+            Dir::SE => Dir::SW,
+            Dir::SW => Dir::NW,
+        }
+    }
+
+    // Direction after mirroring the board left-right
+    pub fn mirrored(&self) -> Self {
+        match self {
+            Dir::N => Dir::N,
+            Dir::E => Dir::W,
+            Dir::S => Dir::S,
+            Dir::W => Dir::E,
+            Dir::NW => Dir::NE,
+            Dir::NE => Dir::NW,
+            Dir::SE => Dir::SW,
+            Dir::SW => Dir::SE,
+        }
+    }
+
     pub fn proximity(&self) -> usize {
         // 0 for direct neighbors (N,E,S,W), 1 for diagonal neighbors
         if self.is_simple4() {
@@ -113,8 +162,11 @@ impl Dir {
 
 // Move - combines Player and Vertex
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::player"))]
     pub player: Player,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_impls::vertex"))]
     pub vertex: Vertex,
 }
 
@@ -141,11 +193,55 @@ impl From<Move> for usize {
     }
 }
 
+// SGF property notation, e.g. "B[dd]" or "W[]" for a pass
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let player = if self.player == Player::Black {
+            'B'
+        } else {
+            'W'
+        };
+        write!(f, "{}[{}]", player, vertex_to_sgf(self.vertex))
+    }
+}
+
 impl Nat for Move {
     // Move encoding: player | (vertex << 1)
     const COUNT: usize = Vertex::COUNT << 1;
 }
 
+// Vertex stored in 16 bits, for the per-vertex maps and lists of Board
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PackedVertex(u16);
+
+impl From<Vertex> for PackedVertex {
+    fn from(v: Vertex) -> Self {
+        PackedVertex(usize::from(v) as u16)
+    }
+}
+
+impl From<PackedVertex> for Vertex {
+    fn from(v: PackedVertex) -> Self {
+        Vertex::from(v.0 as usize)
+    }
+}
+
+impl From<usize> for PackedVertex {
+    fn from(raw: usize) -> Self {
+        PackedVertex(raw as u16)
+    }
+}
+
+impl From<PackedVertex> for usize {
+    fn from(v: PackedVertex) -> usize {
+        v.0 as usize
+    }
+}
+
+impl Nat for PackedVertex {
+    const COUNT: usize = Vertex::COUNT;
+}
+
 // Helper function for Vertex creation with full coordinates (including sentinels)
 pub fn vertex_of_coords_full(row: i32, column: i32) -> Vertex {
     assert!(row >= 0 && row < (MAX_BOARD_SIZE + 2) as i32);
@@ -154,7 +250,109 @@ pub fn vertex_of_coords_full(row: i32, column: i32) -> Vertex {
     Vertex::from_coords(row as isize - 1, column as isize - 1)
 }
 
+pub fn try_vertex_of_coords_full(row: i32, column: i32) -> Result<Vertex, BoardError> {
+    let range = 0..(MAX_BOARD_SIZE + 2) as i32;
+    if !range.contains(&row) || !range.contains(&column) {
+        return Err(BoardError::BadCoords { row, column });
+    }
+    Ok(vertex_of_coords_full(row, column))
+}
+
+// 0-based row (counted from the top) and column of a point of a board. Only
+// the checked constructors make one, so it is on the board it was made for,
+// unlike the sentinel arithmetic of vertex_of_coords_full.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Coord {
+    row: usize,
+    col: usize,
+}
+
+impl Coord {
+    pub fn new(row: usize, col: usize, width: usize, height: usize) -> Result<Self, BoardError> {
+        if row >= height || col >= width {
+            return Err(BoardError::BadCoords {
+                row: row as i32,
+                column: col as i32,
+            });
+        }
+        Ok(Coord { row, col })
+    }
+
+    // None for pass, Vertex::none() and points off the board
+    pub fn of_vertex(v: Vertex, width: usize, height: usize) -> Option<Self> {
+        if v == Vertex::pass() || v == Vertex::none() {
+            return None;
+        }
+        let (row, col) = (v.row(), v.column());
+        (row >= 0 && col >= 0 && (row as usize) < height && (col as usize) < width).then_some(
+            Coord {
+                row: row as usize,
+                col: col as usize,
+            },
+        )
+    }
+
+    pub fn row(self) -> usize {
+        self.row
+    }
+
+    pub fn col(self) -> usize {
+        self.col
+    }
+
+    pub fn to_vertex(self) -> Vertex {
+        Vertex::from_coords(self.row as isize, self.col as isize)
+    }
+
+    // 1 on the edge, 2 on the second line, ... of a width x height board
+    pub fn line(self, width: usize, height: usize) -> usize {
+        let edge_dist = self
+            .row
+            .min(self.col)
+            .min(height - 1 - self.row)
+            .min(width - 1 - self.col);
+        edge_dist + 1
+    }
+}
+
+impl From<Coord> for Vertex {
+    fn from(coord: Coord) -> Self {
+        coord.to_vertex()
+    }
+}
+
 // Helper function for Vertex navigation
+// Symmetries of a width x height board: bit 2 transposes (square boards
+// only), bit 0 mirrors rows and bit 1 columns
+pub fn board_symmetry_count(width: usize, height: usize) -> usize {
+    if width == height {
+        8
+    } else {
+        4
+    }
+}
+
+// Coordinates of the point at row, column under symmetry sym
+pub fn symmetric_coords(
+    sym: usize,
+    width: usize,
+    height: usize,
+    row: usize,
+    column: usize,
+) -> (usize, usize) {
+    let (mut r, mut c) = (row, column);
+    if sym & 4 != 0 {
+        (r, c) = (c, r);
+    }
+    if sym & 1 != 0 {
+        r = height - 1 - r;
+    }
+    if sym & 2 != 0 {
+        c = width - 1 - c;
+    }
+    (r, c)
+}
+
 pub fn vertex_nbr(v: Vertex, dir: Dir) -> Vertex {
     match dir {
         Dir::N => v.up(),
@@ -168,6 +366,38 @@ pub fn vertex_nbr(v: Vertex, dir: Dir) -> Vertex {
     }
 }
 
+// Neighbor iteration for code outside the board internals, which use the
+// for_each_4_nbr! macro; in release both unroll to the same code
+pub trait VertexNeighbors {
+    // N, E, S, W
+    fn neighbors4(self) -> std::array::IntoIter<Vertex, 4>;
+    // In Dir order: N, E, S, W, then NW, NE, SE, SW
+    fn neighbors8(self) -> std::array::IntoIter<Vertex, 8>;
+}
+
+impl VertexNeighbors for Vertex {
+    #[inline(always)]
+    fn neighbors4(self) -> std::array::IntoIter<Vertex, 4> {
+        [self.up(), self.right(), self.down(), self.left()].into_iter()
+    }
+
+    #[inline(always)]
+    fn neighbors8(self) -> std::array::IntoIter<Vertex, 8> {
+        let (up, down) = (self.up(), self.down());
+        [
+            up,
+            self.right(),
+            down,
+            self.left(),
+            up.left(),
+            up.right(),
+            down.right(),
+            down.left(),
+        ]
+        .into_iter()
+    }
+}
+
 // Helper functions for Color
 pub fn color_is_player(color: Color) -> bool {
     use std::convert::TryFrom;
@@ -179,6 +409,11 @@ pub fn color_to_player(color: Color) -> Player {
     Player::try_from(color).expect("Color is not a player color")
 }
 
+pub fn try_color_to_player(color: Color) -> Result<Player, BoardError> {
+    use std::convert::TryFrom;
+    Player::try_from(color).map_err(|_| BoardError::NotAPlayer(color))
+}
+
 pub fn color_to_showboard_char(color: Color) -> char {
     match color {
         Color::Black => '#',
@@ -188,6 +423,158 @@ pub fn color_to_showboard_char(color: Color) -> char {
     }
 }
 
+// GTP column letters, 'I' is skipped
+const GTP_COLUMNS: &[u8] = b"ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+// "D4" style coordinates: column letter, row number counted from the bottom
+pub fn vertex_to_gtp(v: Vertex, height: usize) -> String {
+    if v == Vertex::pass() {
+        return "PASS".to_string();
+    }
+    format!(
+        "{}{}",
+        GTP_COLUMNS[v.column() as usize] as char,
+        height as isize - v.row()
+    )
+}
+
+// Strict inverse of vertex_to_gtp ("pass" in any case is accepted)
+pub fn vertex_of_gtp(s: &str, width: usize, height: usize) -> Option<Vertex> {
+    if s.eq_ignore_ascii_case("pass") {
+        return Some(Vertex::pass());
+    }
+    let (&letter, digits) = s.as_bytes().split_first()?;
+    let column = GTP_COLUMNS.iter().position(|&c| c == letter)?;
+    if digits.is_empty() || digits[0] == b'0' || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let row_from_bottom: usize = std::str::from_utf8(digits).ok()?.parse().ok()?;
+    if column >= width || row_from_bottom > height {
+        return None;
+    }
+    Some(Vertex::from_coords(
+        (height - row_from_bottom) as isize,
+        column as isize,
+    ))
+}
+
+// Result of parsing human input, see parse_coord
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParsedCoord {
+    Vertex(Vertex),
+    Resign,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum CoordError {
+    #[error("empty coordinate")]
+    Empty,
+    // 'I' is not a GTP column
+    #[error("column I is skipped in GTP coordinates")]
+    ColumnI,
+    #[error("bad column '{0}'")]
+    BadColumn(String),
+    #[error("bad row '{0}'")]
+    BadRow(String),
+    #[error("column {column} is outside a board {width} wide")]
+    ColumnOutOfBoard { column: usize, width: usize },
+    #[error("row {row} is outside a board {height} high")]
+    RowOutOfBoard { row: usize, height: usize },
+}
+
+// Lenient vertex parser for console and GTP input. Accepts any case, surrounding
+// whitespace, "pass"/"resign" and numeric "column-row" pairs like "4-4". Rows and
+// numeric columns are 1-based, rows counted from the bottom as in GTP.
+pub fn parse_coord(s: &str, width: usize, height: usize) -> Result<ParsedCoord, CoordError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(CoordError::Empty);
+    }
+    if s.eq_ignore_ascii_case("pass") {
+        return Ok(ParsedCoord::Vertex(Vertex::pass()));
+    }
+    if s.eq_ignore_ascii_case("resign") {
+        return Ok(ParsedCoord::Resign);
+    }
+
+    let (column, row) = match s.split_once('-') {
+        Some((column, row)) => {
+            let column = parse_coord_number(column.trim())
+                .ok_or_else(|| CoordError::BadColumn(column.to_string()))?;
+            (column - 1, row.trim())
+        }
+        None => {
+            let letter = s.chars().next().unwrap().to_ascii_uppercase();
+            if letter == 'I' {
+                return Err(CoordError::ColumnI);
+            }
+            let column = GTP_COLUMNS
+                .iter()
+                .position(|&c| c as char == letter)
+                .ok_or_else(|| CoordError::BadColumn(letter.to_string()))?;
+            (column, s[letter.len_utf8()..].trim())
+        }
+    };
+    let row = parse_coord_number(row).ok_or_else(|| CoordError::BadRow(row.to_string()))?;
+
+    if column >= width {
+        return Err(CoordError::ColumnOutOfBoard {
+            column: column + 1,
+            width,
+        });
+    }
+    if row > height {
+        return Err(CoordError::RowOutOfBoard { row, height });
+    }
+    Ok(ParsedCoord::Vertex(Vertex::from_coords(
+        (height - row) as isize,
+        column as isize,
+    )))
+}
+
+// Positive decimal number without sign
+fn parse_coord_number(s: &str) -> Option<usize> {
+    if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok().filter(|&n| n > 0)
+}
+
+// "dd" style coordinates: column letter first, rows counted from the top, "" is pass
+pub fn vertex_to_sgf(v: Vertex) -> String {
+    if v == Vertex::pass() {
+        return String::new();
+    }
+    let letter = |coord: isize| (b'a' + coord as u8) as char;
+    format!("{}{}", letter(v.column()), letter(v.row()))
+}
+
+// Inverse of vertex_to_sgf, "tt" is also pass on boards up to 19x19
+pub fn vertex_of_sgf(s: &str, width: usize, height: usize) -> Option<Vertex> {
+    let s = s.trim();
+    if s.is_empty() || (s == "tt" && width <= 19 && height <= 19) {
+        return Some(Vertex::pass());
+    }
+    let bytes = s.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    let column = sgf_coord(bytes[0])?;
+    let row = sgf_coord(bytes[1])?;
+    if column >= width || row >= height {
+        return None;
+    }
+    Some(Vertex::from_coords(row as isize, column as isize))
+}
+
+fn sgf_coord(c: u8) -> Option<usize> {
+    match c {
+        b'a'..=b'z' => Some((c - b'a') as usize),
+        b'A'..=b'Z' => Some((c - b'A') as usize + 26),
+        _ => None,
+    }
+}
+
 // Type aliases for maps
 pub type PlayerMap<T> = nat_map::NatMap<{ Player::COUNT }, Player, T>;
 pub type VertexMap<T> = nat_map::NatMap<{ Vertex::COUNT }, Vertex, T>;
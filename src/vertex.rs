@@ -0,0 +1,74 @@
+use crate::types::MAX_BOARD_SIZE;
+
+// go_game_types::Vertex with room for MAX_BOARD_SIZE boards, used instead of it
+// with the large-boards feature. Same layout: row-major with one sentinel row
+// and column on each side, then pass and none.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Vertex(u32);
+
+impl Vertex {
+    pub const ROW_SIZE: usize = MAX_BOARD_SIZE + 2;
+    pub const COUNT_ON_BOARD: usize = Self::ROW_SIZE * Self::ROW_SIZE;
+    pub const COUNT: usize = Self::COUNT_ON_BOARD + 2;
+
+    pub fn from_coords(row: isize, column: isize) -> Self {
+        let index = (row + 1) * Self::ROW_SIZE as isize + column + 1;
+        assert!(
+            index >= 0 && index < Self::COUNT_ON_BOARD as isize,
+            "Coordinates ({}, {}) produce invalid index",
+            row,
+            column
+        );
+        Vertex(index as u32)
+    }
+
+    pub fn row(self) -> isize {
+        self.0 as isize / Self::ROW_SIZE as isize - 1
+    }
+
+    pub fn column(self) -> isize {
+        self.0 as isize % Self::ROW_SIZE as isize - 1
+    }
+
+    pub fn pass() -> Self {
+        Vertex(Self::COUNT_ON_BOARD as u32)
+    }
+
+    pub fn none() -> Self {
+        Vertex((Self::COUNT_ON_BOARD + 1) as u32)
+    }
+
+    pub fn up(self) -> Self {
+        Vertex(self.0 - Self::ROW_SIZE as u32)
+    }
+
+    pub fn down(self) -> Self {
+        Vertex(self.0 + Self::ROW_SIZE as u32)
+    }
+
+    pub fn left(self) -> Self {
+        Vertex(self.0 - 1)
+    }
+
+    pub fn right(self) -> Self {
+        Vertex(self.0 + 1)
+    }
+}
+
+impl From<usize> for Vertex {
+    fn from(value: usize) -> Self {
+        assert!(
+            value < Vertex::COUNT,
+            "Vertex value {} exceeds maximum {}",
+            value,
+            Vertex::COUNT - 1
+        );
+        Vertex(value as u32)
+    }
+}
+
+impl From<Vertex> for usize {
+    fn from(vertex: Vertex) -> Self {
+        vertex.0 as usize
+    }
+}
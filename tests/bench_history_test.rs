@@ -0,0 +1,43 @@
+use go_game_board::{BenchHistory, BenchRecord, Benchmark};
+
+fn record(config: &str, kpps: f64) -> BenchRecord {
+    BenchRecord {
+        timestamp: 1,
+        git_rev: "abc123".to_string(),
+        config: config.to_string(),
+        playout_cnt: 1000,
+        kpps,
+        cc_per_move: 100.0,
+    }
+}
+
+#[test]
+fn test_history_round_trip_and_regression_check() {
+    let path = std::env::temp_dir().join(format!("bench_history_{}.csv", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    assert!(BenchHistory::load(&path).unwrap().records.is_empty());
+    BenchHistory::append(&path, &record("a", 100.0)).unwrap();
+    BenchHistory::append(&path, &record("a", 120.0)).unwrap();
+    BenchHistory::append(&path, &record("b", 50.0)).unwrap();
+
+    let history = BenchHistory::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(history.records.len(), 3);
+    assert_eq!(history.records[1], record("a", 120.0));
+    assert_eq!(history.best("a").unwrap().kpps, 120.0);
+
+    assert!(history.check(&record("a", 115.0), 0.05).is_ok());
+    assert!(history.check(&record("a", 110.0), 0.05).is_err());
+    assert!(history.check(&record("c", 1.0), 0.05).is_ok());
+}
+
+#[test]
+fn test_run_stats() {
+    let stats = Benchmark::new().run_stats(1000);
+    assert_eq!(stats.playout_cnt, 1000);
+    assert_eq!(stats.black_win_cnt + stats.white_win_cnt, 1000);
+    let record = BenchRecord::new("test", &stats);
+    assert_eq!(record.playout_cnt, 1000);
+    assert!(record.kpps > 0.0);
+}
@@ -1,4 +1,9 @@
-use go_game_board::Benchmark;
+use go_game_board::alloc_counter::thread_alloc_cnt;
+use go_game_board::benchmark::{board_copy_cost, tsc_frequency_ghz};
+use go_game_board::{
+    BenchCheckpoint, BenchPolicy, Benchmark, Board, Color, FreqSource, Gammas, Player,
+    PolicyConfig, Spread, Vertex,
+};
 
 #[test]
 fn test_benchmark_10k() {
@@ -22,4 +27,205 @@ fn benchmark_performance() {
     println!("{}", bench.run(100000, None));
     println!("{}", bench.run(100000, None));
     println!("{}", bench.run(100000, None));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_compare_identical_policies() {
+    let gammas = Gammas::new();
+    let policy = PolicyConfig::new(&gammas);
+    let mut bench = Benchmark::new();
+    let result = bench.compare(&policy, &policy, 1000);
+    println!("{}", result);
+    assert_eq!(result.game_cnt, 1000);
+    let (low, high) = result.confidence_interval().unwrap();
+    assert!(low < 0.5 && 0.5 < high);
+}
+
+#[test]
+fn test_compare_without_games() {
+    let gammas = Gammas::new();
+    let policy = PolicyConfig::new(&gammas);
+    let result = Benchmark::new().compare(&policy, &policy, 0);
+    assert_eq!(result.game_cnt, 0);
+    assert_eq!(result.a_win_rate(), None);
+    assert_eq!(result.confidence_interval(), None);
+    assert_eq!(result.to_string(), "A won 0/0 games");
+}
+
+#[test]
+fn test_board_copy_cost() {
+    let (clone_ns, copy_from_ns) = board_copy_cost(10000);
+    println!(
+        "Board::clone {:.1} ns, Board::copy_from {:.1} ns",
+        clone_ns, copy_from_ns
+    );
+}
+
+#[test]
+fn test_checkpointed_run_resumes() {
+    let path = std::env::temp_dir().join(format!("bench_checkpoint_{}.txt", std::process::id()));
+    let mut bench = Benchmark::new();
+    let expected = bench.run_stats(10000);
+
+    let stats = bench.run_checkpointed(10000, &path, 3000, false).unwrap();
+    assert_eq!(stats.move_cnt, 1150865);
+    assert_eq!(stats.black_win_cnt, expected.black_win_cnt);
+    let done = BenchCheckpoint::load(&path).unwrap();
+    assert_eq!(done.done_cnt, 10000);
+
+    // A run interrupted after 4000 playouts
+    bench.run_checkpointed(4000, &path, 1500, false).unwrap();
+    let mut partial = BenchCheckpoint::load(&path).unwrap();
+    partial.playout_cnt = 10000;
+    partial.save(&path).unwrap();
+
+    let resumed = bench.run_checkpointed(10000, &path, 2500, true).unwrap();
+    assert_eq!(resumed.move_cnt, 1150865);
+    assert_eq!(resumed.black_win_cnt, expected.black_win_cnt);
+    assert_eq!(resumed.white_win_cnt, expected.white_win_cnt);
+    assert!(bench.run_checkpointed(500, &path, 100, true).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_run_repeated() {
+    let mut bench = Benchmark::new();
+    let stats = bench.run_repeated(3, 200);
+    assert_eq!(stats.runs.len(), 3);
+    let move_cnt = stats.runs[0].move_cnt;
+    assert!(stats.runs.iter().all(|run| run.move_cnt == move_cnt));
+    // 6.5 komi, no jigo
+    assert!(stats
+        .runs
+        .iter()
+        .all(|run| run.black_win_cnt + run.white_win_cnt == 200 && run.jigo_cnt == 0));
+    assert!(stats.kpps.min <= stats.kpps.median && stats.kpps.median <= stats.kpps.max);
+    assert!(stats.cc_per_move.stddev >= 0.0);
+    println!("{}", stats);
+}
+
+#[test]
+fn test_spread() {
+    let spread = Spread::of(&[4.0, 1.0, 3.0, 2.0]);
+    assert_eq!(spread.median, 2.5);
+    assert_eq!((spread.min, spread.max), (1.0, 4.0));
+    assert!((spread.stddev - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+    assert_eq!(Spread::of(&[7.0]).stddev, 0.0);
+}
+
+#[test]
+fn test_cpu_frequency() {
+    let stats = Benchmark::new().run_stats(10);
+    assert!(stats.cpu_freq_ghz > 0.0);
+    if let Some(ghz) = tsc_frequency_ghz() {
+        assert!((0.1..20.0).contains(&ghz), "TSC at {} GHz", ghz);
+        assert_eq!(stats.freq_source, FreqSource::Tsc);
+        assert_eq!(stats.cpu_freq_ghz, ghz);
+    }
+}
+
+#[test]
+fn test_playouts_do_not_allocate() {
+    let mut bench = Benchmark::new();
+    let stats = bench.run_stats(100);
+    match thread_alloc_cnt() {
+        Some(start_cnt) => {
+            assert_eq!(stats.allocs_per_playout, Some(0.0));
+            let v = std::hint::black_box(vec![0u8; 100]);
+            assert!(thread_alloc_cnt().unwrap() > start_cnt);
+            drop(v);
+        }
+        None => assert_eq!(stats.allocs_per_playout, None),
+    }
+}
+
+#[test]
+fn test_builder_defaults_match_new() {
+    let mut built = Benchmark::builder().build().unwrap();
+    assert_eq!(
+        built.run_stats(1000).move_cnt,
+        Benchmark::new().run_stats(1000).move_cnt
+    );
+
+    let mut other_seed = Benchmark::builder().seed(7).build().unwrap();
+    let stats = other_seed.run_stats(1000);
+    assert_ne!(stats.move_cnt, built.run_stats(1000).move_cnt);
+    assert_eq!(stats.move_cnt, other_seed.run_stats(1000).move_cnt);
+}
+
+#[test]
+fn test_builder_start_positions() {
+    let sgf = "(;GM[1]SZ[7]KM[0.5];B[dd];W[cc];B[dc];W[cd])";
+    let bench = Benchmark::builder().sgf(sgf).build().unwrap();
+    let board = bench.start_board();
+    assert_eq!((board.width(), board.height()), (7, 7));
+    assert_eq!(board.komi(), 0.5);
+    assert_eq!(board.act_player(), Player::Black);
+    assert_eq!(board.stone_count(Player::White), 2);
+
+    let ascii = "
+        . . . . .
+        . # O . .
+        . # O . .
+        . . . . .
+        . . . . .
+    ";
+    let mut bench = Benchmark::builder()
+        .ascii(ascii, Player::White)
+        .policy(BenchPolicy::Uniform)
+        .build()
+        .unwrap();
+    let board = bench.start_board();
+    assert_eq!((board.width(), board.height()), (5, 5));
+    assert_eq!(board.act_player(), Player::White);
+    assert_eq!(board.color_at(Vertex::from_coords(1, 2)), Color::White);
+    let stats = bench.run_stats(100);
+    assert_eq!(
+        stats.black_win_cnt + stats.white_win_cnt + stats.jigo_cnt,
+        100
+    );
+
+    let mut bench = Benchmark::builder()
+        .position(&Benchmark::new().start_board().clone())
+        .policy(BenchPolicy::Mogo)
+        .build()
+        .unwrap();
+    assert!(bench.run_stats(10).move_cnt > 0);
+
+    assert!(Benchmark::builder().sgf("(;SZ[7];B[zz])").build().is_err());
+    assert!(Benchmark::builder()
+        .ascii(". x\n. .", Player::Black)
+        .build()
+        .is_err());
+    assert!(Benchmark::builder().board_size(0, 9).build().is_err());
+}
+
+#[test]
+fn test_builder_gammas() {
+    let path = std::env::temp_dir().join(format!("bench_gammas_{}.txt", std::process::id()));
+    let gammas = Gammas::new();
+    gammas.save(&path).unwrap();
+    let bench = Benchmark::builder().gammas_file(&path).build().unwrap();
+    assert_eq!(bench.gammas().to_text(), gammas.to_text());
+    std::fs::remove_file(&path).unwrap();
+    assert!(Benchmark::builder().gammas_file(&path).build().is_err());
+
+    let text = gammas.to_text();
+    let bench = Benchmark::builder().gammas(gammas).build().unwrap();
+    assert_eq!(bench.gammas().to_text(), text);
+}
+
+#[test]
+fn test_run_from_keeps_start_position() {
+    let mut bench = Benchmark::new();
+    let mut start = Board::with_size(7, 7);
+    start.play_legal(Player::Black, Vertex::from_coords(3, 3));
+    start.set_komi(-100.0);
+    let stats = bench.run_from(&start, 100);
+    assert_eq!(stats.black_win_cnt, 100);
+    assert_eq!(bench.start_board().width(), 9);
+    assert_eq!(
+        bench.run_stats(1000).move_cnt,
+        Benchmark::new().run_stats(1000).move_cnt
+    );
+}
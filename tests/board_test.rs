@@ -0,0 +1,1156 @@
+use go_game_board::sgf;
+use go_game_board::{
+    color_is_player, Board, BoardError, BoardObserver, BoardPool, BoardStats, ChainHandle, Color,
+    FastRandom, Gammas, Move, Nat, Player, PlayerMap, Playouts, Sampler, Score, Vertex, VertexMap,
+    MAX_BOARD_SIZE,
+};
+
+#[test]
+fn test_estimate_score_of_finished_position() {
+    let mut board = Board::with_size(5, 5);
+    let center = Vertex::from_coords(2, 2);
+    board.play_legal(Player::Black, center);
+    board.play_legal(Player::White, Vertex::pass());
+    board.play_legal(Player::Black, Vertex::pass());
+
+    let gammas = Gammas::new();
+    let estimate = board
+        .estimate_score(&gammas, 10, &mut FastRandom::new(1))
+        .unwrap();
+    assert_eq!(estimate.mean, 1.0 - 6.5);
+    assert_eq!(estimate.stddev, 0.0);
+    assert_eq!(estimate.ownership[center], 1.0);
+    assert_eq!(estimate.ownership[Vertex::from_coords(0, 0)], 0.0);
+
+    assert!(board
+        .estimate_score(&gammas, 0, &mut FastRandom::new(1))
+        .is_none());
+}
+
+#[test]
+fn test_estimate_score_of_open_position() {
+    let board = Board::with_size(5, 5);
+    let gammas = Gammas::new();
+    let estimate = board
+        .estimate_score(&gammas, 200, &mut FastRandom::new(1))
+        .unwrap();
+    assert!(estimate.stddev > 0.0);
+    assert!(estimate.mean.abs() <= 25.0 + 6.5);
+    for row in 0..5 {
+        for col in 0..5 {
+            let own = estimate.ownership[Vertex::from_coords(row, col)];
+            assert!((-1.0..=1.0).contains(&own));
+        }
+    }
+}
+
+#[test]
+fn test_stones_match_board_after_captures() {
+    let gammas = Gammas::new();
+    let mut playouts = Playouts::new(&gammas, 5);
+    for _ in 0..20 {
+        playouts.run(&Board::new(), &gammas);
+        let board = playouts.board();
+        for pl in Player::all() {
+            let mut stones: Vec<usize> = board.stones(pl).map(usize::from).collect();
+            let mut expected: Vec<usize> = Vertex::all()
+                .filter(|&v| board.color_at(v) == Color::from(pl))
+                .map(usize::from)
+                .collect();
+            stones.sort();
+            expected.sort();
+            assert_eq!(stones, expected);
+        }
+    }
+}
+
+#[test]
+fn test_setup_matches_played_position() {
+    let black = [(0, 1), (1, 0), (2, 2), (2, 3)];
+    let white = [(0, 2), (1, 1), (3, 3)];
+    let to_vertices = |coords: &[(isize, isize)]| -> Vec<Vertex> {
+        coords
+            .iter()
+            .map(|&(r, c)| Vertex::from_coords(r, c))
+            .collect()
+    };
+
+    let mut played = Board::with_size(5, 5);
+    for ii in 0..black.len() {
+        played.play_legal(Player::Black, to_vertices(&black)[ii]);
+        let white_v = to_vertices(&white)
+            .get(ii)
+            .copied()
+            .unwrap_or(Vertex::pass());
+        played.play_legal(Player::White, white_v);
+    }
+
+    let mut setup = Board::with_size(5, 5);
+    setup.setup(Player::Black, &to_vertices(&black)).unwrap();
+    setup.setup(Player::White, &to_vertices(&white)).unwrap();
+    assert_eq!(setup.act_player(), Player::Black);
+    assert_eq!(setup.move_count(), 0);
+    assert_eq!(setup.positional_hash(), played.positional_hash());
+    for v in Vertex::all() {
+        assert_eq!(setup.hash3x3_at(v), played.hash3x3_at(v));
+    }
+
+    // White stone at (1, 1) is in atari and can be captured
+    setup.play_legal(Player::Black, Vertex::from_coords(2, 1));
+    setup.play_legal(Player::White, Vertex::pass());
+    setup.play_legal(Player::Black, Vertex::from_coords(1, 2));
+    assert_eq!(setup.color_at(Vertex::from_coords(1, 1)), Color::Empty);
+
+    setup.remove_stone(Vertex::from_coords(2, 2)).unwrap();
+    assert_eq!(setup.color_at(Vertex::from_coords(2, 2)), Color::Empty);
+    assert!(setup.remove_stone(Vertex::from_coords(2, 2)).is_err());
+}
+
+#[test]
+fn test_setup_rejects_chains_without_liberties() {
+    let mut board = Board::with_size(5, 5);
+    board
+        .setup(
+            Player::Black,
+            &[Vertex::from_coords(0, 1), Vertex::from_coords(1, 0)],
+        )
+        .unwrap();
+    let hash = board.positional_hash();
+    assert!(board
+        .setup(Player::White, &[Vertex::from_coords(0, 0)])
+        .is_err());
+    assert_eq!(board.positional_hash(), hash);
+    assert!(board.setup(Player::White, &[Vertex::pass()]).is_err());
+}
+
+#[test]
+fn test_setup_keeps_prisoners() {
+    let mut board = Board::with_size(5, 5);
+    let v = |row, col| Vertex::from_coords(row, col);
+    board.setup(Player::White, &[v(0, 0)]).unwrap();
+    board.try_play(Player::Black, v(0, 1)).unwrap();
+    board.try_play(Player::White, v(3, 3)).unwrap();
+    board.try_play(Player::Black, v(1, 0)).unwrap();
+    assert_eq!(board.prisoners(Player::Black), 1);
+
+    board.setup(Player::White, &[v(4, 0)]).unwrap();
+    assert_eq!(board.prisoners(Player::Black), 1);
+    assert_eq!(board.prisoners(Player::White), 0);
+}
+
+#[test]
+fn test_try_play_reports_illegal_moves() {
+    assert_eq!(
+        Board::try_with_size(0, 9).err(),
+        Some(BoardError::BadSize {
+            width: 0,
+            height: 9
+        })
+    );
+
+    let mut board = Board::try_with_size(3, 3).unwrap();
+    let black = Vertex::from_coords(0, 1);
+    board.try_play(Player::Black, black).unwrap();
+    assert_eq!(
+        board.try_play(Player::White, black),
+        Err(BoardError::Occupied(black))
+    );
+    let off_board = Vertex::from_coords(3, 0);
+    assert_eq!(
+        board.try_play(Player::White, off_board),
+        Err(BoardError::OffBoard(off_board))
+    );
+
+    board
+        .try_play(Player::White, Vertex::from_coords(2, 2))
+        .unwrap();
+    board
+        .try_play(Player::Black, Vertex::from_coords(1, 0))
+        .unwrap();
+    let suicide = Vertex::from_coords(0, 0);
+    assert_eq!(
+        board.try_play(Player::White, suicide),
+        Err(BoardError::Suicide(suicide))
+    );
+    assert_eq!(board.try_play(Player::White, Vertex::pass()), Ok(()));
+    assert_eq!(board.move_count(), 4);
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let mut board = Board::with_size(5, 4);
+    board.set_komi(0.5);
+    for (pl, row, col) in [
+        (Player::Black, 0, 1),
+        (Player::White, 0, 2),
+        (Player::Black, 1, 0),
+        (Player::White, 1, 3),
+        (Player::Black, 2, 2),
+        (Player::White, 3, 4),
+    ] {
+        board.play_legal(pl, Vertex::from_coords(row, col));
+    }
+
+    let snapshot = board.snapshot();
+    assert_eq!(
+        snapshot.rows,
+        [".#O..", "#..O.", "..#..", "....O"].map(String::from)
+    );
+    let restored = Board::from_snapshot(&snapshot).unwrap();
+    assert_eq!(restored.snapshot(), snapshot);
+    assert_eq!(restored.act_player(), Player::Black);
+    assert_eq!(restored.positional_hash(), board.positional_hash());
+
+    let mut bad = snapshot.clone();
+    bad.rows[3] = "..x..".to_string();
+    assert!(Board::from_snapshot(&bad).is_err());
+}
+
+#[test]
+fn test_board_pool_recycles_boards() {
+    let mut board = Board::with_size(9, 9);
+    board.play_legal(Player::Black, Vertex::from_coords(4, 4));
+
+    let mut pool = BoardPool::new();
+    let first = pool.acquire(&board);
+    assert_eq!(first.snapshot(), board.snapshot());
+    pool.release(first);
+    assert_eq!(pool.free_count(), 1);
+
+    board.play_legal(Player::White, Vertex::from_coords(3, 4));
+    let mut second = pool.acquire(&board);
+    assert_eq!(pool.free_count(), 0);
+    assert_eq!(second.snapshot(), board.snapshot());
+    assert_eq!(second.positional_hash(), board.positional_hash());
+
+    second.play_legal(Player::Black, Vertex::from_coords(3, 3));
+    board.play_legal(Player::Black, Vertex::from_coords(3, 3));
+    assert_eq!(second.positional_hash(), board.positional_hash());
+    assert_eq!(
+        second.hash3x3_changed_count(),
+        board.hash3x3_changed_count()
+    );
+}
+
+#[test]
+fn test_board_memory_footprint() {
+    // 27992 bytes with 32-bit vertices in the maps and lists, 21240 with
+    // chain data per vertex instead of in an arena
+    let size = std::mem::size_of::<Board>();
+    println!("Board: {} bytes", size);
+    #[cfg(not(feature = "large-boards"))]
+    assert!(size <= 21_000, "Board grew to {} bytes", size);
+    // 729 instead of 441 entries per vertex map
+    #[cfg(feature = "large-boards")]
+    assert!(size <= 36_000, "Board grew to {} bytes", size);
+    #[cfg(feature = "boxed-board")]
+    assert!(size <= 1_000, "Boxed board grew to {} bytes", size);
+    assert_eq!(std::mem::size_of::<go_game_board::board::Chain>(), 16);
+}
+
+#[test]
+fn test_new_boxed_in_deep_recursion() {
+    // One position per frame, on the heap
+    fn descend(board: &Board, depth: usize) -> usize {
+        if depth == 0 || board.both_player_pass() {
+            return board.move_count();
+        }
+        let mut next = Board::new_boxed(board.width(), board.height());
+        next.copy_from(board);
+        let v = next.empties().next().unwrap_or(Vertex::pass());
+        let v = if next.is_legal(next.act_player(), v) {
+            v
+        } else {
+            Vertex::pass()
+        };
+        next.play_legal(next.act_player(), v);
+        descend(&next, depth - 1)
+    }
+    let board = Board::new_boxed(9, 9);
+    assert_eq!(board.width(), 9);
+    assert!(descend(&board, 2000) > 80);
+}
+
+// Mirrors the board from the notifications alone
+struct MirrorObserver {
+    colors: VertexMap<Color>,
+    ko_v: Vertex,
+    captured_cnt: usize,
+    move_cnt: usize,
+}
+
+impl BoardObserver for MirrorObserver {
+    fn on_stone_placed(&mut self, player: Player, v: Vertex) {
+        self.colors[v] = Color::from(player);
+    }
+
+    fn on_chain_captured(&mut self, player: Player, stones: &[Vertex]) {
+        for &v in stones {
+            assert_eq!(self.colors[v], Color::from(player));
+            self.colors[v] = Color::Empty;
+        }
+        self.captured_cnt += stones.len();
+    }
+
+    fn on_ko_set(&mut self, ko_v: Vertex) {
+        self.ko_v = ko_v;
+    }
+
+    fn on_move_played(&mut self, _player: Player, _v: Vertex) {
+        self.move_cnt += 1;
+    }
+}
+
+#[test]
+fn test_observer_mirrors_playouts() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(5);
+    let mut ko_cnt = 0;
+    for _ in 0..20 {
+        let mut board = Board::with_size(7, 7);
+        let mut observer = MirrorObserver {
+            colors: VertexMap::new_with(Color::Empty),
+            ko_v: Vertex::none(),
+            captured_cnt: 0,
+            move_cnt: 0,
+        };
+        let mut sampler = Sampler::new(&board, &gammas);
+        sampler.new_playout(&board, &gammas);
+        while !board.both_player_pass() {
+            let v = sampler.sample_move(&board, &mut random);
+            board.play_legal_observed(board.act_player(), v, &mut observer);
+            sampler.move_played(&board, &gammas);
+            assert_eq!(observer.ko_v, board.ko_vertex());
+            ko_cnt += (board.ko_vertex() != Vertex::none()) as usize;
+        }
+        for v in Vertex::all() {
+            if board.color_at(v) != Color::OffBoard {
+                assert_eq!(observer.colors[v], board.color_at(v));
+            }
+        }
+        assert!(observer.captured_cnt > 0);
+        assert_eq!(observer.move_cnt, board.move_count());
+    }
+    assert!(ko_cnt > 0);
+}
+
+#[test]
+fn test_changed_vertices_cover_pattern_changes() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(11);
+    for _ in 0..10 {
+        let mut board = Board::with_size(9, 9);
+        let mut sampler = Sampler::new(&board, &gammas);
+        sampler.new_playout(&board, &gammas);
+        while !board.both_player_pass() {
+            let before = board.clone();
+            let v = sampler.sample_move(&board, &mut random);
+            board.play_legal(board.act_player(), v);
+            sampler.move_played(&board, &gammas);
+
+            let changed: Vec<Vertex> = board.changed_vertices().collect();
+            let mut seen = VertexMap::new_with(false);
+            for &c in changed.iter() {
+                assert!(!seen[c], "duplicate");
+                seen[c] = true;
+                assert_eq!(board.color_at(c), Color::Empty);
+            }
+            for u in Vertex::all() {
+                if board.color_at(u) == Color::Empty && board.hash3x3_at(u) != before.hash3x3_at(u)
+                {
+                    assert!(seen[u]);
+                }
+                if before.color_at(u) != Color::Empty && board.color_at(u) == Color::Empty {
+                    assert!(seen[u], "captured point missing");
+                }
+            }
+
+            let touched: Vec<Vertex> = board.touched_vertices().collect();
+            if v == Vertex::pass() {
+                assert!(changed.is_empty());
+                assert!(touched.is_empty());
+            } else {
+                assert_eq!(touched[0], v);
+                assert_eq!(&touched[1..], &changed[..]);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hash_after_matches_played_position() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(13);
+    let mut capture_cnt = 0;
+    for _ in 0..10 {
+        let mut board = Board::with_size(9, 9);
+        let mut sampler = Sampler::new(&board, &gammas);
+        sampler.new_playout(&board, &gammas);
+        while !board.both_player_pass() {
+            let v = sampler.sample_move(&board, &mut random);
+            let player = board.act_player();
+            let hash = board.hash_after(player, v);
+            let empty_cnt = board.empty_vertex_count();
+            board.play_legal(player, v);
+            sampler.move_played(&board, &gammas);
+            assert_eq!(hash, board.positional_hash());
+            capture_cnt += (board.empty_vertex_count() > empty_cnt) as usize;
+        }
+    }
+    assert!(capture_cnt > 0);
+}
+
+#[test]
+fn test_stone_counts_and_prisoners() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(17);
+    let mut board = Board::with_size(9, 9);
+    let mut placed = PlayerMap::new_with(0);
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.new_playout(&board, &gammas);
+    while !board.both_player_pass() {
+        let player = board.act_player();
+        let v = sampler.sample_move(&board, &mut random);
+        board.play_legal(player, v);
+        sampler.move_played(&board, &gammas);
+        placed[player] += (v != Vertex::pass()) as usize;
+        for pl in [Player::Black, Player::White] {
+            assert_eq!(board.stone_count(pl), board.stones(pl).count());
+            assert_eq!(
+                board.prisoners(pl.opponent()),
+                placed[pl] - board.stone_count(pl)
+            );
+        }
+    }
+    assert!(board.prisoners(Player::Black) + board.prisoners(Player::White) > 0);
+
+    board.clear();
+    assert_eq!(board.prisoners(Player::Black), 0);
+    assert_eq!(board.stone_count(Player::White), 0);
+}
+
+#[test]
+fn test_eye_score_map_explains_playout_score() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(19);
+    let mut board = Board::with_size(9, 9);
+    board.set_komi(0.0);
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.new_playout(&board, &gammas);
+    while !board.both_player_pass() {
+        let v = sampler.sample_move(&board, &mut random);
+        board.play_legal(board.act_player(), v);
+        sampler.move_played(&board, &gammas);
+    }
+    let eye_map = board.eye_score_map();
+    let eye_sum: i32 = Vertex::all().map(|v| eye_map[v]).sum();
+    let stones = board.stone_count(Player::Black) as i32 - board.stone_count(Player::White) as i32;
+    assert_eq!(
+        board.exact_playout_score(),
+        Score::of_points(stones + eye_sum)
+    );
+    assert_ne!(eye_sum, 0);
+
+    // Large territories are not eyes, so only Tromp-Taylor counts them
+    let mut board = Board::with_size(5, 5);
+    let wall = |col| (0..5).map(move |row| Vertex::from_coords(row, col));
+    board
+        .setup(Player::Black, &wall(2).collect::<Vec<_>>())
+        .unwrap();
+    board
+        .setup(Player::White, &wall(3).collect::<Vec<_>>())
+        .unwrap();
+    let eye_map = board.eye_score_map();
+    assert!(Vertex::all().all(|v| eye_map[v] == 0));
+    assert_eq!(
+        board.exact_playout_score(),
+        Score::ZERO - Score::of_f32(6.5)
+    );
+    assert_eq!(board.area_score(), 15 - 10);
+}
+
+#[test]
+fn test_last_move_context_hash() {
+    let v = |row, col| Vertex::from_coords(row, col);
+    let mut board = Board::with_size(9, 9);
+    assert_eq!(board.last_move_context_hash().as_u64(), 0);
+
+    // The same shape in two places
+    board.play_legal(Player::Black, v(2, 2));
+    board.play_legal(Player::White, v(2, 3));
+    let first = board.last_move_context_hash();
+    let mut shifted = Board::with_size(9, 9);
+    shifted.play_legal(Player::Black, v(5, 4));
+    shifted.play_legal(Player::White, v(5, 5));
+    assert_eq!(shifted.last_move_context_hash(), first);
+
+    // A stone two points away counts, three points away not
+    let mut board = Board::with_size(9, 9);
+    board.play_legal(Player::Black, v(5, 4));
+    board.play_legal(Player::Black, v(7, 7));
+    board.play_legal(Player::White, v(5, 5));
+    let near = board.last_move_context_hash();
+    assert_ne!(near, first);
+    let mut board = Board::with_size(9, 9);
+    board.play_legal(Player::Black, v(5, 4));
+    board.play_legal(Player::Black, v(8, 8));
+    board.play_legal(Player::White, v(5, 5));
+    assert_eq!(board.last_move_context_hash(), first);
+
+    // The edge is part of the context
+    let mut edge = Board::with_size(9, 9);
+    edge.play_legal(Player::Black, v(0, 2));
+    edge.play_legal(Player::White, v(0, 3));
+    assert_ne!(edge.last_move_context_hash(), first);
+
+    edge.play_legal(Player::Black, Vertex::pass());
+    assert_eq!(edge.last_move_context_hash().as_u64(), 0);
+}
+
+#[test]
+fn test_legal_mask_matches_is_legal() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(23);
+    for _ in 0..5 {
+        let mut board = Board::with_size(9, 9);
+        let mut sampler = Sampler::new(&board, &gammas);
+        sampler.new_playout(&board, &gammas);
+        while !board.both_player_pass() {
+            for pl in [Player::Black, Player::White] {
+                let mask = board.legal_mask(pl);
+                for v in Vertex::all() {
+                    let legal = board.is_legal(pl, v) && v != Vertex::pass();
+                    assert_eq!(mask[v], legal, "{:?} {:?}", pl, v);
+                }
+            }
+            let v = sampler.sample_move(&board, &mut random);
+            board.play_legal(board.act_player(), v);
+            sampler.move_played(&board, &gammas);
+        }
+    }
+}
+
+#[test]
+fn test_board_stats() {
+    let mut board = Board::with_size(5, 5);
+    let moves = [
+        (Player::Black, (1, 0)),
+        (Player::White, (0, 2)),
+        (Player::Black, (0, 1)),
+        (Player::White, (2, 2)),
+        (Player::Black, (2, 1)),
+        (Player::White, (1, 3)),
+        (Player::Black, (4, 4)),
+        (Player::White, (1, 1)),
+        (Player::Black, (1, 2)),
+    ];
+    for (player, (row, col)) in moves {
+        board
+            .try_play(player, Vertex::from_coords(row, col))
+            .unwrap();
+    }
+    assert_eq!(
+        board.stats(),
+        BoardStats {
+            black_stones: 5,
+            white_stones: 3,
+            empty: 17,
+            move_no: board.move_count(),
+            black_prisoners: 1,
+            white_prisoners: 0,
+            ko: Vertex::from_coords(1, 1),
+        }
+    );
+
+    board.clear();
+    let stats = board.stats();
+    assert_eq!((stats.black_stones, stats.empty), (0, 25));
+    assert_eq!(stats.ko, Vertex::none());
+}
+
+#[test]
+fn test_chain_handles_survive_merges() {
+    let mut board = Board::with_size(5, 5);
+    let v = |row, col| Vertex::from_coords(row, col);
+    board.try_play(Player::Black, v(2, 1)).unwrap();
+    board.try_play(Player::White, v(0, 0)).unwrap();
+    board.try_play(Player::Black, v(2, 3)).unwrap();
+    let left = board.chain_handle(v(2, 1)).unwrap();
+    let right = board.chain_handle(v(2, 3)).unwrap();
+    assert_ne!(left, right);
+    assert_eq!(board.chain_handle(v(1, 1)), None);
+
+    // Connecting makes both handles name the merged chain
+    board.try_play(Player::White, v(4, 4)).unwrap();
+    board.try_play(Player::Black, v(2, 2)).unwrap();
+    let merged = board.chain_handle(v(2, 2)).unwrap();
+    assert_eq!(board.resolve_chain(left), Some(merged));
+    assert_eq!(board.resolve_chain(right), Some(merged));
+    assert!([v(2, 1), v(2, 3)].contains(&merged.stone()));
+
+    // A captured stone's handle stays dead after the point is played again
+    let corner: ChainHandle = board.chain_handle(v(0, 0)).unwrap();
+    board.try_play(Player::White, v(4, 0)).unwrap();
+    board.try_play(Player::Black, v(0, 1)).unwrap();
+    board.try_play(Player::White, v(4, 1)).unwrap();
+    board.try_play(Player::Black, v(1, 0)).unwrap();
+    assert_eq!(board.color_at(v(0, 0)), Color::Empty);
+    assert_eq!(board.resolve_chain(corner), None);
+    board.setup(Player::White, &[v(0, 0)]).unwrap_err();
+    board.remove_stone(v(0, 1)).unwrap();
+    board.setup(Player::White, &[v(0, 0)]).unwrap();
+    assert_eq!(board.resolve_chain(corner), None);
+    assert_eq!(board.resolve_chain(left), board.chain_handle(v(2, 2)));
+}
+
+#[test]
+fn test_atari_escape_vertex_follows_merges_and_captures() {
+    let mut board = Board::with_size(5, 5);
+    let v = |row, col| Vertex::from_coords(row, col);
+    board.try_play(Player::Black, v(0, 0)).unwrap();
+    let corner = board.chain_handle(v(0, 0)).unwrap();
+    assert_eq!(board.atari_escape_vertex(corner), None);
+    board.try_play(Player::White, v(1, 0)).unwrap();
+    assert_eq!(board.atari_escape_vertex(corner), Some(v(0, 1)));
+
+    // Merging out of atari, then into it again with the old handle
+    board.try_play(Player::Black, v(0, 2)).unwrap();
+    board.try_play(Player::White, v(1, 1)).unwrap();
+    board.try_play(Player::Black, v(0, 1)).unwrap();
+    assert_eq!(board.atari_escape_vertex(corner), None);
+    board.try_play(Player::White, v(1, 2)).unwrap();
+    assert_eq!(board.atari_escape_vertex(corner), Some(v(0, 3)));
+    board.try_play(Player::Black, v(0, 3)).unwrap();
+    assert_eq!(board.atari_escape_vertex(corner), None);
+    board.try_play(Player::White, v(1, 3)).unwrap();
+    assert_eq!(board.atari_escape_vertex(corner), Some(v(0, 4)));
+    board.try_play(Player::Black, v(4, 4)).unwrap();
+    board.try_play(Player::White, v(0, 4)).unwrap();
+    assert_eq!(board.color_at(v(0, 0)), Color::Empty);
+    assert_eq!(board.atari_escape_vertex(corner), None);
+}
+
+#[test]
+fn test_atari_escape_vertex_after_ko_captures() {
+    let mut board = Board::with_size(5, 5);
+    let v = |row, col| Vertex::from_coords(row, col);
+    board
+        .setup(Player::Black, &[v(1, 0), v(0, 1), v(2, 1)])
+        .unwrap();
+    board
+        .setup(Player::White, &[v(0, 2), v(2, 2), v(1, 3)])
+        .unwrap();
+    board.try_play(Player::White, v(1, 1)).unwrap();
+    let white = board.chain_handle(v(1, 1)).unwrap();
+    assert_eq!(board.atari_escape_vertex(white), Some(v(1, 2)));
+
+    board.try_play(Player::Black, v(1, 2)).unwrap();
+    let black = board.chain_handle(v(1, 2)).unwrap();
+    assert_eq!(board.atari_escape_vertex(white), None);
+    assert_eq!(board.atari_escape_vertex(black), Some(v(1, 1)));
+
+    // Capture back after a ko threat exchange
+    board.try_play(Player::White, v(4, 4)).unwrap();
+    board.try_play(Player::Black, v(4, 3)).unwrap();
+    board.try_play(Player::White, v(1, 1)).unwrap();
+    let white = board.chain_handle(v(1, 1)).unwrap();
+    assert_eq!(board.atari_escape_vertex(black), None);
+    assert_eq!(board.atari_escape_vertex(white), Some(v(1, 2)));
+    assert_eq!(
+        board.chain_at(v(1, 1)).unwrap().atari_vertex(),
+        Some(v(1, 2))
+    );
+    assert_eq!(
+        board.chain_at(v(0, 1)).unwrap().atari_vertex(),
+        Some(v(0, 0))
+    );
+    assert_eq!(board.chain_at(v(1, 0)).unwrap().atari_vertex(), None);
+}
+
+#[test]
+fn test_large_chain_merges() {
+    // Two 18 stone lines joined by one stone, then a capture of the whole chain
+    let mut board = Board::with_size(19, 19);
+    for col in 0..18 {
+        board
+            .try_play(Player::Black, Vertex::from_coords(0, col))
+            .unwrap();
+        board
+            .try_play(Player::Black, Vertex::from_coords(2, col))
+            .unwrap();
+    }
+    board
+        .try_play(Player::Black, Vertex::from_coords(1, 0))
+        .unwrap();
+    let handle = board.chain_handle(Vertex::from_coords(0, 0)).unwrap();
+    for col in 0..18 {
+        for row in [0, 2] {
+            let v = Vertex::from_coords(row, col);
+            assert_eq!(board.chain_handle(v), Some(handle));
+        }
+    }
+    let chain = board.chain_at(Vertex::from_coords(2, 17)).unwrap();
+    assert_eq!(chain.size, 37);
+    assert_eq!(board.chain_stones(Vertex::from_coords(2, 5)).count(), 37);
+
+    let libs: Vec<Vertex> = (1..18)
+        .map(|col| Vertex::from_coords(1, col))
+        .chain((0..18).map(|col| Vertex::from_coords(3, col)))
+        .chain([0, 2].map(|row| Vertex::from_coords(row, 18)))
+        .collect();
+    for &v in &libs {
+        board.try_play(Player::White, v).unwrap();
+    }
+    assert_eq!(board.stone_count(Player::Black), 0);
+    assert_eq!(board.prisoners(Player::White), 37);
+    assert_eq!(board.resolve_chain(handle), None);
+}
+
+#[test]
+fn test_rebuild_from_colors_matches_played_position() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(23);
+    let mut board = Board::with_size(9, 9);
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.new_playout(&board, &gammas);
+    for _ in 0..60 {
+        let player = board.act_player();
+        let v = sampler.sample_move(&board, &mut random);
+        board.play_legal(player, v);
+        sampler.move_played(&board, &gammas);
+    }
+
+    let mut colors = VertexMap::new_with(Color::OffBoard);
+    for v in Vertex::all() {
+        colors[v] = board.color_at(v);
+    }
+    let mut rebuilt = Board::with_size(9, 9);
+    rebuilt
+        .rebuild_from_colors(&colors, board.act_player(), board.ko_vertex())
+        .unwrap();
+    assert_eq!(rebuilt.positional_hash(), board.positional_hash());
+    assert_eq!(rebuilt.act_player(), board.act_player());
+    assert_eq!(rebuilt.ko_vertex(), board.ko_vertex());
+    assert_eq!(rebuilt.empty_vertex_count(), board.empty_vertex_count());
+    for v in Vertex::all() {
+        if board.color_at(v) == Color::OffBoard {
+            continue;
+        }
+        // Color bits only, played boards can keep stale atari bits
+        let colors = |b: &Board| usize::from(b.hash3x3_at(v)) & 0xffff;
+        assert_eq!(colors(&rebuilt), colors(&board));
+        assert_eq!(
+            rebuilt.is_legal(Player::Black, v),
+            board.is_legal(Player::Black, v)
+        );
+        let size = |b: &Board| b.chain_at(v).map(|chain| chain.size);
+        assert_eq!(size(&rebuilt), size(&board));
+    }
+
+    // Bad ko and stones without liberties leave the board untouched
+    let stone = Vertex::all()
+        .find(|&v| color_is_player(board.color_at(v)))
+        .unwrap();
+    assert_eq!(
+        rebuilt.rebuild_from_colors(&colors, Player::Black, stone),
+        Err(BoardError::Occupied(stone))
+    );
+    let mut surrounded = VertexMap::new_with(Color::Empty);
+    surrounded[Vertex::from_coords(0, 0)] = Color::Black;
+    surrounded[Vertex::from_coords(0, 1)] = Color::White;
+    surrounded[Vertex::from_coords(1, 0)] = Color::White;
+    assert!(rebuilt
+        .rebuild_from_colors(&surrounded, Player::Black, Vertex::none())
+        .is_err());
+    assert_eq!(rebuilt.positional_hash(), board.positional_hash());
+}
+
+#[test]
+fn test_lazy_atari_bits() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(5);
+    let mut board = Board::with_size(9, 9);
+    board.set_lazy_atari(true);
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.new_playout(&board, &gammas);
+    let mut colors = VertexMap::new_with(Color::OffBoard);
+    while !board.both_player_pass() {
+        let player = board.act_player();
+        let v = sampler.sample_move(&board, &mut random);
+        board.play_legal(player, v);
+        sampler.move_played(&board, &gammas);
+
+        for v in Vertex::all() {
+            colors[v] = board.color_at(v);
+        }
+        let mut fresh = Board::with_size(9, 9);
+        fresh
+            .rebuild_from_colors(&colors, board.act_player(), Vertex::none())
+            .unwrap();
+        for v in board.empties() {
+            assert_eq!(board.hash3x3_at(v), fresh.hash3x3_at(v));
+        }
+        // The sampler saw every atari change through changed_vertices
+        let pl = board.act_player();
+        for (v, gamma) in sampler.distribution(pl) {
+            assert_eq!(gamma, gammas.get(board.hash3x3_at(v), pl));
+        }
+    }
+
+    board.set_lazy_atari(false);
+    for v in board.empties() {
+        let mut lazy = board.clone();
+        lazy.set_lazy_atari(true);
+        assert_eq!(board.hash3x3_at(v), lazy.hash3x3_at(v));
+    }
+}
+
+#[test]
+fn test_geometry_queries() {
+    let board = Board::with_size(9, 7);
+    let v = |row, col| Vertex::from_coords(row, col);
+    assert!(board.is_on_board(v(6, 8)));
+    assert!(!board.is_on_board(v(7, 0)));
+    assert!(!board.is_on_board(v(0, 9)));
+    assert!(!board.is_on_board(Vertex::pass()));
+    assert!(!board.is_on_board(Vertex::none()));
+
+    assert_eq!(board.line_of(v(0, 4)), Some(1));
+    assert_eq!(board.line_of(v(3, 4)), Some(4));
+    assert_eq!(board.line_of(v(5, 2)), Some(2));
+    assert_eq!(board.line_of(Vertex::pass()), None);
+
+    for (row, col) in [(0, 0), (0, 8), (6, 0), (6, 8)] {
+        assert!(board.is_corner(v(row, col)));
+        assert!(board.is_edge(v(row, col)));
+    }
+    assert!(board.is_edge(v(3, 8)));
+    assert!(!board.is_corner(v(3, 8)));
+    assert!(!board.is_edge(v(1, 1)));
+    assert!(!board.is_corner(v(7, 9)));
+    let edge_cnt = Vertex::all().filter(|&v| board.is_edge(v)).count();
+    assert_eq!(edge_cnt, 2 * 9 + 2 * 7 - 4);
+}
+
+#[test]
+fn test_resize() {
+    assert_eq!((Board::small().width(), Board::small().height()), (9, 9));
+    assert_eq!(Board::standard().width(), 19);
+
+    let gammas = Gammas::new();
+    let mut playouts = Playouts::new(&gammas, 5);
+    let mut start = Board::standard();
+    start.set_komi(7.5);
+    playouts.run(&start, &gammas);
+    let mut board = playouts.board().clone();
+
+    let too_wide = MAX_BOARD_SIZE + 1;
+    assert_eq!(
+        board.resize(too_wide, 9),
+        Err(BoardError::BadSize {
+            width: too_wide,
+            height: 9
+        })
+    );
+    assert_eq!(board.width(), 19);
+    assert!(board.both_player_pass());
+
+    board.resize(9, 7).unwrap();
+    let fresh = Board::with_size(9, 7);
+    assert_eq!((board.width(), board.height()), (9, 7));
+    assert_eq!(board.komi(), 7.5);
+    assert_eq!(board.positional_hash(), fresh.positional_hash());
+    assert_eq!(board.empties().count(), 63);
+    for v in Vertex::all() {
+        assert_eq!(board.color_at(v), fresh.color_at(v));
+        assert_eq!(board.hash3x3_at(v), fresh.hash3x3_at(v));
+    }
+    playouts.run(&board, &gammas);
+    assert!(playouts.board().both_player_pass());
+    assert_eq!(playouts.board().width(), 9);
+}
+
+#[test]
+fn test_replay_moves() {
+    let v = |row, col| Vertex::from_coords(row, col);
+    let mv = |player, vertex| Move::of_player_vertex(player, vertex);
+    // Black takes the ko at (1, 1), white may not retake at once
+    let moves = [
+        mv(Player::Black, v(0, 1)),
+        mv(Player::White, v(0, 2)),
+        mv(Player::Black, v(1, 0)),
+        mv(Player::White, v(1, 3)),
+        mv(Player::Black, v(2, 1)),
+        mv(Player::White, v(2, 2)),
+        mv(Player::Black, Vertex::pass()),
+        mv(Player::White, v(1, 1)),
+        mv(Player::Black, v(1, 2)),
+        mv(Player::White, v(1, 1)),
+    ];
+
+    let mut board = Board::with_size(5, 5);
+    assert_eq!(board.replay(&moves), Err((9, BoardError::Ko(v(1, 1)))));
+    let mut expected = Board::with_size(5, 5);
+    for m in &moves[..9] {
+        expected.play_legal(m.player, m.vertex);
+    }
+    assert_eq!(board.positional_hash(), expected.positional_hash());
+    assert_eq!(board.color_at(v(1, 1)), Color::Empty);
+
+    let mut board = Board::with_size(5, 5);
+    board.replay(&moves[..9]).unwrap();
+    assert_eq!(board.last_vertex(), v(1, 2));
+    assert_eq!(
+        board.play_move(mv(Player::White, v(0, 1))),
+        Err(BoardError::Occupied(v(0, 1)))
+    );
+    board.play_move(mv(Player::White, v(3, 3))).unwrap();
+    assert_eq!(board.color_at(v(3, 3)), Color::White);
+}
+
+#[test]
+fn test_verify_hash3x3_during_playouts() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(11);
+    for lazy_atari in [false, true] {
+        for _ in 0..5 {
+            let mut board = Board::with_size(9, 9);
+            board.set_lazy_atari(lazy_atari);
+            let mut sampler = Sampler::new(&board, &gammas);
+            sampler.new_playout(&board, &gammas);
+            while !board.both_player_pass() {
+                let player = board.act_player();
+                let v = sampler.sample_move(&board, &mut random);
+                board.play_legal(player, v);
+                sampler.move_played(&board, &gammas);
+                assert_eq!(board.verify_hash3x3(), Ok(()));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_pattern_histogram() {
+    let board = Board::with_size(3, 3);
+    let histogram = board.pattern_histogram();
+    // Corners, edges and the center
+    let counts: Vec<usize> = histogram.iter().map(|&(_, cnt)| cnt).collect();
+    assert_eq!(counts, vec![4, 4, 1]);
+    let center = Vertex::from_coords(1, 1);
+    assert_eq!(histogram[2].0, board.hash3x3_at(center).canonical());
+
+    let mut board = Board::with_size(9, 9);
+    board.play_legal(Player::Black, Vertex::from_coords(4, 4));
+    let histogram = board.pattern_histogram();
+    assert_eq!(histogram.iter().map(|&(_, cnt)| cnt).sum::<usize>(), 80);
+    for pair in histogram.windows(2) {
+        assert!(pair[0].1 >= pair[1].1);
+    }
+    let corner = Vertex::from_coords(0, 0);
+    let corner_cnt = histogram
+        .iter()
+        .find(|&&(hash, _)| hash == board.hash3x3_at(corner).canonical())
+        .unwrap()
+        .1;
+    assert_eq!(corner_cnt, 4);
+}
+
+#[test]
+fn test_boards_equal_by_position() {
+    let v = |row, col| Vertex::from_coords(row, col);
+    let mut a = Board::with_size(5, 5);
+    let mut b = Board::with_size(5, 5);
+    a.play_legal(Player::Black, v(1, 1));
+    a.play_legal(Player::White, v(3, 3));
+    a.play_legal(Player::Black, v(2, 2));
+    b.play_legal(Player::Black, v(2, 2));
+    b.play_legal(Player::White, v(3, 3));
+    b.play_legal(Player::Black, v(1, 1));
+    b.set_komi(0.5);
+    assert!(a.same_position(&b));
+    let set: std::collections::HashSet<Board> = [a.clone(), b.clone()].into_iter().collect();
+    assert_eq!(set.len(), 1);
+
+    // Player to move
+    let mut passed = a.clone();
+    passed.play_legal(Player::White, Vertex::pass());
+    passed.play_legal(Player::Black, Vertex::pass());
+    assert!(passed == a);
+    passed.play_legal(Player::White, Vertex::pass());
+    assert!(passed != a);
+    assert!(Board::with_size(5, 5) != Board::with_size(7, 7));
+
+    // The ko point, with the same stones
+    let mut ko = Board::with_size(5, 5);
+    ko.setup(Player::Black, &[v(1, 0), v(0, 1), v(2, 1)])
+        .unwrap();
+    ko.setup(Player::White, &[v(0, 2), v(2, 2), v(1, 3), v(1, 1)])
+        .unwrap();
+    ko.play_legal(Player::Black, v(1, 2));
+    assert_eq!(ko.ko_vertex(), v(1, 1));
+    let mut no_ko = ko.clone();
+    no_ko.play_legal(Player::White, Vertex::pass());
+    no_ko.play_legal(Player::Black, Vertex::pass());
+    assert!(no_ko != ko);
+    assert_eq!(no_ko.ko_vertex(), Vertex::none());
+}
+
+#[test]
+fn test_is_legal_fast_agrees_with_is_legal() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(17);
+    for lazy in [false, true] {
+        for size in [5, 9, 19] {
+            let mut board = Board::with_size(size, size);
+            board.set_lazy_atari(lazy);
+            let mut sampler = Sampler::new(&board, &gammas);
+            sampler.new_playout(&board, &gammas);
+            let mut suicide_cnt = 0;
+            while !board.both_player_pass() {
+                for pl in [Player::Black, Player::White] {
+                    for v in Vertex::all() {
+                        assert_eq!(board.is_legal_fast(pl, v), board.is_legal(pl, v));
+                        suicide_cnt += board.is_suicide(pl, v) as usize;
+                    }
+                }
+                let v = sampler.sample_move(&board, &mut random);
+                board.play_legal(board.act_player(), v);
+                sampler.move_played(&board, &gammas);
+            }
+            assert!(board.is_legal_fast(Player::Black, Vertex::pass()));
+            assert!(suicide_cnt > 0);
+        }
+    }
+}
+
+#[test]
+fn test_is_legal_fast_agrees_with_is_legal_in_random_positions() {
+    // Uniformly random moves from random stones reach shapes playouts avoid
+    let mut random = FastRandom::new(3);
+    for size in [2, 3, 4, 5, 7] {
+        for _ in 0..50 {
+            let mut board = Board::with_size(size, size);
+            let mut colors = VertexMap::new_with(Color::OffBoard);
+            for v in Vertex::all().filter(|&v| board.is_on_board(v)) {
+                colors[v] = match random.get_next_uint() % 3 {
+                    0 => Color::Black,
+                    1 => Color::White,
+                    _ => Color::Empty,
+                };
+            }
+            if board
+                .rebuild_from_colors(&colors, Player::Black, Vertex::none())
+                .is_err()
+            {
+                continue;
+            }
+            for lazy in [false, true] {
+                let mut board = board.clone();
+                board.set_lazy_atari(lazy);
+                for _ in 0..50 {
+                    for pl in [Player::Black, Player::White] {
+                        for v in Vertex::all() {
+                            assert_eq!(board.is_legal_fast(pl, v), board.is_legal(pl, v));
+                        }
+                    }
+                    let pl = board.act_player();
+                    let legal: Vec<Vertex> =
+                        board.empties().filter(|&v| board.is_legal(pl, v)).collect();
+                    let v = match legal.len() {
+                        0 => Vertex::pass(),
+                        cnt => legal[random.get_next_uint() as usize % cnt],
+                    };
+                    board.play_legal(pl, v);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_set_to_move() {
+    let v = |row, col| Vertex::from_coords(row, col);
+    let mut board = Board::with_size(5, 5);
+    board.set_to_move(Player::White);
+    assert_eq!(board.act_player(), Player::White);
+    assert_eq!(board.last_vertex(), Vertex::none());
+    board.play_legal(Player::White, v(2, 2));
+    assert_eq!(board.act_player(), Player::Black);
+
+    // Keeps the ko for the player already to move, drops it otherwise
+    let mut ko = Board::with_size(5, 5);
+    ko.setup(Player::Black, &[v(1, 0), v(0, 1), v(2, 1)])
+        .unwrap();
+    ko.setup(Player::White, &[v(0, 2), v(2, 2), v(1, 3), v(1, 1)])
+        .unwrap();
+    ko.play_legal(Player::Black, v(1, 2));
+    let hash = ko.positional_hash();
+    ko.set_to_move(Player::White);
+    assert_eq!(ko.ko_vertex(), v(1, 1));
+    ko.set_to_move(Player::Black);
+    assert_eq!(ko.ko_vertex(), Vertex::none());
+    assert_eq!(ko.positional_hash(), hash);
+
+    let game = sgf::parse_game("(;SZ[5]AB[bb]PL[W])").unwrap();
+    assert_eq!(game.to_move, Some(Player::White));
+    assert_eq!(game.final_position().unwrap().act_player(), Player::White);
+    let game = sgf::parse_game("(;SZ[5]PL[W];W[cc])").unwrap();
+    assert_eq!(game.final_position().unwrap().act_player(), Player::Black);
+    assert!(sgf::parse_game("(;SZ[5];B[cc]PL[W])").is_err());
+    assert!(sgf::parse_game("(;SZ[5]PL[X])").is_err());
+    assert!(sgf::parse_game("(;SZ[5]KM[1e40])").is_err());
+    assert!(sgf::parse_game("(;SZ[5]KM[nan])").is_err());
+}
+
+#[cfg(feature = "large-boards")]
+#[test]
+fn test_large_board_playouts() {
+    assert_eq!(Board::standard().width(), 19);
+    let gammas = Gammas::new();
+    let mut playouts = Playouts::new(&gammas, 3);
+    for size in [21, 25] {
+        let start = Board::with_size(size, size);
+        for _ in 0..20 {
+            playouts.run(&start, &gammas);
+            let board = playouts.board();
+            assert!(board.both_player_pass());
+            let corner = Vertex::from_coords(size as isize - 1, size as isize - 1);
+            assert_eq!(board.coord_of(corner).map(|c| c.row()), Some(size - 1));
+            assert_eq!(
+                board.empty_vertex_count()
+                    + board.stone_count(Player::Black)
+                    + board.stone_count(Player::White),
+                size * size
+            );
+        }
+    }
+    assert_eq!(
+        go_game_board::vertex_to_gtp(Vertex::from_coords(0, 24), 25),
+        "Z25"
+    );
+}
+
+// The C++ port indexes its liberty counts by chain root, above 625 here
+#[cfg(all(feature = "cpp-compat", feature = "large-boards"))]
+#[test]
+fn test_cpp_compat_is_legal_on_large_board() {
+    let mut board = Board::with_size(25, 25);
+    let v = |row, col| Vertex::from_coords(row, col);
+    board
+        .setup(
+            Player::White,
+            &[v(24, 11), v(24, 13), v(23, 12), v(23, 24), v(24, 23)],
+        )
+        .unwrap();
+    assert!(!board.is_legal(Player::Black, v(24, 12)));
+    assert!(board.is_legal(Player::White, v(24, 12)));
+    assert!(!board.is_legal(Player::Black, v(24, 24)));
+
+    // Capturing makes the same points legal
+    board.setup(Player::Black, &[v(24, 10), v(23, 11)]).unwrap();
+    assert!(board.is_legal(Player::Black, v(24, 12)));
+    board
+        .setup(Player::Black, &[v(22, 24), v(23, 23), v(24, 22)])
+        .unwrap();
+    assert!(board.is_legal(Player::Black, v(24, 24)));
+}
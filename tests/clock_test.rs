@@ -0,0 +1,65 @@
+use go_game_board::{Board, Clock, Engine, Gammas, MonteCarloEngine, TimeControl};
+use std::time::Duration;
+
+fn secs(s: u64) -> Duration {
+    Duration::from_secs(s)
+}
+
+#[test]
+fn test_absolute_expires() {
+    let mut clock = Clock::new(TimeControl::Absolute {
+        main_time: secs(10),
+    });
+    assert_eq!(clock.time_for_move(20), Duration::from_millis(500));
+    clock.spend(secs(9));
+    assert!(!clock.is_expired());
+    clock.spend(secs(2));
+    assert!(clock.is_expired());
+    assert_eq!(clock.time_for_move(20), Duration::ZERO);
+}
+
+#[test]
+fn test_byo_yomi_loses_periods() {
+    let mut clock = Clock::new(TimeControl::ByoYomi {
+        main_time: secs(5),
+        period_time: secs(10),
+        periods: 3,
+    });
+    clock.spend(secs(14));
+    assert!(!clock.is_expired());
+    assert_eq!(clock.main_time_left(), Duration::ZERO);
+    // Overtime moves below one period are free, every full period is lost
+    clock.spend(secs(9));
+    clock.spend(secs(25));
+    assert!(!clock.is_expired());
+    clock.spend(secs(10));
+    assert!(clock.is_expired());
+}
+
+#[test]
+fn test_canadian_period_resets() {
+    let mut clock = Clock::new(TimeControl::Canadian {
+        main_time: Duration::ZERO,
+        period_time: secs(10),
+        period_stones: 2,
+    });
+    assert_eq!(clock.time_for_move(20), secs(4));
+    clock.spend(secs(6));
+    clock.spend(secs(4));
+    assert!(!clock.is_expired());
+    clock.spend(secs(8));
+    clock.spend(secs(3));
+    assert!(clock.is_expired());
+}
+
+#[test]
+fn test_monte_carlo_respects_clock() {
+    let gammas = Gammas::new();
+    let mut engine = MonteCarloEngine::new(&gammas, 0, 1);
+    let mut clock = Clock::new(TimeControl::Absolute { main_time: secs(2) });
+    let board = Board::new();
+    engine.genmove_with_clock(&board, &mut clock);
+    assert!(!clock.is_expired());
+    assert!(clock.main_time_left() < secs(2));
+    assert!(engine.playouts_per_second() > 0.0);
+}
@@ -0,0 +1,119 @@
+use go_game_board::{Config, Rules, SamplerParams, DEFAULT_BOARD_SIZE, MAX_BOARD_SIZE};
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_config_from_toml() {
+    let text = r#"
+        # 9x9 experiment
+        komi = 5.5
+        rules = "japanese"
+        gammas = "patterns/gammas.txt"
+
+        [gtp]
+        playouts = 5000
+        threads = 4
+
+        [bench]
+        board_size = 19
+        seed = 7
+
+        [self_play]
+        board_size = 13
+
+        [sampler]
+        capture_bonus = 3
+        pass_gamma = 0.25
+    "#;
+    let config = Config::from_toml(text, Path::new("/experiments")).unwrap();
+    // komi replaces the komi of the rules wherever it is in the file
+    assert_eq!(
+        config.rules,
+        Rules {
+            komi: 5.5,
+            ..Rules::japanese()
+        }
+    );
+    assert_eq!(
+        config.gammas_path,
+        Some(PathBuf::from("/experiments/patterns/gammas.txt"))
+    );
+    assert_eq!((config.gtp.playout_cnt, config.gtp.thread_cnt), (5000, 4));
+    assert_eq!(config.gtp.board_size, DEFAULT_BOARD_SIZE);
+    assert_eq!((config.bench.board_size, config.bench.seed), (19, 7));
+    assert_eq!(
+        config.bench.playout_cnt,
+        Config::default().bench.playout_cnt
+    );
+    assert_eq!(config.self_play.board_size, 13);
+    assert_eq!(
+        config.sampler,
+        SamplerParams {
+            capture_bonus: 3.0,
+            pass_gamma: 0.25,
+            ..SamplerParams::default()
+        }
+    );
+
+    assert_eq!(Config::from_toml("", Path::new(".")), Ok(Config::default()));
+    let absolute = Config::from_toml("gammas = \"/data/gammas.txt\"", Path::new("/experiments"));
+    assert_eq!(
+        absolute.unwrap().gammas_path,
+        Some(PathBuf::from("/data/gammas.txt"))
+    );
+}
+
+#[test]
+fn test_config_errors() {
+    let error = |text: &str| Config::from_toml(text, Path::new(".")).unwrap_err();
+    assert_eq!(error("playouts = 10"), "unknown key playouts");
+    assert_eq!(error("[gtp]\nplayout = 10"), "unknown key gtp.playout");
+    assert_eq!(
+        error("[gtp]\nthreads = 0"),
+        "gtp.threads: expected a positive integer"
+    );
+    assert_eq!(error("rules = \"korean\""), "rules: unknown rules korean");
+    assert_eq!(error("komi = \"7.5\""), "komi: expected a number");
+    assert!(error("komi = inf").starts_with("komi: komi inf is not a number"));
+    assert_eq!(error("gtp = 5"), "gtp: expected a table");
+    for section in ["gtp", "bench", "self_play"] {
+        let too_large = format!("[{}]\nboard_size = {}", section, MAX_BOARD_SIZE + 1);
+        assert_eq!(
+            error(&too_large),
+            format!(
+                "{}.board_size: expected an integer in 1..={}",
+                section, MAX_BOARD_SIZE
+            )
+        );
+        assert!(error(&format!("[{}]\nboard_size = 0", section)).starts_with(section));
+    }
+    assert_eq!(
+        error("[bench]\nseed = -1"),
+        "bench.seed: expected an integer in 0..2^32"
+    );
+    assert!(error("[sampler]\npass_gamma = -1").starts_with("sampler.pass_gamma: "));
+    assert_eq!(
+        error("[sampler]\nbonus = 1"),
+        "sampler.bonus: unknown parameter bonus"
+    );
+    // TOML syntax
+    assert!(error("komi = ").contains("line 1"));
+}
+
+#[test]
+fn test_config_load() {
+    let dir = std::env::temp_dir().join(format!("config_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("experiment.toml");
+    std::fs::write(&path, "gammas = \"missing.txt\"\n[gtp]\nplayouts = 50\n").unwrap();
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.gtp.playout_cnt, 50);
+    assert_eq!(config.gammas_path, Some(dir.join("missing.txt")));
+    assert!(config.gammas().is_err());
+    assert!(Config::default().gammas().is_ok());
+
+    std::fs::write(&path, "[gtp]\nplayouts = 0\n").unwrap();
+    let error = Config::load(&path).unwrap_err();
+    assert!(error.starts_with(&path.display().to_string()), "{}", error);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(Config::load(&path).is_err());
+}
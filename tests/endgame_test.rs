@@ -0,0 +1,127 @@
+use go_game_board::{
+    cleanup_moves, dead_stones, score_with_dead_removal, Board, CleanupKind, CleanupMove, Color,
+    FastRandom, Gammas, Nat, Player, Vertex, VertexMap,
+};
+
+// Black wall on column 1, white wall on column 3, neutral column 2
+fn walls() -> (Board, VertexMap<f64>) {
+    let mut board = Board::with_size(5, 5);
+    let black: Vec<Vertex> = (0..5).map(|r| Vertex::from_coords(r, 1)).collect();
+    let white: Vec<Vertex> = (0..5).map(|r| Vertex::from_coords(r, 3)).collect();
+    board.setup(Player::Black, &black).unwrap();
+    board.setup(Player::White, &white).unwrap();
+    let mut ownership = VertexMap::new_with(0.0);
+    for r in 0..5 {
+        for c in 0..5 {
+            ownership[Vertex::from_coords(r, c)] = match c {
+                0 | 1 => 1.0,
+                2 => 0.0,
+                _ => -1.0,
+            };
+        }
+    }
+    (board, ownership)
+}
+
+#[test]
+fn test_cleanup_fills_dame() {
+    let (board, ownership) = walls();
+    let moves = cleanup_moves(&board, Player::Black, &ownership);
+    let expected: Vec<CleanupMove> = (0..5)
+        .map(|r| CleanupMove {
+            v: Vertex::from_coords(r, 2),
+            kind: CleanupKind::FillDame,
+        })
+        .collect();
+    assert_eq!(moves, expected);
+
+    let mut finished = board.clone();
+    for r in 0..5 {
+        let pl = if r % 2 == 0 {
+            Player::Black
+        } else {
+            Player::White
+        };
+        finished.play_legal(pl, Vertex::from_coords(r, 2));
+    }
+    assert!(cleanup_moves(&finished, Player::Black, &ownership).is_empty());
+    assert!(cleanup_moves(&finished, Player::White, &ownership).is_empty());
+}
+
+#[test]
+fn test_cleanup_captures_dead_stones_and_defends() {
+    let (mut board, ownership) = walls();
+    let v = |r, c| Vertex::from_coords(r, c);
+    board.setup(Player::White, &[v(2, 0)]).unwrap();
+    let moves = cleanup_moves(&board, Player::Black, &ownership);
+    assert_eq!(
+        moves[0],
+        CleanupMove {
+            v: v(1, 0),
+            kind: CleanupKind::CaptureDead
+        }
+    );
+    assert_eq!(
+        moves[1],
+        CleanupMove {
+            v: v(3, 0),
+            kind: CleanupKind::CaptureDead
+        }
+    );
+    // The dead stone does not make the black area neutral
+    assert!(moves[2..]
+        .iter()
+        .all(|m| m.kind == CleanupKind::FillDame && m.v.column() == 2));
+
+    // Extending the dead stone after a black move is still atari
+    board.play_legal(Player::Black, v(1, 0));
+    let moves = cleanup_moves(&board, Player::White, &ownership);
+    assert!(moves.iter().all(|m| m.v != v(3, 0)));
+
+    let mut board = Board::with_size(5, 5);
+    board.setup(Player::Black, &[v(0, 0)]).unwrap();
+    board.setup(Player::White, &[v(0, 1)]).unwrap();
+    let moves = cleanup_moves(&board, Player::Black, &VertexMap::new_with(0.0));
+    assert_eq!(
+        moves[0],
+        CleanupMove {
+            v: v(1, 0),
+            kind: CleanupKind::Defend
+        }
+    );
+}
+
+#[test]
+fn test_score_with_dead_removal() {
+    // Both groups have eyes, the white stone in black's area is dead
+    let rows = [
+        ".#.#O.O", //
+        "####OOO", //
+        "...#O.O", //
+        ".O.#OOO", //
+        "...#O.O", //
+        "####OOO", //
+        ".#.#O.O", //
+    ];
+    let mut board = Board::with_size(7, 7);
+    board.set_komi(0.5);
+    for (player, symbol) in [(Player::Black, '#'), (Player::White, 'O')] {
+        let stones: Vec<Vertex> = Vertex::all()
+            .filter(|v| board.is_on_board(*v))
+            .filter(|v| rows[v.row() as usize].as_bytes()[v.column() as usize] == symbol as u8)
+            .collect();
+        board.setup(player, &stones).unwrap();
+    }
+    let dead = Vertex::from_coords(3, 1);
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(7);
+    let estimate = board.estimate_score(&gammas, 200, &mut random).unwrap();
+    assert_eq!(dead_stones(&board, &estimate.ownership), vec![dead]);
+
+    board.play_legal(Player::Black, Vertex::pass());
+    board.play_legal(Player::White, Vertex::pass());
+    assert_eq!(board.tromp_taylor_score(), 19.0 - 22.0 - 0.5);
+    let score = score_with_dead_removal(&board, &gammas, 200, &mut random);
+    assert_eq!(score, 28.0 - 21.0 - 0.5);
+    assert_eq!(board.color_at(dead), Color::White);
+}
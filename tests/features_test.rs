@@ -0,0 +1,40 @@
+use go_game_board::features::{line_map, CFG_FAR};
+use go_game_board::{Board, DistanceFeatures, Player, Vertex};
+
+#[test]
+fn test_line_map() {
+    let line = line_map(19, 19);
+    let v = |row, col| Vertex::from_coords(row, col);
+    assert_eq!(line[v(0, 0)], 1);
+    assert_eq!(line[v(2, 15)], 3);
+    assert_eq!(line[v(15, 3)], 4);
+    assert_eq!(line[v(9, 9)], 5);
+    assert_eq!(line[v(-1, 3)], 0);
+    assert_eq!(line_map(9, 9)[v(4, 8)], 1);
+}
+
+#[test]
+fn test_cfg_distance_treats_chain_as_one_node() {
+    let v = |row, col| Vertex::from_coords(row, col);
+    let mut board = Board::with_size(9, 9);
+    board
+        .setup(Player::White, &[v(4, 4), v(4, 5), v(4, 6)])
+        .unwrap();
+    board.play_legal(Player::Black, v(3, 4));
+
+    let mut features = DistanceFeatures::new(3);
+    features.update(&board);
+    let dist = features.cfg_distance();
+    assert_eq!(dist[v(3, 4)], 0);
+    assert_eq!(dist[v(3, 5)], 1);
+    // The whole white chain is one step away
+    assert_eq!(dist[v(4, 4)], 1);
+    assert_eq!(dist[v(4, 6)], 1);
+    assert_eq!(dist[v(4, 7)], 2);
+    assert_eq!(dist[v(8, 8)], CFG_FAR);
+    assert_eq!(features.line()[v(4, 4)], 5);
+
+    board.play_legal(Player::White, Vertex::pass());
+    features.update(&board);
+    assert_eq!(features.cfg_distance()[v(3, 4)], CFG_FAR);
+}
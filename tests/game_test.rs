@@ -0,0 +1,361 @@
+use go_game_board::{
+    fixed_handicap_vertices, Board, Color, CycleDetection, Game, GameKey, Move, Player, Rules,
+    Vertex,
+};
+
+fn mv(player: Player, row: isize, col: isize) -> Move {
+    Move::of_player_vertex(player, Vertex::from_coords(row, col))
+}
+
+fn pass(player: Player) -> Move {
+    Move::of_player_vertex(player, Vertex::pass())
+}
+
+#[test]
+fn test_suicide_under_tromp_taylor() {
+    let mut game = Game::new(5, 5, Rules::tromp_taylor());
+    for m in [
+        mv(Player::Black, 0, 0),
+        mv(Player::White, 1, 0),
+        pass(Player::Black),
+        mv(Player::White, 1, 1),
+        pass(Player::Black),
+        mv(Player::White, 0, 2),
+    ] {
+        assert!(game.play(m));
+    }
+
+    // Two-stone suicide is allowed and scores two prisoners for white
+    assert!(game.play(mv(Player::Black, 0, 1)));
+    assert_eq!(
+        game.board().color_at(Vertex::from_coords(0, 0)),
+        Color::Empty
+    );
+    assert_eq!(game.prisoners(Player::White), 2);
+
+    // Single-stone suicide recreates the position, forbidden by superko
+    assert!(game.play(pass(Player::White)));
+    assert!(!game.is_legal(mv(Player::Black, 0, 0)));
+    assert!(!game.play(mv(Player::Black, 0, 0)));
+
+    let mut japanese = Game::new(5, 5, Rules::japanese());
+    japanese.play(mv(Player::White, 0, 1));
+    japanese.play(mv(Player::White, 1, 0));
+    assert!(!japanese.is_legal(mv(Player::Black, 0, 0)));
+}
+
+#[test]
+fn test_area_and_territory_scoring() {
+    let mut chinese = Game::new(5, 5, Rules::chinese());
+    let mut japanese = Game::new(5, 5, Rules::japanese());
+    for row in 0..5 {
+        for game in [&mut chinese, &mut japanese] {
+            assert!(game.play(mv(Player::Black, row, 2)));
+            assert!(game.play(mv(Player::White, row, 3)));
+        }
+    }
+
+    assert_eq!(chinese.board().area_score(), 5);
+    assert_eq!(chinese.score(), 15.0 - 10.0 - 7.5);
+    assert_eq!(japanese.score(), 10.0 - 5.0 - 6.5);
+    assert_eq!(chinese.board().tromp_taylor_score(), chinese.score());
+}
+
+#[test]
+fn test_fixed_handicap_compensation() {
+    let mut game = Game::new(19, 19, Rules::chinese());
+    assert!(game.set_fixed_handicap(4));
+    assert!(!game.set_fixed_handicap(2));
+    assert_eq!(game.board().act_player(), Player::White);
+    // D4 is on the 4th line from the bottom
+    assert_eq!(
+        game.board().color_at(Vertex::from_coords(15, 3)),
+        Color::Black
+    );
+    assert_eq!(game.effective_komi(), 0.5 + 4.0);
+
+    let mut aga = Game::new(19, 19, Rules::aga());
+    assert!(aga.set_fixed_handicap(9));
+    assert_eq!(aga.effective_komi(), 0.5 + 8.0);
+
+    let mut japanese = Game::new(9, 9, Rules::japanese());
+    assert!(japanese.set_fixed_handicap(2));
+    assert_eq!(japanese.effective_komi(), 0.5);
+    assert!(!Game::new(8, 8, Rules::japanese()).set_fixed_handicap(5));
+    assert_eq!(fixed_handicap_vertices(7, 7, 4).map(|vs| vs.len()), Some(4));
+    for stone_cnt in 5..=9 {
+        assert_eq!(fixed_handicap_vertices(7, 7, stone_cnt), None);
+        assert!(fixed_handicap_vertices(9, 9, stone_cnt).is_some());
+    }
+
+    // The handicap stones alone own the whole board
+    assert_eq!(game.result(), format!("B+{}", 361.0 - 4.5));
+}
+
+#[test]
+fn test_kifu_diagrams() {
+    let mut game = Game::new(5, 5, Rules::chinese());
+    // The second diagram starts from the position after move 4
+    for m in [
+        mv(Player::Black, 0, 0),
+        mv(Player::White, 0, 1),
+        mv(Player::Black, 2, 2),
+        mv(Player::White, 1, 1),
+        mv(Player::Black, 0, 2),
+        pass(Player::White),
+        mv(Player::Black, 1, 0),
+    ] {
+        assert!(game.play(m));
+    }
+    assert_eq!(game.kifu_ranges(4), vec![0..4, 4..7]);
+
+    let expected = "Moves 1-4
+      A   B   C   D   E
+  5   1   2   .   .   .
+  4   .   4   .   .   .
+  3   .   .   3   .   .
+  2   .   .   .   .   .
+  1   .   .   .   .   .
+
+Moves 5-7
+      A   B   C   D   E
+  5   #   O   5   .   .
+  4   7   O   .   .   .
+  3   .   .   #   .   .
+  2   .   .   .   .   .
+  1   .   .   .   .   .
+6: pass
+
+";
+    assert_eq!(game.to_kifu_ascii(4), expected);
+
+    let tex = game.to_kifu_tex(4);
+    assert!(tex.starts_with("\\cleargoban\n\\gobansize{5}\n\\black[1]{a5}\n\\white[2]{b5}\n"));
+    assert!(tex.contains("\\black{a5,c3}\n\\white{b5,b4}\n\\black[5]{c5}\n"));
+    assert!(tex.contains("Moves 5--7\\\\\n6: pass\n"));
+}
+
+#[test]
+fn test_kifu_diagrams_of_setup_position() {
+    let mut start = Board::with_size(5, 5);
+    start
+        .setup(
+            Player::Black,
+            &[Vertex::from_coords(0, 0), Vertex::from_coords(1, 1)],
+        )
+        .unwrap();
+    start.set_to_move(Player::White);
+    let mut game = Game::from_position(start, Rules::chinese());
+    assert!(game.play(mv(Player::White, 0, 1)));
+    // Out of turn, as after genmove w
+    game.set_to_move(Player::White);
+    assert!(game.play(mv(Player::White, 2, 2)));
+    assert!(game.play(mv(Player::Black, 0, 2)));
+
+    let expected = "Moves 1-3
+      A   B   C   D   E
+  5   #   1   3   .   .
+  4   .   #   .   .   .
+  3   .   .   2   .   .
+  2   .   .   .   .   .
+  1   .   .   .   .   .
+
+";
+    assert_eq!(game.to_kifu_ascii(10), expected);
+    let tex = game.to_kifu_tex(10);
+    assert!(
+        tex.contains("\\black{a5,b4}\n\\white[1]{b5}\n\\white[2]{c3}\n\\black[3]{c5}\n"),
+        "{}",
+        tex
+    );
+}
+
+// Three independent kos (at rows 2, 8 and 14) where black can take the first
+// and third and white the second
+fn triple_ko_game() -> Game {
+    let mut game = Game::new(19, 19, Rules::japanese());
+    let mut black = Vec::new();
+    let mut white = Vec::new();
+    for (r, black_to_take) in [(2, true), (8, false), (14, true)] {
+        black.extend([(r - 1, 3), (r, 2), (r + 1, 3)]);
+        white.extend([(r - 1, 4), (r + 1, 4), (r, 5)]);
+        if black_to_take {
+            white.push((r, 3));
+        } else {
+            black.push((r, 4));
+        }
+    }
+    assert_eq!((black.len(), white.len()), (10, 11));
+    for (idx, &(row, col)) in white.iter().enumerate() {
+        let b = match black.get(idx) {
+            Some(&(row, col)) => mv(Player::Black, row, col),
+            None => pass(Player::Black),
+        };
+        assert!(game.play(b));
+        assert!(game.play(mv(Player::White, row, col)));
+    }
+    game
+}
+
+#[test]
+fn test_triple_ko_voids_game() {
+    // Each player always has a ko to take that is not banned
+    let cycle = [
+        mv(Player::Black, 2, 4),
+        mv(Player::White, 8, 3),
+        mv(Player::Black, 14, 4),
+        mv(Player::White, 2, 3),
+        mv(Player::Black, 8, 4),
+        mv(Player::White, 14, 3),
+    ];
+
+    let mut game = triple_ko_game();
+    for m in cycle.iter().chain(cycle.iter()) {
+        assert!(game.play(*m));
+    }
+    assert_eq!(game.void_cycle(), None);
+    assert!(!game.is_over());
+
+    let mut game = triple_ko_game();
+    game.set_cycle_detection(Some(CycleDetection::default()));
+    for (idx, m) in cycle.iter().chain(cycle.iter()).enumerate() {
+        assert_eq!(game.void_cycle(), None, "move {}", idx);
+        assert!(game.play(*m));
+    }
+    assert_eq!(game.void_cycle(), Some(6));
+    assert!(game.is_over());
+    assert_eq!(game.result(), "Void");
+
+    // Taking back the last move undoes the cycle
+    let hash = game.board().positional_hash();
+    assert!(game.undo());
+    assert_eq!(game.void_cycle(), None);
+    assert_eq!(game.moves().len(), 2 * 11 + 12 - 1);
+    assert_eq!(game.position_history().len(), game.moves().len() + 1);
+    assert!(game.play(cycle[5]));
+    assert_eq!(game.void_cycle(), Some(6));
+    assert_eq!(game.board().positional_hash(), hash);
+
+    // A cycle longer than max_cycle_len is allowed
+    let mut game = triple_ko_game();
+    game.set_cycle_detection(Some(CycleDetection {
+        max_cycle_len: 5,
+        repetitions: 2,
+    }));
+    for m in cycle.iter().chain(cycle.iter()) {
+        assert!(game.play(*m));
+    }
+    assert_eq!(game.void_cycle(), None);
+}
+
+#[test]
+fn test_situational_superko_probing() {
+    let moves = [
+        mv(Player::Black, 4, 4),
+        mv(Player::White, 0, 1),
+        mv(Player::Black, 4, 3),
+        mv(Player::White, 1, 0),
+    ];
+    let suicide = mv(Player::Black, 0, 0);
+
+    // The suicide recreates the position white made with the last move
+    let mut positional = Game::new(5, 5, Rules::tromp_taylor());
+    let mut situational = Game::new(5, 5, Rules::new_zealand());
+    for m in moves {
+        assert!(positional.play(m));
+        assert!(situational.play(m));
+    }
+    assert_eq!(positional.repeated_position(suicide), Some(4));
+    assert!(!positional.is_legal(suicide));
+    assert_eq!(situational.repeated_position(suicide), None);
+    assert!(situational.is_legal(suicide));
+
+    let history = situational.position_history();
+    assert_eq!(history.len(), 5);
+    let (hash, player) = history[4];
+    assert_eq!(player, Player::White);
+    assert_eq!(situational.find_position(hash, Player::White), Some(4));
+    assert_eq!(situational.find_position(hash, Player::Black), None);
+    assert_eq!(positional.find_position(hash, Player::Black), Some(4));
+
+    assert!(situational.play(suicide));
+    assert_eq!(situational.position_history()[5], (hash, Player::Black));
+    // Now black's suicide would repeat under both rules
+    assert!(situational.play(pass(Player::White)));
+    assert_eq!(situational.repeated_position(suicide), Some(5));
+}
+
+#[test]
+fn test_game_key() {
+    let key_after = |rules: Rules, moves: &[Move]| {
+        let mut game = Game::new(9, 9, rules);
+        for &m in moves {
+            assert!(game.play(m));
+        }
+        game.key()
+    };
+    let chinese = key_after(Rules::chinese(), &[mv(Player::Black, 2, 3)]);
+    // All eight symmetries of the move give the same key
+    for (row, col) in [(3, 2), (6, 3), (2, 5), (5, 6), (6, 5), (3, 6), (5, 2)] {
+        assert_eq!(
+            key_after(Rules::chinese(), &[mv(Player::Black, row, col)]),
+            chinese
+        );
+    }
+    assert_ne!(
+        key_after(Rules::chinese(), &[mv(Player::Black, 2, 2)]),
+        chinese
+    );
+    assert_ne!(
+        key_after(Rules::japanese(), &[mv(Player::Black, 2, 3)]),
+        chinese
+    );
+
+    let mut rules = Rules::chinese();
+    rules.komi += 1.0;
+    let other_komi = key_after(rules, &[mv(Player::Black, 2, 3)]);
+    assert_ne!(other_komi, chinese);
+    assert_eq!(other_komi.komi_half_points, chinese.komi_half_points + 2);
+
+    // Same stones with white to move
+    let white_to_move = key_after(
+        Rules::chinese(),
+        &[
+            pass(Player::Black),
+            pass(Player::White),
+            mv(Player::Black, 2, 3),
+        ],
+    );
+    assert_eq!(white_to_move, chinese);
+    // A white stone there instead
+    let white_stone = key_after(
+        Rules::chinese(),
+        &[pass(Player::Black), mv(Player::White, 2, 3)],
+    );
+    assert_ne!(white_stone.position, chinese.position);
+    let mut game = Game::new(9, 9, Rules::chinese());
+    assert!(game.play(mv(Player::Black, 2, 3)));
+    assert!(game.play(pass(Player::White)));
+    assert!(game.key().black_to_move);
+    assert_eq!(
+        GameKey {
+            black_to_move: false,
+            ..game.key()
+        },
+        chinese
+    );
+}
+
+#[test]
+fn test_white_moves_first() {
+    let mut game = Game::new(9, 9, Rules::chinese());
+    game.set_to_move(Player::White);
+    assert_eq!(game.board().act_player(), Player::White);
+    assert_eq!(
+        game.position_history(),
+        &[(game.board().positional_hash(), Player::Black)]
+    );
+    assert!(game.play(mv(Player::White, 4, 4)));
+    assert_eq!(game.board().act_player(), Player::Black);
+    assert_eq!(game.position_history()[1].1, Player::White);
+}
@@ -0,0 +1,161 @@
+use go_game_board::{Board, Gammas, GammasFormat, Hash3x3, Nat, Player, Vertex};
+
+#[test]
+fn test_ranked_moves() {
+    let gammas = Gammas::new();
+    let mut board = Board::with_size(5, 5);
+    // Black eye in the corner
+    board.play_legal(Player::Black, Vertex::from_coords(0, 1));
+    board.play_legal(Player::White, Vertex::pass());
+    board.play_legal(Player::Black, Vertex::from_coords(1, 0));
+    board.play_legal(Player::White, Vertex::pass());
+    let eye = Vertex::from_coords(0, 0);
+
+    let black = gammas.ranked_moves(&board, Player::Black);
+    assert_eq!(black.len(), 22);
+    assert!(black.iter().all(|&(v, _)| v != eye));
+    assert!(black.windows(2).all(|w| w[0].1 >= w[1].1));
+
+    let white = gammas.ranked_moves(&board, Player::White);
+    assert!(white.iter().all(|&(v, _)| v != eye));
+    assert_eq!(white.len(), 22);
+}
+
+#[test]
+fn test_set_and_text_round_trip() {
+    let mut board = Board::with_size(5, 5);
+    board.play_legal(Player::Black, Vertex::from_coords(1, 2));
+    let hash = board.hash3x3_at(Vertex::from_coords(1, 1));
+
+    let mut gammas = Gammas::new();
+    gammas.set(hash, Player::Black, 7.0);
+    assert_eq!(gammas.get(hash, Player::Black), 7.0);
+    assert_eq!(gammas.get(hash, Player::White), 1.0);
+    assert_eq!(gammas.get(hash.rotated(), Player::Black), 1.0);
+    gammas.set_symmetric(hash, Player::White, 0.25);
+    for sym in hash.symmetries() {
+        assert_eq!(gammas.get(sym, Player::White), 0.25);
+    }
+
+    let text = gammas.to_text();
+    assert_eq!(
+        text.lines().count(),
+        1 + hash
+            .symmetries()
+            .iter()
+            .map(|&h| usize::from(h))
+            .collect::<std::collections::BTreeSet<_>>()
+            .len()
+    );
+    let loaded = Gammas::of_text(&format!("# tuned\n\n{}", text)).unwrap();
+    assert_eq!(loaded.to_text(), text);
+    assert_eq!(loaded.get(hash, Player::Black), 7.0);
+    assert_eq!(Gammas::of_text("").unwrap().to_text(), "");
+
+    for bad in ["1 B", "zz B 1.0", "1 X 1.0", "1 B -1", "100000 B 1.0"] {
+        let e = Gammas::of_text(&format!("1 W 2\n{}", bad)).err().unwrap();
+        assert!(e.starts_with("line 2: bad gamma"), "{}", e);
+    }
+}
+
+fn tuned_gammas() -> Gammas {
+    let mut board = Board::with_size(5, 5);
+    board.play_legal(Player::Black, Vertex::from_coords(1, 2));
+    let mut gammas = Gammas::new();
+    gammas.set(
+        board.hash3x3_at(Vertex::from_coords(1, 1)),
+        Player::Black,
+        0.1,
+    );
+    gammas.set(
+        board.hash3x3_at(Vertex::from_coords(2, 2)),
+        Player::White,
+        1234.5,
+    );
+    gammas
+}
+
+#[test]
+fn test_binary_formats() {
+    let gammas = tuned_gammas();
+    let bytes = gammas.to_binary(GammasFormat::F64);
+    assert_eq!(bytes.len(), 16 + (1 << 21) * 8);
+    assert_eq!(
+        Gammas::of_binary(&bytes).unwrap().to_text(),
+        gammas.to_text()
+    );
+
+    let half = Gammas::of_binary(&gammas.to_binary(GammasFormat::F16)).unwrap();
+    let single = Gammas::of_binary(&gammas.to_binary(GammasFormat::F32)).unwrap();
+    for line in gammas.to_text().lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let hash = usize::from_str_radix(fields[0], 16).unwrap().into();
+        let pl = if fields[1] == "B" {
+            Player::Black
+        } else {
+            Player::White
+        };
+        let gamma: f64 = fields[2].parse().unwrap();
+        assert!((half.get(hash, pl) - gamma).abs() <= gamma * 1e-3);
+        assert!((single.get(hash, pl) - gamma).abs() <= gamma * 1e-7);
+    }
+    // Zero and one gammas of the uniform table are exact in every format
+    assert_eq!(
+        half.to_text().lines().count(),
+        gammas.to_text().lines().count()
+    );
+
+    assert!(Gammas::of_binary(b"GGGAMMAS").is_err());
+    assert!(Gammas::of_binary(&bytes[..bytes.len() - 1]).is_err());
+    let mut unknown = bytes.clone();
+    unknown[8] = 9;
+    assert!(Gammas::of_binary(&unknown).is_err());
+}
+
+#[test]
+fn test_half_precision_saturates_at_largest_finite() {
+    let hash = Hash3x3::all().next().unwrap();
+    for (gamma, expected) in [
+        (65504.0, 65504.0),
+        (65505.0, 65504.0),
+        (65519.0, 65504.0),
+        (65520.0, 65504.0),
+        (65535.0, 65504.0),
+        (1e9, 65504.0),
+        (65480.0, 65472.0),
+        (65500.0, 65504.0),
+    ] {
+        let mut gammas = Gammas::new();
+        gammas.set(hash, Player::Black, gamma);
+        gammas.set(hash, Player::White, -gamma);
+        let half = Gammas::of_binary(&gammas.to_binary(GammasFormat::F16)).unwrap();
+        assert_eq!(half.get(hash, Player::Black), expected, "{gamma}");
+        assert_eq!(half.get(hash, Player::White), -expected, "{gamma}");
+    }
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mapped_table() {
+    let gammas = tuned_gammas();
+    let path = std::env::temp_dir().join(format!("gammas_{}.bin", std::process::id()));
+    gammas.save_binary(&path, GammasFormat::F64).unwrap();
+    let mut mapped = Gammas::map_binary(&path).unwrap();
+    assert!(mapped.is_mapped());
+    assert_eq!(mapped.to_text(), gammas.to_text());
+
+    // Changing a mapped table copies it, the file stays as it was
+    let hash = Vertex::from_coords(0, 0);
+    let hash = Board::with_size(5, 5).hash3x3_at(hash);
+    mapped.set(hash, Player::Black, 3.0);
+    assert!(!mapped.is_mapped());
+    assert_eq!(mapped.get(hash, Player::Black), 3.0);
+    assert_eq!(
+        Gammas::load_binary(&path).unwrap().to_text(),
+        gammas.to_text()
+    );
+
+    gammas.save_binary(&path, GammasFormat::F16).unwrap();
+    assert!(Gammas::map_binary(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
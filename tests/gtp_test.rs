@@ -0,0 +1,378 @@
+use go_game_board::{
+    Color, Gammas, Gtp, MonteCarloEngine, Player, Rules, SamplerEngine, TimeControl, Vertex,
+    MAX_BOARD_SIZE,
+};
+use std::time::{Duration, Instant};
+
+fn gtp(gammas: &Gammas) -> Gtp<'_> {
+    Gtp::new(
+        gammas,
+        Box::new(SamplerEngine::new(gammas, 1)),
+        Rules::chinese(),
+    )
+}
+
+#[test]
+fn test_standard_commands() {
+    let gammas = Gammas::new();
+    let mut gtp = gtp(&gammas);
+    assert_eq!(gtp.response("protocol_version").unwrap(), "= 2\n\n");
+    assert_eq!(gtp.response("7 boardsize 9").unwrap(), "=7\n\n");
+    assert_eq!(gtp.response("play black e5").unwrap(), "=\n\n");
+    assert_eq!(gtp.response("play white E5").unwrap(), "? illegal move\n\n");
+    assert_eq!(gtp.response("known_command gg-hash").unwrap(), "= true\n\n");
+    assert_eq!(gtp.response("frobnicate").unwrap(), "? unknown command\n\n");
+    assert!(gtp.response("   # comment").is_none());
+
+    let response = gtp.response("genmove w").unwrap();
+    assert!(response.starts_with("= "));
+    assert_eq!(gtp.game().moves().len(), 2);
+    // Out of turn
+    let response = gtp.response("genmove w").unwrap();
+    assert!(response.starts_with("= "), "{}", response);
+    assert_eq!(gtp.game().moves().len(), 3);
+    assert_eq!(gtp.game().board().act_player(), Player::Black);
+
+    let mut output = Vec::new();
+    gtp.run(&b"known_command quit\nquit\nname\n"[..], &mut output)
+        .unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "= true\n\n=\n\n");
+}
+
+#[test]
+fn test_debug_commands() {
+    let gammas = Gammas::new();
+    let mut gtp = gtp(&gammas);
+    gtp.response("boardsize 5");
+    let empty_hash = gtp.response("gg-hash").unwrap();
+    gtp.response("play b c3");
+    gtp.response("play w c4");
+    gtp.response("play b d4");
+    assert_ne!(gtp.response("gg-hash").unwrap(), empty_hash);
+
+    assert_eq!(
+        gtp.response("gg-chain-info c3").unwrap(),
+        "= color black\nsize 1\nliberties 3\npseudo_liberties 3\natari none\n\
+         stones C3\nliberty_list B3 D3 C2\n\n"
+    );
+    assert_eq!(
+        gtp.response("gg-chain-info c4").unwrap(),
+        "= color white\nsize 1\nliberties 2\npseudo_liberties 2\natari none\n\
+         stones C4\nliberty_list C5 B4\n\n"
+    );
+    assert_eq!(
+        gtp.response("gg-chain-info a1").unwrap(),
+        "? no stone at vertex\n\n"
+    );
+
+    let maps = gtp.response("gg-showboard-maps").unwrap();
+    assert!(maps.starts_with("=\ncolor_at:\n. . . . .\n. . O # .\n. . # . .\n"));
+    assert!(maps.contains("chain.size:\n"));
+
+    let ownership = gtp.response("gg-ownership 10").unwrap();
+    let rows: Vec<&str> = ownership.trim().lines().skip(1).collect();
+    assert_eq!(rows.len(), 5);
+    for row in rows {
+        let values: Vec<f64> = row.split_whitespace().map(|x| x.parse().unwrap()).collect();
+        assert_eq!(values.len(), 5);
+        assert!(values.iter().all(|x| (-1.0..=1.0).contains(x)));
+    }
+    assert_eq!(
+        gtp.response("gg-ownership 0").unwrap(),
+        "? need at least one playout\n\n"
+    );
+}
+
+#[test]
+fn test_score_commands() {
+    let gammas = Gammas::new();
+    let mut gtp = gtp(&gammas);
+    gtp.response("boardsize 5");
+    gtp.response("komi 0.5");
+    gtp.response("play b c3");
+    gtp.response("play w pass");
+    gtp.response("play b pass");
+    // Finished, every playout ends at once, empty points are nobody's
+    assert_eq!(gtp.response("final_score").unwrap(), "= B+0.5\n\n");
+    assert_eq!(
+        gtp.response("gg-score-estimate 10").unwrap(),
+        "= mean 0.50\nstddev 0.00\n\n"
+    );
+    assert_eq!(
+        gtp.response("gg-score-estimate 0").unwrap(),
+        "? need at least one playout\n\n"
+    );
+
+    gtp.response("clear_board");
+    gtp.response("komi 30.5");
+    gtp.response("play b pass");
+    gtp.response("play w pass");
+    assert_eq!(gtp.response("final_score").unwrap(), "= W+30.5\n\n");
+}
+
+#[test]
+fn test_showboard_stats() {
+    let gammas = Gammas::new();
+    let mut gtp = gtp(&gammas);
+    gtp.response("boardsize 5");
+    for mv in [
+        "b a4", "w c5", "b b5", "w c3", "b b3", "w d4", "b e1", "w b4", "b c4",
+    ] {
+        assert_eq!(gtp.response(&format!("play {}", mv)).unwrap(), "=\n\n");
+    }
+    let board = gtp.response("showboard").unwrap();
+    assert!(board.starts_with("=\n 5 . # O . .\n 4 # . # O .\n"));
+    assert!(board.ends_with("   A B C D E\nmove 9, captured by black 1, by white 0, ko B4\n\n"));
+
+    let one_color = gtp.response("gg-showboard-one-color").unwrap();
+    assert!(one_color.starts_with("=\n 5 . X X . .\n 4 X . X X .\n"));
+    assert_eq!(one_color.len(), board.len());
+}
+
+#[test]
+fn test_boardsize_resizes_in_place() {
+    let gammas = Gammas::new();
+    let mut gtp = gtp(&gammas);
+    gtp.response("komi 5.5");
+    gtp.response("play b d4");
+    assert_eq!(gtp.response("boardsize 7").unwrap(), "=\n\n");
+    assert_eq!(gtp.game().board().width(), 7);
+    assert!(gtp.game().moves().is_empty());
+    assert_eq!(gtp.game().rules().komi, 5.5);
+    assert_eq!(gtp.response("play b g7").unwrap(), "=\n\n");
+
+    let too_large = format!("boardsize {}", MAX_BOARD_SIZE + 1);
+    assert_eq!(gtp.response(&too_large).unwrap(), "? unacceptable size\n\n");
+    assert_eq!(gtp.game().board().height(), 7);
+    assert_eq!(gtp.game().moves().len(), 1);
+
+    for komi in ["inf", "nan", "1e40"] {
+        let response = gtp.response(&format!("komi {}", komi)).unwrap();
+        assert!(response.starts_with("? komi "), "{}", response);
+    }
+    assert_eq!(gtp.game().rules().komi, 5.5);
+    assert_eq!(gtp.game().board().komi(), 5.5);
+}
+
+#[test]
+fn test_loadsgf_reg_genmove_and_undo() {
+    let gammas = Gammas::new();
+    let mut gtp = gtp(&gammas);
+    let path = std::env::temp_dir().join(format!("gtp_loadsgf_{}.sgf", std::process::id()));
+    std::fs::write(&path, "(;SZ[5]KM[2.5]AB[aa]PL[W];W[cc];B[dd];W[bb])").unwrap();
+    let path = path.to_str().unwrap().to_string();
+
+    // Before move 3, the third move is next
+    assert_eq!(
+        gtp.response(&format!("loadsgf {} 3", path)).unwrap(),
+        "= white\n\n"
+    );
+    assert_eq!(gtp.game().board().width(), 5);
+    assert_eq!(gtp.game().rules().komi, 2.5);
+    assert_eq!(gtp.game().moves().len(), 2);
+    let hash = gtp.response("gg-hash").unwrap();
+
+    // reg_genmove leaves the position alone, for either color
+    for color in ["white", "black"] {
+        let reply = gtp.response(&format!("reg_genmove {}", color)).unwrap();
+        assert!(reply.starts_with("= "), "{}", reply);
+        assert_eq!(gtp.game().moves().len(), 2);
+    }
+    assert_eq!(gtp.response("gg-hash").unwrap(), hash);
+
+    assert_eq!(
+        gtp.response(&format!("loadsgf {}", path)).unwrap(),
+        "= black\n\n"
+    );
+    assert_eq!(gtp.response("undo").unwrap(), "=\n\n");
+    assert_eq!(gtp.response("gg-hash").unwrap(), hash);
+    assert_eq!(gtp.game().board().act_player(), Player::White);
+    assert_eq!(gtp.response("undo").unwrap(), "=\n\n");
+    assert_eq!(gtp.response("undo").unwrap(), "=\n\n");
+    assert_eq!(gtp.response("undo").unwrap(), "? cannot undo\n\n");
+    // Back at the setup stone, white to move
+    let board = gtp.response("showboard").unwrap();
+    assert!(board.starts_with("=\n 5 # . . . .\n"), "{}", board);
+    assert_eq!(gtp.game().board().act_player(), Player::White);
+
+    // Komi keeps the loaded position
+    assert_eq!(gtp.response("komi 6.5").unwrap(), "=\n\n");
+    assert_eq!(gtp.game().board().stone_count(Player::Black), 1);
+
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        gtp.response(&format!("loadsgf {}", path)).unwrap(),
+        "? cannot load file\n\n"
+    );
+}
+
+#[test]
+fn test_lz_analyze() {
+    let gammas = Gammas::new();
+    let mc_gtp = || {
+        let mut gtp = Gtp::new(
+            &gammas,
+            Box::new(MonteCarloEngine::new(&gammas, 300, 7)),
+            Rules::chinese(),
+        );
+        gtp.response("boardsize 5");
+        gtp.response("play b c3");
+        gtp
+    };
+    let mut gtp = mc_gtp();
+    let response = gtp.response("lz-analyze w 10").unwrap();
+    assert!(response.starts_with("=\ninfo move "), "{}", response);
+    let infos: Vec<&str> = response.trim_end().split("info ").skip(1).collect();
+    assert!(infos.len() > 1 && infos.len() <= 24);
+    for (order, info) in infos.iter().enumerate() {
+        let words: Vec<&str> = info.split_whitespace().collect();
+        assert_eq!(words[0], "move");
+        assert_eq!(words[2], "visits");
+        let winrate: u32 = words[5].parse().unwrap();
+        assert!(winrate <= 10000);
+        assert_eq!(words[7], order.to_string());
+        // 1-ply variations
+        assert_eq!(words[8..], ["pv", words[1]]);
+    }
+    // Nothing played, the best move is the one genmove picks
+    assert_eq!(gtp.game().moves().len(), 1);
+    let best = infos[0].split_whitespace().nth(1).unwrap();
+    assert_eq!(
+        mc_gtp().response("reg_genmove w").unwrap(),
+        format!("= {}\n\n", best)
+    );
+
+    let kata = gtp.response("kata-analyze interval 50").unwrap();
+    assert!(kata.contains(" winrate 0."), "{}", kata);
+    assert_eq!(
+        gtp.response("lz-analyze x").unwrap(),
+        "? invalid argument x\n\n"
+    );
+    // Engines without a search have nothing to show
+    assert_eq!(
+        Gtp::new(
+            &gammas,
+            Box::new(SamplerEngine::new(&gammas, 1)),
+            Rules::chinese()
+        )
+        .response("lz-analyze")
+        .unwrap(),
+        "=\n\n"
+    );
+}
+
+#[test]
+fn test_byo_yomi_game() {
+    let gammas = Gammas::new();
+    let mut gtp = Gtp::new(
+        &gammas,
+        Box::new(MonteCarloEngine::new(&gammas, 1000, 7)),
+        Rules::chinese(),
+    );
+    gtp.response("boardsize 5");
+    assert!(gtp.clock(Player::Black).is_none());
+    assert_eq!(
+        gtp.response("kgs-time_settings byoyomi 0 1 3").unwrap(),
+        "=\n\n"
+    );
+    let control = TimeControl::ByoYomi {
+        main_time: Duration::ZERO,
+        period_time: Duration::from_secs(1),
+        periods: 3,
+    };
+    assert_eq!(gtp.clock(Player::White).unwrap().control(), control);
+
+    // Every move fits in one period, none is lost
+    for color in ["b", "w", "b", "w"] {
+        let start = Instant::now();
+        assert!(gtp
+            .response(&format!("genmove {}", color))
+            .unwrap()
+            .starts_with("= "));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    assert_eq!(gtp.game().moves().len(), 4);
+    for pl in [Player::Black, Player::White] {
+        let clock = gtp.clock(pl).unwrap();
+        assert!(!clock.is_expired());
+        assert_eq!(clock.main_time_left(), Duration::ZERO);
+    }
+
+    // The controller's clock wins
+    gtp.response("time_left b 30 0");
+    assert_eq!(
+        gtp.clock(Player::Black).unwrap().main_time_left(),
+        Duration::from_secs(30)
+    );
+    gtp.response("clear_board");
+    assert_eq!(
+        gtp.clock(Player::Black).unwrap().main_time_left(),
+        Duration::ZERO
+    );
+
+    // Standard time_settings: absolute, no limit, Canadian
+    gtp.response("time_settings 60 0 0");
+    assert_eq!(
+        gtp.clock(Player::Black).unwrap().control(),
+        TimeControl::Absolute {
+            main_time: Duration::from_secs(60)
+        }
+    );
+    gtp.response("time_settings 0 10 0");
+    assert!(gtp.clock(Player::Black).is_none());
+    gtp.response("time_settings 0 10 5");
+    assert!(matches!(
+        gtp.clock(Player::Black).unwrap().control(),
+        TimeControl::Canadian {
+            period_stones: 5,
+            ..
+        }
+    ));
+    assert_eq!(
+        gtp.response("kgs-time_settings fischer 1 2").unwrap(),
+        "? invalid argument fischer\n\n"
+    );
+}
+
+#[test]
+fn test_state_carries_over_to_new_gammas() {
+    let gammas = Gammas::new();
+    let mut gtp = gtp(&gammas);
+    let path = std::env::temp_dir().join(format!("gtp_state_{}.sgf", std::process::id()));
+    std::fs::write(&path, "(;SZ[7:5]KM[2.5]AB[aa][bb]PL[W];W[cc])").unwrap();
+    gtp.response(&format!("loadsgf {}", path.to_str().unwrap()));
+    gtp.response("kgs-time_settings absolute 600");
+    // Out of turn, white is to move twice
+    gtp.response("genmove w");
+    let board = gtp.response("showboard").unwrap();
+    let hash = gtp.response("gg-hash").unwrap();
+
+    let reloaded = Gammas::new();
+    let mut next = Gtp::new(
+        &reloaded,
+        Box::new(SamplerEngine::new(&reloaded, 1)),
+        Rules::japanese(),
+    );
+    next.restore(gtp.into_state());
+    assert_eq!(next.response("showboard").unwrap(), board);
+    assert_eq!(next.response("gg-hash").unwrap(), hash);
+    assert_eq!(next.game().moves().len(), 2);
+    assert_eq!(next.game().board().act_player(), Player::Black);
+    assert_eq!(
+        (next.game().board().width(), next.game().board().height()),
+        (7, 5)
+    );
+    let mut rules = Rules::chinese();
+    rules.komi = 2.5;
+    assert_eq!(next.game().rules(), &rules);
+    assert!(next.clock(Player::White).unwrap().main_time_left() < Duration::from_secs(600));
+
+    // Undo goes back to the setup stones
+    assert_eq!(next.response("undo").unwrap(), "=\n\n");
+    assert_eq!(next.response("undo").unwrap(), "=\n\n");
+    assert_eq!(next.response("undo").unwrap(), "? cannot undo\n\n");
+    assert_eq!(
+        next.game().board().color_at(Vertex::from_coords(1, 1)),
+        Color::Black
+    );
+}
@@ -0,0 +1,56 @@
+use go_game_board::lockstep::compare_with_scalar;
+use go_game_board::{Board, Gammas, LockstepPlayouts, Player, Playouts, Vertex};
+
+#[test]
+fn test_one_lane_matches_scalar_playouts() {
+    let gammas = Gammas::new();
+    let start = Board::with_size(9, 9);
+    let mut playouts = Playouts::new(&gammas, 5);
+    let scalar: Vec<_> = (0..50).map(|_| playouts.run(&start, &gammas)).collect();
+    let mut lockstep = LockstepPlayouts::new(&gammas, 1, 5).unwrap();
+    assert_eq!(lockstep.run(&start, &gammas, 50), scalar);
+}
+
+#[test]
+fn test_lanes_run_all_playouts() {
+    let gammas = Gammas::new();
+    let mut start = Board::with_size(9, 9);
+    start.play_legal(Player::Black, Vertex::from_coords(4, 4));
+    let mut lockstep = LockstepPlayouts::new(&gammas, 8, 5).unwrap();
+    assert_eq!(lockstep.lane_count(), 8);
+    for playout_cnt in [0, 3, 8, 101] {
+        let results = lockstep.run(&start, &gammas, playout_cnt);
+        assert_eq!(results.len(), playout_cnt);
+        assert!(results
+            .iter()
+            .all(|r| r.move_cnt > 0 && r.first_v != Vertex::none()));
+    }
+
+    // Finished start positions give empty playouts
+    start.play_legal(Player::White, Vertex::pass());
+    start.play_legal(Player::Black, Vertex::pass());
+    let results = lockstep.run(&start, &gammas, 20);
+    assert_eq!(results.len(), 20);
+    assert!(results.iter().all(|r| r.move_cnt == 0));
+}
+
+#[test]
+fn test_zero_lanes_are_rejected() {
+    let gammas = Gammas::new();
+    assert!(LockstepPlayouts::new(&gammas, 0, 5).is_err());
+    assert!(compare_with_scalar(9, 9, &gammas, 0, 10).is_err());
+}
+
+#[test]
+#[ignore] // Run with cargo test --release -- --ignored
+fn lockstep_performance() {
+    let gammas = Gammas::new();
+    for (size, playout_cnt) in [(9, 100000), (19, 10000)] {
+        for lane_cnt in [4, 8, 16] {
+            println!(
+                "{}",
+                compare_with_scalar(size, size, &gammas, lane_cnt, playout_cnt).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use go_game_board::match_runner::{
+    play_game_with_resign, run_match, MatchConfig, SprtConfig, SprtDecision,
+};
+use go_game_board::{
+    Board, Engine, Gammas, MonteCarloEngine, Player, ResignPolicy, ResignTracker, SamplerEngine,
+    Vertex,
+};
+
+#[test]
+fn test_monte_carlo_beats_sampler() {
+    let gammas = Gammas::new();
+    let mut mc = MonteCarloEngine::new(&gammas, 64, 1);
+    let mut sampler = SamplerEngine::new(&gammas, 2);
+    let config = MatchConfig {
+        width: 5,
+        height: 5,
+        max_games: 200,
+        sprt: Some(SprtConfig {
+            elo0: 0.0,
+            elo1: 200.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }),
+        resign: None,
+    };
+
+    let report = run_match(&mut mc, &mut sampler, &config);
+    println!("{}", report);
+    assert_eq!(report.decision, SprtDecision::AcceptH1);
+    assert!(report.elo().unwrap().0 > 0.0);
+}
+
+#[test]
+fn test_match_without_games() {
+    let gammas = Gammas::new();
+    let mut a = SamplerEngine::new(&gammas, 1);
+    let mut b = SamplerEngine::new(&gammas, 2);
+    let config = MatchConfig {
+        max_games: 0,
+        ..MatchConfig::default()
+    };
+    let report = run_match(&mut a, &mut b, &config);
+    assert_eq!(report.game_cnt, 0);
+    assert_eq!(report.a_score(), None);
+    assert_eq!(report.elo(), None);
+    assert!(report.to_string().ends_with("0 games: 0 - 0"));
+}
+
+#[test]
+fn test_resign_tracker() {
+    let policy = ResignPolicy {
+        threshold: 0.2,
+        consecutive_moves: 2,
+        min_move_no: 10,
+    };
+    let mut tracker = ResignTracker::new(policy);
+    assert!(!tracker.should_resign(4, Some(0.0)));
+    assert!(!tracker.should_resign(6, Some(0.0)));
+    assert!(!tracker.should_resign(10, Some(0.1)));
+    assert!(!tracker.should_resign(12, Some(0.5)));
+    assert!(!tracker.should_resign(14, Some(0.1)));
+    assert!(!tracker.should_resign(16, None));
+    assert!(!tracker.should_resign(18, Some(0.1)));
+    assert!(tracker.should_resign(20, Some(0.1)));
+}
+
+// Passes and always thinks it is lost
+struct Hopeless;
+
+impl Engine for Hopeless {
+    fn name(&self) -> String {
+        "hopeless".to_string()
+    }
+
+    fn genmove(&mut self, _board: &Board) -> Vertex {
+        Vertex::pass()
+    }
+
+    fn last_winrate(&self) -> Option<f64> {
+        Some(0.0)
+    }
+}
+
+#[test]
+fn test_hopeless_engine_resigns() {
+    let gammas = Gammas::new();
+    let mut sampler = SamplerEngine::new(&gammas, 3);
+    let policy = ResignPolicy {
+        threshold: 0.1,
+        consecutive_moves: 3,
+        min_move_no: 0,
+    };
+    let winner = play_game_with_resign(
+        &mut Hopeless,
+        &mut sampler,
+        Player::Black,
+        5,
+        5,
+        Some(policy),
+    );
+    assert_eq!(winner, Player::White);
+
+    let mut mc = MonteCarloEngine::new(&gammas, 16, 1);
+    let board = Board::with_size(5, 5);
+    mc.genmove(&board);
+    let winrate = mc.last_winrate().unwrap();
+    assert!((0.0..=1.0).contains(&winrate));
+}
+
+#[test]
+fn test_deterministic_engine_ignores_thread_count() {
+    let gammas = Gammas::new();
+    let mut board = Board::with_size(7, 7);
+    let mut single = MonteCarloEngine::new(&gammas, 200, 3);
+    single.set_deterministic(true);
+    let mut threaded = MonteCarloEngine::new(&gammas, 200, 3);
+    threaded.set_deterministic(true);
+    threaded.set_thread_count(4);
+    for _ in 0..4 {
+        let v = single.genmove(&board);
+        assert_eq!(threaded.genmove(&board), v);
+        assert_eq!(threaded.last_winrate(), single.last_winrate());
+        board.play_legal(board.act_player(), v);
+    }
+}
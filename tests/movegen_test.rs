@@ -0,0 +1,117 @@
+use go_game_board::tactics::is_ladder_captured;
+use go_game_board::{
+    ordered_local_moves, urgent_moves, Board, Gammas, Player, UrgentKind, Vertex, VertexNeighbors,
+};
+
+fn v(row: isize, col: isize) -> Vertex {
+    Vertex::from_coords(row, col)
+}
+
+fn board_with(black: &[(isize, isize)], white: &[(isize, isize)]) -> Board {
+    let mut board = Board::with_size(9, 9);
+    let to_vertices =
+        |coords: &[(isize, isize)]| coords.iter().map(|&(r, c)| v(r, c)).collect::<Vec<_>>();
+    board.setup(Player::Black, &to_vertices(black)).unwrap();
+    board.setup(Player::White, &to_vertices(white)).unwrap();
+    board
+}
+
+#[test]
+fn test_ladder() {
+    // Black can chase towards either corner, so both need a breaker
+    let ladder = board_with(&[(3, 4), (4, 3), (5, 5)], &[(4, 4)]);
+    assert_eq!(is_ladder_captured(&ladder, v(4, 4), 1000), Some(true));
+
+    let one_breaker = board_with(&[(3, 4), (4, 3), (5, 5)], &[(4, 4), (6, 2)]);
+    assert_eq!(is_ladder_captured(&one_breaker, v(4, 4), 1000), Some(true));
+
+    let broken = board_with(&[(3, 4), (4, 3), (5, 5)], &[(4, 4), (6, 2), (2, 6)]);
+    assert_eq!(is_ladder_captured(&broken, v(4, 4), 1000), Some(false));
+
+    // Out of budget counts as escaped
+    assert_eq!(is_ladder_captured(&ladder, v(4, 4), 2), Some(false));
+
+    // Empty point
+    assert_eq!(is_ladder_captured(&ladder, v(0, 0), 1000), None);
+}
+
+#[test]
+fn test_captures_and_escapes() {
+    // White (4,4) in atari; black (2,2) in atari with an escape to three liberties
+    let board = board_with(
+        &[(3, 4), (4, 3), (5, 4), (2, 2)],
+        &[(4, 4), (1, 2), (2, 1), (3, 2)],
+    );
+    let black = urgent_moves(&board, Player::Black);
+    assert_eq!(black[0].v, v(2, 3));
+    assert_eq!(black[0].kind, UrgentKind::AtariEscape);
+    assert!(black
+        .iter()
+        .any(|m| m.v == v(4, 5) && m.kind == UrgentKind::Capture && m.size == 1));
+
+    let white = urgent_moves(&board, Player::White);
+    let kinds: Vec<_> = white.iter().map(|m| (m.v, m.kind)).collect();
+    assert_eq!(
+        kinds,
+        [
+            (v(4, 5), UrgentKind::AtariEscape),
+            (v(2, 3), UrgentKind::Capture)
+        ]
+    );
+}
+
+#[test]
+fn test_nakade_vital_point() {
+    // Straight three eye in the corner
+    let board = board_with(&[(0, 3), (1, 0), (1, 1), (1, 2), (1, 3)], &[]);
+    let white = urgent_moves(&board, Player::White);
+    assert_eq!(white.len(), 1);
+    assert_eq!(white[0].v, v(0, 1));
+    assert_eq!(white[0].kind, UrgentKind::NakadeVital);
+    assert_eq!(urgent_moves(&board, Player::Black)[0].v, v(0, 1));
+}
+
+#[test]
+fn test_ordered_local_moves() {
+    let board = board_with(
+        &[(3, 4), (4, 3), (5, 4), (2, 2)],
+        &[(4, 4), (1, 2), (2, 1), (3, 2)],
+    );
+    let region: Vec<Vertex> = (1..=5)
+        .flat_map(|row| (1..=5).map(move |col| v(row, col)))
+        .collect();
+    let black = ordered_local_moves(&board, &region, Player::Black, None);
+    // The capture, then the escape
+    assert_eq!(black[..2], [v(4, 5), v(2, 3)]);
+    assert!(black.iter().all(|&m| board.is_legal(Player::Black, m)));
+    assert_eq!(
+        black.len(),
+        region
+            .iter()
+            .filter(|&&m| board.is_legal(Player::Black, m))
+            .count()
+    );
+    let white = ordered_local_moves(&board, &region, Player::White, None);
+    assert_eq!(white[..2], [v(2, 3), v(4, 5)]);
+
+    // Next to the last move first, same order with uniform gammas
+    let mut board = Board::with_size(9, 9);
+    board.play_legal(Player::Black, v(4, 4));
+    let white = ordered_local_moves(&board, &region, Player::White, None);
+    let mut near: Vec<Vertex> = v(4, 4).neighbors8().collect();
+    near.sort_by_key(|&m| usize::from(m));
+    assert_eq!(white[..8], near[..]);
+    let gammas = Gammas::new();
+    assert_eq!(
+        ordered_local_moves(&board, &region, Player::White, Some(&gammas)),
+        white
+    );
+
+    // Ataris before the other moves next to the last move
+    board.play_legal(Player::White, v(3, 4));
+    board.play_legal(Player::Black, v(0, 0));
+    board.play_legal(Player::White, v(4, 3));
+    board.play_legal(Player::Black, v(8, 8));
+    let white = ordered_local_moves(&board, &region, Player::White, None);
+    assert_eq!(white[..2], [v(4, 5), v(5, 4)]);
+}
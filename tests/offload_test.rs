@@ -0,0 +1,119 @@
+use go_game_board::offload::{BLACK_POINT, EMPTY_POINT, NO_KO, PASS_CHOICE, WHITE_POINT};
+use go_game_board::{
+    apply_choices, Board, BoardError, Color, Hash3x3, Player, PlayoutBatch, Vertex,
+};
+
+fn v(row: isize, col: isize) -> Vertex {
+    Vertex::from_coords(row, col)
+}
+
+#[test]
+fn test_export_flat_state() {
+    let mut ko = Board::with_size(5, 5);
+    ko.setup(Player::Black, &[v(1, 0), v(0, 1), v(2, 1)])
+        .unwrap();
+    ko.setup(Player::White, &[v(0, 2), v(2, 2), v(1, 3), v(1, 1)])
+        .unwrap();
+    ko.play_legal(Player::Black, v(1, 2));
+    let boards = [Board::with_size(5, 5), ko];
+    let batch = PlayoutBatch::of_boards(&boards).unwrap();
+
+    assert_eq!(batch.board_count(), 2);
+    assert_eq!(batch.colors.len(), 50);
+    assert_eq!(batch.to_move, [0, 1]);
+    assert_eq!(batch.ko, [NO_KO, batch.point_of(v(1, 1))]);
+    assert_eq!(batch.board_empties(0).len(), 25);
+    assert_eq!(batch.board_empties(1).len(), 25 - 7);
+    assert_eq!(batch.empties.len(), batch.patterns.len());
+
+    let second = &batch.colors[25..];
+    assert_eq!(second[batch.point_of(v(1, 2)) as usize], BLACK_POINT);
+    assert_eq!(second[batch.point_of(v(0, 2)) as usize], WHITE_POINT);
+    assert_eq!(second[batch.point_of(v(1, 1)) as usize], EMPTY_POINT);
+    let offset = batch.empty_offsets[1] as usize;
+    for (idx, &point) in batch.board_empties(1).iter().enumerate() {
+        let empty_v = batch.vertex_of(point).unwrap();
+        assert_eq!(boards[1].color_at(empty_v), Color::Empty);
+        assert_eq!(
+            Hash3x3::from(batch.patterns[offset + idx] as usize),
+            boards[1].hash3x3_at(empty_v)
+        );
+    }
+
+    let mut batch = batch;
+    batch.clear();
+    assert_eq!(batch.board_count(), 0);
+    assert_eq!(
+        batch.push(&Board::with_size(9, 9)),
+        Err(BoardError::BadSize {
+            width: 9,
+            height: 9
+        })
+    );
+}
+
+#[test]
+fn test_apply_external_choices() {
+    let mut boards = vec![Board::with_size(5, 5); 3];
+    let batch = PlayoutBatch::of_boards(&boards).unwrap();
+    let center = batch.point_of(v(2, 2));
+    apply_choices(&mut boards, &[center, PASS_CHOICE, 0]).unwrap();
+    assert_eq!(boards[0].color_at(v(2, 2)), Color::Black);
+    assert_eq!(boards[1].last_vertex(), Vertex::pass());
+    assert_eq!(boards[2].color_at(v(0, 0)), Color::Black);
+    assert!(boards.iter().all(|b| b.act_player() == Player::White));
+
+    assert_eq!(
+        apply_choices(&mut boards, &[1, center, 0]),
+        Err((2, BoardError::Occupied(v(0, 0))))
+    );
+    assert_eq!(boards[1].color_at(v(2, 2)), Color::White);
+    assert_eq!(boards[0].color_at(v(0, 1)), Color::White);
+    assert!(matches!(
+        apply_choices(&mut boards[2..], &[25]),
+        Err((0, BoardError::BadCoords { row: 5, column: 0 }))
+    ));
+
+    let before = boards.clone();
+    assert_eq!(
+        apply_choices(&mut boards, &[PASS_CHOICE, PASS_CHOICE]),
+        Err((
+            2,
+            BoardError::ChoiceCount {
+                boards: 3,
+                choices: 2
+            }
+        ))
+    );
+    assert!(boards == before);
+}
+
+// A whole playout chosen outside of Board from the flat buffers only
+#[test]
+fn test_external_playout_loop() {
+    let mut boards = vec![Board::with_size(5, 5); 4];
+    let mut batch = PlayoutBatch::new(5, 5);
+    for _ in 0..200 {
+        batch.clear();
+        for board in boards.iter() {
+            batch.push(board).unwrap();
+        }
+        let choices: Vec<u32> = (0..boards.len())
+            .map(|idx| {
+                let board = &boards[idx];
+                batch
+                    .board_empties(idx)
+                    .iter()
+                    .copied()
+                    .find(|&point| {
+                        let empty_v = batch.vertex_of(point).unwrap();
+                        board.is_legal(board.act_player(), empty_v)
+                            && !board.hash3x3_at(empty_v).is_eyelike(board.act_player())
+                    })
+                    .unwrap_or(PASS_CHOICE)
+            })
+            .collect();
+        apply_choices(&mut boards, &choices).unwrap();
+    }
+    assert!(boards.iter().all(|board| board.both_player_pass()));
+}
@@ -0,0 +1,101 @@
+use go_game_board::sgf;
+use go_game_board::{Board, Hash3x3, Nat, PatternStats, Player, Vertex};
+
+#[test]
+fn test_sgf_main_line() {
+    let text = "(;GM[1]SZ[9]KM[6.5];B[ee];W[gc](;B[cg];W[]) (;B[aa]))";
+    let game = sgf::parse_game(text).unwrap();
+    assert_eq!((game.width, game.height), (9, 9));
+    assert_eq!(game.komi, 6.5);
+    assert_eq!(game.moves.len(), 4);
+    assert_eq!(game.moves[0].player, Player::Black);
+    assert_eq!(game.moves[0].vertex, Vertex::from_coords(4, 4));
+    assert_eq!(game.moves[1].vertex, Vertex::from_coords(2, 6));
+    assert_eq!(game.moves[2].vertex, Vertex::from_coords(6, 2));
+    assert_eq!(game.moves[3].vertex, Vertex::pass());
+
+    assert!(sgf::parse_game("(;SZ[9];B[ee]").is_err());
+    assert!(sgf::parse_game("(;SZ[9];B[zz])").is_err());
+}
+
+#[test]
+fn test_canonical_is_symmetry_invariant() {
+    for raw in (0..Hash3x3::COUNT).step_by(997) {
+        let hash = Hash3x3::from(raw);
+        let canonical = hash.canonical();
+        for sym in hash.symmetries() {
+            assert_eq!(sym.canonical(), canonical);
+        }
+        assert_eq!(hash.rotated().rotated().rotated().rotated(), hash);
+        assert_eq!(hash.mirrored().mirrored(), hash);
+    }
+}
+
+#[test]
+fn test_pattern_stats_counts() {
+    let game = sgf::parse_game("(;SZ[5];B[cc];W[bc];B[])").unwrap();
+    let mut stats = PatternStats::new();
+    assert_eq!(stats.add_game(&game), Ok(3));
+    assert_eq!(stats.position_count(), 2);
+
+    // First move: every vertex of the empty board was available
+    let empty = Board::with_size(5, 5);
+    let center = stats.get(empty.hash3x3_at(Vertex::from_coords(2, 2)), Player::Black);
+    assert_eq!(center.played, 1);
+    assert_eq!(center.available, 9);
+
+    let total: u64 = stats.entries().iter().map(|(_, _, c)| c.available).sum();
+    assert_eq!(total, 25 + 24);
+}
+
+#[test]
+fn test_pattern_stats_counts_5x5() {
+    let game = sgf::parse_game("(;SZ[5];B[cc];W[bc];B[])").unwrap();
+    let mut stats = PatternStats::new();
+    stats.add_game(&game).unwrap();
+
+    // The center is the only point whose 5x5 area lies on the empty board
+    let empty = Board::with_size(5, 5);
+    let center = stats.get_context(
+        empty.canonical_context_hash_at(Vertex::from_coords(2, 2)),
+        Player::Black,
+    );
+    assert_eq!(center.played, 1);
+    assert_eq!(center.available, 1);
+
+    let total: u64 = stats
+        .context_entries()
+        .iter()
+        .map(|(_, _, c)| c.available)
+        .sum();
+    assert_eq!(total, 25 + 24);
+}
+
+#[test]
+fn test_canonical_context_hash_is_symmetry_invariant() {
+    let v = |row, col| Vertex::from_coords(row, col);
+    let mut board = Board::with_size(9, 9);
+    board.play_legal(Player::Black, v(1, 2));
+    board.play_legal(Player::White, v(2, 3));
+    let hash = board.canonical_context_hash_at(v(1, 1));
+
+    // The same shape reflected into the other corners
+    let mut mirrored = Board::with_size(9, 9);
+    mirrored.play_legal(Player::Black, v(1, 6));
+    mirrored.play_legal(Player::White, v(2, 5));
+    assert_ne!(
+        mirrored.context_hash_at(v(1, 7)),
+        board.context_hash_at(v(1, 1))
+    );
+    assert_eq!(mirrored.canonical_context_hash_at(v(1, 7)), hash);
+    let mut transposed = Board::with_size(9, 9);
+    transposed.play_legal(Player::Black, v(2, 1));
+    transposed.play_legal(Player::White, v(3, 2));
+    assert_eq!(transposed.canonical_context_hash_at(v(1, 1)), hash);
+
+    // Different colors give a different pattern
+    let mut swapped = Board::with_size(9, 9);
+    swapped.play_legal(Player::White, v(1, 2));
+    swapped.play_legal(Player::Black, v(2, 3));
+    assert_ne!(swapped.canonical_context_hash_at(v(1, 1)), hash);
+}
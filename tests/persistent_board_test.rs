@@ -0,0 +1,55 @@
+use go_game_board::{
+    Board, BoardError, FastRandom, Gammas, PersistentBoard, Player, Sampler, Vertex,
+};
+
+#[test]
+fn test_matches_board_in_playouts() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(7);
+    for _ in 0..20 {
+        let mut board = Board::with_size(7, 7);
+        let mut persistent = PersistentBoard::of_board(&board);
+        let mut sampler = Sampler::new(&board, &gammas);
+        sampler.new_playout(&board, &gammas);
+        while !board.both_player_pass() {
+            let v = sampler.sample_move(&board, &mut random);
+            persistent = persistent.play(v).unwrap();
+            board.play_legal(board.act_player(), v);
+            sampler.move_played(&board, &gammas);
+
+            assert_eq!(persistent.positional_hash(), board.positional_hash());
+            assert_eq!(persistent.ko_vertex(), board.ko_vertex());
+            assert_eq!(persistent.act_player(), board.act_player());
+        }
+        assert_eq!(persistent.to_board().snapshot().rows, board.snapshot().rows);
+    }
+}
+
+#[test]
+fn test_play_shares_untouched_rows() {
+    let start = PersistentBoard::new(9, 9).unwrap();
+    let next = start.play(Vertex::from_coords(4, 4)).unwrap();
+    assert_eq!(next.shared_row_count(&start), 8);
+    assert_eq!(
+        start.color_at(Vertex::from_coords(4, 4)),
+        go_game_board::Color::Empty
+    );
+    assert_eq!(next.act_player(), Player::White);
+
+    let passed = next.play(Vertex::pass()).unwrap();
+    assert_eq!(passed.shared_row_count(&next), 9);
+}
+
+#[test]
+fn test_illegal_moves() {
+    let board = PersistentBoard::new(3, 3).unwrap();
+    let board = board.play(Vertex::from_coords(0, 1)).unwrap();
+    assert_eq!(
+        board.play(Vertex::from_coords(0, 1)).err(),
+        Some(BoardError::Occupied(Vertex::from_coords(0, 1)))
+    );
+    let board = board.play(Vertex::from_coords(2, 2)).unwrap();
+    let board = board.play(Vertex::from_coords(1, 0)).unwrap();
+    assert!(!board.is_legal(Vertex::from_coords(0, 0)));
+    assert!(PersistentBoard::new(0, 3).is_err());
+}
@@ -0,0 +1,152 @@
+use go_game_board::{Board, Gammas, Nat, PlayoutPool, Player, Playouts, Vertex};
+
+#[test]
+fn test_batch_results_follow_position_order() {
+    let gammas = Gammas::new();
+    let mut pool = PlayoutPool::new(&gammas, 4, 7);
+
+    let mut finished = Board::new();
+    finished.play_legal(Player::Black, Vertex::pass());
+    finished.play_legal(Player::White, Vertex::pass());
+    let positions: Vec<Board> = (0..10)
+        .map(|i| if i % 3 == 0 { finished.clone() } else { Board::new() })
+        .collect();
+
+    let results = pool.run_batch(&positions, &gammas);
+    assert_eq!(results.len(), positions.len());
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result.first_v == Vertex::none(), i % 3 == 0);
+        assert_eq!(result.move_cnt == 0, i % 3 == 0);
+    }
+}
+
+#[test]
+fn test_run_many_splits_between_threads() {
+    let gammas = Gammas::new();
+    let mut pool = PlayoutPool::new(&gammas, 3, 7);
+    let results = pool.run_many(&Board::new(), 100, &gammas);
+    assert_eq!(results.len(), 100);
+    assert!(results.iter().all(|r| r.move_cnt > 0));
+    let black_wins = results.iter().filter(|r| r.winner == Player::Black).count();
+    assert!(black_wins > 0 && black_wins < 100);
+}
+
+#[test]
+fn test_play_heat_decay() {
+    let gammas = Gammas::new();
+    let mut playouts = Playouts::new(&gammas, 3);
+    let start = Board::with_size(5, 5);
+    let corner = Vertex::from_coords(0, 0);
+
+    playouts.run(&start, &gammas);
+    assert_eq!(playouts.play_heat()[corner], 0.0);
+
+    playouts.set_play_heat_decay(Some(0.5));
+    playouts.run(&start, &gammas);
+    let first = playouts.board().play_count_map()[corner] as f64;
+    assert_eq!(playouts.play_heat()[corner], first);
+    playouts.run(&start, &gammas);
+    let second = playouts.board().play_count_map()[corner] as f64;
+    assert_eq!(playouts.play_heat()[corner], first * 0.5 + second);
+}
+
+#[test]
+fn test_criticality_from_batch() {
+    let gammas = Gammas::new();
+    let mut pool = PlayoutPool::new(&gammas, 3, 9);
+    let start = Board::with_size(5, 5);
+    let (results, criticality) = pool.run_many_with_criticality(&start, 200, &gammas);
+    assert_eq!(results.len(), 200);
+    assert_eq!(criticality.playout_count(), 200);
+
+    let mut max_criticality: f64 = 0.0;
+    for row in 0..5 {
+        for col in 0..5 {
+            let v = Vertex::from_coords(row, col);
+            assert!((-1.0..=1.0).contains(&criticality.ownership(v)));
+            max_criticality = max_criticality.max(criticality.criticality(v));
+        }
+    }
+    // On an open board the outcome depends on who gets the center
+    assert!(max_criticality > 0.05);
+
+    // Off-board vertices are never owned and never critical
+    assert_eq!(criticality.criticality(Vertex::from_coords(-1, 0)), 0.0);
+}
+
+#[test]
+fn test_deterministic_mode_ignores_thread_count() {
+    let gammas = Gammas::new();
+    let start = Board::with_size(7, 7);
+    let run = |thread_cnt: usize| {
+        let mut pool = PlayoutPool::new(&gammas, thread_cnt, 5);
+        pool.set_deterministic(true);
+        // Two calls of different sizes continue the numbering
+        let (mut results, criticality) = pool.run_many_with_criticality(&start, 101, &gammas);
+        results.extend(pool.run_many(&start, 37, &gammas));
+        results.extend(pool.run_batch(&vec![start.clone(); 9], &gammas));
+        let ownership: Vec<u64> = Vertex::all()
+            .map(|v| criticality.ownership(v).to_bits() ^ criticality.criticality(v).to_bits())
+            .collect();
+        (results, ownership)
+    };
+
+    let (single, single_ownership) = run(1);
+    assert_eq!(single.len(), 147);
+    for thread_cnt in [2, 3, 8] {
+        let (results, ownership) = run(thread_cnt);
+        assert_eq!(results, single);
+        assert_eq!(ownership, single_ownership);
+    }
+
+    // Distinct playouts, not one seed repeated
+    let score_sum: i32 = single.iter().map(|r| r.score).sum();
+    assert_ne!(score_sum, single[0].score * single.len() as i32);
+}
+
+#[test]
+fn test_deterministic_pool_ignores_last_good_reply() {
+    let gammas = Gammas::new();
+    let start = Board::with_size(7, 7);
+    let run = |thread_cnt: usize, last_good_reply: bool| {
+        let mut pool = PlayoutPool::new(&gammas, thread_cnt, 5);
+        pool.set_last_good_reply(last_good_reply);
+        pool.set_deterministic(true);
+        let mut results = pool.run_many(&start, 200, &gammas);
+        results.extend(pool.run_many(&start, 200, &gammas));
+        results
+    };
+
+    let single = run(1, true);
+    assert_eq!(single, run(1, false));
+    for thread_cnt in [2, 4] {
+        assert_eq!(run(thread_cnt, true), single);
+    }
+}
+
+#[test]
+fn test_results_iterator_matches_run() {
+    let gammas = Gammas::new();
+    let board = Board::with_size(9, 9);
+    let mut by_run = Playouts::new(&gammas, 5);
+    let expected: Vec<_> = (0..3).map(|_| by_run.run(&board, &gammas)).collect();
+    let mut playouts = Playouts::new(&gammas, 5);
+    let results: Vec<_> = playouts.results(&board, &gammas).take(3).collect();
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn test_evaluate_is_win_rate_of_player_to_move() {
+    let gammas = Gammas::new();
+    let mut playouts = Playouts::new(&gammas, 3);
+    let mut board = Board::with_size(5, 5);
+    assert!((0.0..=1.0).contains(&playouts.evaluate(&board, &gammas, 100)));
+
+    board.set_komi(-100.0);
+    assert_eq!(playouts.evaluate(&board, &gammas, 50), 1.0);
+    board.play_legal(Player::Black, Vertex::pass());
+    assert_eq!(playouts.evaluate(&board, &gammas, 50), 0.0);
+    board.set_komi(0.0);
+    board.play_legal(Player::White, Vertex::pass());
+    assert_eq!(playouts.evaluate(&board, &gammas, 10), 0.5);
+}
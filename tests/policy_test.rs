@@ -0,0 +1,131 @@
+use go_game_board::{
+    Benchmark, Board, Color, Engine, FastRandom, Gammas, MogoPolicy, Nat, Player, PlayoutPolicy,
+    PolicyEngine, Sampler, SamplerPolicy, UniformPolicy, Vertex, VertexMap,
+};
+
+#[test]
+fn test_uniform_policy_playouts() {
+    let mut random = FastRandom::new(7);
+    let mut policy = UniformPolicy::new();
+    for _ in 0..20 {
+        let mut board = Board::with_size(9, 9);
+        policy.new_playout(&board);
+        while !board.both_player_pass() {
+            let pl = board.act_player();
+            let v = policy.sample(&board, &mut random);
+            if v != Vertex::pass() {
+                assert!(board.is_legal(pl, v));
+                assert!(!board.hash3x3_at(v).is_eyelike(pl));
+            }
+            board.play_legal(pl, v);
+            policy.move_played(&board);
+            assert!(board.move_count() < 1000);
+        }
+        // Only eyes are left
+        for pl in [Player::Black, Player::White] {
+            for v in board.empties() {
+                assert!(!board.is_legal(pl, v) || board.hash3x3_at(v).is_eyelike(pl));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_uniform_policy_covers_all_moves() {
+    let board = Board::with_size(5, 5);
+    let mut random = FastRandom::new(3);
+    let mut policy = UniformPolicy::new();
+    policy.new_playout(&board);
+    let mut counts = VertexMap::new_with(0);
+    for _ in 0..2500 {
+        counts[policy.sample(&board, &mut random)] += 1;
+    }
+    for v in Vertex::all().filter(|&v| board.color_at(v) == Color::Empty) {
+        assert!(
+            (50..150).contains(&counts[v]),
+            "{:?} sampled {} times",
+            v,
+            counts[v]
+        );
+    }
+}
+
+#[test]
+fn test_benchmark_with_policies() {
+    let gammas = Gammas::new();
+    let mut bench = Benchmark::new();
+    let sampler_stats = bench.run_stats(200);
+    let mut policy = SamplerPolicy::new(Sampler::new(&Board::new(), &gammas), &gammas);
+    let policy_stats = bench.run_stats_with(&mut policy, 200);
+    assert_eq!(policy_stats.move_cnt, sampler_stats.move_cnt);
+    assert_eq!(policy_stats.black_win_cnt, sampler_stats.black_win_cnt);
+
+    let mut uniform = UniformPolicy::new();
+    let uniform_stats = bench.run_stats_with(&mut uniform, 200);
+    assert_eq!(uniform_stats.playout_cnt, 200);
+    assert_eq!(
+        uniform_stats.black_win_cnt + uniform_stats.white_win_cnt,
+        200
+    );
+    assert!(uniform_stats.move_cnt > 200 * 81 / 2);
+
+    let result = bench.compare_with(&mut policy, &mut uniform, 100);
+    assert_eq!(result.game_cnt, 100);
+}
+
+#[test]
+fn test_policy_engine_plays_legal_moves() {
+    let mut engine = PolicyEngine::new(UniformPolicy::new(), 50, 1);
+    let mut board = Board::with_size(7, 7);
+    for _ in 0..10 {
+        let v = engine.genmove(&board);
+        assert_ne!(v, Vertex::pass());
+        assert!(board.is_legal(board.act_player(), v));
+        board.play_legal(board.act_player(), v);
+        let winrate = engine.last_winrate().unwrap();
+        assert!(winrate > 0.0 && winrate < 1.0);
+    }
+}
+
+#[test]
+fn test_mogo_patterns() {
+    let mut board = Board::with_size(9, 9);
+    let v = |row, col| Vertex::from_coords(row, col);
+    assert!(!MogoPolicy::is_pattern(board.hash3x3_at(v(4, 4))));
+
+    // Enclosing hane below, in any orientation and for both colors
+    board.setup(Player::Black, &[v(3, 3), v(3, 5)]).unwrap();
+    board.setup(Player::White, &[v(3, 4)]).unwrap();
+    assert!(MogoPolicy::is_pattern(board.hash3x3_at(v(4, 4))));
+    assert!(!MogoPolicy::is_pattern(board.hash3x3_at(v(5, 4))));
+    let mut rotated = Board::with_size(9, 9);
+    rotated.setup(Player::White, &[v(3, 3), v(5, 3)]).unwrap();
+    rotated.setup(Player::Black, &[v(4, 3)]).unwrap();
+    assert!(MogoPolicy::is_pattern(rotated.hash3x3_at(v(4, 4))));
+}
+
+#[test]
+fn test_mogo_policy_captures_first() {
+    let mut board = Board::with_size(9, 9);
+    let v = |row, col| Vertex::from_coords(row, col);
+    board.setup(Player::White, &[v(0, 0)]).unwrap();
+    board.setup(Player::Black, &[v(0, 1), v(6, 6)]).unwrap();
+    let mut policy = MogoPolicy::new();
+    let mut random = FastRandom::new(5);
+    policy.new_playout(&board);
+    assert_eq!(board.act_player(), Player::Black);
+    assert_eq!(policy.sample(&board, &mut random), v(1, 0));
+}
+
+#[test]
+fn test_mogo_policy_playouts() {
+    let mut bench = Benchmark::new();
+    let mut mogo = MogoPolicy::new();
+    let stats = bench.run_stats_with(&mut mogo, 100);
+    assert_eq!(stats.black_win_cnt + stats.white_win_cnt, 100);
+
+    let mut uniform = UniformPolicy::new();
+    let result = bench.compare_with(&mut mogo, &mut uniform, 200);
+    println!("Mogo vs uniform: {}", result);
+    assert!(result.a_win_rate().unwrap() > 0.6);
+}
@@ -0,0 +1,31 @@
+use go_game_board::sgf;
+use go_game_board::{Board, Gammas, Player, PredictionStats, Vertex};
+
+#[test]
+fn test_uniform_gammas_predict_like_random_guessing() {
+    let game = sgf::parse_game("(;SZ[5];B[cc])").unwrap();
+    let mut stats = PredictionStats::new();
+    assert_eq!(stats.add_game(&game, &Gammas::new()), Ok(1));
+
+    assert_eq!(stats.move_cnt, 1);
+    assert!((stats.top1_accuracy() - 1.0 / 25.0).abs() < 1e-12);
+    assert!((stats.top5_accuracy() - 5.0 / 25.0).abs() < 1e-12);
+    assert!((stats.mean_log_likelihood() - (1.0f64 / 25.0).ln()).abs() < 1e-12);
+}
+
+#[test]
+fn test_zero_probability_moves_and_no_moves() {
+    let stats = PredictionStats::new();
+    assert_eq!(stats.top1_accuracy(), 0.0);
+    assert_eq!(stats.mean_log_likelihood(), 0.0);
+
+    // The center of an empty board has the same pattern as all inner points
+    let mut gammas = Gammas::new();
+    let center = Board::with_size(5, 5).hash3x3_at(Vertex::from_coords(2, 2));
+    gammas.set(center, Player::Black, 0.0);
+    let mut stats = PredictionStats::new();
+    let game = sgf::parse_game("(;SZ[5];B[aa];W[ee];B[cc])").unwrap();
+    assert_eq!(stats.add_game(&game, &gammas), Ok(3));
+    assert_eq!(stats.zero_prob_cnt, 1);
+    assert_eq!(stats.mean_log_likelihood(), f64::NEG_INFINITY);
+}
@@ -0,0 +1,60 @@
+use go_game_board::{
+    run_regression, Gammas, Gtp, RegressionOutcome, RegressionSummary, Rules, SamplerEngine,
+};
+
+#[test]
+fn test_run_regression_file() {
+    let dir = std::env::temp_dir().join(format!("regress_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("pos.sgf"), "(;SZ[5];B[cc];W[dd])").unwrap();
+    let tst = "\
+# Setup, relative to the .tst file
+loadsgf pos.sgf 2
+1 name
+#? [go_game_board]
+2 loadsgf pos.sgf
+#? [black]
+3 reg_genmove black
+#? [!PASS|C3|D2]
+4 name
+#? [gnugo|other]
+5 name
+#? [gnugo]*
+6 name
+#? [GO_GAME_BOARD]*
+7 frobnicate
+#? [x]
+";
+
+    let gammas = Gammas::new();
+    let mut gtp = Gtp::new(
+        &gammas,
+        Box::new(SamplerEngine::new(&gammas, 1)),
+        Rules::chinese(),
+    );
+    let results = run_regression(&mut gtp, tst, &dir);
+    let outcomes: Vec<_> = results.iter().map(|r| (r.id.as_str(), r.outcome)).collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("1", RegressionOutcome::Pass),
+            ("2", RegressionOutcome::Pass),
+            ("3", RegressionOutcome::Pass),
+            ("4", RegressionOutcome::Fail),
+            ("5", RegressionOutcome::KnownFail),
+            ("6", RegressionOutcome::UnexpectedPass),
+            ("7", RegressionOutcome::Fail),
+        ]
+    );
+    assert_eq!(results[2].command, "reg_genmove black");
+    assert_eq!(results[3].expected, "gnugo|other");
+    assert_eq!(results[6].answer, "? unknown command");
+    assert_eq!(gtp.game().moves().len(), 2);
+
+    let summary = RegressionSummary::of_results(&results);
+    assert_eq!(
+        summary.to_string(),
+        "3 passed, 2 failed, 1 known failures, 1 unexpected passes"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "render")]
+
+use go_game_board::render::{board_to_svg, RenderOptions};
+use go_game_board::{Board, Player, Vertex, VertexMap};
+
+#[test]
+fn test_board_to_svg() {
+    let mut board = Board::with_size(9, 9);
+    let v = |row, col| Vertex::from_coords(row, col);
+    // Black captures at (4, 2) and leaves a ko at (4, 1)
+    board
+        .setup(Player::Black, &[v(3, 1), v(4, 0), v(5, 1)])
+        .unwrap();
+    board
+        .setup(Player::White, &[v(3, 2), v(4, 3), v(5, 2), v(4, 1)])
+        .unwrap();
+    board.play_legal(Player::Black, v(4, 2));
+    assert_eq!(board.ko_vertex(), v(4, 1));
+    let svg = board_to_svg(&board, &RenderOptions::default());
+
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<line ").count(), 18);
+    assert_eq!(svg.matches("fill=\"black\" stroke=\"black\"").count(), 4);
+    assert_eq!(svg.matches("fill=\"white\" stroke=\"black\"").count(), 3);
+    // Star points
+    assert_eq!(svg.matches("fill=\"black\"/>").count(), 5);
+    assert_eq!(svg.matches("class=\"last-move\"").count(), 1);
+    assert_eq!(svg.matches("class=\"ko\"").count(), 1);
+    assert_eq!(svg.matches(">J</text>").count(), 2);
+
+    let mut ownership = VertexMap::new_with(0.0);
+    ownership[v(8, 8)] = -0.5;
+    let options = RenderOptions {
+        ownership: Some(&ownership),
+        coordinates: false,
+        ..RenderOptions::default()
+    };
+    let svg = board_to_svg(&board, &options);
+    assert_eq!(svg.matches("fill-opacity=\"0.300\"").count(), 1);
+    assert!(!svg.contains("<text"));
+
+    let options = RenderOptions {
+        one_color: true,
+        ..RenderOptions::default()
+    };
+    let svg = board_to_svg(&board, &options);
+    assert!(!svg.contains("fill=\"white\" stroke=\"black\""));
+    assert_eq!(
+        svg.matches("fill=\"gray\"").count(),
+        board.stone_count(Player::Black) + board.stone_count(Player::White)
+    );
+}
@@ -0,0 +1,433 @@
+use go_game_board::{
+    Board, FastRandom, Gammas, Hash3x3, LastGoodReply, Move, Nat, Player, Playouts, SampleKind,
+    Sampler, Vertex, VertexMap,
+};
+
+// Same position with different empty vertex list orders
+fn transposed_boards() -> (Board, Board) {
+    let x1 = Vertex::from_coords(0, 0);
+    let x2 = Vertex::from_coords(2, 2);
+    let y1 = Vertex::from_coords(4, 4);
+    let y2 = Vertex::from_coords(1, 3);
+    let mut a = Board::with_size(5, 5);
+    let mut b = Board::with_size(5, 5);
+    for (v_a, v_b, pl) in [
+        (x1, x2, Player::Black),
+        (y1, y1, Player::White),
+        (x2, x1, Player::Black),
+        (y2, y2, Player::White),
+    ] {
+        a.play_legal(pl, v_a);
+        b.play_legal(pl, v_b);
+    }
+    (a, b)
+}
+
+fn samples(board: &Board, gammas: &Gammas, row_major: bool) -> Vec<Vertex> {
+    let mut sampler = Sampler::new(board, gammas);
+    sampler.set_row_major_order(row_major);
+    sampler.set_proximity_bonus(1.0, 1.0);
+    sampler.new_playout(board, gammas);
+    let mut random = FastRandom::new(11);
+    (0..200)
+        .map(|_| sampler.sample_move(board, &mut random))
+        .collect()
+}
+
+#[test]
+fn test_row_major_order_depends_only_on_position() {
+    let gammas = Gammas::new();
+    let (a, b) = transposed_boards();
+    assert_eq!(a.positional_hash(), b.positional_hash());
+    let order = |board: &Board| -> Vec<Vertex> { board.empties().collect() };
+    assert_ne!(order(&a), order(&b));
+
+    assert_ne!(samples(&a, &gammas, false), samples(&b, &gammas, false));
+    assert_eq!(samples(&a, &gammas, true), samples(&b, &gammas, true));
+}
+
+// Two playouts reaching the same position after different move orders must
+// go on identically, down to the rounding of the gamma sums
+#[test]
+fn test_row_major_order_after_transposed_moves() {
+    let mut gammas = Gammas::new();
+    for hash in Hash3x3::all() {
+        for pl in Player::all() {
+            let factor = 1.37f64.powi((usize::from(hash) * 7 + 3) as i32 % 23 - 11);
+            gammas.set(hash, pl, gammas.get(hash, pl) * factor);
+        }
+    }
+    let black: Vec<Vertex> = (0..9).map(|col| Vertex::from_coords(1, col)).collect();
+    let white: Vec<Vertex> = (0..9).map(|col| Vertex::from_coords(7, col)).collect();
+    let moves = |order: &[usize]| -> Vec<(Vertex, Vertex)> {
+        order.iter().map(|&idx| (black[idx], white[idx])).collect()
+    };
+    let a = moves(&[0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    let b = moves(&[7, 5, 3, 1, 0, 2, 4, 6, 8]);
+
+    let continued = |moves: &[(Vertex, Vertex)]| {
+        let mut board = Board::with_size(9, 9);
+        let mut sampler = Sampler::new(&board, &gammas);
+        sampler.set_row_major_order(true);
+        sampler.new_playout(&board, &gammas);
+        for &(b, w) in moves {
+            for (pl, v) in [(Player::Black, b), (Player::White, w)] {
+                board.play_legal(pl, v);
+                sampler.move_played(&board, &gammas);
+            }
+        }
+        let hash = board.positional_hash();
+        sampler.set_trace(true);
+        let mut random = FastRandom::new(3);
+        for _ in 0..30 {
+            let v = sampler.sample_move(&board, &mut random);
+            board.play_legal(board.act_player(), v);
+            sampler.move_played(&board, &gammas);
+        }
+        (hash, sampler.trace().to_vec())
+    };
+    let (hash_a, trace_a) = continued(&a);
+    let (hash_b, trace_b) = continued(&b);
+    assert_eq!(hash_a, hash_b);
+    assert_eq!(trace_a, trace_b);
+}
+
+#[test]
+fn test_last_good_reply_update_and_forget() {
+    let a = Vertex::from_coords(0, 0);
+    let b = Vertex::from_coords(1, 1);
+    let c = Vertex::from_coords(2, 2);
+    let d = Vertex::from_coords(3, 3);
+    let game = |white_reply| {
+        [
+            Move::of_player_vertex(Player::Black, a),
+            Move::of_player_vertex(Player::White, b),
+            Move::of_player_vertex(Player::Black, c),
+            Move::of_player_vertex(Player::White, white_reply),
+        ]
+    };
+
+    let mut lgr = LastGoodReply::new();
+    lgr.update(&game(d), Player::White);
+    assert_eq!(lgr.reply(Player::White, b, c), Some(d));
+    // LGRF-1 applies when the earlier move differs
+    assert_eq!(lgr.reply(Player::White, a, c), Some(d));
+    // The loser's replies are not stored
+    assert_eq!(lgr.reply(Player::Black, a, b), None);
+
+    // White lost after playing d, so the reply is forgotten
+    lgr.update(&game(d), Player::Black);
+    assert_eq!(lgr.reply(Player::White, b, c), None);
+    assert_eq!(lgr.reply(Player::Black, a, b), Some(c));
+}
+
+#[test]
+fn test_playouts_with_last_good_reply() {
+    let gammas = Gammas::new();
+    let mut playouts = Playouts::new(&gammas, 5);
+    playouts.set_last_good_reply(true);
+    let start = Board::with_size(5, 5);
+    for _ in 0..50 {
+        let result = playouts.run(&start, &gammas);
+        assert!(result.move_cnt > 0);
+        assert!(playouts.board().both_player_pass());
+    }
+}
+
+#[test]
+fn test_urgent_moves_stage() {
+    // White stone in atari, black to move
+    let mut board = Board::with_size(9, 9);
+    for (pl, row, col) in [
+        (Player::Black, 3, 4),
+        (Player::White, 4, 4),
+        (Player::Black, 4, 3),
+        (Player::White, 0, 8),
+        (Player::Black, 5, 4),
+        (Player::White, 8, 0),
+    ] {
+        board.play_legal(pl, Vertex::from_coords(row, col));
+    }
+    let gammas = Gammas::new();
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.set_urgent_moves(true);
+    sampler.new_playout(&board, &gammas);
+    let mut random = FastRandom::new(5);
+    for _ in 0..10 {
+        assert_eq!(
+            sampler.sample_move(&board, &mut random),
+            Vertex::from_coords(4, 5)
+        );
+    }
+}
+
+#[test]
+fn test_capture_bonus_and_pass_gamma() {
+    // The last white stone is in atari, black to move
+    let mut board = Board::with_size(9, 9);
+    for (pl, row, col) in [
+        (Player::Black, 3, 4),
+        (Player::White, 0, 8),
+        (Player::Black, 4, 3),
+        (Player::White, 8, 0),
+        (Player::Black, 5, 4),
+        (Player::White, 4, 4),
+    ] {
+        board.play_legal(pl, Vertex::from_coords(row, col));
+    }
+    let capture = Vertex::from_coords(4, 5);
+    let diagonal = Vertex::from_coords(3, 5);
+    let gammas = Gammas::new();
+    let probability = |distribution: &[(Vertex, f64)], v: Vertex| {
+        distribution
+            .iter()
+            .find(|&&(dv, _)| dv == v)
+            .map_or(0.0, |&(_, p)| p)
+    };
+    let ratio = |capture_bonus: f64| {
+        let mut sampler = Sampler::new(&board, &gammas);
+        sampler.set_capture_bonus(capture_bonus);
+        sampler.new_playout(&board, &gammas);
+        let distribution = sampler.move_distribution(&board);
+        probability(&distribution, capture) / probability(&distribution, diagonal)
+    };
+    assert!((ratio(3.0) / ratio(1.0) - 3.0).abs() < 1e-9);
+
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.set_pass_gamma(20.0);
+    sampler.new_playout(&board, &gammas);
+    let distribution = sampler.move_distribution(&board);
+    let total: f64 = distribution.iter().map(|&(_, p)| p).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    let pass_probability = probability(&distribution, Vertex::pass());
+    assert!(pass_probability > 0.0);
+
+    let mut random = FastRandom::new(9);
+    let sample_cnt = 10000;
+    let pass_cnt = (0..sample_cnt)
+        .filter(|_| sampler.sample_move(&board, &mut random) == Vertex::pass())
+        .count();
+    let expected = pass_probability * sample_cnt as f64;
+    let sigma = (expected * (1.0 - pass_probability)).sqrt();
+    assert!(
+        (pass_cnt as f64 - expected).abs() < 4.0 * sigma,
+        "{} passes, expected {:.0}",
+        pass_cnt,
+        expected
+    );
+}
+
+#[test]
+fn test_distribution_matches_empty_points() {
+    let gammas = Gammas::new();
+    let board = Board::with_size(5, 5);
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.new_playout(&board, &gammas);
+    let mut empties: Vec<Vertex> = board.empties().collect();
+    empties.sort_by_key(|&v| usize::from(v));
+    let distribution: Vec<(Vertex, f64)> = sampler.distribution(Player::Black).collect();
+    let points: Vec<Vertex> = distribution.iter().map(|&(v, _)| v).collect();
+    assert_eq!(points, empties);
+    assert!(distribution
+        .iter()
+        .all(|&(v, gamma)| gamma == gammas.get(board.hash3x3_at(v), Player::Black)));
+}
+
+#[test]
+fn test_legacy_pass_plays_same_playouts() {
+    let gammas = Gammas::new();
+    let playout = |legacy: bool| -> Vec<Vertex> {
+        let mut board = Board::with_size(9, 9);
+        let mut sampler = Sampler::new(&board, &gammas);
+        sampler.set_legacy_pass(legacy);
+        let mut random = FastRandom::new(11);
+        sampler.new_playout(&board, &gammas);
+        let mut moves = Vec::new();
+        while !board.both_player_pass() {
+            let v = sampler.sample_move(&board, &mut random);
+            board.play_legal(board.act_player(), v);
+            sampler.move_played(&board, &gammas);
+            moves.push(v);
+        }
+        // No gamma left for either player when the playout ends
+        for pl in [Player::Black, Player::White] {
+            assert_eq!(sampler.distribution(pl).count(), 0);
+        }
+        moves
+    };
+    assert_eq!(playout(false), playout(true));
+}
+
+#[test]
+#[cfg(not(feature = "fixed-point-gammas"))]
+fn test_tiny_gammas_are_not_a_pass() {
+    let mut gammas = Gammas::new();
+    let board = Board::with_size(5, 5);
+    for v in board.empties() {
+        gammas.set(board.hash3x3_at(v), Player::Black, 1e-12);
+    }
+    let mut random = FastRandom::new(3);
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.new_playout(&board, &gammas);
+    assert_ne!(sampler.sample_move(&board, &mut random), Vertex::pass());
+    sampler.set_legacy_pass(true);
+    assert_eq!(sampler.sample_move(&board, &mut random), Vertex::pass());
+    sampler.set_pass_epsilon(1e-13);
+    assert_ne!(sampler.sample_move(&board, &mut random), Vertex::pass());
+}
+
+// Gammas below the 2^-32 fixed point resolution count as zero
+#[test]
+#[cfg(feature = "fixed-point-gammas")]
+fn test_fixed_point_tiny_gammas_are_a_pass() {
+    let mut gammas = Gammas::new();
+    let board = Board::with_size(5, 5);
+    for v in board.empties() {
+        gammas.set(board.hash3x3_at(v), Player::Black, 1e-12);
+    }
+    let mut random = FastRandom::new(3);
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.new_playout(&board, &gammas);
+    assert_eq!(sampler.distribution(Player::Black).count(), 0);
+    assert_eq!(sampler.sample_move(&board, &mut random), Vertex::pass());
+    for v in board.empties() {
+        gammas.set(board.hash3x3_at(v), Player::Black, 1e-9);
+    }
+    sampler.new_playout(&board, &gammas);
+    assert_ne!(sampler.sample_move(&board, &mut random), Vertex::pass());
+}
+
+// Uniform gammas scaled by a factor depending on all hash bits, atari
+// included, so any missed hash change shows up in act_gamma
+fn varied_gammas() -> Gammas {
+    let mut gammas = Gammas::new();
+    for hash in Hash3x3::all() {
+        for pl in Player::all() {
+            let gamma = gammas.get(hash, pl);
+            if gamma > 0.0 {
+                let factor = 1 + (usize::from(hash) * 7 + 3) % 11;
+                gammas.set(hash, pl, gamma * factor as f64);
+            }
+        }
+    }
+    gammas
+}
+
+#[test]
+fn test_incremental_gammas_match_from_scratch() {
+    let gammas = varied_gammas();
+    let mut random = FastRandom::new(17);
+    for size in [5, 9] {
+        for _ in 0..10 {
+            let mut board = Board::with_size(size, size);
+            let mut sampler = Sampler::new(&board, &gammas);
+            sampler.new_playout(&board, &gammas);
+            while !board.both_player_pass() {
+                let pl = board.act_player();
+                let v = sampler.sample_move(&board, &mut random);
+                board.play_legal(pl, v);
+                sampler.move_played(&board, &gammas);
+                if let Err(e) = sampler.check_incremental(&board, &gammas) {
+                    panic!("move {}: {}", board.move_count(), e);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sample_move_matches_distribution() {
+    let gammas = varied_gammas();
+    let mut random = FastRandom::new(3);
+    let mut board = Board::with_size(7, 7);
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.set_proximity_bonus(5.0, 2.0);
+    sampler.new_playout(&board, &gammas);
+    for _ in 0..15 {
+        let pl = board.act_player();
+        let v = sampler.sample_move(&board, &mut random);
+        board.play_legal(pl, v);
+        sampler.move_played(&board, &gammas);
+    }
+
+    let distribution = sampler.move_distribution(&board);
+    let total: f64 = distribution.iter().map(|&(_, p)| p).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    let sample_cnt = 40000;
+    let mut counts = VertexMap::new_with(0usize);
+    for _ in 0..sample_cnt {
+        counts[sampler.sample_move(&board, &mut random)] += 1;
+    }
+
+    // Pearson's chi-square against the critical value at p = 0.001 by the
+    // Wilson-Hilferty approximation
+    let mut chi_square = 0.0;
+    for &(v, p) in &distribution {
+        let expected = p * sample_cnt as f64;
+        let diff = counts[v] as f64 - expected;
+        chi_square += diff * diff / expected;
+        counts[v] = 0;
+    }
+    assert!(
+        Vertex::all().all(|v| counts[v] == 0),
+        "move outside the distribution"
+    );
+    let df = (distribution.len() - 1) as f64;
+    let z = 3.09;
+    let critical = df * (1.0 - 2.0 / (9.0 * df) + z * (2.0 / (9.0 * df)).sqrt()).powi(3);
+    assert!(
+        chi_square < critical,
+        "chi-square {:.1} over {:.1} with {} degrees of freedom",
+        chi_square,
+        critical,
+        df
+    );
+}
+
+#[test]
+fn test_trace_records_every_decision() {
+    let gammas = Gammas::new();
+    let mut board = Board::with_size(7, 7);
+    let mut sampler = Sampler::new(&board, &gammas);
+    let mut random = FastRandom::new(3);
+    let mut untraced = FastRandom::new(3);
+    let mut moves = Vec::new();
+
+    sampler.set_trace(true);
+    sampler.new_playout(&board, &gammas);
+    while !board.both_player_pass() {
+        let pl = board.act_player();
+        let v = sampler.sample_move(&board, &mut random);
+        board.play_legal(pl, v);
+        sampler.move_played(&board, &gammas);
+        moves.push(Move::of_player_vertex(pl, v));
+    }
+
+    let trace = sampler.trace();
+    assert_eq!(trace.iter().map(|d| d.mv).collect::<Vec<_>>(), moves);
+    assert_eq!(trace[0].move_no, 1);
+    assert_eq!(trace.last().unwrap().kind, SampleKind::Pass);
+    // Nothing is local before the first stone
+    assert_eq!(trace[0].kind, SampleKind::NonLocal);
+    assert!(trace.iter().any(|d| d.kind == SampleKind::Local));
+    for decision in trace.iter().filter(|d| d.kind != SampleKind::Pass) {
+        assert!(decision.gamma > 0.0);
+        assert!(decision.gamma <= decision.local_gamma + decision.non_local_gamma);
+    }
+    let mut log = Vec::new();
+    sampler.write_trace(&mut log).unwrap();
+    let log = String::from_utf8(log).unwrap();
+    assert_eq!(log.lines().count(), moves.len());
+    assert!(log.starts_with(&format!("1 {} NonLocal ", moves[0])));
+
+    // Tracing does not change the moves
+    let mut board = Board::with_size(7, 7);
+    sampler.set_trace(false);
+    sampler.new_playout(&board, &gammas);
+    for mv in moves.iter() {
+        assert_eq!(sampler.sample_move(&board, &mut untraced), mv.vertex);
+        board.play_legal(mv.player, mv.vertex);
+        sampler.move_played(&board, &gammas);
+    }
+    assert!(sampler.trace().is_empty());
+}
@@ -0,0 +1,109 @@
+use go_game_board::{
+    Board, FastRandom, Game, Gammas, Player, PlayoutOutcome, Playouts, Rules, Sampler, Score,
+};
+
+#[test]
+fn test_score_half_points() {
+    let score = Score::of_points(10) - Score::of_f32(6.5);
+    assert_eq!(score.half_points(), 7);
+    assert_eq!(score.to_f32(), 3.5);
+    assert_eq!(score.winner(), Some(Player::Black));
+    assert_eq!(score.to_string(), "B+3.5");
+    assert_eq!((-score).to_string(), "W+3.5");
+    assert_eq!(Score::of_half_points(-4).to_string(), "W+2");
+
+    let jigo = Score::of_points(7) - Score::of_f32(7.0);
+    assert!(jigo.is_jigo());
+    assert_eq!(jigo, Score::ZERO);
+    assert_eq!(jigo.winner(), None);
+    assert_eq!(jigo.to_string(), "0");
+}
+
+#[test]
+fn test_komi_out_of_range() {
+    let mut board = Board::with_size(5, 5);
+    for komi in [f32::INFINITY, f32::NEG_INFINITY, f32::NAN, 1e30, -1000.5] {
+        assert!(board.try_set_komi(komi).is_err(), "{}", komi);
+    }
+    assert_eq!(board.komi(), 6.5);
+    board.try_set_komi(-1000.0).unwrap();
+    assert_eq!(board.komi(), -1000.0);
+
+    let mut game = Game::new(5, 5, Rules::chinese());
+    assert!(game.set_komi(f32::NAN).is_err());
+    assert_eq!(game.rules().komi, 7.5);
+    game.set_komi(0.5).unwrap();
+    assert_eq!(game.board().komi(), 0.5);
+
+    // Score arithmetic saturates instead of overflowing
+    let huge = Score::of_f32(f32::MAX);
+    assert_eq!(huge.half_points(), i32::MAX);
+    assert_eq!(Score::of_f32(f32::NAN), Score::ZERO);
+    assert_eq!((-huge - huge).half_points(), -i32::MAX - 1);
+    assert_eq!((huge + huge).half_points(), i32::MAX);
+    assert_eq!((-(-huge - huge)).half_points(), i32::MAX);
+    assert_eq!(
+        (-huge - huge).to_string(),
+        format!("W+{}", Score::of_half_points(i32::MAX).to_f32())
+    );
+}
+
+#[test]
+fn test_exact_playout_score_keeps_komi() {
+    let gammas = Gammas::new();
+    let mut random = FastRandom::new(3);
+    let mut board = Board::with_size(9, 9);
+    let mut sampler = Sampler::new(&board, &gammas);
+    sampler.new_playout(&board, &gammas);
+    while !board.both_player_pass() {
+        let v = sampler.sample_move(&board, &mut random);
+        board.play_legal(board.act_player(), v);
+        sampler.move_played(&board, &gammas);
+    }
+
+    board.set_komi(0.0);
+    let points = board.exact_playout_score();
+    assert_eq!(points.half_points() % 2, 0);
+
+    // The C++ score cannot tell 6.5 from 6.0 komi
+    board.set_komi(6.5);
+    let half_komi = (board.playout_score(), board.exact_playout_score());
+    board.set_komi(6.0);
+    let integer_komi = (board.playout_score(), board.exact_playout_score());
+    assert_eq!(half_komi.0, integer_komi.0);
+    assert_eq!(half_komi.1, points - Score::of_f32(6.5));
+    assert_eq!(integer_komi.1, points - Score::of_points(6));
+    assert_eq!(integer_komi.1.half_points() - half_komi.1.half_points(), 1);
+
+    // Jigo goes to white, playout_outcome keeps it apart
+    board.set_komi(points.to_f32());
+    assert!(board.exact_playout_score().is_jigo());
+    assert_eq!(board.playout_winner(), Player::White);
+    assert_eq!(board.playout_outcome(), PlayoutOutcome::Jigo);
+    assert_eq!(board.playout_outcome().winner(), None);
+    board.set_komi(points.to_f32() - 0.5);
+    assert_eq!(board.playout_winner(), Player::Black);
+    assert_eq!(board.playout_outcome(), PlayoutOutcome::BlackWin);
+    board.set_komi(points.to_f32() + 0.5);
+    assert_eq!(board.playout_outcome(), PlayoutOutcome::WhiteWin);
+}
+
+#[test]
+fn test_integer_komi_jigo_outcomes() {
+    let gammas = Gammas::new();
+    let mut playouts = Playouts::new(&gammas, 7);
+    // An even number of points, so both sides can get half
+    let mut start = Board::with_size(4, 4);
+    start.set_komi(0.0);
+    let mut jigo_cnt = 0;
+    for result in playouts.results(&start, &gammas).take(300) {
+        match result.outcome {
+            PlayoutOutcome::Jigo => {
+                jigo_cnt += 1;
+                assert_eq!(result.winner, Player::White);
+            }
+            outcome => assert_eq!(outcome.winner(), Some(result.winner)),
+        }
+    }
+    assert!(jigo_cnt > 0);
+}
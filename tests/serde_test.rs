@@ -0,0 +1,55 @@
+#![cfg(feature = "serde")]
+
+use go_game_board::{
+    Board, Criticality, Move, Player, PlayoutOutcome, PlayoutResult, Rules, Vertex,
+};
+
+#[test]
+fn test_core_types_round_trip() {
+    let mv = Move::of_player_vertex(Player::White, Vertex::from_coords(3, 4));
+    let json = serde_json::to_string(&mv).unwrap();
+    assert_eq!(serde_json::from_str::<Move>(&json).unwrap(), mv);
+
+    let rules = Rules::chinese();
+    let json = serde_json::to_string(&rules).unwrap();
+    assert_eq!(serde_json::from_str::<Rules>(&json).unwrap(), rules);
+
+    let result = PlayoutResult {
+        first_v: Vertex::pass(),
+        winner: Player::Black,
+        outcome: PlayoutOutcome::BlackWin,
+        score: 3,
+        move_cnt: 80,
+    };
+    let json = serde_json::to_string(&result).unwrap();
+    assert!(json.contains("\"black\""));
+    assert_eq!(
+        serde_json::from_str::<PlayoutResult>(&json).unwrap(),
+        result
+    );
+
+    assert!(serde_json::from_str::<Move>(r#"{"player":"red","vertex":1}"#).is_err());
+    assert!(serde_json::from_str::<Move>(r#"{"player":"black","vertex":100000}"#).is_err());
+}
+
+#[test]
+fn test_board_snapshot_round_trip() {
+    let mut board = Board::with_size(7, 7);
+    board.play_legal(Player::Black, Vertex::from_coords(3, 3));
+    board.play_legal(Player::White, Vertex::from_coords(2, 3));
+
+    let json = serde_json::to_string(&board.snapshot()).unwrap();
+    let restored = Board::from_snapshot(&serde_json::from_str(&json).unwrap()).unwrap();
+    assert_eq!(restored.snapshot(), board.snapshot());
+}
+
+#[test]
+fn test_criticality_round_trip() {
+    let board = Board::with_size(5, 5);
+    let mut criticality = Criticality::new();
+    criticality.add(&board, Player::White);
+
+    let json = serde_json::to_string(&criticality).unwrap();
+    let restored: Criticality = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.playout_count(), 1);
+}
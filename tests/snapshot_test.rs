@@ -0,0 +1,65 @@
+use go_game_board::snapshot::{
+    check_golden, format_snapshot, parse_snapshot, playout_snapshot, GOLDEN_MODE,
+};
+use go_game_board::Gammas;
+use std::path::Path;
+
+#[test]
+fn test_playouts_match_golden_snapshot() {
+    let gammas = Gammas::new();
+    for (size, seed, playout_cnt) in [(9, 1, 200), (19, 2, 20)] {
+        let snapshot = playout_snapshot(size, size, &gammas, seed, playout_cnt);
+        let path = format!(
+            "{}/tests/golden/{}/playouts_{}x{}.txt",
+            env!("CARGO_MANIFEST_DIR"),
+            GOLDEN_MODE,
+            size,
+            size
+        );
+        check_golden(Path::new(&path), &snapshot).unwrap();
+    }
+}
+
+#[test]
+fn test_snapshot_format_round_trip() {
+    let snapshot = playout_snapshot(5, 5, &Gammas::new(), 3, 10);
+    assert_eq!(
+        parse_snapshot(&format_snapshot(&snapshot)).unwrap(),
+        snapshot
+    );
+}
+
+// Sums of the uniform gammas are exact in f64, so the fixed-point playouts
+// have to be the very same as the default ones
+#[test]
+fn test_fixed_point_golden_matches_default() {
+    for size in [9, 19] {
+        let read = |mode: &str| {
+            let path = format!(
+                "{}/tests/golden/{}/playouts_{}x{}.txt",
+                env!("CARGO_MANIFEST_DIR"),
+                mode,
+                size,
+                size
+            );
+            parse_snapshot(&std::fs::read_to_string(&path).unwrap()).unwrap()
+        };
+        assert_eq!(read("fixed-point"), read("default"));
+        assert_eq!(read("cpp-compat-fixed-point"), read("cpp-compat"));
+        assert_eq!(read("fixed-point-large-boards"), read("large-boards"));
+        assert_eq!(
+            read("cpp-compat-fixed-point-large-boards"),
+            read("cpp-compat-large-boards")
+        );
+
+        // Large boards play the same games, only the hashes differ
+        let games = |mode: &str| -> Vec<(i32, usize)> {
+            read(mode)
+                .iter()
+                .map(|entry| (entry.score, entry.move_cnt))
+                .collect()
+        };
+        assert_eq!(games("large-boards"), games("default"));
+        assert_eq!(games("cpp-compat-large-boards"), games("cpp-compat"));
+    }
+}
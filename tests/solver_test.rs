@@ -0,0 +1,53 @@
+use go_game_board::solver::{solve, solve_game};
+use go_game_board::{Board, Game, Move, Player, Rules, Vertex};
+
+fn no_komi() -> Rules {
+    let mut rules = Rules::chinese();
+    rules.komi = 0.0;
+    rules
+}
+
+#[test]
+fn test_solve_one_line_boards() {
+    let result = solve(1, 1, no_komi());
+    assert_eq!(result.score, 0.0);
+    assert_eq!(result.best_move, Vertex::pass());
+
+    assert_eq!(solve(2, 1, no_komi()).score, 0.0);
+
+    // Black takes the middle and the whole board
+    let result = solve(3, 1, no_komi());
+    assert_eq!(result.score, 3.0);
+    assert_eq!(result.best_move, Vertex::from_coords(0, 1));
+
+    assert_eq!(solve(4, 1, no_komi()).score, 4.0);
+}
+
+#[test]
+fn test_solve_2x2() {
+    assert_eq!(solve(2, 2, no_komi()).score, 1.0);
+
+    let mut rules = no_komi();
+    rules.komi = 1.5;
+    assert_eq!(solve(2, 2, rules).score, -0.5);
+}
+
+#[test]
+fn test_territory_score_counts_prisoners() {
+    let mut rules = Rules::japanese();
+    rules.komi = 0.0;
+    let v = |col| Vertex::from_coords(0, col);
+    // ". # # ." with white to move, once after capturing a white stone
+    let mut captured = Game::new(4, 1, rules);
+    for (player, col) in [(Player::Black, 2), (Player::White, 0), (Player::Black, 1)] {
+        assert!(captured.play(Move::of_player_vertex(player, v(col))));
+    }
+    assert_eq!(captured.prisoners(Player::Black), 1);
+    let mut board = Board::with_size(4, 1);
+    board.setup(Player::Black, &[v(1), v(2)]).unwrap();
+    board.set_to_move(Player::White);
+    let set_up = Game::from_position(board, rules);
+
+    let with_prisoner = solve_game(&captured).score;
+    assert_eq!(with_prisoner, solve_game(&set_up).score + 1.0);
+}
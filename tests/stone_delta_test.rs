@@ -0,0 +1,67 @@
+use go_game_board::{Board, Gammas, Player, Playouts, StoneDeltaStats, Vertex};
+
+#[test]
+fn test_stone_deltas_of_playouts() {
+    let gammas = Gammas::new();
+    let start = Board::with_size(9, 9);
+    let mut playouts = Playouts::new(&gammas, 11);
+    let stats = StoneDeltaStats::collect(&mut playouts, &start, &gammas, 200);
+    assert_eq!(stats.playout_count(), 200);
+    assert!(stats.max_move_count() > 81);
+
+    // Black moves first, so the first move always adds a black stone and the
+    // second always evens it
+    assert_eq!(stats.mean_delta(1), 1.0);
+    assert_eq!(stats.mean_delta(2), 0.0);
+    assert_eq!(stats.mean_delta(0), 0.0);
+    assert_eq!(stats.mean_delta(stats.max_move_count() + 1), 0.0);
+
+    // Stones on the board at the end are placed minus captured
+    let mut playouts = Playouts::new(&gammas, 11);
+    let mut one = StoneDeltaStats::new();
+    one.start_playout();
+    let result = playouts.run_observed(&start, &gammas, &mut one);
+    assert_eq!(one.max_move_count(), result.move_cnt);
+    for pl in [Player::Black, Player::White] {
+        assert_eq!(
+            one.placed(pl) - one.captured(pl),
+            playouts.board().stone_count(pl) as u64
+        );
+    }
+    let final_delta = playouts.board().stone_count(Player::Black) as f64
+        - playouts.board().stone_count(Player::White) as f64;
+    assert_eq!(one.mean_delta(result.move_cnt), final_delta);
+
+    // The same playouts as run, the observer does not change them
+    let mut plain = Playouts::new(&gammas, 11);
+    assert_eq!(plain.run(&start, &gammas), result);
+
+    let mut merged = StoneDeltaStats::new();
+    merged.merge(&one);
+    merged.merge(&stats);
+    assert_eq!(merged.playout_count(), 201);
+    assert_eq!(
+        merged.placed(Player::White),
+        stats.placed(Player::White) + one.placed(Player::White)
+    );
+}
+
+#[test]
+fn test_suicide_counts_after_the_capture() {
+    let mut board = Board::with_size(5, 5);
+    board
+        .setup(
+            Player::White,
+            &[Vertex::from_coords(0, 1), Vertex::from_coords(1, 0)],
+        )
+        .unwrap();
+    let mut stats = StoneDeltaStats::new();
+    stats.start_playout();
+    board.play_legal_observed(Player::Black, Vertex::from_coords(0, 0), &mut stats);
+    board.play_legal_observed(Player::White, Vertex::pass(), &mut stats);
+    assert_eq!(stats.max_move_count(), 2);
+    assert_eq!(stats.placed(Player::Black), 1);
+    assert_eq!(stats.captured(Player::Black), 1);
+    assert_eq!(stats.mean_delta(1), 0.0);
+    assert_eq!(stats.mean_delta(2), 0.0);
+}
@@ -0,0 +1,102 @@
+use go_game_board::tactics::{can_connect, ko_threats, KoThreat, KoThreatKind};
+use go_game_board::{Board, Player, Vertex};
+
+fn board_with_black(coords: &[(isize, isize)]) -> Board {
+    let mut board = Board::with_size(9, 9);
+    let stones: Vec<Vertex> = coords
+        .iter()
+        .map(|&(r, c)| Vertex::from_coords(r, c))
+        .collect();
+    board.setup(Player::Black, &stones).unwrap();
+    board
+}
+
+#[test]
+fn test_can_connect_shapes() {
+    let v = |row, col| Vertex::from_coords(row, col);
+
+    let solid = board_with_black(&[(4, 4), (4, 5)]);
+    assert_eq!(can_connect(&solid, v(4, 4), v(4, 5), 0), Some(true));
+
+    let diagonal = board_with_black(&[(4, 4), (5, 5)]);
+    assert_eq!(can_connect(&diagonal, v(4, 4), v(5, 5), 0), Some(true));
+
+    let bamboo = board_with_black(&[(4, 4), (4, 5), (6, 4), (6, 5)]);
+    assert_eq!(can_connect(&bamboo, v(4, 4), v(6, 5), 0), Some(true));
+
+    let one_point_jump = board_with_black(&[(4, 4), (4, 6)]);
+    assert_eq!(
+        can_connect(&one_point_jump, v(4, 4), v(4, 6), 100),
+        Some(false)
+    );
+
+    let knight = board_with_black(&[(4, 4), (5, 6)]);
+    assert_eq!(can_connect(&knight, v(4, 4), v(5, 6), 100), Some(false));
+
+    let far = board_with_black(&[(0, 0), (8, 8)]);
+    assert_eq!(can_connect(&far, v(0, 0), v(8, 8), 100), Some(false));
+
+    // Not two stones of the same color
+    assert_eq!(can_connect(&far, v(0, 0), v(4, 4), 100), None);
+    assert_eq!(can_connect(&far, v(4, 4), v(4, 4), 100), None);
+}
+
+#[test]
+fn test_can_connect_under_the_stones() {
+    let v = |row, col| Vertex::from_coords(row, col);
+    // One-point jump on the first line with a stone above the gap: white cannot
+    // play in the gap
+    let board = board_with_black(&[(8, 2), (8, 4), (7, 3)]);
+    assert_eq!(can_connect(&board, v(8, 2), v(8, 4), 100), Some(true));
+}
+
+#[test]
+fn test_ko_threats() {
+    let v = |row, col| Vertex::from_coords(row, col);
+    let mut board = board_with_black(&[(3, 4), (4, 3)]);
+    // Second stone is already in atari, capturing it is no threat
+    board.setup(Player::White, &[v(4, 4), v(0, 1)]).unwrap();
+    board.setup(Player::Black, &[v(0, 0), v(0, 2)]).unwrap();
+    let threats = ko_threats(&board, Player::Black);
+    assert!(threats.contains(&KoThreat {
+        v: v(4, 5),
+        kind: KoThreatKind::Atari,
+        size: 1
+    }));
+    assert!(threats.contains(&KoThreat {
+        v: v(5, 4),
+        kind: KoThreatKind::Atari,
+        size: 1
+    }));
+    assert!(threats.iter().all(|t| t.v != v(1, 1)));
+    assert!(threats
+        .iter()
+        .all(|t| t.kind == KoThreatKind::Atari || t.kind == KoThreatKind::Ladder));
+}
+
+#[test]
+fn test_ko_threats_ladder_and_nakade() {
+    let v = |row, col| Vertex::from_coords(row, col);
+    let mut board = board_with_black(&[(3, 4), (5, 5)]);
+    board.setup(Player::White, &[v(4, 4)]).unwrap();
+    let threats = ko_threats(&board, Player::Black);
+    assert!(threats.contains(&KoThreat {
+        v: v(4, 3),
+        kind: KoThreatKind::Ladder,
+        size: 1
+    }));
+
+    let mut board = Board::with_size(9, 9);
+    let wall = [(1, 0), (1, 1), (1, 2), (1, 3), (0, 3)];
+    let wall: Vec<Vertex> = wall.iter().map(|&(r, c)| v(r, c)).collect();
+    board.setup(Player::White, &wall).unwrap();
+    let threats = ko_threats(&board, Player::Black);
+    assert_eq!(
+        threats,
+        vec![KoThreat {
+            v: v(0, 1),
+            kind: KoThreatKind::Nakade,
+            size: 3
+        }]
+    );
+}
@@ -0,0 +1,83 @@
+use go_game_board::{
+    Board, BoardError, Color, FastRandom, Graph, Player, Rectangle, Topology, TopologyBoard, Torus,
+    Vertex,
+};
+
+fn v(row: isize, col: isize) -> Vertex {
+    Vertex::from_coords(row, col)
+}
+
+#[test]
+fn test_rectangle_matches_board() {
+    let mut random = FastRandom::new(7);
+    for _ in 0..5 {
+        let mut board = Board::with_size(9, 9);
+        let mut topology_board = TopologyBoard::new(Rectangle::new(9, 9).unwrap());
+        // Random play may repeat a ko cycle forever, as in match_runner
+        while !topology_board.both_player_pass() && topology_board.move_count() < 3 * 81 {
+            let pl = topology_board.act_player();
+            let mv = topology_board.play_random(&mut random);
+            board.play_legal(pl, mv);
+            assert_eq!(board.positional_hash(), topology_board.positional_hash());
+            assert_eq!(board.ko_vertex(), topology_board.ko_vertex());
+        }
+        assert_eq!(board.area_score(), topology_board.area_score());
+    }
+}
+
+#[test]
+fn test_torus_captures_across_edges() {
+    let torus = Torus::new(5, 5).unwrap();
+    let mut neighbors = Vec::new();
+    torus.for_each_neighbor(v(0, 0), |nbr| neighbors.push(nbr));
+    assert_eq!(neighbors, vec![v(4, 0), v(0, 1), v(1, 0), v(0, 4)]);
+
+    let mut board = TopologyBoard::new(torus);
+    for mv in [
+        v(1, 0),
+        v(0, 0),
+        v(0, 1),
+        Vertex::pass(),
+        v(4, 0),
+        Vertex::pass(),
+    ] {
+        board.play(mv).unwrap();
+    }
+    assert_eq!(board.color_at(v(0, 0)), Color::White);
+    board.play(v(0, 4)).unwrap();
+    assert_eq!(board.color_at(v(0, 0)), Color::Empty);
+    assert_eq!(board.play(v(5, 5)), Err(BoardError::OffBoard(v(5, 5))));
+    assert!(Torus::new(1, 5).is_err());
+
+    // No edges, so random playouts fill it up to eyes
+    let mut random = FastRandom::new(3);
+    let mut board = TopologyBoard::new(torus);
+    while !board.both_player_pass() && board.move_count() < 3 * 25 {
+        board.play_random(&mut random);
+    }
+    assert!(board.area_score().abs() <= 25);
+    assert!(board.legal_moves().len() < 25);
+}
+
+#[test]
+fn test_graph_ring() {
+    let ring = [v(0, 0), v(0, 1), v(0, 2), v(0, 3), v(0, 4), v(0, 5)];
+    let edges: Vec<_> = (0..ring.len())
+        .map(|idx| (ring[idx], ring[(idx + 1) % ring.len()]))
+        .collect();
+    let graph = Graph::new(&edges).unwrap();
+    assert_eq!(graph.points().collect::<Vec<_>>(), ring);
+    assert!(!graph.contains(v(1, 0)));
+    assert!(ring.iter().all(|&p| graph.contains(p)));
+
+    let mut board = TopologyBoard::new(graph);
+    board.play(ring[0]).unwrap();
+    board.play(ring[1]).unwrap();
+    assert_eq!(board.play(v(1, 1)), Err(BoardError::OffBoard(v(1, 1))));
+    board.play(ring[2]).unwrap();
+    assert_eq!(board.color_at(ring[1]), Color::Empty);
+    assert_eq!(board.act_player(), Player::White);
+    // Black has 2 stones and all 4 empty points
+    assert_eq!(board.area_score(), 6);
+    assert!(Graph::new(&[(v(0, 0), Vertex::pass())]).is_err());
+}
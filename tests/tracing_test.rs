@@ -0,0 +1,83 @@
+#![cfg(feature = "tracing")]
+use go_game_board::{Gammas, Gtp, MonteCarloEngine, Rules};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+// Records "span <name> <fields>" and "event <fields>" lines
+struct Collector {
+    lines: Arc<Mutex<Vec<String>>>,
+    next_id: AtomicU64,
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0 += &format!(" {}={:?}", field.name(), value);
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes) -> Id {
+        let mut fields = Fields(format!("span {}", attrs.metadata().name()));
+        attrs.record(&mut fields);
+        self.lines.lock().unwrap().push(fields.0);
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &Id, _values: &Record) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event) {
+        let mut fields = Fields("event".to_string());
+        event.record(&mut fields);
+        self.lines.lock().unwrap().push(fields.0);
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn test_gtp_search_and_playouts_are_traced() {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let collector = Collector {
+        lines: lines.clone(),
+        next_id: AtomicU64::new(1),
+    };
+    let gammas = Gammas::new();
+    tracing::subscriber::with_default(collector, || {
+        let engine = MonteCarloEngine::new(&gammas, 50, 1);
+        let mut gtp = Gtp::new(&gammas, Box::new(engine), Rules::chinese());
+        for command in ["boardsize 9", "3 genmove b", "foo"] {
+            gtp.response(command);
+        }
+    });
+
+    let lines = lines.lock().unwrap();
+    let has = |prefix: &str, part: &str| {
+        lines
+            .iter()
+            .any(|line| line.starts_with(prefix) && line.contains(part))
+    };
+    assert!(has("span gtp_command", "command=\"genmove\" id=\"3\""));
+    assert!(has("span search", "move_no=1 player=Black threads=1"));
+    assert!(has("span playouts", "count=1"));
+    assert!(has("event", "message=search finished mv="));
+    assert!(has("event", "playouts=50"));
+    assert!(has("event", "message=gtp ok args=[\"b\"]"));
+    assert!(has(
+        "event",
+        "message=gtp error args=[] error=\"unknown command\""
+    ));
+}
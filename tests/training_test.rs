@@ -0,0 +1,89 @@
+use go_game_board::{
+    self_play, Board, Gammas, Move, Player, TrainingPosition, TrainingReader, TrainingWriter,
+    Vertex,
+};
+
+#[test]
+fn test_position_lists_legal_move_patterns() {
+    let mut board = Board::with_size(5, 5);
+    board.play_legal(Player::Black, Vertex::from_coords(2, 2));
+    let mv = Move::of_player_vertex(Player::White, Vertex::from_coords(2, 3));
+    let position = TrainingPosition::of_move(&board, mv).unwrap();
+
+    assert_eq!(position.player, Player::White);
+    let cnt: u32 = position.candidates.iter().map(|&(_, cnt)| cnt).sum();
+    assert_eq!(cnt, 24);
+    for pair in position.candidates.windows(2) {
+        assert!(usize::from(pair[0].0) < usize::from(pair[1].0));
+    }
+    assert_eq!(
+        position.candidates[position.chosen].0,
+        board.hash3x3_at(mv.vertex)
+    );
+    // The four points next to the black stone share one pattern up to
+    // symmetry, but not as raw hashes
+    assert!(position.candidates.iter().all(|&(_, cnt)| cnt < 4));
+
+    let pass = Move::of_player_vertex(Player::White, Vertex::pass());
+    assert_eq!(TrainingPosition::of_move(&board, pass), None);
+    let occupied = Move::of_player_vertex(Player::White, Vertex::from_coords(2, 2));
+    assert_eq!(TrainingPosition::of_move(&board, occupied), None);
+}
+
+#[test]
+fn test_self_play_round_trip() {
+    let gammas = Gammas::new();
+    let mut positions = Vec::new();
+    let position_cnt = self_play(&gammas, 9, 9, 5, 3, |p| positions.push(p));
+    assert_eq!(position_cnt, positions.len());
+    assert!(position_cnt > 5 * 50);
+
+    let mut again = Vec::new();
+    self_play(&gammas, 9, 9, 5, 3, |p| again.push(p));
+    assert_eq!(positions, again);
+
+    let mut writer = TrainingWriter::new(Vec::new()).unwrap();
+    for position in positions.iter() {
+        writer.write(position).unwrap();
+    }
+    assert_eq!(writer.position_count(), position_cnt as u64);
+    let bytes = writer.finish().unwrap();
+    let read: Vec<TrainingPosition> = TrainingReader::new(&bytes[..])
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(read, positions);
+
+    let truncated = TrainingReader::new(&bytes[..bytes.len() - 1]).unwrap();
+    assert!(truncated.last().unwrap().is_err());
+    assert!(TrainingReader::new(&b"GGGAMMAS"[..]).is_err());
+}
+
+#[test]
+fn test_training_file() {
+    let gammas = Gammas::new();
+    let path = std::env::temp_dir().join(format!("training_test_{}.bin", std::process::id()));
+    let mut writer = TrainingWriter::create(&path).unwrap();
+    let position_cnt = self_play(&gammas, 7, 7, 2, 1, |p| writer.write(&p).unwrap());
+    writer.finish().unwrap();
+
+    let read = TrainingReader::open(&path).unwrap().count();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read, position_cnt);
+
+    let gz_path = path.with_extension("bin.gz");
+    let result = TrainingWriter::create(&gz_path);
+    if cfg!(feature = "gzip") {
+        let mut writer = result.unwrap();
+        let position_cnt = self_play(&gammas, 7, 7, 2, 1, |p| writer.write(&p).unwrap());
+        // Complete before the output is dropped
+        let out = writer.finish().unwrap();
+        let read: Result<Vec<_>, _> = TrainingReader::open(&gz_path).unwrap().collect();
+        assert_eq!(read.unwrap().len(), position_cnt);
+        drop(out);
+    } else {
+        assert!(result.is_err());
+        assert!(!gz_path.exists());
+    }
+    let _ = std::fs::remove_file(&gz_path);
+}
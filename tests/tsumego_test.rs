@@ -0,0 +1,72 @@
+use go_game_board::tsumego::solve;
+use go_game_board::{Board, BoardError, Player, TsumegoGoal, TsumegoStatus, Vertex};
+
+// Black group with a straight three eye space on the bottom edge:
+//   W W W W W
+//   B B B B W
+//   . . . B W
+fn straight_three() -> Board {
+    let v = |row, col| Vertex::from_coords(row, col);
+    let mut board = Board::with_size(5, 5);
+    board
+        .setup(
+            Player::Black,
+            &[v(3, 0), v(3, 1), v(3, 2), v(3, 3), v(4, 3)],
+        )
+        .unwrap();
+    board
+        .setup(
+            Player::White,
+            &[
+                v(2, 0),
+                v(2, 1),
+                v(2, 2),
+                v(2, 3),
+                v(2, 4),
+                v(3, 4),
+                v(4, 4),
+            ],
+        )
+        .unwrap();
+    board
+}
+
+fn eye_space() -> Vec<Vertex> {
+    (0..3).map(|col| Vertex::from_coords(4, col)).collect()
+}
+
+#[test]
+fn test_straight_three_black_to_live() {
+    let board = straight_three();
+    let target = Vertex::from_coords(3, 0);
+    let result = solve(&board, &eye_space(), TsumegoGoal::Live(target), 20).unwrap();
+    assert_eq!(result.status, TsumegoStatus::Solved);
+    assert_eq!(result.pv[0], Vertex::from_coords(4, 1));
+
+    // Black moves first, so white cannot kill
+    let result = solve(&board, &eye_space(), TsumegoGoal::Kill(target), 20).unwrap();
+    assert_eq!(result.status, TsumegoStatus::Failed);
+}
+
+#[test]
+fn test_straight_three_white_to_kill() {
+    let mut board = straight_three();
+    board.play_legal(Player::Black, Vertex::pass());
+    let target = Vertex::from_coords(3, 0);
+    let result = solve(&board, &eye_space(), TsumegoGoal::Kill(target), 20).unwrap();
+    assert_eq!(result.status, TsumegoStatus::Solved);
+    assert_eq!(result.pv[0], Vertex::from_coords(4, 1));
+
+    let result = solve(&board, &eye_space(), TsumegoGoal::Kill(target), 1).unwrap();
+    assert_eq!(result.status, TsumegoStatus::Unknown);
+}
+
+#[test]
+fn test_target_must_be_a_stone() {
+    let board = straight_three();
+    let target = Vertex::from_coords(4, 1);
+    assert_eq!(
+        solve(&board, &eye_space(), TsumegoGoal::Kill(target), 20).unwrap_err(),
+        BoardError::NoStone(target)
+    );
+}
@@ -0,0 +1,124 @@
+use go_game_board::{
+    find_param, Benchmark, Board, Gammas, Sampler, SamplerParams, SpsaConfig, SpsaTuner, PARAMS,
+};
+
+#[test]
+fn test_param_registry() {
+    let defaults = SamplerParams::default();
+    for (idx, param) in PARAMS.iter().enumerate() {
+        assert!(PARAMS[..idx].iter().all(|other| other.name != param.name));
+        let value = param.value(&defaults);
+        assert!(param.min <= value && value <= param.max, "{}", param.name);
+        assert!(param.step > 0.0);
+    }
+
+    let mut params = defaults;
+    assert_eq!(params.set("capture_bonus", 3.0), Ok(()));
+    assert_eq!(params.capture_bonus, 3.0);
+    assert_eq!(params.get("capture_bonus"), Some(3.0));
+    assert!(params.set("capture_bonus", 1000.0).is_err());
+    assert!(params.set("komi", 1.0).is_err());
+    assert_eq!(params.get("komi"), None);
+    assert_eq!(find_param("pass_gamma").map(|param| param.min), Some(0.0));
+}
+
+#[test]
+fn test_params_text() {
+    let params = SamplerParams {
+        proximity_direct: 7.5,
+        proximity_diagonal: 3.25,
+        capture_bonus: 4.0,
+        pass_gamma: 0.125,
+    };
+    let text = params.to_text();
+    assert!(text.starts_with("proximity_direct = 7.5\n"));
+    assert_eq!(SamplerParams::from_text(&text), Ok(params));
+
+    let partial = SamplerParams::from_text("# tuned\n\ncapture_bonus = 2\n").unwrap();
+    assert_eq!(
+        partial,
+        SamplerParams {
+            capture_bonus: 2.0,
+            ..SamplerParams::default()
+        }
+    );
+    assert_eq!(
+        SamplerParams::from_text("capture_bonus 2"),
+        Err("line 1: expected name = value".to_string())
+    );
+    assert!(SamplerParams::from_text("pass_gamma = -1").is_err());
+
+    let path = std::env::temp_dir().join(format!("sampler_params_{}.txt", std::process::id()));
+    params.save(&path).unwrap();
+    assert_eq!(SamplerParams::load(&path), Ok(params));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_apply_matches_setters() {
+    let gammas = Gammas::new();
+    let board = Board::with_size(9, 9);
+    let params = SamplerParams {
+        proximity_direct: 4.0,
+        proximity_diagonal: 2.0,
+        capture_bonus: 3.0,
+        pass_gamma: 1.5,
+    };
+    let mut applied = Sampler::new(&board, &gammas);
+    params.apply(&mut applied);
+    let mut set = Sampler::new(&board, &gammas);
+    set.set_proximity_bonus(4.0, 2.0);
+    set.set_capture_bonus(3.0);
+    set.set_pass_gamma(1.5);
+    applied.new_playout(&board, &gammas);
+    set.new_playout(&board, &gammas);
+    assert_eq!(
+        applied.move_distribution(&board),
+        set.move_distribution(&board)
+    );
+}
+
+#[test]
+fn test_spsa_improves_policy() {
+    // Passing in the middle of a game gives the opponent free moves
+    let gammas = Gammas::new();
+    let mut bench = Benchmark::builder().board_size(9, 9).build().unwrap();
+    let start = SamplerParams {
+        pass_gamma: 2.0,
+        ..SamplerParams::default()
+    };
+    let config = SpsaConfig {
+        game_cnt: 100,
+        ..SpsaConfig::default()
+    };
+    let mut tuner = SpsaTuner::new(start, config);
+    let tuned = tuner.run(&mut bench, &gammas, 40);
+    assert_eq!(tuner.iteration(), 40);
+    assert_eq!(tuner.params(), &tuned);
+    assert!(tuned.pass_gamma < 1.5, "{:?}", tuned);
+    // Capturing the last move's chain pays off too
+    assert!(tuned.capture_bonus > 2.0, "{:?}", tuned);
+    for param in PARAMS.iter() {
+        let value = param.value(&tuned);
+        assert!(param.min <= value && value <= param.max, "{}", param.name);
+    }
+
+    // Same seed, same games
+    let mut again = SpsaTuner::new(start, config);
+    assert_eq!(again.run(&mut bench, &gammas, 40), tuned);
+}
+
+#[test]
+fn test_spsa_without_games_keeps_params() {
+    let gammas = Gammas::new();
+    let mut bench = Benchmark::builder().board_size(9, 9).build().unwrap();
+    let config = SpsaConfig {
+        game_cnt: 0,
+        ..SpsaConfig::default()
+    };
+    let mut tuner = SpsaTuner::new(SamplerParams::default(), config);
+    let result = tuner.step(&mut bench, &gammas);
+    assert_eq!(result.game_cnt, 0);
+    assert_eq!(tuner.iteration(), 1);
+    assert_eq!(tuner.params(), &SamplerParams::default());
+}
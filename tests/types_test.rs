@@ -0,0 +1,148 @@
+use go_game_board::*;
+
+#[test]
+fn test_gtp_coordinates() {
+    let v = Vertex::from_coords(15, 3);
+    assert_eq!(vertex_to_gtp(v, 19), "D4");
+    assert_eq!(vertex_of_gtp("D4", 19, 19), Some(v));
+    assert_eq!(vertex_to_gtp(Vertex::from_coords(0, 8), 19), "J19");
+    assert_eq!(vertex_of_gtp("pass", 19, 19), Some(Vertex::pass()));
+    assert_eq!(vertex_to_gtp(Vertex::pass(), 19), "PASS");
+
+    assert_eq!(vertex_of_gtp("I5", 19, 19), None);
+    assert_eq!(vertex_of_gtp("A0", 19, 19), None);
+    assert_eq!(vertex_of_gtp("A10", 9, 9), None);
+    assert_eq!(vertex_of_gtp("K1", 9, 9), None);
+
+    for row in 0..13 {
+        for column in 0..9 {
+            let v = Vertex::from_coords(row, column);
+            assert_eq!(vertex_of_gtp(&vertex_to_gtp(v, 13), 9, 13), Some(v));
+        }
+    }
+}
+
+#[test]
+fn test_sgf_coordinates_and_move_display() {
+    let v = Vertex::from_coords(3, 2);
+    assert_eq!(vertex_to_sgf(v), "cd");
+    assert_eq!(vertex_of_sgf("cd", 19, 19), Some(v));
+    assert_eq!(vertex_of_sgf("", 19, 19), Some(Vertex::pass()));
+
+    assert_eq!(
+        Move::of_player_vertex(Player::Black, v).to_string(),
+        "B[cd]"
+    );
+    assert_eq!(
+        Move::of_player_vertex(Player::White, Vertex::pass()).to_string(),
+        "W[]"
+    );
+}
+
+#[test]
+fn test_parse_coord() {
+    let d4 = ParsedCoord::Vertex(Vertex::from_coords(15, 3));
+    assert_eq!(parse_coord("D4", 19, 19), Ok(d4));
+    assert_eq!(parse_coord(" d4 ", 19, 19), Ok(d4));
+    assert_eq!(parse_coord("4-4", 19, 19), Ok(d4));
+    assert_eq!(parse_coord("k10", 19, 19), parse_coord("10-10", 19, 19));
+    assert_eq!(
+        parse_coord("Pass", 19, 19),
+        Ok(ParsedCoord::Vertex(Vertex::pass()))
+    );
+    assert_eq!(parse_coord("RESIGN", 19, 19), Ok(ParsedCoord::Resign));
+
+    assert_eq!(parse_coord("", 19, 19), Err(CoordError::Empty));
+    assert_eq!(parse_coord("i3", 19, 19), Err(CoordError::ColumnI));
+    assert_eq!(
+        parse_coord("?3", 19, 19),
+        Err(CoordError::BadColumn("?".to_string()))
+    );
+    assert_eq!(
+        parse_coord("d0", 19, 19),
+        Err(CoordError::BadRow("0".to_string()))
+    );
+    assert_eq!(
+        parse_coord("x-3", 19, 19),
+        Err(CoordError::BadColumn("x".to_string()))
+    );
+    assert_eq!(
+        parse_coord("k1", 9, 9),
+        Err(CoordError::ColumnOutOfBoard {
+            column: 10,
+            width: 9
+        })
+    );
+    assert_eq!(
+        parse_coord("a10", 9, 9),
+        Err(CoordError::RowOutOfBoard { row: 10, height: 9 })
+    );
+}
+
+#[test]
+fn test_try_conversions() {
+    assert_eq!(
+        try_vertex_of_coords_full(1, 1),
+        Ok(vertex_of_coords_full(1, 1))
+    );
+    assert_eq!(
+        try_vertex_of_coords_full(-1, 3),
+        Err(BoardError::BadCoords { row: -1, column: 3 })
+    );
+    assert_eq!(try_color_to_player(Color::White), Ok(Player::White));
+    assert_eq!(
+        try_color_to_player(Color::Empty),
+        Err(BoardError::NotAPlayer(Color::Empty))
+    );
+    let error: ParseError = CoordError::ColumnI.into();
+    assert_eq!(error.to_string(), "column I is skipped in GTP coordinates");
+}
+
+#[test]
+fn test_checked_coords() {
+    let board = Board::with_size(9, 7);
+    let coord = board.coord(6, 8).unwrap();
+    assert_eq!((coord.row(), coord.col()), (6, 8));
+    assert_eq!(Vertex::from(coord), Vertex::from_coords(6, 8));
+    assert_eq!(board.coord_of(coord.to_vertex()), Some(coord));
+    assert_eq!(
+        board.coord(7, 0),
+        Err(BoardError::BadCoords { row: 7, column: 0 })
+    );
+    assert!(board.coord(0, 9).is_err());
+    assert_eq!(
+        Coord::new(7, 0, 9, 9).map(Vertex::from),
+        Ok(Vertex::from_coords(7, 0))
+    );
+
+    // Sentinels around the board, pass and none have no coordinates
+    assert_eq!(board.coord_of(Vertex::from_coords(-1, 0)), None);
+    assert_eq!(board.coord_of(Vertex::from_coords(0, 9)), None);
+    assert_eq!(board.coord_of(Vertex::from_coords(7, 0)), None);
+    assert_eq!(board.coord_of(Vertex::pass()), None);
+    assert_eq!(board.coord_of(Vertex::none()), None);
+}
+
+#[test]
+fn test_board_accepts_coords() {
+    let mut board = Board::with_size(9, 9);
+    let coord = board.coord(2, 3).unwrap();
+    assert!(board.is_legal(Player::Black, coord));
+    board.try_play(Player::Black, coord).unwrap();
+    assert_eq!(board.color_at(coord), Color::Black);
+    assert_eq!(board.chain_at(coord).unwrap().size, 1);
+    assert!(!board.is_legal(Player::White, coord));
+    board.remove_stone(coord).unwrap();
+    assert_eq!(board.color_at(coord.to_vertex()), Color::Empty);
+}
+
+#[test]
+fn test_vertex_neighbors() {
+    let v = Vertex::from_coords(3, 4);
+    let nbrs4: Vec<Vertex> = v.neighbors4().collect();
+    assert_eq!(nbrs4, vec![v.up(), v.right(), v.down(), v.left()]);
+    let nbrs8: Vec<Vertex> = v.neighbors8().collect();
+    let by_dir: Vec<Vertex> = Dir::all().map(|dir| vertex_nbr(v, dir)).collect();
+    assert_eq!(nbrs8, by_dir);
+    assert_eq!(nbrs8[..4], nbrs4[..]);
+}